@@ -0,0 +1,226 @@
+//! Resumable checkpoints for long runs against metered APIs (`fetch`,
+//! `geocode opencage` & `describegpt`), set with `--checkpoint <file>`.
+//!
+//! A row-by-row command periodically saves the number of input records it has
+//! fully written, along with the length of its output (and `--report`) file at
+//! that point. When a run dies, rerunning it with the same arguments truncates
+//! the output back to the checkpoint - dropping any partially written record -
+//! skips the records already done and appends from there. `describegpt` instead
+//! saves each LLM completion as it comes in, so a rerun only pays for the
+//! inference phases that didn't complete.
+//!
+//! The checkpoint is a small JSON file. It records the command line & the size
+//! and modification time of the input, so it is never resumed by a different
+//! run, and it is removed once the run completes.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::{CliError, CliResult};
+
+/// How often a row-by-row command saves its checkpoint.
+const SAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Serialize, Deserialize, PartialEq, Eq)]
+struct InputFingerprint {
+    len:      u64,
+    /// seconds since the Unix epoch
+    modified: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct State {
+    /// the arguments of the run, without the qsv binary
+    command:    Vec<String>,
+    input:      InputFingerprint,
+    /// the number of input records fully written to the output
+    rows:       u64,
+    output_len: u64,
+    report_len: Option<u64>,
+    /// `describegpt`'s completions, by cache key
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    phases:     BTreeMap<String, serde_json::Value>,
+}
+
+pub struct Checkpoint {
+    path:       PathBuf,
+    state:      State,
+    resumed:    bool,
+    last_saved: Instant,
+}
+
+impl Checkpoint {
+    /// Open the checkpoint at `path` for a run of `argv` on `input`.
+    /// If the file exists, the run resumes from it - unless it was saved by a run
+    /// with different arguments or a different input, which is an error.
+    pub fn open(path: &str, argv: &[&str], input: Option<&str>) -> CliResult<Self> {
+        let command: Vec<String> = argv.iter().skip(1).map(ToString::to_string).collect();
+        let input = match input {
+            Some(input) if input != "-" => fingerprint(input)?,
+            _ => {
+                return fail_incorrectusage_clierror!(
+                    "--checkpoint requires an input file - stdin cannot be resumed."
+                );
+            },
+        };
+        let path = PathBuf::from(path);
+
+        if !path.exists() {
+            return Ok(Self {
+                path,
+                state: State {
+                    command,
+                    input,
+                    rows: 0,
+                    output_len: 0,
+                    report_len: None,
+                    phases: BTreeMap::new(),
+                },
+                resumed: false,
+                last_saved: Instant::now(),
+            });
+        }
+
+        let state: State = serde_json::from_slice(&fs::read(&path)?).map_err(|e| {
+            CliError::Other(format!(
+                "Invalid checkpoint file {}: {e}. Remove it to start over.",
+                path.display()
+            ))
+        })?;
+        if state.command != command {
+            return fail_clierror!(
+                "Checkpoint file {} was saved by a different run: qsv {}. Rerun with the same \
+                 arguments to resume it, or remove it to start over.",
+                path.display(),
+                state.command.join(" ")
+            );
+        }
+        if state.input != input {
+            return fail_clierror!(
+                "The input has changed since checkpoint file {} was saved. Remove it to start \
+                 over.",
+                path.display()
+            );
+        }
+        Ok(Self {
+            path,
+            state,
+            resumed: true,
+            last_saved: Instant::now(),
+        })
+    }
+
+    /// Whether the run is resuming from a saved checkpoint.
+    pub const fn is_resumed(&self) -> bool {
+        self.resumed
+    }
+
+    /// The number of input records already written.
+    #[cfg_attr(not(any(feature = "fetch", feature = "geocode")), allow(dead_code))]
+    pub const fn rows(&self) -> u64 {
+        self.state.rows
+    }
+
+    /// The length of the output file when the checkpoint was saved.
+    #[cfg_attr(not(any(feature = "fetch", feature = "geocode")), allow(dead_code))]
+    pub const fn output_len(&self) -> u64 {
+        self.state.output_len
+    }
+
+    /// The length of the report file when the checkpoint was saved.
+    #[cfg_attr(not(any(feature = "fetch", feature = "geocode")), allow(dead_code))]
+    pub const fn report_len(&self) -> Option<u64> {
+        self.state.report_len
+    }
+
+    /// Whether it's time to save the checkpoint again.
+    #[cfg_attr(not(any(feature = "fetch", feature = "geocode")), allow(dead_code))]
+    pub fn is_due(&self) -> bool {
+        self.last_saved.elapsed() >= SAVE_INTERVAL
+    }
+
+    /// Save the checkpoint after `rows` records were written to `output` (and `report`).
+    /// Both writers MUST be flushed first, so the lengths saved are those of complete records.
+    #[cfg_attr(not(any(feature = "fetch", feature = "geocode")), allow(dead_code))]
+    pub fn save(&mut self, rows: u64, output: &str, report: Option<&str>) -> CliResult<()> {
+        self.state.rows = rows;
+        self.state.output_len = fs::metadata(output)?.len();
+        self.state.report_len = match report {
+            Some(report) => Some(fs::metadata(report)?.len()),
+            None => None,
+        };
+        self.write()
+    }
+
+    /// The saved value of the phase `key`, if any.
+    pub fn phase<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        self.state
+            .phases
+            .get(key)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
+
+    /// Save the value of the phase `key`.
+    pub fn save_phase<T: Serialize>(&mut self, key: &str, value: &T) -> CliResult<()> {
+        self.state
+            .phases
+            .insert(key.to_string(), serde_json::to_value(value)?);
+        self.write()
+    }
+
+    /// The run completed - remove the checkpoint.
+    pub fn finish(self) -> CliResult<()> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Write the checkpoint to a temp file & rename it over the old one, so a run that
+    /// dies while saving never leaves a truncated checkpoint behind.
+    fn write(&mut self) -> CliResult<()> {
+        let mut temp_path = self.path.clone().into_os_string();
+        temp_path.push(".tmp");
+        fs::write(&temp_path, serde_json::to_vec_pretty(&self.state)?)?;
+        fs::rename(&temp_path, &self.path)?;
+        self.last_saved = Instant::now();
+        Ok(())
+    }
+}
+
+/// Check that `output` is a file a checkpointed run can append to.
+#[cfg_attr(not(any(feature = "fetch", feature = "geocode")), allow(dead_code))]
+pub fn check_output(output: Option<&String>) -> CliResult<()> {
+    match output {
+        Some(output) if output != "-" => {
+            if crate::config::is_snappy_extension(Path::new(output)) {
+                return fail_incorrectusage_clierror!(
+                    "--checkpoint cannot resume a snappy-compressed --output file."
+                );
+            }
+            Ok(())
+        },
+        _ => fail_incorrectusage_clierror!(
+            "--checkpoint requires the --output option - stdout cannot be resumed."
+        ),
+    }
+}
+
+fn fingerprint(input: &str) -> CliResult<InputFingerprint> {
+    let metadata = fs::metadata(input)?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |since| since.as_secs());
+    Ok(InputFingerprint {
+        len: metadata.len(),
+        modified,
+    })
+}
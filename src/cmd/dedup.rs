@@ -29,6 +29,9 @@ Examples:
   # Deduplicate ignoring case of col1 and col2 columns:
   qsv dedup -s col1,col2 --ignore-case unsorted.csv -o deduped.csv
 
+  # Deduplicate on email addresses, ignoring surrounding spaces and case:
+  qsv dedup -s email --key-transform trim,lower unsorted.csv -o deduped.csv
+
   # Write duplicates to a separate file:
   qsv dedup -s col1,col2 --dupes-output dupes.csv unsorted.csv -o deduped.csv
 
@@ -46,6 +49,15 @@ dedup options:
                                See 'qsv select --help' for the format details.
     -N, --numeric              Compare according to string numerical value
     -i, --ignore-case          Compare strings disregarding case.
+    --key-transform <ops>      A comma-separated pipeline of key normalization
+                               operations applied in order to the selected columns
+                               before comparing them, e.g. "squeeze,lower,digits".
+                               Uses the same operations as `qsv join --key-transform`:
+                               trim, ltrim, rtrim, lower, upper, squeeze, squeeze0,
+                               digits, alnum, strip_zeros and date.
+                               See 'qsv join --help' for details.
+                               The output rows are NOT modified. With --sorted, the
+                               input must be sorted on the transformed keys.
     --sorted                   The input is already sorted. Do not load the CSV into
                                memory to sort it first. Meant to be used in tandem and
                                after an extsort.
@@ -84,8 +96,9 @@ use crate::{
     CliResult,
     cmd::sort::{iter_cmp, iter_cmp_ignore_case, iter_cmp_num},
     config::{Config, Delimiter},
-    select::SelectColumns,
+    select::{SelectColumns, Selection},
     util,
+    util::ByteString,
 };
 #[derive(Deserialize)]
 struct Args {
@@ -93,6 +106,7 @@ struct Args {
     flag_select:         SelectColumns,
     flag_numeric:        bool,
     flag_ignore_case:    bool,
+    flag_key_transform:  Option<String>,
     flag_sorted:         bool,
     flag_dupes_output:   Option<String>,
     flag_output:         Option<String>,
@@ -122,6 +136,8 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         ComparisonMode::Normal
     };
 
+    let key_transform = util::KeyTransform::from_flag(args.flag_key_transform.as_deref())?;
+
     let rconfig = Config::new(args.arg_input.as_ref())
         .delimiter(args.flag_delimiter)
         .no_headers_flag(args.flag_no_headers)
@@ -160,12 +176,20 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                     wtr.write_byte_record(&record)?;
                     break;
                 }
-                let a = sel.select(&record);
-                let b = sel.select(&next_record);
-                let comparison = match compare_mode {
-                    ComparisonMode::Normal => iter_cmp(a, b),
-                    ComparisonMode::Numeric => iter_cmp_num(a, b),
-                    ComparisonMode::IgnoreCase => iter_cmp_ignore_case(a, b),
+                let comparison = if let Some(ref kt) = key_transform {
+                    cmp_keys(
+                        &compare_mode,
+                        &transformed_key(kt, &sel, &record),
+                        &transformed_key(kt, &sel, &next_record),
+                    )
+                } else {
+                    let a = sel.select(&record);
+                    let b = sel.select(&next_record);
+                    match compare_mode {
+                        ComparisonMode::Normal => iter_cmp(a, b),
+                        ComparisonMode::Numeric => iter_cmp_num(a, b),
+                        ComparisonMode::IgnoreCase => iter_cmp_ignore_case(a, b),
+                    }
                 };
                 match comparison {
                     Ordering::Equal => {
@@ -205,6 +229,40 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
         util::njobs(args.flag_jobs);
 
+        if let Some(ref kt) = key_transform {
+            // transform each row's key once, then sort & scan on the transformed keys
+            let mut all = rdr
+                .byte_records()
+                .map(|r| r.map(|r| (transformed_key(kt, &sel, &r), r)))
+                .collect::<Result<Vec<_>, _>>()?;
+            all.par_sort_by(|(k1, _), (k2, _)| cmp_keys(&compare_mode, k1, k2));
+
+            let mut iter = all.iter();
+            if let Some(first) = iter.next() {
+                let (mut prev_key, mut prev) = (&first.0, &first.1);
+                for (key, current) in iter {
+                    if cmp_keys(&compare_mode, prev_key, key) == Ordering::Equal {
+                        dupe_count += 1;
+                        if let Some(ref mut w) = dupewtr {
+                            w.write_byte_record(prev)?;
+                        }
+                    } else {
+                        wtr.write_byte_record(prev)?;
+                    }
+                    prev_key = key;
+                    prev = current;
+                }
+                wtr.write_byte_record(prev)?;
+            }
+            return finish(
+                dupewtr,
+                wtr,
+                dupe_count,
+                args.flag_quiet,
+                args.flag_human_readable,
+            );
+        }
+
         let mut all = rdr.byte_records().collect::<Result<Vec<_>, _>>()?;
         match compare_mode {
             ComparisonMode::Normal => {
@@ -258,16 +316,33 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         }
     }
 
+    finish(
+        dupewtr,
+        wtr,
+        dupe_count,
+        args.flag_quiet,
+        args.flag_human_readable,
+    )
+}
+
+/// Flush the writers and report the duplicate count.
+fn finish<W: std::io::Write>(
+    dupewtr: Option<csv::Writer<W>>,
+    mut wtr: csv::Writer<W>,
+    dupe_count: usize,
+    quiet: bool,
+    human_readable: bool,
+) -> CliResult<()> {
     if let Some(mut w) = dupewtr {
         w.flush()?;
     }
     wtr.flush()?;
 
-    if args.flag_quiet {
+    if quiet {
         return Ok(());
     }
 
-    if args.flag_human_readable {
+    if human_readable {
         use indicatif::HumanCount;
 
         eprintln!("{}", HumanCount(dupe_count as u64));
@@ -277,3 +352,21 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
     Ok(())
 }
+
+/// Select and normalize the key columns of a row with a `--key-transform` pipeline.
+#[inline]
+fn transformed_key(kt: &util::KeyTransform, sel: &Selection, row: &ByteRecord) -> Vec<ByteString> {
+    sel.select(row).map(|v| kt.apply(v)).collect()
+}
+
+/// Compare two transformed keys using the selected comparison mode.
+#[inline]
+fn cmp_keys(compare_mode: &ComparisonMode, a: &[ByteString], b: &[ByteString]) -> Ordering {
+    let a = a.iter().map(Vec::as_slice);
+    let b = b.iter().map(Vec::as_slice);
+    match compare_mode {
+        ComparisonMode::Normal => iter_cmp(a, b),
+        ComparisonMode::Numeric => iter_cmp_num(a, b),
+        ComparisonMode::IgnoreCase => iter_cmp_ignore_case(a, b),
+    }
+}
@@ -68,6 +68,11 @@ an existing cached value's TTL. Adjust the QSV_DG_REDIS_CONNSTR, QSV_REDIS_MAX_P
 QSV_REDIS_TTL_SECS & QSV_REDIS_TTL_REFRESH env vars to change Redis cache settings.
 A QSV_REDIS_TTL_SECS of 0 disables expiration (entries are cached indefinitely).
 
+With --checkpoint <file>, each LLM completion is also saved to <file> as soon as it comes in.
+If a run dies midway, rerunning it with the same arguments reuses the completions already saved -
+even with --no-cache or --fresh - so only the inference phases that didn't complete are paid for.
+The checkpoint file is removed once the run completes.
+
 Examples:
 
   # Generate a Data Dictionary, Description & Tags of data.csv using the bundled defaults
@@ -499,6 +504,9 @@ describegpt options:
     --forget               Remove a cached response if it exists and then exit.
     --flush-cache          Flush the current cache entries on startup.
                            WARNING: This operation is irreversible.
+    --checkpoint <file>    Save each LLM completion to <file>, so a run that dies can be resumed by
+                           rerunning it with the same arguments. Requires an <input> file.
+                           Not supported with --prepare-context or --process-response.
 
                            MCP SAMPLING OPTIONS:
     --prepare-context      Output the prompt context as JSON to stdout without calling the LLM.
@@ -586,7 +594,7 @@ use std::{
     io::{Read, Write},
     path::{Path, PathBuf},
     sync::{
-        LazyLock, OnceLock,
+        LazyLock, Mutex, OnceLock,
         atomic::{AtomicBool, Ordering},
     },
     time::{Duration, Instant},
@@ -607,6 +615,7 @@ use whatlang::detect;
 
 use crate::{
     CliError, CliResult,
+    checkpoint::Checkpoint,
     config::Config,
     llmutil, regex_oncelock, util,
     util::{QUIET_FLAG, print_status, process_input, run_qsv_cmd},
@@ -702,6 +711,7 @@ struct Args {
     flag_fresh:              bool,
     flag_forget:             bool,
     flag_flush_cache:        bool,
+    flag_checkpoint:         Option<String>,
     flag_prepare_context:    bool,
     flag_process_response:   bool,
     flag_format:             Option<String>,
@@ -988,6 +998,10 @@ static CONTEXT_FILE: OnceLock<Option<ContextFile>> = OnceLock::new();
 /// succeeds — so downstream phases always see the better dictionary.
 static FIRST_PASS_DICT_JSON: std::sync::RwLock<Option<String>> = std::sync::RwLock::new(None);
 
+/// The --checkpoint of the run, holding the completions received so far by cache key.
+/// Set just before inference and taken (& removed) once every phase has completed.
+static CHECKPOINT: Mutex<Option<Checkpoint>> = Mutex::new(None);
+
 /// RAII guard that clears `FIRST_PASS_DICT_JSON` on drop. Built via `seed()` which sets the
 /// slot and returns the guard; the slot is cleared when the guard goes out of scope, whether
 /// the caller returns `Ok` or short-circuits with `?` on an error from the refine LLM call.
@@ -3579,6 +3593,42 @@ fn get_cached_completion(
     cache_type: &CacheType,
     kind: PromptType,
     messages: &serde_json::Value,
+) -> CliResult<CompletionResponse> {
+    // with --checkpoint, reuse the completion saved by the run we're resuming,
+    // and save each new completion as soon as it comes in
+    let checkpoint_key = if CHECKPOINT.lock().unwrap().is_some() {
+        Some(get_cache_key(args, kind, model))
+    } else {
+        None
+    };
+    if let Some(ref key) = checkpoint_key
+        && let Some(completion) = CHECKPOINT
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|checkpoint| checkpoint.phase::<CompletionResponse>(key))
+    {
+        print_status("    Checkpoint hit!", None);
+        return Ok(completion);
+    }
+
+    let completion = complete_with_cache(args, client, model, api_key, cache_type, kind, messages)?;
+    if let Some(key) = checkpoint_key
+        && let Some(checkpoint) = CHECKPOINT.lock().unwrap().as_mut()
+    {
+        checkpoint.save_phase(&key, &completion)?;
+    }
+    Ok(completion)
+}
+
+fn complete_with_cache(
+    args: &Args,
+    client: &Client,
+    model: &str,
+    api_key: &str,
+    cache_type: &CacheType,
+    kind: PromptType,
+    messages: &serde_json::Value,
 ) -> CliResult<CompletionResponse> {
     match cache_type {
         CacheType::Disk => {
@@ -6657,6 +6707,11 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             "--prepare-context and --process-response are mutually exclusive."
         );
     }
+    if args.flag_checkpoint.is_some() && (args.flag_prepare_context || args.flag_process_response) {
+        return fail_incorrectusage_clierror!(
+            "--checkpoint is not supported with --prepare-context or --process-response."
+        );
+    }
 
     // --process-response mode: read LLM responses from stdin, process and output results
     // This branch is early because it doesn't need an input file or analysis step.
@@ -7102,9 +7157,22 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
     print_status("\nInteracting with LLM...", None);
 
+    if let Some(ref path) = args.flag_checkpoint {
+        let checkpoint = Checkpoint::open(path, argv, args.arg_input.as_deref())?;
+        if checkpoint.is_resumed() {
+            print_status("Resuming from checkpoint...", None);
+        }
+        *CHECKPOINT.lock().unwrap() = Some(checkpoint);
+    }
+
     // Run inference options
     run_inference_options(&input_path, &args, &api_key, &cache_type, &analysis_results)?;
 
+    // every phase completed, so the checkpoint is no longer needed
+    if let Some(checkpoint) = CHECKPOINT.lock().unwrap().take() {
+        checkpoint.finish()?;
+    }
+
     // Print total elapsed time
     print_status("\ndescribegpt DONE!", Some(start_time.elapsed()));
 
@@ -8233,6 +8301,7 @@ p_fewshot_examples = ""
             flag_fresh:              false,
             flag_forget:             false,
             flag_flush_cache:        false,
+            flag_checkpoint:         None,
             flag_prepare_context:    false,
             flag_process_response:   false,
             flag_format:             None,
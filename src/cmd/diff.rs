@@ -37,6 +37,9 @@ qsv diff --key 0,1 left.csv right.csv
 # Find the difference using first two columns as key and sort result by those columns
 qsv diff -k 0,1 --sort-columns 0,1 left.csv right.csv
 
+# Find the difference matching keys case-insensitively and ignoring surrounding spaces
+qsv diff -k email --key-transform trim,lower left.csv right.csv
+
 # Find the difference but replace equal field values with empty string (key fields still appear)
 qsv diff --drop-equal-fields left.csv right.csv
 
@@ -52,11 +55,63 @@ qsv diff --no-headers-output left.csv right.csv
 # Find the difference when both CSVs have no headers (generic headers _col_1, _col_2, etc. are used)
 qsv diff --no-headers-left --no-headers-right left.csv right.csv
 
+# Ignore numeric differences of up to 0.01, and differences in case
+qsv diff --abs-tolerance 0.01 --ignore-case left.csv right.csv
+
+# Diff two CSVs larger than memory, partitioning them in /data/tmp
+qsv diff --external --tmp-dir /data/tmp left.csv right.csv
+
+# Diff a CSV on stdin against a snapshot
+zcat today.csv.gz | qsv diff --external yesterday.csv -
+
+# Write a JSONL change log of the differences, for a sync job
+qsv diff --format jsonl -o changes.jsonl left.csv right.csv
+
+# Write the differences as an RFC 6902 JSON Patch
+qsv diff --format jsonpatch -k id left.csv right.csv
+
+# Apply a change log to the left CSV to reproduce the right CSV
+qsv diff --apply changes.jsonl -o right.csv left.csv
+
+EXTERNAL DIFF:
+By default, diff holds both CSVs' primary keys in memory, so it can't compare CSVs larger
+than memory. With --external, both CSVs are first hash-partitioned on their primary key into
+temporary files in --tmp-dir, so that rows with the same key land in the same pair of
+partitions. Each pair is then diffed in memory, and the differences are sorted by line, as
+without --external. Only the differences are held in memory.
+As each CSV is read only once, one of them can be stdin.
+The number of partitions is derived from the size of the CSVs & --memory-limit, unless set
+with --partitions. --sort-columns is not supported with --external.
+
+CHANGE LOGS:
+With --format jsonl, the differences are written as a change log, one JSON object per line.
+The first line is a summary, with the headers, the key columns, the --key-transform and the
+added, deleted & modified row counts:
+  {"op":"summary","headers":[...],"key":["id"],"key_transform":null,"added":1,"deleted":1,"modified":1}
+It is followed by a line per changed row, with its key. Added & deleted rows have all their
+values, and added rows their 0-based row position in the right CSV. Modified rows only have
+the changed columns, with their old & new values:
+  {"op":"add","key":{"id":"4"},"position":3,"values":{"id":"4","name":"dan"}}
+  {"op":"delete","key":{"id":"2"},"values":{"id":"2","name":"bob"}}
+  {"op":"modify","key":{"id":"3"},"changes":{"name":{"old":"cat","new":"kat"}}}
+
+With --format jsonpatch, the differences are written as an RFC 6902 JSON Patch, modeling the
+CSV as a JSON object of rows keyed by their primary key - e.g. "/3/name" for the name column
+of the row with key 3. Multi-column keys are written as a CSV record, e.g. "/3,x/name".
+Modified columns are a "replace" op and deleted rows a "remove" op, each preceded by a "test"
+op with the old value. Added rows are an "add" op. The summary counts are sent to stderr.
+
+--apply replays a JSONL change log or a JSON Patch on the left CSV to reproduce the right
+CSV: deleted rows are dropped, modified columns are updated - after checking their old
+values - and added rows are inserted at their position (JSON Patch added rows are appended).
+For a JSON Patch, set the same --key & --key-transform as the diff that created it.
+
 For more examples, see https://github.com/dathere/qsv/blob/master/tests/test_diff.rs
 See also https://github.com/dathere/qsv/wiki/Indexing-Compression-Diff#diff
 
 Usage:
     qsv diff [options] [<input-left>] [<input-right>]
+    qsv diff --apply <changelog> [options] [<input-left>]
     qsv diff --help
 
 diff options:
@@ -85,6 +140,17 @@ diff options:
                                 and it is assumed that the right CSV has the same
                                 selected column names in the same order as the left CSV.
                                 (default: 0)
+    --key-transform <ops>       A comma-separated pipeline of key normalization
+                                operations applied in order to the key columns of
+                                both CSVs before matching records, e.g. "trim,lower".
+                                Uses the same operations as `qsv join --key-transform`:
+                                trim, ltrim, rtrim, lower, upper, squeeze, squeeze0,
+                                digits, alnum, strip_zeros and date.
+                                See 'qsv join --help' for details.
+                                Records are only matched on the transformed keys:
+                                the diff result shows their original values, and
+                                a matched record whose key is spelled differently
+                                (e.g. "Ann" and "ann" with lower) is modified.
     --sort-columns <arg...>     The column indices by which the diff result should be
                                 sorted as a comma separated list of indices, e.g. 0,1,2
                                 or column names, e.g. name,age.
@@ -100,15 +166,45 @@ diff options:
     --drop-equal-fields         Drop values of equal fields in modified rows of the CSV
                                 diff result (and replace them with the empty string).
                                 Key field values will not be dropped.
+                                Only supported with --format csv.
     --drop-equal-columns        Drop entire columns from the diff result that have no
                                 differences anywhere. A column is kept if it is a key
                                 column, if it differs in any modified row, or if it has
                                 a non-empty value in any added or deleted row. Otherwise
                                 it is dropped. Can be combined with --drop-equal-fields.
+                                Only supported with --format csv.
+    --abs-tolerance <eps>       Treat numeric values that differ by at most <eps> as equal,
+                                e.g. 0.001.
+    --rel-tolerance <eps>       Treat numeric values as equal when they differ by at most
+                                <eps> times the larger of their absolute values,
+                                e.g. 0.01 for 1%. When both tolerances are set, values
+                                within either one are equal.
+    -i, --ignore-case           Compare values case-insensitively. Note that this does
+                                not apply to the key columns - use --key-transform lower
+                                to match keys case-insensitively.
+    --format <format>           The diff result format. One of csv, jsonl (a change
+                                log) or jsonpatch (an RFC 6902 JSON Patch).
+                                See CHANGE LOGS above. [default: csv]
+    --apply <changelog>         Apply a jsonl or jsonpatch change log to <input-left>,
+                                writing the resulting CSV. See CHANGE LOGS above.
     -j, --jobs <arg>            The number of jobs to run in parallel.
                                 When not set, the number of jobs is set to the number
                                 of CPUs detected.
 
+external diff options:
+    --external                  Diff CSVs larger than memory by partitioning them on
+                                disk. See EXTERNAL DIFF above.
+    --partitions <n>            The number of partitions to split both CSVs in, up to
+                                1024. When not set, it is derived from the size of the
+                                CSVs and --memory-limit.
+    --memory-limit <arg>        The maximum amount of memory to diff a pair of partitions.
+                                If less than 50, this is a percentage of total memory.
+                                If more than 50, this is the memory in MB to allocate, capped
+                                at 90 percent of total memory.
+                                [default: 20]
+    --tmp-dir <arg>             The directory for the partition files.
+                                [default: ./]
+
 Common options:
     -h, --help                  Display this message
     -o, --output <file>         Write output to <file> instead of stdout.
@@ -117,23 +213,37 @@ Common options:
                                 and --delimiter-output.
 "#;
 
-use std::io::{self, Write};
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+};
 
 use csv::ByteRecord;
 use csv_diff::{
-    csv_diff::CsvByteDiffBuilder, csv_headers::Headers, diff_result::DiffByteRecords,
-    diff_row::DiffByteRecord,
+    csv_diff::CsvByteDiffBuilder,
+    diff_row::{ByteRecordLineInfo, DiffByteRecord},
 };
+use foldhash::{HashMap, HashMapExt, HashSet};
 use serde::Deserialize;
 
+use self::{
+    changelog::{ChangeLog, DiffFormat},
+    compare::ValueCompare,
+};
 use super::rename::rename_headers_all_generic;
 use crate::{
     CliResult,
     clitypes::CliError,
+    cmd::extdedup::calculate_memory_limit,
     config::{Config, Delimiter},
     util,
 };
 
+mod changelog;
+mod compare;
+mod external;
+
 #[derive(Deserialize)]
 struct Args {
     arg_input_left:          Option<String>,
@@ -147,9 +257,19 @@ struct Args {
     flag_delimiter_right:    Option<Delimiter>,
     flag_delimiter_output:   Option<Delimiter>,
     flag_key:                Option<String>,
+    flag_key_transform:      Option<String>,
     flag_sort_columns:       Option<String>,
     flag_drop_equal_fields:  bool,
     flag_drop_equal_columns: bool,
+    flag_format:             String,
+    flag_apply:              Option<String>,
+    flag_abs_tolerance:      Option<f64>,
+    flag_rel_tolerance:      Option<f64>,
+    flag_ignore_case:        bool,
+    flag_external:           bool,
+    flag_partitions:         Option<usize>,
+    flag_memory_limit:       Option<u64>,
+    flag_tmp_dir:            Option<String>,
     flag_delimiter:          Option<Delimiter>,
 }
 
@@ -168,74 +288,193 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         .delimiter(args.flag_delimiter_left)
         .no_headers(args.flag_no_headers_left);
 
+    if let Some(ref changelog_path) = args.flag_apply {
+        if rconfig_left.is_stdin() {
+            return fail_incorrectusage_clierror!(
+                "diff --apply does not support stdin. A file path is required for <input-left>."
+            );
+        }
+        let wtr = Config::new(args.flag_output.as_ref())
+            .delimiter(args.flag_delimiter_output)
+            .writer()?;
+        return changelog::apply(
+            &changelog::ApplyArgs {
+                changelog:     changelog_path,
+                key:           args.flag_key.as_deref(),
+                key_transform: args.flag_key_transform.as_deref(),
+                no_headers:    args.flag_no_headers_left,
+            },
+            &rconfig_left,
+            wtr,
+        );
+    }
+    let format = DiffFormat::from_flag(&args.flag_format)?;
+    // a change log has every value of the records it adds, deletes & modifies, so it
+    // can be applied
+    if format != DiffFormat::Csv && (args.flag_drop_equal_fields || args.flag_drop_equal_columns) {
+        return fail_incorrectusage_clierror!(
+            "--drop-equal-fields and --drop-equal-columns are only supported with --format csv."
+        );
+    }
+
     let rconfig_right = Config::new(args.arg_input_right.as_ref())
         .delimiter(args.flag_delimiter_right)
         .no_headers(args.flag_no_headers_right);
 
-    if rconfig_left.is_stdin() || rconfig_right.is_stdin() {
+    // --external reads each CSV only once, so one of them can be stdin
+    if args.flag_external {
+        if rconfig_left.is_stdin() && rconfig_right.is_stdin() {
+            return fail_incorrectusage_clierror!(
+                "diff --external supports stdin for only one of the two arguments."
+            );
+        }
+    } else if rconfig_left.is_stdin() || rconfig_right.is_stdin() {
         return fail_incorrectusage_clierror!(
-            "diff does not support stdin. A file path is required for both arguments."
+            "diff does not support stdin. A file path is required for both arguments, unless \
+             --external is set."
         );
     }
 
+    let key_transform = util::KeyTransform::from_flag(args.flag_key_transform.as_deref())?;
+    let value_compare = ValueCompare::new(
+        args.flag_abs_tolerance,
+        args.flag_rel_tolerance,
+        args.flag_ignore_case,
+    )?;
+
     let mut csv_rdr_left = rconfig_left.reader()?;
     let mut csv_rdr_right = rconfig_right.reader()?;
 
-    let headers_left = csv_rdr_left.byte_headers()?;
-    let headers_right = csv_rdr_right.byte_headers()?;
+    let headers_left = csv_rdr_left.byte_headers()?.clone();
+    let headers_right = csv_rdr_right.byte_headers()?.clone();
 
     let primary_key_cols: Vec<usize> = match args.flag_key {
         None => vec![0],
-        Some(s) => parse_indices_or_colnames(&s, headers_left, headers_right, "key")?,
+        Some(s) => parse_indices_or_colnames(&s, &headers_left, &headers_right, "key")?,
+    };
+
+    // the column names of the JSON change logs
+    let header_names: Vec<String> = if args.flag_no_headers_left {
+        (1..=headers_left.len())
+            .map(|i| format!("_col_{i}"))
+            .collect()
+    } else {
+        headers_left
+            .iter()
+            .map(|h| String::from_utf8_lossy(h).into_owned())
+            .collect()
     };
 
     let sort_cols = args
         .flag_sort_columns
         .as_deref()
-        .map(|s| parse_indices_or_colnames(s, headers_left, headers_right, "sort"))
+        .map(|s| parse_indices_or_colnames(s, &headers_left, &headers_right, "sort"))
         .transpose()?;
 
-    let wtr = Config::new(args.flag_output.as_ref())
-        .delimiter(args.flag_delimiter_output)
-        .writer()?;
-
     util::njobs(args.flag_jobs);
 
     // ===== DIFF PROCESSING =====
 
-    let csv_diff = CsvByteDiffBuilder::new()
-        .primary_key_columns(primary_key_cols.clone())
-        .build()
-        .map_err(|e| CliError::Other(format!("Cannot instantiate diff: {e}")))?;
+    let (result_headers, num_columns, mut diff_records, positions) = if args.flag_external {
+        if sort_cols.is_some() {
+            return fail_incorrectusage_clierror!(
+                "--sort-columns is not supported with --external."
+            );
+        }
+        let tmp_dir = args.flag_tmp_dir.as_deref().unwrap_or("./");
+        if !Path::new(tmp_dir).exists() {
+            return fail_clierror!("tmp-dir '{tmp_dir}' does not exist");
+        }
+        let num_partitions = match args.flag_partitions {
+            Some(partitions) => partitions.clamp(1, external::MAX_PARTITIONS),
+            None => {
+                // a CSV on stdin is assumed to be as large as the other one
+                let file_size = |rconfig: &Config| {
+                    rconfig
+                        .path
+                        .as_ref()
+                        .and_then(|path| fs::metadata(path).ok())
+                        .map(|metadata| metadata.len())
+                };
+                let (size_left, size_right) = (file_size(&rconfig_left), file_size(&rconfig_right));
+                let inputs_size = size_left.or(size_right).unwrap_or_default()
+                    + size_right.or(size_left).unwrap_or_default();
+                external::num_partitions(
+                    inputs_size,
+                    calculate_memory_limit(args.flag_memory_limit),
+                )
+            },
+        };
+        log::info!("diff --external: {num_partitions} partitions");
 
-    let mut diff_byte_records = csv_diff
-        .diff(csv_rdr_left.into(), csv_rdr_right.into())
-        .try_to_diff_byte_records()?;
+        let partitioner = external::Partitioner {
+            key_cols: &primary_key_cols,
+            key_transform: key_transform.as_ref(),
+            num_partitions,
+            tmp_dir,
+        };
+        let diff = partitioner.diff(
+            &mut csv_rdr_left,
+            &mut csv_rdr_right,
+            args.flag_no_headers_left,
+            args.flag_no_headers_right,
+        )?;
+        let result_headers = if args.flag_no_headers_left {
+            (!args.flag_no_headers_right).then_some(headers_right)
+        } else {
+            Some(headers_left)
+        };
+        (
+            result_headers,
+            Some(header_names.len()),
+            diff.records,
+            Some(diff.positions),
+        )
+    } else {
+        diff_in_memory(
+            csv_rdr_left,
+            csv_rdr_right,
+            &rconfig_right,
+            (args.flag_no_headers_left, args.flag_no_headers_right),
+            key_transform.as_ref(),
+            &primary_key_cols,
+            sort_cols,
+            format == DiffFormat::Jsonl,
+        )?
+    };
 
-    match sort_cols {
-        Some(sort_cols) => {
-            diff_byte_records
-                .sort_by_columns(sort_cols)
-                .map_err(|e| CliError::Other(e.to_string()))?;
-        },
-        None => {
-            diff_byte_records.sort_by_line();
-        },
+    if let Some(ref value_compare) = value_compare {
+        diff_records = value_compare.filter(diff_records);
     }
 
+    if format != DiffFormat::Csv {
+        let changelog = ChangeLog {
+            headers:       &header_names,
+            key_cols:      &primary_key_cols,
+            key_transform: args.flag_key_transform.as_deref(),
+            positions:     &positions.unwrap_or_default(),
+        };
+        let wtr = Config::new(args.flag_output.as_ref()).io_writer()?;
+        return changelog.write(format, &diff_records, wtr);
+    }
+
+    let wtr = Config::new(args.flag_output.as_ref())
+        .delimiter(args.flag_delimiter_output)
+        .writer()?;
+
     // When --drop-equal-columns is set, compute which columns to keep with a single
     // borrow-pass over the (already fully buffered) diff records. A column is kept if
     // it is a key column, if it differs in any modified row, or if it has a non-empty
     // value in any added or deleted row.
     let keep_cols: Option<Vec<usize>> = if args.flag_drop_equal_columns {
-        let num_cols = diff_byte_records.num_columns().unwrap_or(0);
+        let num_cols = num_columns.unwrap_or(0);
         let mut keep = vec![false; num_cols];
         for &k in &primary_key_cols {
             if k < num_cols {
                 keep[k] = true;
             }
         }
-        for dbr in diff_byte_records.iter() {
+        for dbr in &diff_records {
             match dbr {
                 DiffByteRecord::Modify { field_indices, .. } => {
                     for &fi in field_indices {
@@ -270,7 +509,273 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         primary_key_cols,
         keep_cols,
     );
-    Ok(csv_diff_writer.write_diff_byte_records(diff_byte_records)?)
+    Ok(csv_diff_writer.write_diff_byte_records(
+        result_headers.as_ref(),
+        num_columns,
+        diff_records,
+    )?)
+}
+
+/// The sorted differences, the header row & number of columns of the diff result and,
+/// with `with_positions`, the row index of the added records in the right CSV.
+type DiffResult = (
+    Option<ByteRecord>,
+    Option<usize>,
+    Vec<DiffByteRecord>,
+    Option<HashMap<Vec<Vec<u8>>, u64>>,
+);
+
+/// Diff the two CSVs in memory with csv_diff.
+#[allow(clippy::too_many_arguments)]
+fn diff_in_memory(
+    mut csv_rdr_left: csv::Reader<Box<dyn io::Read + Send + 'static>>,
+    mut csv_rdr_right: csv::Reader<Box<dyn io::Read + Send + 'static>>,
+    rconfig_right: &Config,
+    (no_headers_left, no_headers_right): (bool, bool),
+    key_transform: Option<&util::KeyTransform>,
+    primary_key_cols: &[usize],
+    sort_cols: Option<Vec<usize>>,
+    with_positions: bool,
+) -> CliResult<DiffResult> {
+    // csv_diff pairs records on the raw bytes of their key, so with --key-transform, it
+    // diffs a stream of the records with their key columns transformed (see `transform_keys`)
+    if let Some(kt) = key_transform {
+        csv_rdr_left = TransformedKeys::reader(csv_rdr_left, primary_key_cols, kt, no_headers_left);
+        csv_rdr_right =
+            TransformedKeys::reader(csv_rdr_right, primary_key_cols, kt, no_headers_right);
+    }
+
+    let csv_diff = CsvByteDiffBuilder::new()
+        .primary_key_columns(primary_key_cols.to_vec())
+        .build()
+        .map_err(|e| CliError::Other(format!("Cannot instantiate diff: {e}")))?;
+
+    let mut diff_byte_records = csv_diff
+        .diff(csv_rdr_left.into(), csv_rdr_right.into())
+        .try_to_diff_byte_records()?;
+
+    match sort_cols {
+        Some(sort_cols) => {
+            diff_byte_records
+                .sort_by_columns(sort_cols)
+                .map_err(|e| CliError::Other(e.to_string()))?;
+        },
+        None => {
+            diff_byte_records.sort_by_line();
+        },
+    }
+
+    let headers = diff_byte_records.headers();
+    let mut result_headers = headers
+        .headers_left()
+        .or_else(|| headers.headers_right())
+        .cloned();
+    let mut num_columns = diff_byte_records.num_columns();
+    if key_transform.is_some() {
+        // drop the original key values of `transform_keys`
+        num_columns = num_columns.map(|n| n.saturating_sub(primary_key_cols.len()));
+        if let (Some(headers), Some(n)) = (&mut result_headers, num_columns) {
+            headers.truncate(n);
+        }
+    }
+    let diff_records: Vec<DiffByteRecord> = match key_transform {
+        Some(_) => diff_byte_records
+            .into_iter()
+            .map(|dbr| restore_keys(dbr, primary_key_cols))
+            .collect(),
+        None => diff_byte_records.into_iter().collect(),
+    };
+
+    // the rows of the right CSV the added records are at, so --apply can insert them there
+    let positions = if with_positions {
+        Some(added_positions(
+            &diff_records,
+            rconfig_right,
+            primary_key_cols,
+        )?)
+    } else {
+        None
+    };
+    Ok((result_headers, num_columns, diff_records, positions))
+}
+
+/// The 0-based row index in the right CSV of each added record, by key.
+fn added_positions(
+    diff_records: &[DiffByteRecord],
+    rconfig_right: &Config,
+    key_cols: &[usize],
+) -> CliResult<HashMap<Vec<Vec<u8>>, u64>> {
+    let added: HashSet<Vec<Vec<u8>>> = diff_records
+        .iter()
+        .filter_map(|dbr| match dbr {
+            DiffByteRecord::Add(add) => Some(changelog::key_of(add.byte_record(), key_cols)),
+            _ => None,
+        })
+        .collect();
+    let mut positions = HashMap::with_capacity(added.len());
+    if added.is_empty() {
+        return Ok(positions);
+    }
+
+    let mut rdr = rconfig_right.reader()?;
+    let mut record = ByteRecord::new();
+    let mut row: u64 = 0;
+    while rdr.read_byte_record(&mut record)? {
+        let key = changelog::key_of(&record, key_cols);
+        if added.contains(&key) {
+            positions.insert(key, row);
+        }
+        row += 1;
+    }
+    Ok(positions)
+}
+
+/// Push `record` to `transformed` with its key columns normalized with a `--key-transform`
+/// pipeline, followed by the original key values as extra columns. csv_diff then pairs the
+/// records on their transformed keys, and `restore_keys` puts the original values back, so
+/// the diff result shows the records as they are in the CSVs.
+fn transform_keys(
+    record: &ByteRecord,
+    key_cols: &[usize],
+    kt: &util::KeyTransform,
+    transformed: &mut ByteRecord,
+) {
+    transformed.clear();
+    for (i, field) in record.iter().enumerate() {
+        if key_cols.contains(&i) {
+            transformed.push_field(&kt.apply(field));
+        } else {
+            transformed.push_field(field);
+        }
+    }
+    for &i in key_cols {
+        transformed.push_field(record.get(i).unwrap_or_default());
+    }
+}
+
+/// Undo `transform_keys` on a difference. A paired record whose key was only respelled
+/// is modified in its key columns.
+fn restore_keys(dbr: DiffByteRecord, key_cols: &[usize]) -> DiffByteRecord {
+    let restore = |info: &ByteRecordLineInfo| {
+        let record = info.byte_record();
+        let len = record.len().saturating_sub(key_cols.len());
+        let restored: ByteRecord = (0..len)
+            .map(|i| match key_cols.iter().position(|&k| k == i) {
+                Some(pos) => &record[len + pos],
+                None => &record[i],
+            })
+            .collect();
+        ByteRecordLineInfo::new(restored, info.line())
+    };
+    match dbr {
+        DiffByteRecord::Add(add) => DiffByteRecord::Add(restore(&add)),
+        DiffByteRecord::Delete(delete) => DiffByteRecord::Delete(restore(&delete)),
+        DiffByteRecord::Modify {
+            delete,
+            add,
+            field_indices,
+        } => {
+            let len = delete.byte_record().len().saturating_sub(key_cols.len());
+            // the transformed key columns are equal, so their differences are in the
+            // original values
+            let mut field_indices: Vec<usize> = field_indices
+                .into_iter()
+                .map(|i| if i < len { i } else { key_cols[i - len] })
+                .collect();
+            field_indices.sort_unstable();
+            field_indices.dedup();
+            DiffByteRecord::Modify {
+                delete: restore(&delete),
+                add: restore(&add),
+                field_indices,
+            }
+        },
+    }
+}
+
+/// A CSV read with its key columns transformed with `transform_keys`, so csv_diff can diff
+/// it without a transformed copy on disk.
+struct TransformedKeys {
+    rdr:             csv::Reader<Box<dyn io::Read + Send + 'static>>,
+    key_cols:        Vec<usize>,
+    key_transform:   util::KeyTransform,
+    headers_pending: bool,
+    record:          ByteRecord,
+    transformed:     ByteRecord,
+    wtr:             csv::Writer<Vec<u8>>,
+    buf:             Vec<u8>,
+    pos:             usize,
+}
+
+impl TransformedKeys {
+    fn reader(
+        rdr: csv::Reader<Box<dyn io::Read + Send + 'static>>,
+        key_cols: &[usize],
+        kt: &util::KeyTransform,
+        no_headers: bool,
+    ) -> csv::Reader<Box<dyn io::Read + Send + 'static>> {
+        let transformed = Self {
+            rdr,
+            key_cols: key_cols.to_vec(),
+            key_transform: kt.clone(),
+            headers_pending: !no_headers,
+            record: ByteRecord::new(),
+            transformed: ByteRecord::new(),
+            wtr: csv::Writer::from_writer(Vec::new()),
+            buf: Vec::new(),
+            pos: 0,
+        };
+        csv::ReaderBuilder::new()
+            .has_headers(!no_headers)
+            .from_reader(Box::new(transformed))
+    }
+
+    /// Encode the next row in `buf`, returning false at the end of the CSV.
+    fn fill_buf(&mut self) -> io::Result<bool> {
+        if self.headers_pending {
+            self.headers_pending = false;
+            self.transformed = self.rdr.byte_headers()?.clone();
+            for &i in &self.key_cols {
+                self.transformed
+                    .push_field(format!("_qsv_key_{i}").as_bytes());
+            }
+        } else if self.rdr.read_byte_record(&mut self.record)? {
+            transform_keys(
+                &self.record,
+                &self.key_cols,
+                &self.key_transform,
+                &mut self.transformed,
+            );
+        } else {
+            return Ok(false);
+        }
+        self.wtr.write_byte_record(&self.transformed)?;
+        self.wtr.flush()?;
+        self.buf.clear();
+        std::mem::swap(&mut self.buf, self.wtr.get_mut());
+        self.pos = 0;
+        Ok(true)
+    }
+}
+
+impl io::Read for TransformedKeys {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.buf.len() {
+            if !self.fill_buf()? {
+                return Ok(0);
+            }
+        }
+        let n = out.len().min(self.buf.len() - self.pos);
+        out[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+fn transformed_config(path: &Path, no_headers: bool) -> Config {
+    Config::new(Some(path.to_string_lossy().into_owned()).as_ref())
+        .delimiter(Some(Delimiter(b',')))
+        .no_headers(no_headers)
 }
 
 trait StrExt {
@@ -396,30 +901,22 @@ impl<W: Write> CsvDiffWriter<W> {
         }
     }
 
-    fn write_headers(&mut self, headers: &Headers, num_columns: Option<&usize>) -> csv::Result<()> {
-        match (headers.headers_left(), headers.headers_right()) {
-            (Some(lbh), Some(rbh)) => {
-                // currently, `diff` can only handle two CSVs that have the same
-                // headers ordering, so in this case we can either choose the left
-                // or right headers, because both are the same
-                debug_assert_eq!(
-                    lbh, rbh,
-                    "csv_diff invariant: left/right headers must match"
-                );
-                if !self.no_headers {
-                    let lbh = lbh.clone();
-                    self.write_projected_header(&lbh)?;
-                }
-            },
-            (Some(bh), None) | (None, Some(bh)) => {
+    /// Write the header row of the left or right CSV - currently, `diff` can only handle
+    /// two CSVs with the same headers ordering, so either one can be used - or generic
+    /// headers when neither CSV has headers.
+    fn write_headers(
+        &mut self,
+        headers: Option<&ByteRecord>,
+        num_columns: Option<usize>,
+    ) -> csv::Result<()> {
+        match headers {
+            Some(bh) => {
                 if !self.no_headers {
-                    let bh = bh.clone();
-                    self.write_projected_header(&bh)?;
+                    self.write_projected_header(bh)?;
                 }
             },
-            (None, None) => {
-                if let (Some(&num_cols), false) = (num_columns.filter(|&&c| c > 0), self.no_headers)
-                {
+            None => {
+                if let (Some(num_cols), false) = (num_columns.filter(|&c| c > 0), self.no_headers) {
                     let headers_generic = rename_headers_all_generic(num_cols);
                     let mut new_rdr = csv::Reader::from_reader(headers_generic.as_bytes());
                     let new_headers = new_rdr.byte_headers()?.clone();
@@ -431,12 +928,14 @@ impl<W: Write> CsvDiffWriter<W> {
         Ok(())
     }
 
-    fn write_diff_byte_records(&mut self, diff_byte_records: DiffByteRecords) -> io::Result<()> {
-        self.write_headers(
-            diff_byte_records.headers(),
-            diff_byte_records.num_columns().as_ref(),
-        )?;
-        for dbr in diff_byte_records {
+    fn write_diff_byte_records(
+        &mut self,
+        headers: Option<&ByteRecord>,
+        num_columns: Option<usize>,
+        diff_records: Vec<DiffByteRecord>,
+    ) -> io::Result<()> {
+        self.write_headers(headers, num_columns)?;
+        for dbr in diff_records {
             self.write_diff_byte_record(&dbr)?;
         }
         self.csv_writer.flush()?;
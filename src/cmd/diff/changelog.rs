@@ -0,0 +1,546 @@
+//! Structured change logs for `diff --format jsonl|jsonpatch`, and `diff --apply` to replay
+//! them on the left CSV.
+//!
+//! The JSONL change log starts with a summary line - the headers, key columns & the
+//! added/deleted/modified counts - followed by one line per changed row.
+//! The JSON Patch (RFC 6902) models a CSV as an object of rows keyed by their primary key,
+//! e.g. `/<key>/<column>`, with `test` operations carrying the old values.
+
+use std::{
+    fs,
+    io::{self, Write},
+};
+
+use csv::ByteRecord;
+use csv_diff::diff_row::DiffByteRecord;
+use foldhash::{HashMap, HashSet};
+use serde_json::{Map, Value, json};
+
+use crate::{CliResult, config::Config, util};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DiffFormat {
+    Csv,
+    Jsonl,
+    JsonPatch,
+}
+
+impl DiffFormat {
+    pub fn from_flag(flag: &str) -> CliResult<Self> {
+        match flag.to_ascii_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "jsonl" => Ok(Self::Jsonl),
+            "jsonpatch" => Ok(Self::JsonPatch),
+            _ => fail_incorrectusage_clierror!(
+                "Invalid --format: {flag}. Valid values are csv, jsonl and jsonpatch."
+            ),
+        }
+    }
+}
+
+/// The primary key values of a record.
+pub fn key_of(record: &ByteRecord, key_cols: &[usize]) -> Vec<Vec<u8>> {
+    key_cols
+        .iter()
+        .map(|&i| record.get(i).unwrap_or_default().to_vec())
+        .collect()
+}
+
+pub struct ChangeLog<'a> {
+    pub headers:       &'a [String],
+    pub key_cols:      &'a [usize],
+    pub key_transform: Option<&'a str>,
+    // the row index of each added record in the right CSV, by key
+    pub positions:     &'a HashMap<Vec<Vec<u8>>, u64>,
+}
+
+impl ChangeLog<'_> {
+    pub fn write(
+        &self,
+        format: DiffFormat,
+        diff_records: &[DiffByteRecord],
+        mut wtr: impl Write,
+    ) -> CliResult<()> {
+        let (mut added, mut deleted, mut modified) = (0_u64, 0_u64, 0_u64);
+        for dbr in diff_records {
+            match dbr {
+                DiffByteRecord::Add(_) => added += 1,
+                DiffByteRecord::Delete(_) => deleted += 1,
+                DiffByteRecord::Modify { .. } => modified += 1,
+            }
+        }
+
+        if format == DiffFormat::Jsonl {
+            let key: Vec<&str> = self
+                .key_cols
+                .iter()
+                .map(|&i| self.headers[i].as_str())
+                .collect();
+            let summary = json!({
+                "op": "summary",
+                "headers": self.headers,
+                "key": key,
+                "key_transform": self.key_transform,
+                "added": added,
+                "deleted": deleted,
+                "modified": modified,
+            });
+            writeln!(wtr, "{summary}")?;
+            for dbr in diff_records {
+                writeln!(wtr, "{}", self.jsonl_change(dbr))?;
+            }
+        } else {
+            let mut ops: Vec<Value> = Vec::new();
+            for dbr in diff_records {
+                self.patch_ops(dbr, &mut ops);
+            }
+            // safety: a serde_json::Value always serializes
+            writeln!(wtr, "{}", serde_json::to_string_pretty(&ops).unwrap())?;
+            winfo!("{added} added, {deleted} deleted, {modified} modified.");
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+
+    fn jsonl_change(&self, dbr: &DiffByteRecord) -> Value {
+        match dbr {
+            DiffByteRecord::Add(add) => {
+                let record = add.byte_record();
+                json!({
+                    "op": "add",
+                    "key": self.key_object(record),
+                    "position": self.positions.get(&key_of(record, self.key_cols)),
+                    "values": self.row_object(record),
+                })
+            },
+            DiffByteRecord::Delete(delete) => {
+                let record = delete.byte_record();
+                json!({
+                    "op": "delete",
+                    "key": self.key_object(record),
+                    "values": self.row_object(record),
+                })
+            },
+            DiffByteRecord::Modify {
+                delete,
+                add,
+                field_indices,
+            } => {
+                let mut changes = Map::with_capacity(field_indices.len());
+                for &i in field_indices {
+                    changes.insert(
+                        self.headers[i].clone(),
+                        json!({
+                            "old": field_string(delete.byte_record(), i),
+                            "new": field_string(add.byte_record(), i),
+                        }),
+                    );
+                }
+                json!({
+                    "op": "modify",
+                    "key": self.key_object(add.byte_record()),
+                    "changes": changes,
+                })
+            },
+        }
+    }
+
+    fn patch_ops(&self, dbr: &DiffByteRecord, ops: &mut Vec<Value>) {
+        match dbr {
+            DiffByteRecord::Add(add) => {
+                let record = add.byte_record();
+                ops.push(json!({
+                    "op": "add",
+                    "path": self.pointer(record, None),
+                    "value": self.row_object(record),
+                }));
+            },
+            DiffByteRecord::Delete(delete) => {
+                let record = delete.byte_record();
+                let path = self.pointer(record, None);
+                ops.push(json!({"op": "test", "path": path, "value": self.row_object(record)}));
+                ops.push(json!({"op": "remove", "path": path}));
+            },
+            DiffByteRecord::Modify {
+                delete,
+                add,
+                field_indices,
+            } => {
+                for &i in field_indices {
+                    let path = self.pointer(add.byte_record(), Some(i));
+                    ops.push(json!({
+                        "op": "test",
+                        "path": path,
+                        "value": field_string(delete.byte_record(), i),
+                    }));
+                    ops.push(json!({
+                        "op": "replace",
+                        "path": path,
+                        "value": field_string(add.byte_record(), i),
+                    }));
+                }
+            },
+        }
+    }
+
+    fn row_object(&self, record: &ByteRecord) -> Value {
+        Value::Object(
+            self.headers
+                .iter()
+                .enumerate()
+                .map(|(i, header)| (header.clone(), Value::String(field_string(record, i))))
+                .collect(),
+        )
+    }
+
+    fn key_object(&self, record: &ByteRecord) -> Value {
+        Value::Object(
+            self.key_cols
+                .iter()
+                .map(|&i| {
+                    (
+                        self.headers[i].clone(),
+                        Value::String(field_string(record, i)),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    /// The JSON Pointer of a row, or of a column of a row.
+    fn pointer(&self, record: &ByteRecord, column: Option<usize>) -> String {
+        let key: Vec<String> = self
+            .key_cols
+            .iter()
+            .map(|&i| field_string(record, i))
+            .collect();
+        let mut pointer = format!("/{}", pointer_escape(&key_segment(&key)));
+        if let Some(i) = column {
+            pointer.push('/');
+            pointer.push_str(&pointer_escape(&self.headers[i]));
+        }
+        pointer
+    }
+}
+
+fn field_string(record: &ByteRecord, i: usize) -> String {
+    String::from_utf8_lossy(record.get(i).unwrap_or_default()).into_owned()
+}
+
+/// A multi-column key is a single JSON Pointer segment, as a CSV record of its values.
+fn key_segment(key: &[String]) -> String {
+    if let [single] = key {
+        return single.clone();
+    }
+    let mut wtr = csv::WriterBuilder::new()
+        .terminator(csv::Terminator::Any(b'\n'))
+        .from_writer(Vec::new());
+    // safety: writing to a Vec can't fail
+    wtr.write_record(key).unwrap();
+    let bytes = wtr.into_inner().unwrap();
+    String::from_utf8_lossy(&bytes)
+        .trim_end_matches('\n')
+        .to_string()
+}
+
+fn pointer_escape(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+fn pointer_unescape(segment: &str) -> String {
+    segment.replace("~1", "/").replace("~0", "~")
+}
+
+/// The changes of a change log, by the key of the row they apply to.
+#[derive(Default)]
+struct Changes {
+    deletes:  HashSet<Vec<Vec<u8>>>,
+    // the changed columns, with their old value if known & new value
+    modifies: HashMap<Vec<Vec<u8>>, Vec<(usize, Option<String>, String)>>,
+    // the added rows, with their row index in the new CSV if known
+    adds:     Vec<(Option<u64>, ByteRecord)>,
+}
+
+pub struct ApplyArgs<'a> {
+    pub changelog:     &'a str,
+    pub key:           Option<&'a str>,
+    pub key_transform: Option<&'a str>,
+    pub no_headers:    bool,
+}
+
+/// Apply a change log to the left CSV, writing the new CSV to `wtr`.
+pub fn apply(
+    args: &ApplyArgs,
+    rconfig: &Config,
+    mut wtr: csv::Writer<Box<dyn io::Write + 'static>>,
+) -> CliResult<()> {
+    let mut rdr = rconfig.reader()?;
+    let headers = rdr.byte_headers()?.clone();
+    let header_names: Vec<String> = if args.no_headers {
+        (1..=headers.len()).map(|i| format!("_col_{i}")).collect()
+    } else {
+        headers
+            .iter()
+            .map(|h| String::from_utf8_lossy(h).into_owned())
+            .collect()
+    };
+    let column_index = |name: &str| -> CliResult<usize> {
+        match header_names.iter().position(|h| h == name) {
+            Some(i) => Ok(i),
+            None => fail_clierror!("Column \"{name}\" of the change log is not in the CSV."),
+        }
+    };
+
+    let changelog = fs::read_to_string(args.changelog)
+        .map_err(|e| format!("Cannot read change log {}: {e}", args.changelog))?;
+    let is_patch = changelog.trim_start().starts_with('[');
+
+    let mut changes = Changes::default();
+    let (key_cols, key_transform) = if is_patch {
+        let key_cols: Vec<usize> = match args.key {
+            None => vec![0],
+            Some(key) => super::parse_indices_or_colnames(key, &headers, &headers, "key")?,
+        };
+        let patch: Vec<Value> = serde_json::from_str(&changelog)
+            .map_err(|e| format!("Cannot parse JSON Patch {}: {e}", args.changelog))?;
+        read_patch(&patch, key_cols.len(), &column_index, &mut changes)?;
+        (key_cols, args.key_transform.map(String::from))
+    } else {
+        read_jsonl(&changelog, &column_index, &mut changes)?
+    };
+    let key_transform = util::KeyTransform::from_flag(key_transform.as_deref())?;
+    // the change log has the original keys, so they are matched like the keys of the CSV
+    if let Some(ref kt) = key_transform {
+        let transform = |key: Vec<Vec<u8>>| -> Vec<Vec<u8>> {
+            key.iter().map(|value| kt.apply(value)).collect()
+        };
+        changes.deletes = changes.deletes.into_iter().map(transform).collect();
+        changes.modifies = changes
+            .modifies
+            .into_iter()
+            .map(|(key, modifications)| (transform(key), modifications))
+            .collect();
+    }
+
+    if !args.no_headers {
+        wtr.write_byte_record(&headers)?;
+    }
+
+    // added rows with a position are inserted where they are in the new CSV
+    let (mut positioned, appended): (Vec<_>, Vec<_>) = changes
+        .adds
+        .into_iter()
+        .partition(|(position, _)| position.is_some());
+    positioned.sort_by_key(|(position, _)| *position);
+    let mut positioned = positioned.into_iter().peekable();
+
+    let mut written: u64 = 0;
+    let mut record = ByteRecord::new();
+    while rdr.read_byte_record(&mut record)? {
+        while let Some((_, add)) = positioned.next_if(|(position, _)| *position <= Some(written)) {
+            wtr.write_byte_record(&add)?;
+            written += 1;
+        }
+
+        let mut key = key_of(&record, &key_cols);
+        if let Some(ref kt) = key_transform {
+            for value in &mut key {
+                *value = kt.apply(value);
+            }
+        }
+        if changes.deletes.remove(&key) {
+            continue;
+        }
+        if let Some(modifications) = changes.modifies.remove(&key) {
+            let mut fields: Vec<Vec<u8>> = record.iter().map(<[u8]>::to_vec).collect();
+            for (i, old, new) in modifications {
+                if let Some(old) = old
+                    && fields.get(i).map(Vec::as_slice) != Some(old.as_bytes())
+                {
+                    return fail_clierror!(
+                        "The change log does not apply: column \"{}\" of the row with key {} is \
+                         not \"{old}\".",
+                        header_names[i],
+                        String::from_utf8_lossy(&key.join(&b","[..]))
+                    );
+                }
+                if let Some(field) = fields.get_mut(i) {
+                    *field = new.into_bytes();
+                }
+            }
+            wtr.write_record(&fields)?;
+        } else {
+            wtr.write_byte_record(&record)?;
+        }
+        written += 1;
+    }
+    for (_, add) in positioned.chain(appended) {
+        wtr.write_byte_record(&add)?;
+    }
+    wtr.flush()?;
+
+    let unmatched = changes.deletes.len() + changes.modifies.len();
+    if unmatched > 0 {
+        return fail_clierror!(
+            "{unmatched} deleted or modified rows of the change log are not in the CSV."
+        );
+    }
+    Ok(())
+}
+
+type ColumnIndex<'a> = dyn Fn(&str) -> CliResult<usize> + 'a;
+
+/// Read a JSONL change log, returning the key columns & key transform of its summary.
+fn read_jsonl(
+    changelog: &str,
+    column_index: &ColumnIndex,
+    changes: &mut Changes,
+) -> CliResult<(Vec<usize>, Option<String>)> {
+    let mut summary: Option<(Vec<usize>, Option<String>)> = None;
+    let mut num_columns = 0;
+    for (i, line) in changelog.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let change: Value = serde_json::from_str(line)
+            .map_err(|e| format!("Cannot parse line {} of the change log: {e}", i + 1))?;
+        let op = change["op"].as_str().unwrap_or_default();
+        if op == "summary" {
+            let mut key_cols = Vec::new();
+            for name in change["key"].as_array().into_iter().flatten() {
+                key_cols.push(column_index(name.as_str().unwrap_or_default())?);
+            }
+            num_columns = change["headers"].as_array().map_or(0, Vec::len);
+            let key_transform = change["key_transform"].as_str().map(String::from);
+            summary = Some((key_cols, key_transform));
+            continue;
+        }
+        let Some((ref key_cols, _)) = summary else {
+            return fail_clierror!("The change log has no summary line.");
+        };
+
+        let key = json_key(&change["key"], key_cols, column_index)?;
+        match op {
+            "add" => {
+                let mut fields = vec![String::new(); num_columns];
+                if let Some(values) = change["values"].as_object() {
+                    for (name, value) in values {
+                        let i = column_index(name)?;
+                        if i >= fields.len() {
+                            fields.resize(i + 1, String::new());
+                        }
+                        fields[i] = value.as_str().unwrap_or_default().to_string();
+                    }
+                }
+                changes
+                    .adds
+                    .push((change["position"].as_u64(), ByteRecord::from(fields)));
+            },
+            "delete" => {
+                changes.deletes.insert(key);
+            },
+            "modify" => {
+                let mut modifications = Vec::new();
+                for (name, values) in change["changes"].as_object().into_iter().flatten() {
+                    modifications.push((
+                        column_index(name)?,
+                        values["old"].as_str().map(String::from),
+                        values["new"].as_str().unwrap_or_default().to_string(),
+                    ));
+                }
+                changes.modifies.insert(key, modifications);
+            },
+            _ => return fail_clierror!("Unknown change log op \"{op}\" on line {}.", i + 1),
+        }
+    }
+    match summary {
+        Some(summary) => Ok(summary),
+        None => fail_clierror!("The change log has no summary line."),
+    }
+}
+
+fn json_key(
+    key: &Value,
+    key_cols: &[usize],
+    column_index: &ColumnIndex,
+) -> CliResult<Vec<Vec<u8>>> {
+    let mut values = vec![Vec::new(); key_cols.len()];
+    for (name, value) in key.as_object().into_iter().flatten() {
+        let i = column_index(name)?;
+        if let Some(pos) = key_cols.iter().position(|&k| k == i) {
+            values[pos] = value.as_str().unwrap_or_default().as_bytes().to_vec();
+        }
+    }
+    Ok(values)
+}
+
+/// Read a JSON Patch of `diff --format jsonpatch`.
+fn read_patch(
+    patch: &[Value],
+    num_key_cols: usize,
+    column_index: &ColumnIndex,
+    changes: &mut Changes,
+) -> CliResult<()> {
+    // the old value of the next replace, from its test op
+    let mut tested: Option<(String, String)> = None;
+    for op in patch {
+        let path = op["path"].as_str().unwrap_or_default();
+        let mut segments = path.strip_prefix('/').unwrap_or(path).splitn(2, '/');
+        let key_segment = pointer_unescape(segments.next().unwrap_or_default());
+        let column = segments.next().map(pointer_unescape);
+        let key = parse_key_segment(&key_segment, num_key_cols)?;
+
+        match (op["op"].as_str().unwrap_or_default(), column) {
+            ("test", Some(_)) => {
+                tested = Some((
+                    path.to_string(),
+                    op["value"].as_str().unwrap_or_default().to_string(),
+                ));
+            },
+            // the old row of a remove
+            ("test", None) => {},
+            ("remove", None) => {
+                changes.deletes.insert(key);
+            },
+            ("add", None) => {
+                let mut fields: Vec<String> = Vec::new();
+                for (name, value) in op["value"].as_object().into_iter().flatten() {
+                    let i = column_index(name)?;
+                    if i >= fields.len() {
+                        fields.resize(i + 1, String::new());
+                    }
+                    fields[i] = value.as_str().unwrap_or_default().to_string();
+                }
+                changes.adds.push((None, ByteRecord::from(fields)));
+            },
+            ("replace" | "add", Some(column)) => {
+                let old = tested
+                    .take()
+                    .filter(|(tested_path, _)| tested_path == path)
+                    .map(|(_, old)| old);
+                changes.modifies.entry(key).or_default().push((
+                    column_index(&column)?,
+                    old,
+                    op["value"].as_str().unwrap_or_default().to_string(),
+                ));
+            },
+            (other, _) => {
+                return fail_clierror!("Unsupported JSON Patch op \"{other}\" at {path}.");
+            },
+        }
+    }
+    Ok(())
+}
+
+fn parse_key_segment(segment: &str, num_key_cols: usize) -> CliResult<Vec<Vec<u8>>> {
+    if num_key_cols <= 1 {
+        return Ok(vec![segment.as_bytes().to_vec()]);
+    }
+    let mut rdr = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .from_reader(segment.as_bytes());
+    let mut record = ByteRecord::new();
+    rdr.read_byte_record(&mut record)?;
+    let key = key_of(&record, &(0..num_key_cols).collect::<Vec<_>>());
+    Ok(key)
+}
@@ -0,0 +1,95 @@
+//! Looser value comparisons for `diff`: numeric tolerances & case-insensitive matching.
+//!
+//! csv_diff compares fields byte for byte, so the modified rows it finds are re-checked
+//! here, dropping the fields - and rows - whose values are equal under the looser rules.
+
+use csv_diff::diff_row::DiffByteRecord;
+
+use crate::CliResult;
+
+pub struct ValueCompare {
+    abs_tolerance: Option<f64>,
+    rel_tolerance: Option<f64>,
+    ignore_case:   bool,
+}
+
+impl ValueCompare {
+    /// None when values are compared exactly.
+    pub fn new(
+        abs_tolerance: Option<f64>,
+        rel_tolerance: Option<f64>,
+        ignore_case: bool,
+    ) -> CliResult<Option<Self>> {
+        for (flag, tolerance) in [
+            ("--abs-tolerance", abs_tolerance),
+            ("--rel-tolerance", rel_tolerance),
+        ] {
+            if let Some(tolerance) = tolerance
+                && !(tolerance.is_finite() && tolerance >= 0.0)
+            {
+                return fail_incorrectusage_clierror!(
+                    "{flag} must be a non-negative number, not {tolerance}."
+                );
+            }
+        }
+        if abs_tolerance.is_none() && rel_tolerance.is_none() && !ignore_case {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            abs_tolerance,
+            rel_tolerance,
+            ignore_case,
+        }))
+    }
+
+    /// Drop the modified fields with equal values, and the modified rows left without any.
+    pub fn filter(&self, diff_records: Vec<DiffByteRecord>) -> Vec<DiffByteRecord> {
+        diff_records
+            .into_iter()
+            .filter_map(|dbr| match dbr {
+                DiffByteRecord::Modify {
+                    delete,
+                    add,
+                    field_indices,
+                } => {
+                    let field_indices: Vec<usize> = field_indices
+                        .into_iter()
+                        .filter(|&i| {
+                            !self.equal(
+                                delete.byte_record().get(i).unwrap_or_default(),
+                                add.byte_record().get(i).unwrap_or_default(),
+                            )
+                        })
+                        .collect();
+                    (!field_indices.is_empty()).then_some(DiffByteRecord::Modify {
+                        delete,
+                        add,
+                        field_indices,
+                    })
+                },
+                other => Some(other),
+            })
+            .collect()
+    }
+
+    fn equal(&self, old: &[u8], new: &[u8]) -> bool {
+        if (self.abs_tolerance.is_some() || self.rel_tolerance.is_some())
+            && let (Ok(old), Ok(new)) = (
+                fast_float2::parse::<f64, _>(old),
+                fast_float2::parse::<f64, _>(new),
+            )
+        {
+            let difference = (old - new).abs();
+            return self.abs_tolerance.is_some_and(|abs| difference <= abs)
+                || self
+                    .rel_tolerance
+                    .is_some_and(|rel| difference <= rel * old.abs().max(new.abs()));
+        }
+        if self.ignore_case {
+            return old.eq_ignore_ascii_case(new)
+                || String::from_utf8_lossy(old).to_lowercase()
+                    == String::from_utf8_lossy(new).to_lowercase();
+        }
+        false
+    }
+}
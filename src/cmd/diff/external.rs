@@ -0,0 +1,247 @@
+//! `diff --external`: an out-of-core diff of CSVs larger than memory.
+//!
+//! Both CSVs are hash-partitioned on their primary key into temporary files, so the rows
+//! with the same key always land in the same pair of partitions. Each pair is small enough
+//! to be diffed in memory, and the differences of all the pairs are then sorted by line,
+//! like the in-memory diff. Only the differences are held in memory.
+
+use std::{
+    fs,
+    hash::BuildHasher,
+    io::{self, Write},
+    path::Path,
+};
+
+use csv::ByteRecord;
+use csv_diff::{
+    csv_diff::CsvByteDiffBuilder,
+    diff_row::{ByteRecordLineInfo, DiffByteRecord},
+};
+use foldhash::{HashMap, HashMapExt, fast::FixedState};
+
+use super::changelog::key_of;
+use crate::{CliResult, clitypes::CliError, util};
+
+// the line number & row index of a record, appended to it in its partition,
+// as csv_diff only knows the position of a record in its partition
+const EXTRA_COLUMNS: usize = 2;
+pub const MAX_PARTITIONS: usize = 1024;
+// the records of each partition are buffered & appended to its file when the buffer is full,
+// so only one partition file is open at a time, whatever the number of partitions
+const PARTITION_BUFFER_SIZE: usize = 32 * 1024;
+const PARTITION_SEED: u64 = 0x5153_5644_4946_4621;
+
+/// The number of partitions the CSVs are split in, so a pair is diffed within `memory_limit`.
+pub fn num_partitions(inputs_size: u64, memory_limit: u64) -> usize {
+    // diffing a pair of partitions takes about twice their size
+    let partitions = inputs_size.saturating_mul(2).div_ceil(memory_limit.max(1));
+    usize::try_from(partitions)
+        .unwrap_or(MAX_PARTITIONS)
+        .clamp(1, MAX_PARTITIONS)
+}
+
+pub struct Partitioner<'a> {
+    pub key_cols:       &'a [usize],
+    pub key_transform:  Option<&'a util::KeyTransform>,
+    pub num_partitions: usize,
+    pub tmp_dir:        &'a str,
+}
+
+pub struct ExternalDiff {
+    pub records:   Vec<DiffByteRecord>,
+    // the row index of each added record in the right CSV, by key
+    pub positions: HashMap<Vec<Vec<u8>>, u64>,
+}
+
+impl Partitioner<'_> {
+    pub fn diff<R: io::Read>(
+        &self,
+        rdr_left: &mut csv::Reader<R>,
+        rdr_right: &mut csv::Reader<R>,
+        no_headers_left: bool,
+        no_headers_right: bool,
+    ) -> CliResult<ExternalDiff> {
+        let partitions_left = self.partition(rdr_left, no_headers_left)?;
+        let partitions_right = self.partition(rdr_right, no_headers_right)?;
+
+        let csv_diff = CsvByteDiffBuilder::new()
+            .primary_key_columns(self.key_cols.to_vec())
+            .build()
+            .map_err(|e| CliError::Other(format!("Cannot instantiate diff: {e}")))?;
+
+        // the differences, with the line they are sorted by & their order on the same line
+        let mut diffs: Vec<(u64, u8, DiffByteRecord)> = Vec::new();
+        let mut positions = HashMap::new();
+        for (tmp_left, tmp_right) in partitions_left.iter().zip(&partitions_right) {
+            let csv_rdr_left = super::transformed_config(tmp_left, no_headers_left).reader()?;
+            let csv_rdr_right = super::transformed_config(tmp_right, no_headers_right).reader()?;
+            let diff_byte_records = csv_diff
+                .diff(csv_rdr_left.into(), csv_rdr_right.into())
+                .try_to_diff_byte_records()?;
+
+            for dbr in diff_byte_records {
+                // the line the difference is sorted by, its order on the same line &,
+                // for an added record, its row index
+                let (line, order, row, dbr) = match dbr {
+                    DiffByteRecord::Delete(delete) => {
+                        let (record, line, _) = split_extra_columns(delete.byte_record());
+                        (
+                            line,
+                            0,
+                            None,
+                            DiffByteRecord::Delete(ByteRecordLineInfo::new(record, line)),
+                        )
+                    },
+                    DiffByteRecord::Modify {
+                        delete,
+                        add,
+                        field_indices,
+                    } => {
+                        let (deleted, line_left, _) = split_extra_columns(delete.byte_record());
+                        let (added, line_right, _) = split_extra_columns(add.byte_record());
+                        // rows that only moved differ in their line number & row index
+                        let field_indices: Vec<usize> = field_indices
+                            .into_iter()
+                            .filter(|&i| i < deleted.len())
+                            .collect();
+                        if field_indices.is_empty() {
+                            continue;
+                        }
+                        (
+                            line_left.min(line_right),
+                            1,
+                            None,
+                            DiffByteRecord::Modify {
+                                delete: ByteRecordLineInfo::new(deleted, line_left),
+                                add: ByteRecordLineInfo::new(added, line_right),
+                                field_indices,
+                            },
+                        )
+                    },
+                    DiffByteRecord::Add(add) => {
+                        let (record, line, row) = split_extra_columns(add.byte_record());
+                        (
+                            line,
+                            2,
+                            Some(row),
+                            DiffByteRecord::Add(ByteRecordLineInfo::new(record, line)),
+                        )
+                    },
+                };
+                let dbr = match self.key_transform {
+                    Some(_) => super::restore_keys(dbr, self.key_cols),
+                    None => dbr,
+                };
+                if let (Some(row), DiffByteRecord::Add(add)) = (row, &dbr) {
+                    positions.insert(key_of(add.byte_record(), self.key_cols), row);
+                }
+                diffs.push((line, order, dbr));
+            }
+        }
+
+        diffs.sort_by_key(|(line, order, _)| (*line, *order));
+        Ok(ExternalDiff {
+            records: diffs.into_iter().map(|(_, _, dbr)| dbr).collect(),
+            positions,
+        })
+    }
+
+    /// Split a CSV into partitions by the hash of its (transformed) key. With --key-transform,
+    /// the records are written as `super::transform_keys` transforms them.
+    fn partition<R: io::Read>(
+        &self,
+        rdr: &mut csv::Reader<R>,
+        no_headers: bool,
+    ) -> CliResult<Vec<tempfile::TempPath>> {
+        let mut partitions = Vec::with_capacity(self.num_partitions);
+        for _ in 0..self.num_partitions {
+            partitions.push(
+                tempfile::Builder::new()
+                    .suffix(".csv")
+                    .tempfile_in(self.tmp_dir)?
+                    .into_temp_path(),
+            );
+        }
+        let mut wtrs: Vec<_> = partitions
+            .iter()
+            .map(|path| {
+                csv::WriterBuilder::new()
+                    .buffer_capacity(PARTITION_BUFFER_SIZE)
+                    .from_writer(PartitionFile(path))
+            })
+            .collect();
+
+        if !no_headers {
+            let mut headers = rdr.byte_headers()?.clone();
+            if self.key_transform.is_some() {
+                for &i in self.key_cols {
+                    headers.push_field(format!("_qsv_key_{i}").as_bytes());
+                }
+            }
+            headers.push_field(b"_qsv_line");
+            headers.push_field(b"_qsv_row");
+            for wtr in &mut wtrs {
+                wtr.write_byte_record(&headers)?;
+            }
+        }
+
+        let hasher = FixedState::with_seed(PARTITION_SEED);
+        let mut itoa_buf = itoa::Buffer::new();
+        let mut record = ByteRecord::new();
+        let mut partitioned = ByteRecord::new();
+        let mut row: u64 = 0;
+        while rdr.read_byte_record(&mut record)? {
+            let line = record.position().map_or(0, csv::Position::line);
+            match self.key_transform {
+                Some(kt) => super::transform_keys(&record, self.key_cols, kt, &mut partitioned),
+                None => partitioned.clone_from(&record),
+            }
+            let hash = hasher.hash_one(key_of(&partitioned, self.key_cols));
+            // the remainder is less than num_partitions, a usize
+            #[allow(clippy::cast_possible_truncation)]
+            let partition = (hash % self.num_partitions as u64) as usize;
+
+            partitioned.push_field(itoa_buf.format(line).as_bytes());
+            partitioned.push_field(itoa_buf.format(row).as_bytes());
+            wtrs[partition].write_byte_record(&partitioned)?;
+            row += 1;
+        }
+        for mut wtr in wtrs {
+            wtr.flush()?;
+        }
+        Ok(partitions)
+    }
+}
+
+/// A partition file, only opened to append a full buffer of records to it.
+struct PartitionFile<'a>(&'a Path);
+
+impl io::Write for PartitionFile<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        fs::OpenOptions::new()
+            .append(true)
+            .open(self.0)?
+            .write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Remove the line number & row index appended to a partitioned record.
+fn split_extra_columns(record: &ByteRecord) -> (ByteRecord, u64, u64) {
+    let len = record.len().saturating_sub(EXTRA_COLUMNS);
+    let number = |i: usize| {
+        record
+            .get(i)
+            .and_then(|n| atoi_simd::parse::<u64, false, false>(n).ok())
+            .unwrap_or_default()
+    };
+    (
+        record.iter().take(len).collect(),
+        number(len),
+        number(len + 1),
+    )
+}
@@ -41,6 +41,9 @@ Examples:
   # Do a case insensitive exclusion on the id column
   qsv exclude --ignore-case id records.csv id previously-processed.csv
 
+  # Exclude on phone numbers, ignoring punctuation and spacing differences
+  qsv exclude --key-transform digits phone records.csv phone do-not-call.csv
+
   # Read records.csv from stdin
   cat records.csv | qsv exclude id - id previously-processed.csv
 
@@ -67,6 +70,12 @@ input arguments:
 
 exclude options:
     -i, --ignore-case      When set, matching is done case insensitively.
+    --key-transform <ops>  A comma-separated pipeline of key normalization
+                           operations applied in order to each key field, e.g.
+                           "squeeze,lower,digits". Uses the same operations as
+                           `qsv join --key-transform`: trim, ltrim, rtrim, lower,
+                           upper, squeeze, squeeze0, digits, alnum, strip_zeros
+                           and date. See 'qsv join --help' for details.
     -v, --invert           When set, matching rows will be the only ones included,
                            forming set intersection, instead of the ones discarded.
 
@@ -99,16 +108,17 @@ const VALUE_SET_INITIAL_CAPACITY: usize = 10_000;
 
 #[derive(Deserialize)]
 struct Args {
-    arg_columns1:     SelectColumns,
-    arg_input1:       String,
-    arg_columns2:     SelectColumns,
-    arg_input2:       String,
-    flag_invert:      bool,
-    flag_output:      Option<String>,
-    flag_no_headers:  bool,
-    flag_ignore_case: bool,
-    flag_delimiter:   Option<Delimiter>,
-    flag_memcheck:    bool,
+    arg_columns1:       SelectColumns,
+    arg_input1:         String,
+    arg_columns2:       SelectColumns,
+    arg_input2:         String,
+    flag_invert:        bool,
+    flag_output:        Option<String>,
+    flag_no_headers:    bool,
+    flag_ignore_case:   bool,
+    flag_key_transform: Option<String>,
+    flag_delimiter:     Option<Delimiter>,
+    flag_memcheck:      bool,
 }
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
@@ -125,13 +135,14 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 }
 
 struct IoState<R: io::Read, W: io::Write> {
-    wtr:        csv::Writer<W>,
-    rdr1:       csv::Reader<R>,
-    sel1:       Selection,
-    rdr2:       csv::Reader<R>,
-    sel2:       Selection,
-    no_headers: bool,
-    casei:      bool,
+    wtr:           csv::Writer<W>,
+    rdr1:          csv::Reader<R>,
+    sel1:          Selection,
+    rdr2:          csv::Reader<R>,
+    sel2:          Selection,
+    no_headers:    bool,
+    casei:         bool,
+    key_transform: Option<util::KeyTransform>,
 }
 
 impl<R: io::Read, W: io::Write> IoState<R, W> {
@@ -144,10 +155,15 @@ impl<R: io::Read, W: io::Write> IoState<R, W> {
     }
 
     fn exclude(mut self, invert: bool) -> CliResult<()> {
-        let values = build_value_set(self.rdr2, &self.sel2, self.casei)?;
+        let values = build_value_set(
+            self.rdr2,
+            &self.sel2,
+            self.casei,
+            self.key_transform.as_ref(),
+        )?;
         let mut row = csv::ByteRecord::new();
         while self.rdr1.read_byte_record(&mut row)? {
-            let key = get_row_key(&self.sel1, &row, self.casei);
+            let key = get_row_key(&self.sel1, &row, self.casei, self.key_transform.as_ref());
             let matched = values.contains(&key);
             if matched == invert {
                 self.wtr.write_record(row.iter())?;
@@ -186,6 +202,7 @@ impl Args {
             sel2,
             no_headers: rconf1.no_headers,
             casei: self.flag_ignore_case,
+            key_transform: util::KeyTransform::from_flag(self.flag_key_transform.as_deref())?,
         })
     }
 
@@ -217,16 +234,26 @@ fn build_value_set<R: io::Read>(
     mut rdr: csv::Reader<R>,
     sel: &Selection,
     casei: bool,
+    key_transform: Option<&util::KeyTransform>,
 ) -> CliResult<HashSet<Vec<ByteString>>> {
     let mut values: HashSet<Vec<ByteString>> = HashSet::with_capacity(VALUE_SET_INITIAL_CAPACITY);
     let mut row = csv::ByteRecord::new();
     while rdr.read_byte_record(&mut row)? {
-        values.insert(get_row_key(sel, &row, casei));
+        values.insert(get_row_key(sel, &row, casei, key_transform));
     }
     Ok(values)
 }
 
 #[inline]
-fn get_row_key(sel: &Selection, row: &csv::ByteRecord, casei: bool) -> Vec<ByteString> {
-    sel.select(row).map(|v| util::transform(v, casei)).collect()
+fn get_row_key(
+    sel: &Selection,
+    row: &csv::ByteRecord,
+    casei: bool,
+    key_transform: Option<&util::KeyTransform>,
+) -> Vec<ByteString> {
+    let key = sel.select(row).map(|v| util::transform(v, casei));
+    match key_transform {
+        Some(kt) => key.map(|v| kt.apply(&v)).collect(),
+        None => key.collect(),
+    }
 }
@@ -47,6 +47,23 @@ to be as fast as allowed. The --rate-limit option sets the maximum number of que
 (QPS) to be made. The default is 0, which means to go as fast as possible, automatically
 throttling as required, based on rate-limit and retry-after response headers.
 
+Requests are sent one at a time by default. For APIs with a generous rate limit but a high
+latency, use --concurrency to keep several requests in flight at once. The output is still
+written in the order of the input, and all the requests share the --rate-limit quota.
+When an API asks to back off, all the in-flight requests wait as well. For example, for an
+API allowing 50 QPS with a latency of 300 ms:
+
+  $ qsv fetch URL data.csv --rate-limit 50 --concurrency 16 > results.jsonl
+
+RESUMING LONG RUNS:
+With --checkpoint <file>, fetch saves its progress every few seconds - the number of records
+written and the length of the --output (and --report) file at that point. If the run dies, or
+stops at --max-errors, rerun it with the same arguments to truncate the output back to the
+checkpoint and append from there, without requesting the records already done again.
+The checkpoint is removed once the run completes. For example:
+
+  $ qsv fetch URL data.csv --disk-cache --checkpoint data.ckpt -o results.jsonl
+
 To use a proxy, set the environment variables HTTP_PROXY, HTTPS_PROXY or ALL_PROXY
 (e.g. export HTTPS_PROXY=socks5://127.0.0.1:1086).
 
@@ -148,10 +165,84 @@ Note that you can pass as many key-value pairs by using --http-header option rep
 
   $ qsv fetch URL data.csv --http-header "X-Api-Key:TEST_KEY" -H "X-Api-Secret:ABC123XYZ" -H "Accept-Language: fr-FR"
 
+USING AUTH PROFILES:
+To keep credentials out of the command line (and the shell history), use the --auth option
+with a named auth profile instead. Profiles are read from the TOML file named by the
+QSV_AUTH_FILE env var (default: ~/.qsv/auth.toml), one table per profile. Its secrets are
+only read from an env var (<field>_env) or a file (<field>_file), never given inline:
+
+  [github]
+  type = "bearer"                 # Authorization: Bearer <token>
+  token_env = "GITHUB_TOKEN"
+
+  [intranet]
+  type = "basic"                  # HTTP Basic auth
+  username = "jdoe"
+  password_file = "~/.secrets/intranet"
+
+  [census]
+  type = "api-key"                # appends &key=<key> to each URL
+  param = "key"
+  key_env = "CENSUS_API_KEY"
+
+  [warehouse]
+  type = "oauth2"                 # OAuth2 client-credentials grant
+  token_url = "https://auth.example.com/oauth/token"
+  client_id = "qsv"
+  client_secret_env = "WAREHOUSE_SECRET"
+  scope = "read:data"             # optional, as are audience & client_auth (basic or post)
+
+  [lake]
+  type = "aws-sigv4"              # AWS Signature Version 4 request signing
+  region = "us-east-1"            # default: the AWS_REGION env var
+  service = "execute-api"
+  # access_key_id, secret_access_key & session_token default to the AWS_* env vars
+
+The OAuth2 access token is requested on startup and automatically refreshed before it
+expires. Responses are cached by URL, without the credentials.
+
+  $ qsv fetch URL data.csv --auth github
+
+PAGINATED API INGESTION:
+With the --paginate option, fetch ingests the records of a paginated REST API, starting
+from <url> instead of reading URLs from a CSV. Each page is parsed as JSON, its records are
+extracted with the --jaq/--jaqfile selector - or the top-level array elements, or the
+page itself, when no selector is given - and all the records are flattened to a CSV,
+with nested keys joined with a "." (e.g. "address.city"). Records that are not JSON
+objects are written in a "value" column.
+
+The pagination <strategy> is one of:
+  link    Follow the URL of the rel="next" relation of the Link response header,
+          until a page has none (e.g. the GitHub API).
+  offset  Set the --page-param (default: "offset") query parameter to the number of
+          records fetched so far, until a page has no records.
+  page    Set the --page-param (default: "page") query parameter to the page number,
+          starting with 1, until a page has no records.
+  cursor  Run the --next jaq selector on each page to get the next cursor, and set the
+          --page-param (default: "cursor") query parameter to it - or fetch it directly
+          if it's a URL - until the cursor is null or empty.
+
+With the offset and page strategies, the --limit-param query parameter is set to --page-size.
+Pages are rate limited, retried and cached like the responses of a regular fetch.
+A page that still fails after --max-retries aborts the ingestion.
+
+# Ingest all the records of a cursor-paginated API, with the records in the "data" array
+$ qsv fetch --paginate cursor "https://api.example.com/v1/items" --jaq '.data[]' \
+    --next '.meta.next_cursor' --rate-limit 5 > items.csv
+
+# Ingest a GitHub repo's issues, 100 issues per page
+$ qsv fetch --paginate link "https://api.github.com/repos/dathere/qsv/issues?per_page=100" \
+    --disk-cache -o issues.csv
+
+# Ingest an offset/limit paginated API, using "skip" & "take" as parameters
+$ qsv fetch --paginate offset "https://api.example.com/records" --page-param skip \
+    --limit-param take --page-size 500 --jaq '.results[]' > records.csv
+
 For more extensive examples, see https://github.com/dathere/qsv/blob/master/tests/test_fetch.rs.
 See also https://github.com/dathere/qsv/wiki/HTTP-and-Web#fetch
 
 Usage:
+    qsv fetch --paginate <strategy> <url> [--jaq <selector> | --jaqfile <file>] [--http-header <k:v>...] [options]
     qsv fetch [<url-column> | --url-template <template>] [--jaq <selector> | --jaqfile <file>] [--http-header <k:v>...] [options] [<input>]
     qsv fetch --help
 
@@ -181,9 +272,15 @@ Fetch options:
                                [default: 0 ]
     --timeout <seconds>        Timeout for each URL request.
                                [default: 30 ]
+    --concurrency <n>          The maximum number of requests in flight at any time.
+                               The output order is preserved. Ignored by --paginate.
+                               See NETWORK OPTIONS above.
+                               [default: 1]
     -H, --http-header <k:v>    Append custom header(s) to the HTTP header. Pass multiple key-value pairs
                                by adding this option multiple times, once for each pair. The key and value
                                should be separated by a colon.
+    --auth <profile>           Authenticate the requests with the named auth profile.
+                               See USING AUTH PROFILES above.
     --max-retries <count>      Maximum number of retries per record before an error is raised.
                                [default: 5]
     --max-errors <count>       Maximum number of errors before aborting.
@@ -203,6 +300,26 @@ Fetch options:
                                qsv_fetch_elapsed_ms & qsv_fetch_response.
                                The short report only has the six columns without the "qsv_fetch_" prefix.
                                [default: none]
+    --checkpoint <file>        Save the progress of the run to <file>, so it can be resumed by
+                               rerunning it with the same arguments. Requires an <input> file
+                               and --output. Not supported with --paginate.
+                               See RESUMING LONG RUNS above.
+
+                               PAGINATION OPTIONS:
+    --paginate <strategy>      Ingest the records of the paginated API at <url> to a CSV,
+                               following its pages with the given strategy -
+                               link, offset, page or cursor. See PAGINATED API INGESTION above.
+    --page-param <name>        The query parameter set to the offset, page number or cursor.
+                               (default: offset, page or cursor respectively)
+    --page-size <n>            The number of records requested per page with the offset & page
+                               strategies. Set to 0 to not send the --limit-param parameter.
+                               [default: 100]
+    --limit-param <name>       The query parameter set to --page-size.
+                               [default: limit]
+    --next <selector>          The jaq selector of the next cursor in a page.
+                               Required by the cursor strategy.
+    --max-pages <n>            Stop after fetching <n> pages. Set to 0 to fetch all the pages.
+                               [default: 0]
 
                                CACHING OPTIONS:
     --no-cache                 Do not cache responses.
@@ -254,7 +371,7 @@ Common options:
 use std::{
     fs,
     num::NonZeroU32,
-    sync::OnceLock,
+    sync::{Arc, OnceLock},
     thread,
     time::{self, Duration},
 };
@@ -290,16 +407,22 @@ use util::expand_tilde;
 
 use crate::{
     CliError, CliResult,
+    auth::{self, Authorize},
+    checkpoint::{self, Checkpoint},
     config::{Config, Delimiter},
     regex_oncelock,
     select::SelectColumns,
     util,
 };
 
+pub mod concurrent;
+mod paginate;
+
 #[derive(Deserialize)]
 struct Args {
     arg_url_column:      SelectColumns,
     arg_input:           Option<String>,
+    arg_url:             Option<String>,
     flag_url_template:   Option<String>,
     flag_new_column:     Option<String>,
     flag_jaq:            Option<String>,
@@ -307,13 +430,22 @@ struct Args {
     flag_pretty:         bool,
     flag_rate_limit:     u32,
     flag_timeout:        u16,
+    flag_concurrency:    usize,
     flag_http_header:    Vec<String>,
+    flag_auth:           Option<String>,
     flag_max_retries:    u8,
     flag_max_errors:     u64,
     flag_store_error:    bool,
     flag_cookies:        bool,
     flag_user_agent:     Option<String>,
     flag_report:         String,
+    flag_checkpoint:     Option<String>,
+    flag_paginate:       Option<String>,
+    flag_page_param:     Option<String>,
+    flag_page_size:      usize,
+    flag_limit_param:    String,
+    flag_next:           Option<String>,
+    flag_max_pages:      u64,
     flag_no_cache:       bool,
     flag_mem_cache_size: usize,
     flag_disk_cache:     bool,
@@ -344,6 +476,9 @@ static DEFAULT_DISKCACHE_TTL_SECS: u64 = 60 * 60 * 24 * 28;
 
 static TIMEOUT_SECS: OnceLock<u64> = OnceLock::new();
 
+// the --auth profile, shared with fetchpost
+pub static AUTH_PROFILE: OnceLock<auth::AuthProfile> = OnceLock::new();
+
 pub static JAQ_FILTER: OnceLock<jaq_core::Filter<data::JustLut<jaq_json::Val>>> = OnceLock::new();
 
 const FETCH_REPORT_PREFIX: &str = "qsv_fetch_";
@@ -453,6 +588,12 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         .set(util::timeout_secs(args.flag_timeout)?)
         .unwrap();
 
+    if let Some(ref profile) = args.flag_auth {
+        let profile = auth::load_profile(profile)?;
+        profile.authenticate()?;
+        let _ = AUTH_PROFILE.set(profile);
+    }
+
     // setup diskcache dir response caching
     let diskcache_dir = match &args.flag_disk_cache_dir {
         Some(dir) => {
@@ -531,16 +672,132 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     };
     log::info!("Cache Type: {cache_type:?}");
 
+    let rate_limit = match args.flag_rate_limit {
+        // safety: u32::MAX is non-zero
+        0 => NonZeroU32::new(u32::MAX).unwrap(),
+        // safety: matched arm guarantees value is in 1..=1000
+        1..=1000 => NonZeroU32::new(args.flag_rate_limit).unwrap(),
+        _ => {
+            return fail_incorrectusage_clierror!(
+                "Rate Limit should be between 0 to 1000 queries per second."
+            );
+        },
+    };
+    debug!("RATE LIMIT: {rate_limit}");
+
+    if args.flag_concurrency == 0 {
+        return fail_incorrectusage_clierror!("--concurrency must be at least 1.");
+    }
+
+    if args.flag_checkpoint.is_some() {
+        if args.flag_paginate.is_some() {
+            return fail_incorrectusage_clierror!("--checkpoint is not supported with --paginate.");
+        }
+        checkpoint::check_output(args.flag_output.as_ref())?;
+    }
+
+    let http_headers: HeaderMap = {
+        let mut map = HeaderMap::with_capacity(args.flag_http_header.len() + 1);
+        for header in &args.flag_http_header {
+            let vals: Vec<&str> = header.split(':').collect();
+
+            if vals.len() != 2 {
+                return fail_incorrectusage_clierror!(
+                    "{vals:?} is not a valid key-value pair. Expecting a key and a value \
+                     separated by a colon."
+                );
+            }
+
+            // allocate new String for header key to put into map
+            let k: String = String::from(vals[0].trim());
+            let header_name: HeaderName =
+                match HeaderName::from_lowercase(k.to_lowercase().as_bytes()) {
+                    Ok(h) => h,
+                    Err(e) => return fail_incorrectusage_clierror!("Invalid header name: {e}"),
+                };
+
+            // allocate new String for header value to put into map
+            let v: String = String::from(vals[1].trim());
+            let header_val: HeaderValue = match HeaderValue::from_str(v.as_str()) {
+                Ok(v) => v,
+                Err(e) => return fail_incorrectusage_clierror!("Invalid header value: {e}"),
+            };
+
+            map.append(header_name, header_val);
+        }
+
+        map.append(
+            reqwest::header::ACCEPT_ENCODING,
+            // safety: DEFAULT_ACCEPT_ENCODING is a static valid header value
+            HeaderValue::from_str(DEFAULT_ACCEPT_ENCODING).unwrap(),
+        );
+        map
+    };
+    debug!("HTTP Header: {http_headers:?}");
+
+    let client_timeout = time::Duration::from_secs(*TIMEOUT_SECS.get().unwrap_or(&30));
+
+    // --concurrency sends its requests with an async client, with the same settings
+    let async_client = if args.flag_concurrency > 1 && args.flag_paginate.is_none() {
+        Some(
+            util::reqwest_async_client_builder(
+                args.flag_user_agent.clone(),
+                u16::try_from(client_timeout.as_secs()).unwrap_or(u16::MAX),
+                None,
+            )?
+            .default_headers(http_headers.clone())
+            .cookie_store(args.flag_cookies)
+            .timeout(client_timeout)
+            .build()?,
+        )
+    } else {
+        None
+    };
+
+    let client = Client::builder()
+        .user_agent(util::set_user_agent(args.flag_user_agent.clone())?)
+        .default_headers(http_headers)
+        .cookie_store(args.flag_cookies)
+        .brotli(true)
+        .gzip(true)
+        .deflate(true)
+        .zstd(true)
+        .use_rustls_tls()
+        .http2_adaptive_window(true)
+        .connection_verbose(log_enabled!(Debug) || log_enabled!(Trace))
+        .timeout(client_timeout)
+        .build()?;
+
+    // set rate limiter with allow_burst set to 1 - see https://github.com/antifuchs/governor/issues/39
+    // (shared with the in-flight requests of --concurrency)
+    let limiter = Arc::new(RateLimiter::direct(
+        // safety: 1 is non-zero
+        Quota::per_second(rate_limit).allow_burst(NonZeroU32::new(1).unwrap()),
+    ));
+
+    if let Some(ref strategy) = args.flag_paginate {
+        return paginate::run(&args, strategy, &cache_type, &client, &limiter);
+    }
+
     let mut rconfig = Config::new(args.arg_input.as_ref())
         .delimiter(args.flag_delimiter)
         .trim(csv::Trim::All)
         .no_headers_flag(args.flag_no_headers);
 
+    // with --checkpoint, a run that died is resumed, appending to its output & report
+    let mut checkpoint = match args.flag_checkpoint {
+        Some(ref path) => Some(Checkpoint::open(path, argv, args.arg_input.as_deref())?),
+        None => None,
+    };
+    let resume = checkpoint
+        .as_ref()
+        .filter(|checkpoint| checkpoint.is_resumed());
+
     let mut rdr = rconfig.reader()?;
-    let mut wtr = if args.flag_new_column.is_some() {
+    let wtr_config = if args.flag_new_column.is_some() {
         // when adding a new column for the response, the output
         // is a regular CSV file
-        Config::new(args.flag_output.as_ref()).writer()?
+        Config::new(args.flag_output.as_ref())
     } else {
         // otherwise, the output is a JSONL file. So we need to configure
         // the csv writer so it doesn't double double quote the JSON response
@@ -548,7 +805,10 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         Config::new(args.flag_output.as_ref())
             .quote_style(csv::QuoteStyle::Never)
             .flexible(true)
-            .writer()?
+    };
+    let mut wtr = match resume {
+        Some(checkpoint) => wtr_config.resume_writer(checkpoint.output_len())?,
+        None => wtr_config.writer()?,
     };
 
     let mut headers = rdr.byte_headers()?.clone();
@@ -556,7 +816,9 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     let include_existing_columns = if let Some(name) = args.flag_new_column {
         // write header with new column
         headers.push_field(name.as_bytes());
-        wtr.write_byte_record(&headers)?;
+        if resume.is_none() {
+            wtr.write_byte_record(&headers)?;
+        }
         true
     } else {
         if args.flag_pretty {
@@ -608,78 +870,6 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         debug!("dynfmt_fields: {dynfmt_fields:?}  url_template: {dynfmt_url_template}");
     }
 
-    let rate_limit = match args.flag_rate_limit {
-        // safety: u32::MAX is non-zero
-        0 => NonZeroU32::new(u32::MAX).unwrap(),
-        // safety: matched arm guarantees value is in 1..=1000
-        1..=1000 => NonZeroU32::new(args.flag_rate_limit).unwrap(),
-        _ => {
-            return fail_incorrectusage_clierror!(
-                "Rate Limit should be between 0 to 1000 queries per second."
-            );
-        },
-    };
-    debug!("RATE LIMIT: {rate_limit}");
-
-    let http_headers: HeaderMap = {
-        let mut map = HeaderMap::with_capacity(args.flag_http_header.len() + 1);
-        for header in args.flag_http_header {
-            let vals: Vec<&str> = header.split(':').collect();
-
-            if vals.len() != 2 {
-                return fail_incorrectusage_clierror!(
-                    "{vals:?} is not a valid key-value pair. Expecting a key and a value \
-                     separated by a colon."
-                );
-            }
-
-            // allocate new String for header key to put into map
-            let k: String = String::from(vals[0].trim());
-            let header_name: HeaderName =
-                match HeaderName::from_lowercase(k.to_lowercase().as_bytes()) {
-                    Ok(h) => h,
-                    Err(e) => return fail_incorrectusage_clierror!("Invalid header name: {e}"),
-                };
-
-            // allocate new String for header value to put into map
-            let v: String = String::from(vals[1].trim());
-            let header_val: HeaderValue = match HeaderValue::from_str(v.as_str()) {
-                Ok(v) => v,
-                Err(e) => return fail_incorrectusage_clierror!("Invalid header value: {e}"),
-            };
-
-            map.append(header_name, header_val);
-        }
-
-        map.append(
-            reqwest::header::ACCEPT_ENCODING,
-            // safety: DEFAULT_ACCEPT_ENCODING is a static valid header value
-            HeaderValue::from_str(DEFAULT_ACCEPT_ENCODING).unwrap(),
-        );
-        map
-    };
-    debug!("HTTP Header: {http_headers:?}");
-
-    let client_timeout = time::Duration::from_secs(*TIMEOUT_SECS.get().unwrap_or(&30));
-    let client = Client::builder()
-        .user_agent(util::set_user_agent(args.flag_user_agent)?)
-        .default_headers(http_headers)
-        .cookie_store(args.flag_cookies)
-        .brotli(true)
-        .gzip(true)
-        .deflate(true)
-        .zstd(true)
-        .use_rustls_tls()
-        .http2_adaptive_window(true)
-        .connection_verbose(log_enabled!(Debug) || log_enabled!(Trace))
-        .timeout(client_timeout)
-        .build()?;
-
-    // set rate limiter with allow_burst set to 1 - see https://github.com/antifuchs/governor/issues/39
-    let limiter =
-        // safety: 1 is non-zero
-        RateLimiter::direct(Quota::per_second(rate_limit).allow_burst(NonZeroU32::new(1).unwrap()));
-
     // prep progress bars
     let show_progress =
         (args.flag_progressbar || util::get_envvar_flag("QSV_PROGRESSBAR")) && !rconfig.is_stdin();
@@ -737,6 +927,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
     let mut report_wtr;
     let report_path;
+    let mut report_file = None;
     if report == ReportKind::None {
         // no report, point report_wtr to /dev/null (AKA sink)
         report_wtr = Config::new(Some("sink".to_string()).as_ref()).writer()?;
@@ -747,9 +938,13 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             .clone()
             .unwrap_or_else(|| "stdin.csv".to_string());
 
-        report_wtr = Config::new(Some(report_path.clone() + FETCH_REPORT_SUFFIX).as_ref())
-            .delimiter(Some(Delimiter(b'\t')))
-            .writer()?;
+        report_file = Some(report_path.clone() + FETCH_REPORT_SUFFIX);
+        let report_config = Config::new(report_file.as_ref()).delimiter(Some(Delimiter(b'\t')));
+        if let Some(report_len) = resume.and_then(Checkpoint::report_len) {
+            report_wtr = report_config.resume_writer(report_len)?;
+        } else {
+            report_wtr = report_config.writer()?;
+        }
         let mut report_headers = if report == ReportKind::Detailed {
             headers.clone()
         } else {
@@ -773,7 +968,9 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         ];
         report_headers = csv::ByteRecord::from(report_header_fields);
 
-        report_wtr.write_byte_record(&report_headers)?;
+        if resume.is_none() {
+            report_wtr.write_byte_record(&report_headers)?;
+        }
     }
 
     // amortize memory allocations
@@ -810,20 +1007,28 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     let mut was_cached;
     let mut now = time::Instant::now();
 
-    while rdr.read_byte_record(&mut record)? {
-        if show_progress {
-            progress.inc(1);
+    // skip the records already written by the run we're resuming
+    let mut rows_written = 0_u64;
+    if let Some(checkpoint) = resume {
+        while rows_written < checkpoint.rows() && rdr.read_byte_record(&mut record)? {
+            rows_written += 1;
         }
-
-        if report != ReportKind::None {
-            now = time::Instant::now();
+        if show_progress {
+            progress.inc(rows_written);
         }
+        winfo!(
+            "Resuming from checkpoint: {} records already fetched.",
+            HumanCount(rows_written)
+        );
+    }
 
+    // construct the URL of a record
+    let mut record_url = |record: &csv::ByteRecord, url: &mut String| {
         if args.flag_url_template.is_some() {
             // we're using a URL template.
             // let's dynamically construct the URL with it
             record_vec.clear();
-            for field in &record {
+            for field in record {
                 record_vec.push(
                     simdutf8::basic::from_utf8(field)
                         .unwrap_or_default()
@@ -833,135 +1038,37 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             if let Ok(formatted) =
                 dynfmt2::SimpleCurlyFormat.format(&dynfmt_url_template, &*record_vec)
             {
-                url = formatted.into_owned();
+                *url = formatted.into_owned();
             }
         } else if let Ok(s) = simdutf8::basic::from_utf8(&record[column_index]) {
             // we're not using a URL template,
             // just use the field as-is as the URL
-            s.clone_into(&mut url);
+            s.clone_into(url);
         } else {
-            url = String::new();
-        }
-
-        if url.is_empty() {
-            final_response.clone_from(&empty_response);
-            was_cached = false;
-        } else {
-            match cache_type {
-                CacheType::InMemory => {
-                    intermediate_value = get_cached_response(
-                        &url,
-                        &client,
-                        &limiter,
-                        jaq_selector.as_ref(),
-                        args.flag_store_error,
-                        args.flag_pretty,
-                        include_existing_columns,
-                        args.flag_max_retries,
-                    );
-                    was_cached = intermediate_value.was_cached();
-                    final_response = intermediate_value.into_inner();
-                    if !args.flag_cache_error && final_response.status_code != 200 {
-                        let mut cache = GET_CACHED_RESPONSE.write();
-                        let _ = cache.cache_remove(&url);
-                    }
-                },
-                CacheType::Disk => {
-                    intermediate_value = get_diskcache_response(
-                        &url,
-                        &client,
-                        &limiter,
-                        jaq_selector.as_ref(),
-                        args.flag_store_error,
-                        args.flag_pretty,
-                        include_existing_columns,
-                        args.flag_max_retries,
-                    )?;
-                    was_cached = intermediate_value.was_cached();
-                    final_response = intermediate_value.into_inner();
-                    if was_cached {
-                        disk_cache_hits += 1;
-                        // log::debug!("Disk cache hit for {url} hit: {disk_cache_hits}");
-                    }
-                    if !args.flag_cache_error && final_response.status_code != 200 {
-                        let key = cross_session_cache_key(
-                            &url,
-                            jaq_selector.as_ref(),
-                            args.flag_store_error,
-                            args.flag_pretty,
-                            include_existing_columns,
-                        );
-                        let _ = GET_DISKCACHE_RESPONSE.cache_remove(&key);
-                        // log::debug!("Removed Disk cache for {url}");
-                    }
-                },
-                CacheType::Redis => {
-                    intermediate_redis_value = get_redis_response(
-                        &url,
-                        &client,
-                        &limiter,
-                        jaq_selector.as_ref(),
-                        args.flag_store_error,
-                        args.flag_pretty,
-                        include_existing_columns,
-                        args.flag_max_retries,
-                    )?;
-                    was_cached = intermediate_redis_value.was_cached();
-                    if was_cached {
-                        redis_cache_hits += 1;
-                    }
-                    final_response = match serde_json::from_str(&intermediate_redis_value) {
-                        Ok(r) => r,
-                        Err(e) => {
-                            return fail_clierror!(
-                                "Cannot deserialize Redis cache value. Try flushing the Redis \
-                                 cache with --flushdb: {e}"
-                            );
-                        },
-                    };
-                    if !args.flag_cache_error && final_response.status_code != 200 {
-                        let key = cross_session_cache_key(
-                            &url,
-                            jaq_selector.as_ref(),
-                            args.flag_store_error,
-                            args.flag_pretty,
-                            include_existing_columns,
-                        );
-
-                        if GET_REDIS_RESPONSE.cache_remove(&key).is_err() && log_enabled!(Warn) {
-                            // failure to remove cache keys is non-fatal. Continue, but log it.
-                            wwarn!(r#"Cannot remove Redis key "{key}""#);
-                        }
-                    }
-                },
-                CacheType::None => {
-                    final_response = get_response(
-                        &url,
-                        &client,
-                        &limiter,
-                        jaq_selector.as_ref(),
-                        args.flag_store_error,
-                        args.flag_pretty,
-                        include_existing_columns,
-                        args.flag_max_retries,
-                    );
-                    was_cached = false;
-                },
-            }
+            url.clear();
         }
+    };
 
-        if final_response.status_code == 200 {
+    // write the response of a record to the output & the report.
+    // Returns true once --max-errors is reached.
+    let mut write_response = |record: &mut csv::ByteRecord,
+                              url: &str,
+                              response: &FetchResponse,
+                              was_cached: bool,
+                              elapsed: time::Duration|
+     -> CliResult<bool> {
+        if response.status_code == 200 {
             running_success_count += 1;
         } else {
             running_error_count += 1;
             error_progress.inc(1);
         }
 
-        final_value.clone_from(&final_response.response);
+        final_value.clone_from(&response.response);
 
         if include_existing_columns {
             record.push_field(final_value.as_bytes());
-            wtr.write_byte_record(&record)?;
+            wtr.write_byte_record(record)?;
         } else {
             jsonl_record.clear();
             if final_value.is_empty() {
@@ -974,15 +1081,15 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
         if report != ReportKind::None {
             if report == ReportKind::Detailed {
-                report_record.clone_from(&record);
+                report_record.clone_from(record);
             } else {
                 report_record.clear();
             }
             report_record.push_field(url.as_bytes());
-            report_record.push_field(final_response.status_code.to_string().as_bytes());
+            report_record.push_field(response.status_code.to_string().as_bytes());
             report_record.push_field(if was_cached { b"1" } else { b"0" });
-            report_record.push_field(final_response.retries.to_string().as_bytes());
-            report_record.push_field(now.elapsed().as_millis().to_string().as_bytes());
+            report_record.push_field(response.retries.to_string().as_bytes());
+            report_record.push_field(elapsed.as_millis().to_string().as_bytes());
             if include_existing_columns {
                 report_record.push_field(final_value.as_bytes());
             } else {
@@ -991,8 +1098,250 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             report_wtr.write_byte_record(&report_record)?;
         }
 
-        if args.flag_max_errors > 0 && running_error_count >= args.flag_max_errors {
-            break;
+        rows_written += 1;
+        if let Some(ref mut checkpoint) = checkpoint
+            && checkpoint.is_due()
+        {
+            // flush everything written so far, so the checkpoint only covers complete records
+            // that are also in the cache
+            wtr.flush()?;
+            report_wtr.flush()?;
+            if cache_type == CacheType::Disk {
+                GET_DISKCACHE_RESPONSE
+                    .flush()
+                    .map_err(|e| CliError::Other(format!("Error flushing DiskCache: {e}")))?;
+            }
+            checkpoint.save(
+                rows_written,
+                args.flag_output.as_deref().unwrap_or_default(),
+                report_file.as_deref(),
+            )?;
+        }
+
+        Ok(args.flag_max_errors > 0 && running_error_count >= args.flag_max_errors)
+    };
+
+    if let Some(async_client) = async_client {
+        // --concurrency: look up each record in the cache as it's read, and keep the requests
+        // of the cache misses in flight, writing out the responses in the input order
+        let mut dispatcher = concurrent::Dispatcher::new(
+            async_client,
+            Arc::clone(&limiter),
+            concurrent::RequestSettings {
+                flag_jaq: jaq_selector.clone(),
+                flag_store_error: args.flag_store_error,
+                flag_pretty: args.flag_pretty,
+                include_existing_columns,
+                flag_max_retries: args.flag_max_retries,
+                timeout_secs: *TIMEOUT_SECS.get().unwrap_or(&30),
+            },
+            args.flag_concurrency,
+        )?;
+
+        'rows: loop {
+            let more_records = rdr.read_byte_record(&mut record)?;
+            if more_records {
+                if show_progress {
+                    progress.inc(1);
+                }
+
+                record_url(&record, &mut url);
+                let tag = (record.clone(), url.clone(), time::Instant::now());
+                if url.is_empty() {
+                    dispatcher.push_done(tag, empty_response.clone(), concurrent::Origin::Skipped);
+                } else {
+                    let key = match cache_type {
+                        CacheType::InMemory => Some(url.clone()),
+                        CacheType::Disk | CacheType::Redis => Some(cross_session_cache_key(
+                            &url,
+                            jaq_selector.as_ref(),
+                            args.flag_store_error,
+                            args.flag_pretty,
+                            include_existing_columns,
+                        )),
+                        CacheType::None => None,
+                    };
+                    let hit = match key {
+                        Some(ref key) => cache_lookup(&cache_type, key)?,
+                        None => None,
+                    };
+                    if let Some(hit) = hit {
+                        dispatcher.push_done(tag, hit, concurrent::Origin::Cache);
+                    } else {
+                        dispatcher.push_request(tag, url.clone(), None, key);
+                    }
+                }
+            }
+
+            // hand back the responses in order, with at most --concurrency records queued
+            while dispatcher.is_full() || (!more_records && !dispatcher.is_empty()) {
+                let Some(done) = dispatcher.pop()? else {
+                    break;
+                };
+                was_cached = match done.origin {
+                    concurrent::Origin::Skipped => false,
+                    concurrent::Origin::Cache => {
+                        match cache_type {
+                            CacheType::Disk => disk_cache_hits += 1,
+                            CacheType::Redis => redis_cache_hits += 1,
+                            _ => (),
+                        }
+                        true
+                    },
+                    concurrent::Origin::Network { key } => {
+                        if let Some(key) = key
+                            && (args.flag_cache_error || done.response.status_code == 200)
+                        {
+                            cache_store(&cache_type, key, &done.response)?;
+                        }
+                        false
+                    },
+                };
+                let (mut done_record, done_url, started) = done.tag;
+                if write_response(
+                    &mut done_record,
+                    done_url.as_str(),
+                    &done.response,
+                    was_cached,
+                    started.elapsed(),
+                )? {
+                    break 'rows;
+                }
+            }
+
+            if !more_records {
+                break;
+            }
+        }
+    } else {
+        while rdr.read_byte_record(&mut record)? {
+            if show_progress {
+                progress.inc(1);
+            }
+
+            if report != ReportKind::None {
+                now = time::Instant::now();
+            }
+
+            record_url(&record, &mut url);
+
+            if url.is_empty() {
+                final_response.clone_from(&empty_response);
+                was_cached = false;
+            } else {
+                match cache_type {
+                    CacheType::InMemory => {
+                        intermediate_value = get_cached_response(
+                            &url,
+                            &client,
+                            &limiter,
+                            jaq_selector.as_ref(),
+                            args.flag_store_error,
+                            args.flag_pretty,
+                            include_existing_columns,
+                            args.flag_max_retries,
+                        );
+                        was_cached = intermediate_value.was_cached();
+                        final_response = intermediate_value.into_inner();
+                        if !args.flag_cache_error && final_response.status_code != 200 {
+                            let mut cache = GET_CACHED_RESPONSE.write();
+                            let _ = cache.cache_remove(&url);
+                        }
+                    },
+                    CacheType::Disk => {
+                        intermediate_value = get_diskcache_response(
+                            &url,
+                            &client,
+                            &limiter,
+                            jaq_selector.as_ref(),
+                            args.flag_store_error,
+                            args.flag_pretty,
+                            include_existing_columns,
+                            args.flag_max_retries,
+                        )?;
+                        was_cached = intermediate_value.was_cached();
+                        final_response = intermediate_value.into_inner();
+                        if was_cached {
+                            disk_cache_hits += 1;
+                            // log::debug!("Disk cache hit for {url} hit: {disk_cache_hits}");
+                        }
+                        if !args.flag_cache_error && final_response.status_code != 200 {
+                            let key = cross_session_cache_key(
+                                &url,
+                                jaq_selector.as_ref(),
+                                args.flag_store_error,
+                                args.flag_pretty,
+                                include_existing_columns,
+                            );
+                            let _ = GET_DISKCACHE_RESPONSE.cache_remove(&key);
+                            // log::debug!("Removed Disk cache for {url}");
+                        }
+                    },
+                    CacheType::Redis => {
+                        intermediate_redis_value = get_redis_response(
+                            &url,
+                            &client,
+                            &limiter,
+                            jaq_selector.as_ref(),
+                            args.flag_store_error,
+                            args.flag_pretty,
+                            include_existing_columns,
+                            args.flag_max_retries,
+                        )?;
+                        was_cached = intermediate_redis_value.was_cached();
+                        if was_cached {
+                            redis_cache_hits += 1;
+                        }
+                        final_response = match serde_json::from_str(&intermediate_redis_value) {
+                            Ok(r) => r,
+                            Err(e) => {
+                                return fail_clierror!(
+                                    "Cannot deserialize Redis cache value. Try flushing the Redis \
+                                     cache with --flushdb: {e}"
+                                );
+                            },
+                        };
+                        if !args.flag_cache_error && final_response.status_code != 200 {
+                            let key = cross_session_cache_key(
+                                &url,
+                                jaq_selector.as_ref(),
+                                args.flag_store_error,
+                                args.flag_pretty,
+                                include_existing_columns,
+                            );
+
+                            if GET_REDIS_RESPONSE.cache_remove(&key).is_err() && log_enabled!(Warn)
+                            {
+                                // failure to remove cache keys is non-fatal. Continue, but log it.
+                                wwarn!(r#"Cannot remove Redis key "{key}""#);
+                            }
+                        }
+                    },
+                    CacheType::None => {
+                        final_response = get_response(
+                            &url,
+                            &client,
+                            &limiter,
+                            jaq_selector.as_ref(),
+                            args.flag_store_error,
+                            args.flag_pretty,
+                            include_existing_columns,
+                            args.flag_max_retries,
+                        );
+                        was_cached = false;
+                    },
+                }
+            }
+
+            if write_response(
+                &mut record,
+                url.as_str(),
+                &final_response,
+                was_cached,
+                now.elapsed(),
+            )? {
+                break;
+            }
         }
     }
 
@@ -1066,7 +1415,20 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             .map_err(|e| CliError::Other(format!("Error flushing DiskCache: {e}")))?;
     }
 
-    Ok(wtr.flush()?)
+    wtr.flush()?;
+    if let Some(mut checkpoint) = checkpoint {
+        if args.flag_max_errors > 0 && running_error_count >= args.flag_max_errors {
+            // stopped at --max-errors - keep the checkpoint, so the run can be resumed
+            checkpoint.save(
+                rows_written,
+                args.flag_output.as_deref().unwrap_or_default(),
+                report_file.as_deref(),
+            )?;
+        } else {
+            checkpoint.finish()?;
+        }
+    }
+    Ok(())
 }
 
 // we only need url in the cache key
@@ -1133,6 +1495,61 @@ fn cross_session_cache_key(
     format!("{url}{flag_jaq:?}{flag_store_error}{flag_pretty}{include_existing_columns}")
 }
 
+/// Look up a cached response without requesting it - for --concurrency, which
+/// requests the cache misses itself.
+fn cache_lookup(cache_type: &CacheType, key: &str) -> CliResult<Option<FetchResponse>> {
+    let key = key.to_string();
+    match cache_type {
+        CacheType::InMemory => Ok(GET_CACHED_RESPONSE
+            .write()
+            .cache_get(&key)
+            .map(|hit| (**hit).clone())),
+        CacheType::Disk => GET_DISKCACHE_RESPONSE
+            .cache_get(&key)
+            .map_err(|e| CliError::Other(format!("Diskcache Error: {e:?}"))),
+        CacheType::Redis => {
+            let Some(value) = GET_REDIS_RESPONSE
+                .cache_get(&key)
+                .map_err(|e| CliError::Other(format!("Redis Error: {e:?}")))?
+            else {
+                return Ok(None);
+            };
+            match serde_json::from_str(&value) {
+                Ok(r) => Ok(Some(r)),
+                Err(e) => fail_clierror!(
+                    "Cannot deserialize Redis cache value. Try flushing the Redis cache with \
+                     --flushdb: {e}"
+                ),
+            }
+        },
+        CacheType::None => Ok(None),
+    }
+}
+
+/// Cache a response requested by --concurrency.
+fn cache_store(cache_type: &CacheType, key: String, response: &FetchResponse) -> CliResult<()> {
+    match cache_type {
+        CacheType::InMemory => {
+            let _ = GET_CACHED_RESPONSE
+                .write()
+                .cache_set(key, Return::new(response.clone()));
+        },
+        CacheType::Disk => {
+            GET_DISKCACHE_RESPONSE
+                .cache_set(key, response.clone())
+                .map_err(|e| CliError::Other(format!("Diskcache Error: {e:?}")))?;
+        },
+        CacheType::Redis => {
+            // safety: FetchResponse only has String/u16/u8 fields - serialization is infallible
+            GET_REDIS_RESPONSE
+                .cache_set(key, simd_json::to_string(response).unwrap())
+                .map_err(|e| CliError::Other(format!("Redis Error: {e:?}")))?;
+        },
+        CacheType::None => (),
+    }
+    Ok(())
+}
+
 // this is a disk cache that can be used across qsv sessions
 // so we need to include the values of flag_jaq, flag_store_error, flag_pretty and
 // include_existing_columns in the cache key
@@ -1271,6 +1688,140 @@ pub fn parse_ratelimit_header_value(value: Option<&HeaderValue>, sentinel_value:
     })
 }
 
+/// Wait until the rate limiter allows another request, or give up after governor_timeout_ms
+fn wait_for_limiter(
+    limiter: &governor::RateLimiter<NotKeyed, InMemoryState, DefaultClock, NoOpMiddleware>,
+    governor_timeout_ms: u64,
+) {
+    let mut limiter_total_wait = 0_u64;
+    while limiter.check().is_err() {
+        limiter_total_wait += MINIMUM_WAIT_MS;
+        thread::sleep(MIN_WAIT);
+        if limiter_total_wait > governor_timeout_ms {
+            debug!("rate limit timed out after {limiter_total_wait} ms");
+            break;
+        } else if limiter_total_wait == MINIMUM_WAIT_MS {
+            debug!("throttling...");
+        }
+    }
+    if log_enabled!(Debug) && limiter_total_wait > 0 && limiter_total_wait <= governor_timeout_ms {
+        debug!("throttled for {limiter_total_wait} ms");
+    }
+}
+
+/// Check if the API response has ratelimit or retry-after headers
+fn has_ratelimit_headers(api_respheader: &HeaderMap) -> bool {
+    !api_respheader.is_empty()
+        && (api_respheader.contains_key("ratelimit-limit")
+            || api_respheader.contains_key("x-ratelimit-limit")
+            || api_respheader.contains_key("retry-after"))
+}
+
+/// Dynamically throttle as required by the ratelimit & retry-after headers of an API response.
+/// Returns false if the ratelimit resets after the timeout, in which case we shouldn't retry.
+fn throttle_per_headers(
+    api_respheader: &HeaderMap,
+    api_status: reqwest::StatusCode,
+    retries: u8,
+    timeout_secs: u64,
+) -> bool {
+    match ratelimit_pause(api_respheader, api_status, retries, timeout_secs) {
+        Some(pause) => {
+            if !pause.is_zero() {
+                thread::sleep(pause);
+            }
+            true
+        },
+        None => false,
+    }
+}
+
+/// How long to pause before retrying, as required by the ratelimit & retry-after headers
+/// of an API response. Returns None if the ratelimit resets after the timeout, in which
+/// case we shouldn't retry.
+fn ratelimit_pause(
+    api_respheader: &HeaderMap,
+    api_status: reqwest::StatusCode,
+    retries: u8,
+    timeout_secs: u64,
+) -> Option<Duration> {
+    let ratelimit_remaining = get_ratelimit_header_value(
+        api_respheader,
+        "ratelimit-remaining",
+        "x-ratelimit-remaining",
+    );
+
+    let ratelimit_reset =
+        get_ratelimit_header_value(api_respheader, "ratelimit-reset", "x-ratelimit-reset");
+
+    // some APIs add the "-second" suffix to ratelimit fields
+    let ratelimit_remaining_sec = get_ratelimit_header_value(
+        api_respheader,
+        "ratelimit-remaining-second",
+        "x-ratelimit-remaining-second",
+    );
+
+    let ratelimit_reset_sec = get_ratelimit_header_value(
+        api_respheader,
+        "ratelimit-reset-second",
+        "x-ratelimit-reset-second",
+    );
+
+    let retry_after = api_respheader.get("retry-after");
+
+    if log_enabled!(Debug) {
+        let rapidapi_proxy_response = api_respheader.get("X-RapidAPI-Proxy-Response");
+
+        debug!(
+            "api_status:{api_status:?} rate_limit_remaining:{ratelimit_remaining:?} \
+             {ratelimit_remaining_sec:?} ratelimit_reset:{ratelimit_reset:?} \
+             {ratelimit_reset_sec:?} retry_after:{retry_after:?} \
+             rapid_api_proxy_response:{rapidapi_proxy_response:?}"
+        );
+    }
+
+    // if there's a ratelimit_remaining field in the response header, get it
+    // otherwise, set remaining to sentinel value 9999
+    let remaining =
+        parse_ratelimit_header_value(ratelimit_remaining.or(ratelimit_remaining_sec), 9999);
+
+    // if there's a retry_after field in the response header, get it
+    // and set reset to it
+    let reset_secs = if let Some(retry_after) = retry_after {
+        // if we cannot parse its value as u64, the retry after value
+        // is most likely an rfc2822 date and not number of seconds to
+        // wait before retrying, which is a valid value
+        // however, we don't want to do date-parsing here, so we just
+        // wait timeout_secs seconds before retrying
+        atoi_simd::parse_pos::<u64, false>(retry_after.to_str().unwrap_or("").as_bytes())
+            .unwrap_or(timeout_secs)
+    } else {
+        parse_ratelimit_header_value(ratelimit_reset.or(ratelimit_reset_sec), 0)
+    };
+
+    // if reset_secs > timeout, then just time out and skip the retries
+    if reset_secs > timeout_secs {
+        warn!("Reset_secs {reset_secs} > timeout_secs {timeout_secs}.");
+        return None;
+    }
+
+    // if there is only one more remaining call per our ratelimit quota or reset >= 1,
+    // dynamically throttle and sleep for ~reset seconds
+    if remaining <= 1 || reset_secs >= 1 {
+        // we add a small random delta to how long fetch sleeps
+        // as we need to add a little jitter as per the spec to avoid thundering herd issues
+        // https://tools.ietf.org/id/draft-polli-ratelimit-headers-00.html#rfc.section.7.5
+        // we multiply by retries as a simple backoff multiplier
+        // we multiply reset_secs by 1001 instead of 1000 to give the server a teeny bit
+        // more breathing room before we hit it again
+        let pause_time = (reset_secs * 1001) + (retries as u64 * rand::rng().random_range(10..30));
+
+        debug!("sleeping for {pause_time} ms until ratelimit is reset/retry_after has elapsed");
+        return Some(Duration::from_millis(pause_time));
+    }
+    Some(Duration::ZERO)
+}
+
 #[inline]
 fn get_response(
     url: &str,
@@ -1285,37 +1836,11 @@ fn get_response(
     // validate the URL
     let valid_url = match Url::parse(url) {
         Ok(valid) => valid.to_string(),
-        Err(e) => {
-            let url_invalid_err = if flag_store_error {
-                if include_existing_columns {
-                    // the output is a CSV
-                    format!("Invalid URL: {e}")
-                } else {
-                    // the output is a JSONL file, so return the error
-                    // in a JSON API compliant format
-                    let json_error = json!({
-                        "errors": [{
-                            "title": "Invalid URL",
-                            "detail": e.to_string()
-                        }]
-                    });
-                    format!("{json_error}")
-                }
-            } else {
-                String::new()
-            };
-            error!("Invalid URL: Store_error: {flag_store_error} - {url_invalid_err}");
-            return FetchResponse {
-                response:    url_invalid_err,
-                status_code: reqwest::StatusCode::NOT_FOUND.as_u16(),
-                retries:     0_u8,
-            };
-        },
+        Err(e) => return invalid_url_response(&e, flag_store_error, include_existing_columns),
     };
     debug!("Using URL: {valid_url}");
 
     // wait until RateLimiter gives Okay or we timeout
-    let mut limiter_total_wait: u64;
     let timeout_secs = *TIMEOUT_SECS.get().unwrap_or(&30_u64);
     let governor_timeout_ms = timeout_secs * 1_000;
 
@@ -1324,99 +1849,31 @@ fn get_response(
     let mut final_value = String::new();
     let mut api_status;
     let mut api_respheader = HeaderMap::new();
-    let mut api_value = String::new();
-    let mut api_value_json_result: Result<serde_json::Value, serde_json::Error>;
-
-    let debug_flag = log_enabled!(Debug);
 
     // request with --max-retries
     'retry: loop {
         // check the rate-limiter
-        limiter_total_wait = 0;
-        while limiter.check().is_err() {
-            limiter_total_wait += MINIMUM_WAIT_MS;
-            thread::sleep(MIN_WAIT);
-            if limiter_total_wait > governor_timeout_ms {
-                debug!("rate limit timed out after {limiter_total_wait} ms");
-                break;
-            } else if limiter_total_wait == MINIMUM_WAIT_MS {
-                debug!("throttling...");
-            }
-        }
-        if debug_flag && limiter_total_wait > 0 && limiter_total_wait <= governor_timeout_ms {
-            debug!("throttled for {limiter_total_wait} ms");
-        }
+        wait_for_limiter(limiter, governor_timeout_ms);
 
         // send the actual request
-        if let Ok(resp) = client.get(&valid_url).send() {
+        if let Ok(resp) = client
+            .get(&valid_url)
+            .auth(AUTH_PROFILE.get())
+            .and_then(|req| Ok(req.send()?))
+        {
             // debug!("{resp:?}");
             api_respheader.clone_from(resp.headers());
             api_status = resp.status();
-            api_value = resp.text().unwrap_or_default();
-
-            if api_status.is_client_error() || api_status.is_server_error() {
-                error_flag = true;
-                error!(
-                    "HTTP error. url: {valid_url:?}, error: {:?}",
-                    api_status.canonical_reason().unwrap_or("unknown error")
-                );
-
-                if flag_store_error {
-                    final_value = format!(
-                        "HTTP ERROR {} - {}",
-                        api_status.as_str(),
-                        api_status.canonical_reason().unwrap_or("unknown error")
-                    );
-                } else {
-                    final_value = String::new();
-                }
-            } else {
-                error_flag = false;
-                // apply jaq selector if provided
-                if let Some(selectors) = flag_jaq {
-                    match process_jaq(&api_value, selectors) {
-                        Ok(s) => {
-                            final_value = s;
-                        },
-                        Err(e) => {
-                            error!(
-                                "jaq error. json: {api_value:?}, selectors: {selectors:?}, error: \
-                                 {e:?}"
-                            );
-
-                            if flag_store_error {
-                                final_value = e.to_string();
-                            } else {
-                                final_value = String::new();
-                            }
-                            error_flag = true;
-                        },
-                    }
-                } else {
-                    // validate the JSON response
-                    api_value_json_result = serde_json::from_str::<serde_json::Value>(&api_value);
-                    match api_value_json_result {
-                        Ok(api_value_json) => {
-                            if flag_pretty {
-                                final_value = format!("{api_value_json:#}");
-                            } else {
-                                // use serde_json CompactFormatter to minify the JSON
-                                final_value = format!("{api_value_json}");
-                            }
-                        },
-                        Err(e) => {
-                            error!("json error. json: {api_value:?}, error: {e:?}");
-
-                            if flag_store_error {
-                                final_value = e.to_string();
-                            } else {
-                                final_value = String::new();
-                            }
-                            error_flag = true;
-                        },
-                    }
-                }
-            }
+            let api_value = resp.text().unwrap_or_default();
+
+            (final_value, error_flag) = process_response(
+                api_status,
+                &api_value,
+                &valid_url,
+                flag_jaq,
+                flag_store_error,
+                flag_pretty,
+            );
         } else {
             error_flag = true;
             api_respheader.clear();
@@ -1427,90 +1884,11 @@ fn get_response(
 
         // check if there's an API error (likely 503-service not available or 493-too many requests)
         // or if the API has ratelimits and we need to do dynamic throttling to respect limits
-        if error_flag
-            || (!api_respheader.is_empty()
-                && (api_respheader.contains_key("ratelimit-limit")
-                    || api_respheader.contains_key("x-ratelimit-limit")
-                    || api_respheader.contains_key("retry-after")))
-        {
-            let ratelimit_remaining = get_ratelimit_header_value(
-                &api_respheader,
-                "ratelimit-remaining",
-                "x-ratelimit-remaining",
-            );
-
-            let ratelimit_reset =
-                get_ratelimit_header_value(&api_respheader, "ratelimit-reset", "x-ratelimit-reset");
-
-            // some APIs add the "-second" suffix to ratelimit fields
-            let ratelimit_remaining_sec = get_ratelimit_header_value(
-                &api_respheader,
-                "ratelimit-remaining-second",
-                "x-ratelimit-remaining-second",
-            );
-
-            let ratelimit_reset_sec = get_ratelimit_header_value(
-                &api_respheader,
-                "ratelimit-reset-second",
-                "x-ratelimit-reset-second",
-            );
-
-            let retry_after = api_respheader.get("retry-after");
-
-            if debug_flag {
-                let rapidapi_proxy_response = api_respheader.get("X-RapidAPI-Proxy-Response");
-
-                debug!(
-                    "api_status:{api_status:?} rate_limit_remaining:{ratelimit_remaining:?} \
-                     {ratelimit_remaining_sec:?} ratelimit_reset:{ratelimit_reset:?} \
-                     {ratelimit_reset_sec:?} retry_after:{retry_after:?} \
-                     rapid_api_proxy_response:{rapidapi_proxy_response:?}"
-                );
-            }
-
-            // if there's a ratelimit_remaining field in the response header, get it
-            // otherwise, set remaining to sentinel value 9999
-            let remaining =
-                parse_ratelimit_header_value(ratelimit_remaining.or(ratelimit_remaining_sec), 9999);
-
-            // if there's a retry_after field in the response header, get it
-            // and set reset to it
-            let reset_secs = if let Some(retry_after) = retry_after {
-                // if we cannot parse its value as u64, the retry after value
-                // is most likely an rfc2822 date and not number of seconds to
-                // wait before retrying, which is a valid value
-                // however, we don't want to do date-parsing here, so we just
-                // wait timeout_secs seconds before retrying
-                atoi_simd::parse_pos::<u64, false>(retry_after.to_str().unwrap_or("").as_bytes())
-                    .unwrap_or(timeout_secs)
-            } else {
-                parse_ratelimit_header_value(ratelimit_reset.or(ratelimit_reset_sec), 0)
-            };
-
-            // if reset_secs > timeout, then just time out and skip the retries
-            if reset_secs > timeout_secs {
-                warn!("Reset_secs {reset_secs} > timeout_secs {timeout_secs}.");
+        if error_flag || has_ratelimit_headers(&api_respheader) {
+            if !throttle_per_headers(&api_respheader, api_status, retries, timeout_secs) {
                 break 'retry;
             }
 
-            // if there is only one more remaining call per our ratelimit quota or reset >= 1,
-            // dynamically throttle and sleep for ~reset seconds
-            if remaining <= 1 || reset_secs >= 1 {
-                // we add a small random delta to how long fetch sleeps
-                // as we need to add a little jitter as per the spec to avoid thundering herd issues
-                // https://tools.ietf.org/id/draft-polli-ratelimit-headers-00.html#rfc.section.7.5
-                // we multiply by retries as a simple backoff multiplier
-                // we multiply reset_secs by 1001 instead of 1000 to give the server a teeny bit
-                // more breathing room before we hit it again
-                let pause_time =
-                    (reset_secs * 1001) + (retries as u64 * rand::rng().random_range(10..30));
-
-                debug!(
-                    "sleeping for {pause_time} ms until ratelimit is reset/retry_after has elapsed"
-                );
-                thread::sleep(time::Duration::from_millis(pause_time));
-            }
-
             if retries >= flag_max_retries {
                 wwarn!("{flag_max_retries} max-retries reached.");
                 break 'retry;
@@ -1523,6 +1901,155 @@ fn get_response(
         }
     } // end retry loop
 
+    final_response(
+        error_flag,
+        final_value,
+        api_status,
+        retries,
+        flag_store_error,
+        include_existing_columns,
+    )
+}
+
+/// The response for a URL that could not be parsed.
+fn invalid_url_response(
+    e: &url::ParseError,
+    flag_store_error: bool,
+    include_existing_columns: bool,
+) -> FetchResponse {
+    let url_invalid_err = if flag_store_error {
+        if include_existing_columns {
+            // the output is a CSV
+            format!("Invalid URL: {e}")
+        } else {
+            // the output is a JSONL file, so return the error
+            // in a JSON API compliant format
+            let json_error = json!({
+                "errors": [{
+                    "title": "Invalid URL",
+                    "detail": e.to_string()
+                }]
+            });
+            format!("{json_error}")
+        }
+    } else {
+        String::new()
+    };
+    error!("Invalid URL: Store_error: {flag_store_error} - {url_invalid_err}");
+    FetchResponse {
+        response:    url_invalid_err,
+        status_code: reqwest::StatusCode::NOT_FOUND.as_u16(),
+        retries:     0_u8,
+    }
+}
+
+/// The response of a request that could not be authenticated with the --auth profile,
+/// e.g. because its secret is missing or its OAuth2 token could not be refreshed.
+fn auth_error_response(
+    e: &CliError,
+    flag_store_error: bool,
+    include_existing_columns: bool,
+) -> FetchResponse {
+    let auth_err = if flag_store_error {
+        if include_existing_columns {
+            // the output is a CSV
+            format!("Authentication failed: {e}")
+        } else {
+            // the output is a JSONL file, so return the error
+            // in a JSON API compliant format
+            let json_error = json!({
+                "errors": [{
+                    "title": "Authentication failed",
+                    "detail": e.to_string()
+                }]
+            });
+            format!("{json_error}")
+        }
+    } else {
+        String::new()
+    };
+    FetchResponse {
+        response:    auth_err,
+        status_code: reqwest::StatusCode::UNAUTHORIZED.as_u16(),
+        retries:     0_u8,
+    }
+}
+
+/// Apply the jaq selector to - or otherwise validate & format - the body of an API response.
+/// Returns the value to store and whether it is an error.
+fn process_response(
+    api_status: reqwest::StatusCode,
+    api_value: &str,
+    valid_url: &str,
+    flag_jaq: Option<&String>,
+    flag_store_error: bool,
+    flag_pretty: bool,
+) -> (String, bool) {
+    if api_status.is_client_error() || api_status.is_server_error() {
+        error!(
+            "HTTP error. url: {valid_url:?}, error: {:?}",
+            api_status.canonical_reason().unwrap_or("unknown error")
+        );
+
+        let final_value = if flag_store_error {
+            format!(
+                "HTTP ERROR {} - {}",
+                api_status.as_str(),
+                api_status.canonical_reason().unwrap_or("unknown error")
+            )
+        } else {
+            String::new()
+        };
+        return (final_value, true);
+    }
+
+    // apply jaq selector if provided
+    if let Some(selectors) = flag_jaq {
+        match process_jaq(api_value, selectors) {
+            Ok(s) => (s, false),
+            Err(e) => {
+                error!("jaq error. json: {api_value:?}, selectors: {selectors:?}, error: {e:?}");
+
+                if flag_store_error {
+                    (e.to_string(), true)
+                } else {
+                    (String::new(), true)
+                }
+            },
+        }
+    } else {
+        // validate the JSON response
+        match serde_json::from_str::<serde_json::Value>(api_value) {
+            Ok(api_value_json) => {
+                if flag_pretty {
+                    (format!("{api_value_json:#}"), false)
+                } else {
+                    // use serde_json CompactFormatter to minify the JSON
+                    (format!("{api_value_json}"), false)
+                }
+            },
+            Err(e) => {
+                error!("json error. json: {api_value:?}, error: {e:?}");
+
+                if flag_store_error {
+                    (e.to_string(), true)
+                } else {
+                    (String::new(), true)
+                }
+            },
+        }
+    }
+}
+
+/// The response stored for a record once its retries are done.
+fn final_response(
+    error_flag: bool,
+    final_value: String,
+    api_status: reqwest::StatusCode,
+    retries: u8,
+    flag_store_error: bool,
+    include_existing_columns: bool,
+) -> FetchResponse {
     if error_flag {
         if flag_store_error && !include_existing_columns {
             let json_error = json!({
@@ -0,0 +1,354 @@
+//! `fetch --concurrency` & `fetchpost --concurrency`: keep up to N requests in flight.
+//!
+//! Records are still read, looked up in the cache & written out one at a time, in order.
+//! Only the requests of cache misses run concurrently on a tokio runtime. They share the
+//! `--rate-limit` governor, and when an API asks to back off (with its rate-limit or
+//! retry-after headers), all of them wait - not just the request that was told to.
+//! Responses are handed back in the order their records were queued, so the output is
+//! the same as a sequential run's.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use governor::{
+    clock::DefaultClock,
+    middleware::NoOpMiddleware,
+    state::{InMemoryState, direct::NotKeyed},
+};
+use log::debug;
+use reqwest::{Client, StatusCode, header::HeaderMap};
+use tokio::{runtime::Runtime, task::JoinHandle, time::Instant};
+use url::Url;
+
+use super::{
+    AUTH_PROFILE, FetchResponse, auth_error_response, final_response, has_ratelimit_headers,
+    invalid_url_response, process_response, ratelimit_pause,
+};
+use crate::{CliError, CliResult, auth::Authorize};
+
+type Limiter = governor::RateLimiter<NotKeyed, InMemoryState, DefaultClock, NoOpMiddleware>;
+
+/// The flags that shape every response of a job.
+pub struct RequestSettings {
+    pub flag_jaq:                 Option<String>,
+    pub flag_store_error:         bool,
+    pub flag_pretty:              bool,
+    pub include_existing_columns: bool,
+    pub flag_max_retries:         u8,
+    pub timeout_secs:             u64,
+}
+
+/// Where the response of a record came from.
+pub enum Origin {
+    /// The record has no URL, so nothing was requested.
+    Skipped,
+    /// A cache hit, or the response of an identical request that was already in flight.
+    Cache,
+    /// A response from the API, to be cached under `key` (if caching is enabled).
+    Network { key: Option<String> },
+}
+
+/// The response of a queued record, along with the tag it was queued with.
+pub struct Completed<T> {
+    pub tag:      T,
+    pub response: FetchResponse,
+    pub origin:   Origin,
+}
+
+enum Slot {
+    Done(FetchResponse, Origin),
+    InFlight {
+        key:     Option<String>,
+        // whether queued duplicates may wait on this request
+        tracked: bool,
+        handle:  JoinHandle<FetchResponse>,
+    },
+    // waits on the tracked in-flight request with the same cache key
+    Duplicate(String),
+}
+
+struct Shared {
+    client:    Client,
+    limiter:   Arc<Limiter>,
+    settings:  RequestSettings,
+    // when an API asks us to back off, every request waits until then
+    resume_at: Mutex<Option<Instant>>,
+}
+
+pub struct Dispatcher<T> {
+    runtime:  Runtime,
+    shared:   Arc<Shared>,
+    window:   usize,
+    queue:    VecDeque<(T, Slot)>,
+    // for each tracked request: the number of queued duplicates waiting on it,
+    // and its response once it has been popped
+    inflight: HashMap<String, (usize, Option<FetchResponse>)>,
+}
+
+impl<T> Dispatcher<T> {
+    pub fn new(
+        client: Client,
+        limiter: Arc<Limiter>,
+        settings: RequestSettings,
+        concurrency: usize,
+    ) -> CliResult<Self> {
+        Ok(Self {
+            runtime:  Runtime::new()?,
+            shared:   Arc::new(Shared {
+                client,
+                limiter,
+                settings,
+                resume_at: Mutex::new(None),
+            }),
+            window:   concurrency.max(1),
+            queue:    VecDeque::with_capacity(concurrency),
+            inflight: HashMap::new(),
+        })
+    }
+
+    /// Queue a record whose response is already known.
+    pub fn push_done(&mut self, tag: T, response: FetchResponse, origin: Origin) {
+        self.queue.push_back((tag, Slot::Done(response, origin)));
+    }
+
+    /// Queue a record whose response has to be requested - a GET, or a POST of `body`.
+    /// The request starts right away, unless an identical one (same cache `key`) is
+    /// already in flight, in which case the record gets its response.
+    pub fn push_request(
+        &mut self,
+        tag: T,
+        url: String,
+        body: Option<Vec<u8>>,
+        key: Option<String>,
+    ) {
+        let mut tracked = false;
+        if let Some(ref key) = key {
+            match self.inflight.get_mut(key) {
+                Some((waiting, None)) => {
+                    *waiting += 1;
+                    self.queue.push_back((tag, Slot::Duplicate(key.clone())));
+                    return;
+                },
+                // the earlier request is done but its response wasn't cached
+                // (e.g. an error without --cache-error), so request it again
+                Some((_, Some(_))) => (),
+                None => {
+                    self.inflight.insert(key.clone(), (0, None));
+                    tracked = true;
+                },
+            }
+        }
+        let handle = self
+            .runtime
+            .spawn(send_request(Arc::clone(&self.shared), url, body));
+        self.queue.push_back((
+            tag,
+            Slot::InFlight {
+                key,
+                tracked,
+                handle,
+            },
+        ));
+    }
+
+    /// Whether --concurrency records are queued, so the oldest one should be popped
+    /// before queueing another.
+    pub fn is_full(&self) -> bool {
+        self.queue.len() >= self.window
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Wait for the response of the oldest queued record.
+    pub fn pop(&mut self) -> CliResult<Option<Completed<T>>> {
+        let Some((tag, slot)) = self.queue.pop_front() else {
+            return Ok(None);
+        };
+        let (response, origin) = match slot {
+            Slot::Done(response, origin) => (response, origin),
+            Slot::InFlight {
+                key,
+                tracked,
+                handle,
+            } => {
+                let response = self
+                    .runtime
+                    .block_on(handle)
+                    .map_err(|e| CliError::Other(format!("Request task failed: {e}")))?;
+                if tracked && let Some(ref key) = key {
+                    match self.inflight.get_mut(key) {
+                        Some((waiting, done)) if *waiting > 0 => *done = Some(response.clone()),
+                        _ => {
+                            self.inflight.remove(key);
+                        },
+                    }
+                }
+                (response, Origin::Network { key })
+            },
+            Slot::Duplicate(key) => {
+                // safety: a duplicate is always queued after the request it waits on,
+                // so that request was popped & its response recorded before
+                let (waiting, done) = self.inflight.get_mut(&key).unwrap();
+                *waiting -= 1;
+                let response = if *waiting == 0 {
+                    self.inflight.remove(&key).and_then(|(_, done)| done)
+                } else {
+                    done.clone()
+                };
+                (response.unwrap(), Origin::Cache)
+            },
+        };
+        Ok(Some(Completed {
+            tag,
+            response,
+            origin,
+        }))
+    }
+}
+
+impl Shared {
+    /// Wait out any backoff requested by the API, then for the rate limiter to give
+    /// the Okay - or until we time out, as the sequential fetch does.
+    async fn wait_for_turn(&self) {
+        // copy the instant out, so the lock isn't held across the await
+        let resume_at = *self.resume_at.lock().unwrap();
+        if let Some(resume_at) = resume_at {
+            tokio::time::sleep_until(resume_at).await;
+        }
+
+        let governor_timeout = Duration::from_secs(self.settings.timeout_secs);
+        if tokio::time::timeout(governor_timeout, self.limiter.until_ready())
+            .await
+            .is_err()
+        {
+            debug!(
+                "rate limit timed out after {} ms",
+                governor_timeout.as_millis()
+            );
+        }
+    }
+
+    /// Pause this request - and have every other request wait as well.
+    async fn back_off(&self, pause: Duration) {
+        let until = Instant::now() + pause;
+        {
+            let mut resume_at = self.resume_at.lock().unwrap();
+            if resume_at.is_none_or(|at| at < until) {
+                *resume_at = Some(until);
+            }
+        }
+        tokio::time::sleep_until(until).await;
+    }
+}
+
+/// The async counterpart of fetch's `get_response`, with the same retry, throttling
+/// and error handling.
+async fn send_request(shared: Arc<Shared>, url: String, body: Option<Vec<u8>>) -> FetchResponse {
+    let settings = &shared.settings;
+
+    // validate the URL
+    let valid_url = match Url::parse(&url) {
+        Ok(valid) => valid.to_string(),
+        Err(e) => {
+            return invalid_url_response(
+                &e,
+                settings.flag_store_error,
+                settings.include_existing_columns,
+            );
+        },
+    };
+    debug!("Using URL: {valid_url}");
+
+    let mut retries = 0_u8;
+    let mut error_flag;
+    let mut final_value = String::new();
+    let mut api_status;
+    let mut api_respheader = HeaderMap::new();
+
+    // request with --max-retries
+    loop {
+        shared.wait_for_turn().await;
+
+        // send the actual request
+        let request = match body {
+            Some(ref body) => shared.client.post(&valid_url).body(body.clone()),
+            None => shared.client.get(&valid_url),
+        };
+        // authenticating may request an OAuth2 token with a blocking client,
+        // so it runs off the runtime's worker threads
+        let request = match AUTH_PROFILE.get() {
+            Some(profile) => tokio::task::spawn_blocking(move || request.auth(Some(profile)))
+                .await
+                .unwrap_or_else(|e| fail_clierror!("Authentication task failed: {e}")),
+            None => Ok(request),
+        };
+        // an authentication error - a missing secret, an unreadable secret file or a
+        // failed token refresh - won't go away with a retry
+        let request = match request {
+            Ok(request) => request,
+            Err(e) => {
+                wwarn!("Cannot authenticate the request to {valid_url}: {e}");
+                return auth_error_response(
+                    &e,
+                    settings.flag_store_error,
+                    settings.include_existing_columns,
+                );
+            },
+        };
+        if let Ok(resp) = request.send().await {
+            api_respheader.clone_from(resp.headers());
+            api_status = resp.status();
+            let api_value = resp.text().await.unwrap_or_default();
+
+            (final_value, error_flag) = process_response(
+                api_status,
+                &api_value,
+                &valid_url,
+                settings.flag_jaq.as_ref(),
+                settings.flag_store_error,
+                settings.flag_pretty,
+            );
+        } else {
+            error_flag = true;
+            api_respheader.clear();
+            api_status = StatusCode::BAD_REQUEST;
+        }
+
+        // check if there's an API error or if the API has ratelimits
+        // and we need to do dynamic throttling to respect limits
+        if error_flag || has_ratelimit_headers(&api_respheader) {
+            let Some(pause) =
+                ratelimit_pause(&api_respheader, api_status, retries, settings.timeout_secs)
+            else {
+                break;
+            };
+            if !pause.is_zero() {
+                shared.back_off(pause).await;
+            }
+
+            if retries >= settings.flag_max_retries {
+                wwarn!("{} max-retries reached.", settings.flag_max_retries);
+                break;
+            }
+            retries += 1;
+            debug!("retrying {retries}...");
+        } else {
+            // there's no request error or ratelimits nor retry-after
+            break;
+        }
+    }
+
+    final_response(
+        error_flag,
+        final_value,
+        api_status,
+        retries,
+        settings.flag_store_error,
+        settings.include_existing_columns,
+    )
+}
@@ -0,0 +1,492 @@
+//! `fetch --paginate`: ingest the records of a paginated REST API to a CSV.
+//!
+//! As each page determines the next one, pages are fetched sequentially - rate limited,
+//! retried & cached like the responses of a regular fetch. The records of all the pages are
+//! flattened at the end, as the CSV header is the union of the keys of all the records.
+
+use std::{fs, sync::OnceLock};
+
+use cached::{ConcurrentCached, RedbCache, RedisCache, Return, macros::concurrent_cached};
+use foldhash::{HashSet, HashSetExt};
+use governor::{
+    clock::DefaultClock,
+    middleware::NoOpMiddleware,
+    state::{InMemoryState, direct::NotKeyed},
+};
+use jaq_core::{Ctx, Vars, data, unwrap_valr};
+use jaq_json::Val;
+use log::{debug, error, info};
+use reqwest::{
+    blocking::Client,
+    header::{HeaderMap, LINK},
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::{
+    AUTH_PROFILE, Args, CacheType, DISKCACHE_DIR, DISKCACHECONFIG, REDISCONFIG, TIMEOUT_SECS,
+    compile_jaq_filter, format_val, has_ratelimit_headers, throttle_per_headers, wait_for_limiter,
+};
+use crate::{CliError, CliResult, auth::Authorize, cmd::json, config::Config};
+
+// the elements of a top-level array, or the page itself
+const DEFAULT_RECORDS_SELECTOR: &str = r#"if type == "array" then .[] else . end"#;
+
+// what a page depends on besides its URL - the --auth profile & the -H headers it is
+// requested with - so runs with different credentials or headers don't share cached pages
+static PAGE_CACHE_CONTEXT: OnceLock<String> = OnceLock::new();
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Strategy {
+    Link,
+    Offset,
+    Page,
+    Cursor,
+}
+
+impl Strategy {
+    fn parse(strategy: &str) -> CliResult<Self> {
+        match strategy.to_ascii_lowercase().as_str() {
+            "link" => Ok(Self::Link),
+            "offset" => Ok(Self::Offset),
+            "page" => Ok(Self::Page),
+            "cursor" => Ok(Self::Cursor),
+            _ => fail_incorrectusage_clierror!(
+                r#"Invalid --paginate strategy "{strategy}". Use link, offset, page or cursor."#
+            ),
+        }
+    }
+
+    const fn default_param(self) -> &'static str {
+        match self {
+            Self::Link => "",
+            Self::Offset => "offset",
+            Self::Page => "page",
+            Self::Cursor => "cursor",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct PageResponse {
+    body:        String,
+    // the absolute URL of the rel="next" Link header, if any
+    next_link:   Option<String>,
+    status_code: u16,
+    retries:     u8,
+}
+
+pub fn run(
+    args: &Args,
+    strategy: &str,
+    cache_type: &CacheType,
+    client: &Client,
+    limiter: &governor::RateLimiter<NotKeyed, InMemoryState, DefaultClock, NoOpMiddleware>,
+) -> CliResult<()> {
+    let strategy = Strategy::parse(strategy)?;
+    let start_url = args.arg_url.as_deref().unwrap_or_default();
+    let mut url = match Url::parse(start_url) {
+        Ok(url) => url,
+        Err(e) => return fail_incorrectusage_clierror!(r#"Invalid URL "{start_url}": {e}"#),
+    };
+
+    let records_filter = compile_jaq_filter(&match args.flag_jaqfile {
+        Some(ref jaq_file) => fs::read_to_string(jaq_file)?,
+        None => args
+            .flag_jaq
+            .clone()
+            .unwrap_or_else(|| DEFAULT_RECORDS_SELECTOR.to_string()),
+    })?;
+
+    // the headers are normalized & sorted, so their order on the command line doesn't matter
+    let mut http_headers: Vec<String> = args
+        .flag_http_header
+        .iter()
+        .map(|header| {
+            let (name, value) = header.split_once(':').unwrap_or((header, ""));
+            format!("{}:{}", name.trim().to_lowercase(), value.trim())
+        })
+        .collect();
+    http_headers.sort_unstable();
+    let _ = PAGE_CACHE_CONTEXT.set(format!("{:?}{http_headers:?}", args.flag_auth));
+    let next_filter = match (strategy, &args.flag_next) {
+        (Strategy::Cursor, Some(next)) => Some(compile_jaq_filter(next)?),
+        (Strategy::Cursor, None) => {
+            return fail_incorrectusage_clierror!(
+                "The cursor strategy requires the --next option."
+            );
+        },
+        _ => None,
+    };
+    let page_param = args
+        .flag_page_param
+        .as_deref()
+        .unwrap_or(strategy.default_param());
+
+    // start from the offset or page number of the URL, if it has one
+    let mut position: u64 = match strategy {
+        Strategy::Offset | Strategy::Page => url
+            .query_pairs()
+            .find(|(k, _)| k == page_param)
+            .and_then(|(_, v)| v.parse().ok())
+            .unwrap_or(u64::from(strategy == Strategy::Page)),
+        Strategy::Link | Strategy::Cursor => 0,
+    };
+
+    let mut records: Vec<serde_json::Value> = Vec::new();
+    let mut fetched: HashSet<String> = HashSet::new();
+    let mut pages = 0_u64;
+    let mut cache_hits = 0_u64;
+    loop {
+        if matches!(strategy, Strategy::Offset | Strategy::Page) {
+            set_query_param(&mut url, page_param, &position.to_string());
+            if args.flag_page_size > 0 {
+                set_query_param(
+                    &mut url,
+                    &args.flag_limit_param,
+                    &args.flag_page_size.to_string(),
+                );
+            }
+        }
+        // guard against APIs that loop back to a page we already have
+        if !fetched.insert(url.to_string()) {
+            wwarn!(r#"Pagination stopped: "{url}" was already fetched."#);
+            break;
+        }
+
+        let (page, was_cached) = get_page(args, cache_type, url.as_str(), client, limiter)?;
+        pages += 1;
+        if was_cached {
+            cache_hits += 1;
+        }
+        debug!(
+            "page {pages}: {url} - status: {} - retries: {} - cached: {was_cached}",
+            page.status_code, page.retries
+        );
+        if !(200..300).contains(&page.status_code) {
+            return fail_clierror!(
+                r#"Cannot fetch page {pages} "{url}": HTTP status {} after {} retries."#,
+                page.status_code,
+                page.retries
+            );
+        }
+
+        let body: Val = if page.body.trim().is_empty() {
+            Val::Null
+        } else {
+            serde_json::from_str(&page.body).map_err(|e| {
+                CliError::Other(format!(r#"Page {pages} "{url}" is not valid JSON: {e}"#))
+            })?
+        };
+
+        let page_records = run_filter(&records_filter, body.clone())?;
+        let page_len = page_records.len() as u64;
+        for record in page_records {
+            let record = json::val_to_json_value(record).map_err(CliError::Other)?;
+            records.push(if record.is_object() {
+                record
+            } else {
+                serde_json::json!({ "value": record })
+            });
+        }
+
+        if args.flag_max_pages > 0 && pages >= args.flag_max_pages {
+            break;
+        }
+        match strategy {
+            Strategy::Link => match page.next_link {
+                // safety: next_link was resolved to an absolute URL
+                Some(next) => url = Url::parse(&next).unwrap(),
+                None => break,
+            },
+            Strategy::Offset | Strategy::Page if page_len == 0 => break,
+            Strategy::Offset => position += page_len,
+            Strategy::Page => position += 1,
+            Strategy::Cursor => {
+                // safety: the cursor strategy requires --next
+                let next_filter = next_filter.as_ref().unwrap();
+                let cursor = run_filter(next_filter, body)?
+                    .first()
+                    .map(format_val)
+                    .unwrap_or_default();
+                if cursor.is_empty() {
+                    break;
+                }
+                if cursor.contains("://") || cursor.starts_with('/') {
+                    url = match url.join(&cursor) {
+                        Ok(next) => next,
+                        Err(e) => return fail_clierror!(r#"Invalid next URL "{cursor}": {e}"#),
+                    };
+                } else {
+                    set_query_param(&mut url, page_param, &cursor);
+                }
+            },
+        }
+    }
+
+    json::json_objects_to_csv(&records, Config::new(args.flag_output.as_ref()).writer()?)?;
+
+    if *cache_type == CacheType::Disk {
+        GET_DISKCACHE_PAGE
+            .flush()
+            .map_err(|e| CliError::Other(format!("Error flushing DiskCache: {e}")))?;
+    }
+
+    winfo!(
+        "{} records ingested from {pages} pages ({cache_hits} cached).",
+        records.len()
+    );
+    Ok(())
+}
+
+/// Run a jaq filter on a page, failing on runtime errors so no records go silently missing.
+fn run_filter(filter: &jaq_core::Filter<data::JustLut<Val>>, input: Val) -> CliResult<Vec<Val>> {
+    let ctx = Ctx::<data::JustLut<Val>>::new(&filter.lut, Vars::new([]));
+    let mut output = Vec::new();
+    for result in filter.id.run((ctx, input)).map(unwrap_valr) {
+        match result {
+            Ok(Val::Null) => {},
+            Ok(val) => output.push(val),
+            Err(e) => return fail_clierror!("jaq filter runtime error: {e}"),
+        }
+    }
+    Ok(output)
+}
+
+/// Set a query parameter of a URL, replacing its current value(s).
+fn set_query_param(url: &mut Url, name: &str, value: &str) {
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(k, _)| k != name)
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(pairs)
+        .append_pair(name, value);
+}
+
+/// The rel="next" URL of a response's Link headers (RFC 8288), resolved against the page URL.
+fn next_link(headers: &HeaderMap, page_url: &Url) -> Option<String> {
+    for value in headers.get_all(LINK) {
+        let Ok(mut rest) = value.to_str() else {
+            continue;
+        };
+        // each link is a <target> followed by its ;-separated parameters
+        while let Some(start) = rest.find('<') {
+            let end = start + rest[start..].find('>')?;
+            let target = &rest[start + 1..end];
+            rest = &rest[end + 1..];
+            let params = rest.find('<').map_or(rest, |next| &rest[..next]);
+            let is_next = params.split(';').any(|param| {
+                param
+                    .trim()
+                    .trim_end_matches(',')
+                    .strip_prefix("rel=")
+                    .is_some_and(|rel| {
+                        rel.trim_matches('"')
+                            .split_ascii_whitespace()
+                            .any(|rel| rel.eq_ignore_ascii_case("next"))
+                    })
+            });
+            if is_next {
+                return page_url.join(target.trim()).ok().map(String::from);
+            }
+        }
+    }
+    None
+}
+
+fn get_page(
+    args: &Args,
+    cache_type: &CacheType,
+    url: &str,
+    client: &Client,
+    limiter: &governor::RateLimiter<NotKeyed, InMemoryState, DefaultClock, NoOpMiddleware>,
+) -> CliResult<(PageResponse, bool)> {
+    // pages are only fetched once per session, so the in-memory cache is of no use
+    let (page, was_cached) = match cache_type {
+        CacheType::Disk => {
+            let page = get_diskcache_page(url, client, limiter, args.flag_max_retries)?;
+            let was_cached = page.was_cached();
+            (page.into_inner(), was_cached)
+        },
+        CacheType::Redis => {
+            let page = get_redis_page(url, client, limiter, args.flag_max_retries)?;
+            let was_cached = page.was_cached();
+            (page.into_inner(), was_cached)
+        },
+        CacheType::InMemory | CacheType::None => (
+            fetch_page(url, client, limiter, args.flag_max_retries),
+            false,
+        ),
+    };
+
+    if !args.flag_cache_error && page.status_code != 200 {
+        let key = page_cache_key(url);
+        match cache_type {
+            CacheType::Disk => {
+                let _ = GET_DISKCACHE_PAGE.cache_remove(&key);
+            },
+            CacheType::Redis => {
+                if GET_REDIS_PAGE.cache_remove(&key).is_err() {
+                    // failure to remove cache keys is non-fatal. Continue, but log it.
+                    wwarn!(r#"Cannot remove Redis key "{key}""#);
+                }
+            },
+            CacheType::InMemory | CacheType::None => {},
+        }
+    }
+    Ok((page, was_cached))
+}
+
+// The page cache key used by both the disk and redis caches, and when removing an error page.
+#[inline]
+fn page_cache_key(url: &str) -> String {
+    let context = PAGE_CACHE_CONTEXT.get().map_or("", String::as_str);
+    format!("{url}{context}")
+}
+
+// pages are cached in the same disk cache directory as regular fetch responses
+#[concurrent_cached(
+    disk = true,
+    ty = "cached::RedbCache<String, PageResponse>",
+    key = "String",
+    convert = r##"{ page_cache_key(url) }"##,
+    create = r##"{
+        let cache_dir = DISKCACHE_DIR.get().unwrap();
+        let diskcache_config = DISKCACHECONFIG.get().unwrap();
+        let mut diskcache_builder = RedbCache::builder("fetch_pages")
+            .disk_dir(cache_dir)
+            .refresh_on_hit(diskcache_config.ttl_refresh)
+            .durable(false);
+        // A zero TTL disables time-based expiration (entries are cached
+        // indefinitely). v3's RedbCache builder rejects .ttl(0), so only set a
+        // TTL when it is non-zero; leaving it unset means "never expire".
+        if !diskcache_config.ttl_secs.is_zero() {
+            diskcache_builder = diskcache_builder.ttl(diskcache_config.ttl_secs);
+        }
+        let diskcache: RedbCache<String, PageResponse> = diskcache_builder
+            .build()
+            .expect("error building pages diskcache");
+        log::info!("Pages disk cache created - dir: {cache_dir} - ttl: {ttl_secs:?}",
+            ttl_secs = diskcache_config.ttl_secs);
+        if let Err(e) = diskcache.remove_expired_entries() {
+            log::warn!("error removing expired pages diskcache entries: {e}");
+        }
+        diskcache
+    }"##,
+    map_error = r##"|e| CliError::Other(format!("Pages Diskcache Error: {e:?}"))"##,
+    with_cached_flag = true
+)]
+fn get_diskcache_page(
+    url: &str,
+    client: &Client,
+    limiter: &governor::RateLimiter<NotKeyed, InMemoryState, DefaultClock, NoOpMiddleware>,
+    max_retries: u8,
+) -> Result<Return<PageResponse>, CliError> {
+    Ok(Return::new(fetch_page(url, client, limiter, max_retries)))
+}
+
+#[concurrent_cached(
+    ty = "cached::RedisCache<String, PageResponse>",
+    key = "String",
+    convert = r##"{ page_cache_key(url) }"##,
+    create = r##" {
+        let redis_config = REDISCONFIG.get().unwrap();
+        let mut rediscache_builder = RedisCache::builder("pages")
+            .namespace("q")
+            .refresh_on_hit(redis_config.ttl_refresh)
+            .connection_string(&redis_config.conn_str)
+            .connection_pool_max_size(redis_config.max_pool_size);
+        // A zero TTL disables expiry (entries are cached indefinitely). v3's
+        // RedisCache builder rejects .ttl(0); leaving it unset means "never expire".
+        if !redis_config.ttl_secs.is_zero() {
+            rediscache_builder = rediscache_builder.ttl(redis_config.ttl_secs);
+        }
+        let rediscache: RedisCache<String, PageResponse> = rediscache_builder
+            .build()
+            .expect("error building pages redis cache");
+        log::info!("Pages Redis cache created - conn_str: {conn_str} - ttl: {ttl_secs:?}",
+            conn_str = redis_config.conn_str,
+            ttl_secs = redis_config.ttl_secs);
+        rediscache
+    } "##,
+    map_error = r##"|e| CliError::Other(format!("Pages Redis Error: {e:?}"))"##,
+    with_cached_flag = true
+)]
+fn get_redis_page(
+    url: &str,
+    client: &Client,
+    limiter: &governor::RateLimiter<NotKeyed, InMemoryState, DefaultClock, NoOpMiddleware>,
+    max_retries: u8,
+) -> Result<Return<PageResponse>, CliError> {
+    Ok(Return::new(fetch_page(url, client, limiter, max_retries)))
+}
+
+/// Fetch a page, retrying failed requests up to max_retries times.
+fn fetch_page(
+    url: &str,
+    client: &Client,
+    limiter: &governor::RateLimiter<NotKeyed, InMemoryState, DefaultClock, NoOpMiddleware>,
+    max_retries: u8,
+) -> PageResponse {
+    let timeout_secs = *TIMEOUT_SECS.get().unwrap_or(&30_u64);
+    // safety: url was parsed before it was passed in
+    let page_url = Url::parse(url).unwrap();
+
+    let mut retries = 0_u8;
+    loop {
+        wait_for_limiter(limiter, timeout_secs * 1_000);
+
+        let resp = client
+            .get(url)
+            .auth(AUTH_PROFILE.get())
+            .and_then(|req| Ok(req.send()?));
+        let (status, headers, body) = match resp {
+            Ok(resp) => {
+                let status = resp.status();
+                let headers = resp.headers().clone();
+                (status, headers, resp.text().unwrap_or_default())
+            },
+            Err(e) => {
+                error!("Cannot fetch {url}: {e}");
+                (
+                    reqwest::StatusCode::BAD_REQUEST,
+                    HeaderMap::new(),
+                    String::new(),
+                )
+            },
+        };
+
+        let failed = status.is_client_error() || status.is_server_error();
+        if !failed {
+            // a successful page is not retried, but we still respect its ratelimit headers
+            if has_ratelimit_headers(&headers) {
+                throttle_per_headers(&headers, status, retries, timeout_secs);
+            }
+            return PageResponse {
+                next_link: next_link(&headers, &page_url),
+                body,
+                status_code: status.as_u16(),
+                retries,
+            };
+        }
+
+        error!(
+            "HTTP error. url: {url:?}, error: {:?}",
+            status.canonical_reason().unwrap_or("unknown error")
+        );
+        if !throttle_per_headers(&headers, status, retries, timeout_secs) || retries >= max_retries
+        {
+            return PageResponse {
+                body,
+                next_link: None,
+                status_code: status.as_u16(),
+                retries,
+            };
+        }
+        retries += 1;
+        info!("retrying {url} ({retries})...");
+    }
+}
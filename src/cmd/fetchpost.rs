@@ -63,6 +63,10 @@ to be as fast as allowed. The --rate-limit option sets the maximum number of que
 (QPS) to be made. The default is 0, which means to go as fast as possible, automatically
 throttling as required, based on rate-limit and retry-after response headers.
 
+Requests are sent one at a time by default. Use --concurrency to keep several requests in
+flight at once - the output is still written in the order of the input, and all the requests
+share the --rate-limit quota & back off together when an API asks to.
+
 To use a proxy, please set env vars HTTP_PROXY, HTTPS_PROXY or ALL_PROXY
 (e.g. export HTTPS_PROXY=socks5://127.0.0.1:1086).
 
@@ -140,6 +144,11 @@ Note that you can pass as many key-value pairs by using --http-header option rep
 
   $ qsv fetchpost https://httpbin.org/post col1-col3 data.csv -H "X-Api-Key:TEST_KEY" -H "X-Api-Secret:ABC123XYZ"
 
+To keep credentials out of the command line, use the --auth option with a named auth profile
+instead (bearer, basic, api-key, oauth2 or aws-sigv4). See "USING AUTH PROFILES" in `qsv fetch --help`.
+
+  $ qsv fetchpost https://httpbin.org/post col1-col3 data.csv --auth warehouse
+
 For more extensive examples, see https://github.com/dathere/qsv/blob/master/tests/test_fetch.rs.
 See also https://github.com/dathere/qsv/wiki/HTTP-and-Web#fetchpost
 
@@ -192,9 +201,14 @@ Fetchpost options:
                                [default: 0 ]
     --timeout <seconds>        Timeout for each URL request.
                                [default: 30 ]
+    --concurrency <n>          The maximum number of requests in flight at any time.
+                               The output order is preserved.
+                               [default: 1]
     -H, --http-header <k:v>    Append custom header(s) to the HTTP header. Pass multiple key-value pairs
                                by adding this option multiple times, once for each pair. The key and value
                                should be separated by a colon.
+    --auth <profile>           Authenticate the requests with the named auth profile.
+                               See "USING AUTH PROFILES" in `qsv fetch --help`.
     --compress                 Compress the HTTP request body using gzip. Note that most servers do not support
                                compressed request bodies unless they are specifically configured to do so. This
                                should only be enabled for trusted scenarios where "zip bombs" are not a concern.
@@ -266,7 +280,14 @@ Common options:
                                Not valid for stdin.
 "#;
 
-use std::{fs, io::Write, num::NonZeroU32, path::PathBuf, sync::OnceLock, thread, time};
+use std::{
+    fs,
+    io::Write,
+    num::NonZeroU32,
+    path::PathBuf,
+    sync::{Arc, OnceLock},
+    thread, time,
+};
 
 use cached::{
     Cached, ConcurrentCached, LruCache, RedbCache, RedisCache, Return,
@@ -299,10 +320,12 @@ use util::expand_tilde;
 
 use crate::{
     CliError, CliResult,
+    auth::{self, Authorize},
     cmd::fetch::{
-        CacheType, DEFAULT_ACCEPT_ENCODING, DiskCacheConfig, FetchResponse, JAQ_FILTER,
-        RedisConfig, ReportKind, compile_jaq_filter, get_ratelimit_header_value,
-        parse_ratelimit_header_value, process_jaq,
+        AUTH_PROFILE, CacheType, DEFAULT_ACCEPT_ENCODING, DiskCacheConfig, FetchResponse,
+        JAQ_FILTER, RedisConfig, ReportKind, compile_jaq_filter,
+        concurrent::{Dispatcher, Origin, RequestSettings},
+        get_ratelimit_header_value, parse_ratelimit_header_value, process_jaq,
     },
     config::{Config, Delimiter},
     select::SelectColumns,
@@ -337,7 +360,9 @@ struct Args {
     flag_pretty:         bool,
     flag_rate_limit:     u32,
     flag_timeout:        u16,
+    flag_concurrency:    usize,
     flag_http_header:    Vec<String>,
+    flag_auth:           Option<String>,
     flag_compress:       bool,
     flag_max_retries:    u8,
     flag_max_errors:     u64,
@@ -402,6 +427,12 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         .set(util::timeout_secs(args.flag_timeout)?)
         .unwrap();
 
+    if let Some(ref profile) = args.flag_auth {
+        let profile = auth::load_profile(profile)?;
+        profile.authenticate()?;
+        let _ = AUTH_PROFILE.set(profile);
+    }
+
     // setup diskcache dir response caching
     let diskcache_dir = match &args.flag_disk_cache_dir {
         Some(dir) => {
@@ -586,6 +617,10 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     };
     info!("RATE LIMIT: {rate_limit}");
 
+    if args.flag_concurrency == 0 {
+        return fail_incorrectusage_clierror!("--concurrency must be at least 1.");
+    }
+
     // build the payload if --payload-tpl is used
     let mut template_content = String::new();
     let mut payload_content_type: ContentType;
@@ -679,6 +714,24 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     debug!("HTTP Header: {http_headers:?}");
 
     let client_timeout = time::Duration::from_secs(*TIMEOUT_FP_SECS.get().unwrap_or(&30));
+
+    // --concurrency sends its requests with an async client, with the same settings
+    let async_client = if args.flag_concurrency > 1 {
+        Some(
+            util::reqwest_async_client_builder(
+                args.flag_user_agent.clone(),
+                u16::try_from(client_timeout.as_secs()).unwrap_or(u16::MAX),
+                None,
+            )?
+            .default_headers(http_headers.clone())
+            .cookie_store(args.flag_cookies)
+            .timeout(client_timeout)
+            .build()?,
+        )
+    } else {
+        None
+    };
+
     let client = Client::builder()
         .user_agent(util::set_user_agent(args.flag_user_agent)?)
         .default_headers(http_headers)
@@ -694,9 +747,11 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         .build()?;
 
     // set rate limiter with allow_burst set to 1 - see https://github.com/antifuchs/governor/issues/39
-    let limiter =
+    // (shared with the in-flight requests of --concurrency)
+    let limiter = Arc::new(RateLimiter::direct(
         // safety: 1 is non-zero
-        RateLimiter::direct(Quota::per_second(rate_limit).allow_burst(NonZeroU32::new(1).unwrap()));
+        Quota::per_second(rate_limit).allow_burst(NonZeroU32::new(1).unwrap()),
+    ));
 
     // prep progress bars
     let show_progress =
@@ -852,204 +907,27 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
     let debug_flag = log_enabled!(Debug);
 
-    while rdr.read_byte_record(&mut record)? {
-        if show_progress {
-            progress.inc(1);
-        }
-
-        if report != ReportKind::None {
-            now = time::Instant::now();
-        }
-
-        // construct body per the column-list
-        if globals_flag {
-            form_body_jsonmap.clone_from(&globals_jsonmap);
-        } else {
-            form_body_jsonmap.clear();
-        }
-        for col_idx in &*col_list {
-            form_body_jsonmap.insert(
-                (header_key_vec[*col_idx]).to_string(),
-                serde_json::Value::String(
-                    simdutf8::basic::from_utf8(record.get(*col_idx).unwrap_or_default())
-                        .unwrap_or_default()
-                        .to_owned(),
-                ),
-            );
-        }
-
-        if payload_content_type != ContentType::Form {
-            let rendered_template = payload_env
-                .get_template("template")?
-                .render(&form_body_jsonmap)?;
-            rendered_json = if payload_content_type == ContentType::Json {
-                serde_json::from_str::<serde_json::Value>(&rendered_template).map_err(|e| {
-                    CliError::Other(format!("Invalid JSON payload: {e}\n{rendered_template}"))
-                })?
-            } else {
-                // ContentType:Manual
-                // Wrap raw payload in a JSON object with qsv_plaintext key
-                json!({
-                    "qsv_plaintext": rendered_template
-                })
-            };
-            // safety: rendered_json is now guaranteed to be a valid JSON object
-            form_body_jsonmap.clone_from(rendered_json.as_object().unwrap());
-        }
-
-        if debug_flag {
-            // deserializing the form_body_jsonmap to a string is expensive
-            // so we only do it when debug is enabled
-            debug!("{form_body_jsonmap:?}");
-        }
-
-        if literal_url_used {
-            url.clone_from(&literal_url);
-        } else if let Ok(s) = simdutf8::basic::from_utf8(&record[column_index]) {
-            s.clone_into(&mut url);
-        } else {
-            url = String::new();
-        }
-        if url.is_empty() {
-            final_response.clone_from(&empty_response);
-            was_cached = false;
-        } else {
-            match cache_type {
-                CacheType::InMemory => {
-                    intermediate_value = get_cached_response(
-                        &url,
-                        &form_body_jsonmap,
-                        payload_content_type,
-                        &client,
-                        &limiter,
-                        jaq_selector.as_ref(),
-                        args.flag_store_error,
-                        args.flag_pretty,
-                        args.flag_compress,
-                        include_existing_columns,
-                        args.flag_max_retries,
-                    );
-                    was_cached = intermediate_value.was_cached();
-                    final_response = intermediate_value.into_inner();
-                    if !args.flag_cache_error && final_response.status_code != 200 {
-                        // key matches get_cached_response's convert macro
-                        // (body-only — see NOTE above the cached fn).
-                        let key = format!("{form_body_jsonmap:?}");
-                        let mut cache = GET_CACHED_RESPONSE.write();
-                        let _ = cache.cache_remove(&key);
-                    }
-                },
-                CacheType::Disk => {
-                    intermediate_value = get_diskcache_response(
-                        &url,
-                        &form_body_jsonmap,
-                        payload_content_type,
-                        &client,
-                        &limiter,
-                        jaq_selector.as_ref(),
-                        args.flag_store_error,
-                        args.flag_pretty,
-                        args.flag_compress,
-                        include_existing_columns,
-                        args.flag_max_retries,
-                    )?;
-                    was_cached = intermediate_value.was_cached();
-                    final_response = intermediate_value.into_inner();
-                    if was_cached {
-                        disk_cache_hits += 1;
-                        // log::debug!("Disk cache hit for {url} hit: {disk_cache_hits}");
-                    }
-                    if !args.flag_cache_error && final_response.status_code != 200 {
-                        let key = cross_session_cache_key(
-                            &url,
-                            &form_body_jsonmap,
-                            payload_content_type,
-                            jaq_selector.as_ref(),
-                            args.flag_store_error,
-                            args.flag_pretty,
-                            args.flag_compress,
-                            include_existing_columns,
-                        );
-                        let _ = GET_DISKCACHE_RESPONSE.cache_remove(&key);
-                        // log::debug!("Removed Disk cache for {url}");
-                    }
-                },
-                CacheType::Redis => {
-                    intermediate_redis_value = get_redis_response(
-                        &url,
-                        &form_body_jsonmap,
-                        payload_content_type,
-                        &client,
-                        &limiter,
-                        jaq_selector.as_ref(),
-                        args.flag_store_error,
-                        args.flag_pretty,
-                        args.flag_compress,
-                        include_existing_columns,
-                        args.flag_max_retries,
-                    )?;
-                    was_cached = intermediate_redis_value.was_cached();
-                    if was_cached {
-                        redis_cache_hits += 1;
-                    }
-                    final_response = match serde_json::from_str(&intermediate_redis_value) {
-                        Ok(r) => r,
-                        Err(e) => {
-                            return fail_clierror!(
-                                "Cannot deserialize Redis cache value. Try flushing the Redis \
-                                 cache with --flushdb: {e}"
-                            );
-                        },
-                    };
-                    if !args.flag_cache_error && final_response.status_code != 200 {
-                        let key = cross_session_cache_key(
-                            &url,
-                            &form_body_jsonmap,
-                            payload_content_type,
-                            jaq_selector.as_ref(),
-                            args.flag_store_error,
-                            args.flag_pretty,
-                            args.flag_compress,
-                            include_existing_columns,
-                        );
-
-                        if GET_REDIS_RESPONSE.cache_remove(&key).is_err() && log_enabled!(Warn) {
-                            // failure to remove cache keys is non-fatal. Continue, but log it.
-                            wwarn!(r#"Cannot remove Redis key "{key}""#);
-                        }
-                    }
-                },
-                CacheType::None => {
-                    final_response = get_response(
-                        &url,
-                        &form_body_jsonmap,
-                        payload_content_type,
-                        &client,
-                        &limiter,
-                        jaq_selector.as_ref(),
-                        args.flag_store_error,
-                        args.flag_pretty,
-                        args.flag_compress,
-                        include_existing_columns,
-                        args.flag_max_retries,
-                    );
-                    was_cached = false;
-                },
-            }
-        }
-
-        if final_response.status_code == 200 {
+    // write the response of a record to the output & the report.
+    // Returns true once --max-errors is reached.
+    let mut write_response = |record: &mut csv::ByteRecord,
+                              url: &str,
+                              form_body_jsonmap: &serde_json::Map<String, Value>,
+                              response: &FetchResponse,
+                              was_cached: bool,
+                              elapsed: time::Duration|
+     -> CliResult<bool> {
+        if response.status_code == 200 {
             running_success_count += 1;
         } else {
             running_error_count += 1;
             error_progress.inc(1);
         }
 
-        final_value.clone_from(&final_response.response);
+        final_value.clone_from(&response.response);
 
         if include_existing_columns {
             record.push_field(final_value.as_bytes());
-            wtr.write_byte_record(&record)?;
+            wtr.write_byte_record(record)?;
         } else {
             jsonl_record.clear();
             if final_value.is_empty() {
@@ -1062,16 +940,16 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
         if report != ReportKind::None {
             if report == ReportKind::Detailed {
-                report_record.clone_from(&record);
+                report_record.clone_from(record);
             } else {
                 report_record.clear();
             }
             report_record.push_field(url.as_bytes());
             report_record.push_field(format!("{form_body_jsonmap:?}").as_bytes());
-            report_record.push_field(final_response.status_code.to_string().as_bytes());
+            report_record.push_field(response.status_code.to_string().as_bytes());
             report_record.push_field(if was_cached { b"1" } else { b"0" });
-            report_record.push_field(final_response.retries.to_string().as_bytes());
-            report_record.push_field(now.elapsed().as_millis().to_string().as_bytes());
+            report_record.push_field(response.retries.to_string().as_bytes());
+            report_record.push_field(elapsed.as_millis().to_string().as_bytes());
             if include_existing_columns {
                 report_record.push_field(final_value.as_bytes());
             } else {
@@ -1080,7 +958,315 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             report_wtr.write_byte_record(&report_record)?;
         }
 
-        if args.flag_max_errors > 0 && running_error_count >= args.flag_max_errors {
+        Ok(args.flag_max_errors > 0 && running_error_count >= args.flag_max_errors)
+    };
+
+    // --concurrency: look up each record in the cache as it's read, and keep the requests
+    // of the cache misses in flight, writing out the responses in the input order
+    let mut dispatcher = match async_client {
+        Some(async_client) => Some(Dispatcher::new(
+            async_client,
+            Arc::clone(&limiter),
+            RequestSettings {
+                flag_jaq: jaq_selector.clone(),
+                flag_store_error: args.flag_store_error,
+                flag_pretty: args.flag_pretty,
+                include_existing_columns,
+                flag_max_retries: args.flag_max_retries,
+                timeout_secs: *TIMEOUT_FP_SECS.get().unwrap_or(&30),
+            },
+            args.flag_concurrency,
+        )?),
+        None => None,
+    };
+
+    'rows: loop {
+        let more_records = rdr.read_byte_record(&mut record)?;
+        if more_records {
+            if show_progress {
+                progress.inc(1);
+            }
+
+            if report != ReportKind::None {
+                now = time::Instant::now();
+            }
+
+            // construct body per the column-list
+            if globals_flag {
+                form_body_jsonmap.clone_from(&globals_jsonmap);
+            } else {
+                form_body_jsonmap.clear();
+            }
+            for col_idx in &*col_list {
+                form_body_jsonmap.insert(
+                    (header_key_vec[*col_idx]).to_string(),
+                    serde_json::Value::String(
+                        simdutf8::basic::from_utf8(record.get(*col_idx).unwrap_or_default())
+                            .unwrap_or_default()
+                            .to_owned(),
+                    ),
+                );
+            }
+
+            if payload_content_type != ContentType::Form {
+                let rendered_template = payload_env
+                    .get_template("template")?
+                    .render(&form_body_jsonmap)?;
+                rendered_json = if payload_content_type == ContentType::Json {
+                    serde_json::from_str::<serde_json::Value>(&rendered_template).map_err(|e| {
+                        CliError::Other(format!("Invalid JSON payload: {e}\n{rendered_template}"))
+                    })?
+                } else {
+                    // ContentType:Manual
+                    // Wrap raw payload in a JSON object with qsv_plaintext key
+                    json!({
+                        "qsv_plaintext": rendered_template
+                    })
+                };
+                // safety: rendered_json is now guaranteed to be a valid JSON object
+                form_body_jsonmap.clone_from(rendered_json.as_object().unwrap());
+            }
+
+            if debug_flag {
+                // deserializing the form_body_jsonmap to a string is expensive
+                // so we only do it when debug is enabled
+                debug!("{form_body_jsonmap:?}");
+            }
+
+            if literal_url_used {
+                url.clone_from(&literal_url);
+            } else if let Ok(s) = simdutf8::basic::from_utf8(&record[column_index]) {
+                s.clone_into(&mut url);
+            } else {
+                url = String::new();
+            }
+
+            if let Some(ref mut dispatcher) = dispatcher {
+                let tag = (
+                    record.clone(),
+                    url.clone(),
+                    form_body_jsonmap.clone(),
+                    time::Instant::now(),
+                );
+                if url.is_empty() {
+                    dispatcher.push_done(tag, empty_response.clone(), Origin::Skipped);
+                } else {
+                    let key = match cache_type {
+                        CacheType::InMemory => Some(format!("{form_body_jsonmap:?}")),
+                        CacheType::Disk | CacheType::Redis => Some(cross_session_cache_key(
+                            &url,
+                            &form_body_jsonmap,
+                            payload_content_type,
+                            jaq_selector.as_ref(),
+                            args.flag_store_error,
+                            args.flag_pretty,
+                            args.flag_compress,
+                            include_existing_columns,
+                        )),
+                        CacheType::None => None,
+                    };
+                    let hit = match key {
+                        Some(ref key) => cache_lookup(&cache_type, key)?,
+                        None => None,
+                    };
+                    if let Some(hit) = hit {
+                        dispatcher.push_done(tag, hit, Origin::Cache);
+                    } else {
+                        let body = encode_body(
+                            &form_body_jsonmap,
+                            payload_content_type,
+                            args.flag_compress,
+                            args.flag_store_error,
+                        );
+                        dispatcher.push_request(tag, url.clone(), Some(body), key);
+                    }
+                }
+            } else {
+                if url.is_empty() {
+                    final_response.clone_from(&empty_response);
+                    was_cached = false;
+                } else {
+                    match cache_type {
+                        CacheType::InMemory => {
+                            intermediate_value = get_cached_response(
+                                &url,
+                                &form_body_jsonmap,
+                                payload_content_type,
+                                &client,
+                                &limiter,
+                                jaq_selector.as_ref(),
+                                args.flag_store_error,
+                                args.flag_pretty,
+                                args.flag_compress,
+                                include_existing_columns,
+                                args.flag_max_retries,
+                            );
+                            was_cached = intermediate_value.was_cached();
+                            final_response = intermediate_value.into_inner();
+                            if !args.flag_cache_error && final_response.status_code != 200 {
+                                // key matches get_cached_response's convert macro
+                                // (body-only — see NOTE above the cached fn).
+                                let key = format!("{form_body_jsonmap:?}");
+                                let mut cache = GET_CACHED_RESPONSE.write();
+                                let _ = cache.cache_remove(&key);
+                            }
+                        },
+                        CacheType::Disk => {
+                            intermediate_value = get_diskcache_response(
+                                &url,
+                                &form_body_jsonmap,
+                                payload_content_type,
+                                &client,
+                                &limiter,
+                                jaq_selector.as_ref(),
+                                args.flag_store_error,
+                                args.flag_pretty,
+                                args.flag_compress,
+                                include_existing_columns,
+                                args.flag_max_retries,
+                            )?;
+                            was_cached = intermediate_value.was_cached();
+                            final_response = intermediate_value.into_inner();
+                            if was_cached {
+                                disk_cache_hits += 1;
+                                // log::debug!("Disk cache hit for {url} hit: {disk_cache_hits}");
+                            }
+                            if !args.flag_cache_error && final_response.status_code != 200 {
+                                let key = cross_session_cache_key(
+                                    &url,
+                                    &form_body_jsonmap,
+                                    payload_content_type,
+                                    jaq_selector.as_ref(),
+                                    args.flag_store_error,
+                                    args.flag_pretty,
+                                    args.flag_compress,
+                                    include_existing_columns,
+                                );
+                                let _ = GET_DISKCACHE_RESPONSE.cache_remove(&key);
+                                // log::debug!("Removed Disk cache for {url}");
+                            }
+                        },
+                        CacheType::Redis => {
+                            intermediate_redis_value = get_redis_response(
+                                &url,
+                                &form_body_jsonmap,
+                                payload_content_type,
+                                &client,
+                                &limiter,
+                                jaq_selector.as_ref(),
+                                args.flag_store_error,
+                                args.flag_pretty,
+                                args.flag_compress,
+                                include_existing_columns,
+                                args.flag_max_retries,
+                            )?;
+                            was_cached = intermediate_redis_value.was_cached();
+                            if was_cached {
+                                redis_cache_hits += 1;
+                            }
+                            final_response = match serde_json::from_str(&intermediate_redis_value) {
+                                Ok(r) => r,
+                                Err(e) => {
+                                    return fail_clierror!(
+                                        "Cannot deserialize Redis cache value. Try flushing the \
+                                         Redis cache with --flushdb: {e}"
+                                    );
+                                },
+                            };
+                            if !args.flag_cache_error && final_response.status_code != 200 {
+                                let key = cross_session_cache_key(
+                                    &url,
+                                    &form_body_jsonmap,
+                                    payload_content_type,
+                                    jaq_selector.as_ref(),
+                                    args.flag_store_error,
+                                    args.flag_pretty,
+                                    args.flag_compress,
+                                    include_existing_columns,
+                                );
+
+                                if GET_REDIS_RESPONSE.cache_remove(&key).is_err()
+                                    && log_enabled!(Warn)
+                                {
+                                    // failure to remove cache keys is non-fatal. Continue, but log
+                                    // it.
+                                    wwarn!(r#"Cannot remove Redis key "{key}""#);
+                                }
+                            }
+                        },
+                        CacheType::None => {
+                            final_response = get_response(
+                                &url,
+                                &form_body_jsonmap,
+                                payload_content_type,
+                                &client,
+                                &limiter,
+                                jaq_selector.as_ref(),
+                                args.flag_store_error,
+                                args.flag_pretty,
+                                args.flag_compress,
+                                include_existing_columns,
+                                args.flag_max_retries,
+                            );
+                            was_cached = false;
+                        },
+                    }
+                }
+
+                if write_response(
+                    &mut record,
+                    url.as_str(),
+                    &form_body_jsonmap,
+                    &final_response,
+                    was_cached,
+                    now.elapsed(),
+                )? {
+                    break;
+                }
+            }
+        }
+
+        // hand back the responses of --concurrency in order, with at most --concurrency
+        // records queued
+        if let Some(ref mut dispatcher) = dispatcher {
+            while dispatcher.is_full() || (!more_records && !dispatcher.is_empty()) {
+                let Some(done) = dispatcher.pop()? else {
+                    break;
+                };
+                was_cached = match done.origin {
+                    Origin::Skipped => false,
+                    Origin::Cache => {
+                        match cache_type {
+                            CacheType::Disk => disk_cache_hits += 1,
+                            CacheType::Redis => redis_cache_hits += 1,
+                            _ => (),
+                        }
+                        true
+                    },
+                    Origin::Network { key } => {
+                        if let Some(key) = key
+                            && (args.flag_cache_error || done.response.status_code == 200)
+                        {
+                            cache_store(&cache_type, key, &done.response)?;
+                        }
+                        false
+                    },
+                };
+                let (mut done_record, done_url, done_form_body, started) = done.tag;
+                if write_response(
+                    &mut done_record,
+                    done_url.as_str(),
+                    &done_form_body,
+                    &done.response,
+                    was_cached,
+                    started.elapsed(),
+                )? {
+                    break 'rows;
+                }
+            }
+        }
+
+        if !more_records {
             break;
         }
     } // main read loop
@@ -1233,6 +1419,104 @@ fn cross_session_cache_key(
     )
 }
 
+/// Look up a cached response without requesting it - for --concurrency, which
+/// requests the cache misses itself.
+fn cache_lookup(cache_type: &CacheType, key: &str) -> CliResult<Option<FetchResponse>> {
+    let key = key.to_string();
+    match cache_type {
+        CacheType::InMemory => Ok(GET_CACHED_RESPONSE
+            .write()
+            .cache_get(&key)
+            .map(|hit| (**hit).clone())),
+        CacheType::Disk => GET_DISKCACHE_RESPONSE
+            .cache_get(&key)
+            .map_err(|e| CliError::Other(format!("Diskcache Error: {e:?}"))),
+        CacheType::Redis => {
+            let Some(value) = GET_REDIS_RESPONSE
+                .cache_get(&key)
+                .map_err(|e| CliError::Other(format!("Redis Error: {e:?}")))?
+            else {
+                return Ok(None);
+            };
+            match serde_json::from_str(&value) {
+                Ok(r) => Ok(Some(r)),
+                Err(e) => fail_clierror!(
+                    "Cannot deserialize Redis cache value. Try flushing the Redis cache with \
+                     --flushdb: {e}"
+                ),
+            }
+        },
+        CacheType::None => Ok(None),
+    }
+}
+
+/// Cache a response requested by --concurrency.
+fn cache_store(cache_type: &CacheType, key: String, response: &FetchResponse) -> CliResult<()> {
+    match cache_type {
+        CacheType::InMemory => {
+            let _ = GET_CACHED_RESPONSE
+                .write()
+                .cache_set(key, Return::new(response.clone()));
+        },
+        CacheType::Disk => {
+            GET_DISKCACHE_RESPONSE
+                .cache_set(key, response.clone())
+                .map_err(|e| CliError::Other(format!("Diskcache Error: {e:?}")))?;
+        },
+        CacheType::Redis => {
+            // safety: FetchResponse only has String/u16/u8 fields - serialization is infallible
+            GET_REDIS_RESPONSE
+                .cache_set(key, simd_json::to_string(response).unwrap())
+                .map_err(|e| CliError::Other(format!("Redis Error: {e:?}")))?;
+        },
+        CacheType::None => (),
+    }
+    Ok(())
+}
+
+/// The body posted for a record - as JSON, URL-encoded or as-is per the content type,
+/// and gzipped with --compress.
+fn encode_body(
+    form_body_jsonmap: &serde_json::Map<String, Value>,
+    payload_content_type: ContentType,
+    flag_compress: bool,
+    flag_store_error: bool,
+) -> Vec<u8> {
+    let form_body_raw = match payload_content_type {
+        ContentType::Json => simd_json::to_string(&form_body_jsonmap)
+            .unwrap() // safety: we know form_body_jsonmap is a valid JSON at this point
+            .as_bytes()
+            .to_owned(),
+        ContentType::Form => match serde_urlencoded::to_string(form_body_jsonmap) {
+            Ok(form_str) => form_str.as_bytes().to_owned(),
+            Err(e) => {
+                let err_msg = format!("Failed to encode form data: {e}");
+                error!("{err_msg}");
+                if flag_store_error {
+                    err_msg.as_bytes().to_owned()
+                } else {
+                    String::new().as_bytes().to_owned()
+                }
+            },
+        },
+        ContentType::Manual => form_body_jsonmap
+            .values()
+            .next()
+            .map(std::string::ToString::to_string)
+            .unwrap_or_default()
+            .as_bytes()
+            .to_owned(),
+    };
+    if flag_compress {
+        // gzip the request body
+        let mut gz_enc = GzEncoder::new(Vec::new(), Compression::default());
+        gz_enc.write_all(&form_body_raw).unwrap();
+        gz_enc.finish().unwrap()
+    } else {
+        form_body_raw
+    }
+}
+
 // this is a disk cache that can be used across qsv sessions
 // so we need to include the values of flag_jaq, flag_store_error, flag_pretty and
 // include_existing_columns in the cache key
@@ -1442,40 +1726,17 @@ fn get_response(
         }
 
         // send the actual request
-        let form_body_raw = match payload_content_type {
-            ContentType::Json => simd_json::to_string(&form_body_jsonmap)
-                .unwrap() // safety: we know form_body_jsonmap is a valid JSON at this point
-                .as_bytes()
-                .to_owned(),
-            ContentType::Form => match serde_urlencoded::to_string(form_body_jsonmap) {
-                Ok(form_str) => form_str.as_bytes().to_owned(),
-                Err(e) => {
-                    let err_msg = format!("Failed to encode form data: {e}");
-                    error!("{err_msg}");
-                    if flag_store_error {
-                        err_msg.as_bytes().to_owned()
-                    } else {
-                        String::new().as_bytes().to_owned()
-                    }
-                },
-            },
-            ContentType::Manual => form_body_jsonmap
-                .values()
-                .next()
-                .map(std::string::ToString::to_string)
-                .unwrap_or_default()
-                .as_bytes()
-                .to_owned(),
-        };
-        let resp_result = if flag_compress {
-            // gzip the request body
-            let mut gz_enc = GzEncoder::new(Vec::new(), Compression::default());
-            gz_enc.write_all(&form_body_raw).unwrap();
-            let gzipped_request_body = gz_enc.finish().unwrap();
-            client.post(&valid_url).body(gzipped_request_body).send()
-        } else {
-            client.post(&valid_url).body(form_body_raw).send()
-        };
+        let request_body = encode_body(
+            form_body_jsonmap,
+            payload_content_type,
+            flag_compress,
+            flag_store_error,
+        );
+        let resp_result = client
+            .post(&valid_url)
+            .body(request_body)
+            .auth(AUTH_PROFILE.get())
+            .and_then(|req| Ok(req.send()?));
 
         if let Ok(resp) = resp_result {
             // debug!("{resp:?}");
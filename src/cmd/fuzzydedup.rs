@@ -0,0 +1,505 @@
+static USAGE: &str = r#"
+Finds near-duplicate rows - e.g. "Acme Inc." and "ACME, Inc" - and groups them into
+clusters, a.k.a. record linkage.
+
+Unlike dedup & extdedup, which only find exact duplicates, fuzzydedup scores each pair
+of rows with the string similarity metrics of `qsv apply operations` on the --compare
+columns. The score of a pair is the weighted average of the similarity (0 to 1) of its
+compared columns, and pairs scoring at least --threshold are a match. Matches are
+transitive - if A matches B and B matches C, all three are in the same cluster.
+
+The --compare spec is a comma-separated list of column:metric[:weight] entries, e.g.
+  name:jaro_winkler:2,city:levenshtein,zip:exact
+where the metric is one of:
+  jaro_winkler   Jaro-Winkler similarity. Favors strings with a common prefix. (default)
+  levenshtein    Normalized Damerau-Levenshtein similarity - 1 minus the edit distance
+                 divided by the length of the longer string.
+  sorensen_dice  Sørensen-Dice similarity of the strings' character bigrams.
+  eudex          1 if the strings sound alike (using the multi-lingual eudex hash), else 0.
+  exact          1 if the strings are equal, else 0.
+The weight defaults to 1. A column empty in both rows is left out of the score, a column
+empty in only one of them scores 0. Before they are compared, values are normalized with
+--normalize, which takes the same operations as `qsv join --key-transform`.
+
+Comparing every pair of rows is quadratic, so for large files, set --block to only compare
+rows with the same blocking key - e.g. the same zip code, or the same phone number once
+normalized with --block-transform digits. Rows with an empty blocking key are not compared.
+
+By default, all rows are written in their original order, with three columns added:
+  cluster_id      1 for the cluster of the first row, 2 for the next cluster, etc.
+                  A row without a near-duplicate is a cluster of its own.
+  cluster_size    The number of rows in the cluster.
+  representative  true for the row picked as the cluster's representative by
+                  --survivorship, else false.
+With --collapse, only the representative of each cluster is written, with its
+cluster_id & cluster_size.
+
+The number of near-duplicate rows - rows that aren't their cluster's representative -
+is sent to <stderr>.
+
+This requires reading all of the CSV data into memory.
+
+Examples:
+
+  # Cluster customers with similar names in the same zip code
+  qsv fuzzydedup --compare name --block zip customers.csv
+
+  # Weigh the name twice as much as the address, with a stricter threshold
+  qsv fuzzydedup --compare name:jaro_winkler:2,address:levenshtein -t 0.9 customers.csv
+
+  # Block on the digits of the phone number and keep the most complete row
+  qsv fuzzydedup -c name:sorensen_dice,email:exact -b phone --block-transform digits \
+    --survivorship most_complete --collapse customers.csv
+
+  # Keep the most recently updated row of each cluster, filling its empty fields
+  # from the other rows of the cluster
+  qsv fuzzydedup -c company -b state --survivorship most_recent:updated_at \
+    --collapse --fill-empty crm.csv -o crm-deduped.csv
+
+For more examples, see https://github.com/dathere/qsv/blob/master/tests/test_fuzzydedup.rs.
+
+Usage:
+    qsv fuzzydedup [options] --compare <spec> [<input>]
+    qsv fuzzydedup --help
+
+fuzzydedup options:
+    -c, --compare <spec>       The columns to compare, with their similarity metric and
+                               weight. See above for the format details.
+    -t, --threshold <score>    The minimum weighted similarity score, from 0 to 1, of
+                               two rows to be near-duplicates. [default: 0.85]
+    --normalize <ops>          A comma-separated pipeline of operations to normalize
+                               the compared values with. Uses the same operations as
+                               `qsv join --key-transform`. Set to "none" to compare
+                               the values as is. [default: trim,lower,squeeze]
+    -b, --block <arg>          Only compare rows with the same values in these columns.
+                               See 'qsv select --help' for the format details.
+    --block-transform <ops>    A comma-separated pipeline of operations to normalize
+                               the blocking key with, e.g. "trim,lower".
+                               Uses the same operations as `qsv join --key-transform`.
+    --survivorship <rule>      How the representative of a cluster is picked. One of:
+                                 first          - the cluster's first row.
+                                 most_complete  - the row with the most non-empty
+                                                  fields.
+                                 most_recent:<column> - the row with the latest date
+                                                  in <column>.
+                               Ties go to the earlier row. [default: first]
+    --collapse                 Only write the representative row of each cluster.
+    --fill-empty               With --collapse, fill the empty fields of the
+                               representative from the other rows of its cluster, in
+                               --survivorship order.
+    -j, --jobs <arg>           The number of jobs to run in parallel.
+                               When not set, the number of jobs is set to the
+                               number of CPUs detected.
+
+Common options:
+    -h, --help                 Display this message
+    -o, --output <file>        Write output to <file> instead of stdout.
+    -n, --no-headers           When set, the first row will not be interpreted
+                               as headers. Columns are then selected by their
+                               1-based index.
+    -d, --delimiter <arg>      The field delimiter for reading CSV data.
+                               Must be a single character. (default: ,)
+    -q, --quiet                Do not print the near-duplicate count to stderr.
+    --memcheck                 Check if there is enough memory to load the entire
+                               CSV into memory using CONSERVATIVE heuristics.
+"#;
+
+use std::cmp::Reverse;
+
+use csv::ByteRecord;
+use foldhash::{HashMap, HashMapExt};
+use rayon::prelude::*;
+use serde::Deserialize;
+use strsim::{jaro_winkler, normalized_damerau_levenshtein, sorensen_dice};
+
+use crate::{
+    CliResult,
+    config::{Config, Delimiter},
+    select::{SelectColumns, Selection},
+    util,
+};
+
+#[derive(Deserialize)]
+struct Args {
+    arg_input:            Option<String>,
+    flag_compare:         String,
+    flag_threshold:       f64,
+    flag_normalize:       String,
+    flag_block:           Option<SelectColumns>,
+    flag_block_transform: Option<String>,
+    flag_survivorship:    String,
+    flag_collapse:        bool,
+    flag_fill_empty:      bool,
+    flag_jobs:            Option<usize>,
+    flag_output:          Option<String>,
+    flag_no_headers:      bool,
+    flag_delimiter:       Option<Delimiter>,
+    flag_quiet:           bool,
+    flag_memcheck:        bool,
+}
+
+#[derive(Clone, Copy)]
+enum Metric {
+    JaroWinkler,
+    Levenshtein,
+    SorensenDice,
+    Eudex,
+    Exact,
+}
+
+impl Metric {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "jaro_winkler" => Self::JaroWinkler,
+            "levenshtein" => Self::Levenshtein,
+            "sorensen_dice" => Self::SorensenDice,
+            "eudex" => Self::Eudex,
+            "exact" => Self::Exact,
+            _ => return None,
+        })
+    }
+
+    fn similarity(self, a: &str, b: &str) -> f64 {
+        match self {
+            Self::JaroWinkler => jaro_winkler(a, b),
+            Self::Levenshtein => normalized_damerau_levenshtein(a, b),
+            Self::SorensenDice => sorensen_dice(a, b),
+            Self::Eudex => {
+                if (eudex::Hash::new(a) - eudex::Hash::new(b)).similar() {
+                    1.0
+                } else {
+                    0.0
+                }
+            },
+            Self::Exact => {
+                if a == b {
+                    1.0
+                } else {
+                    0.0
+                }
+            },
+        }
+    }
+}
+
+struct Comparison {
+    column: usize,
+    metric: Metric,
+    weight: f64,
+}
+
+/// The index of a single column, selected by name or 1-based index.
+fn column_index(column: &str, headers: &ByteRecord, no_headers: bool) -> CliResult<usize> {
+    let sel = SelectColumns::parse(column)?.selection(headers, !no_headers)?;
+    match *sel {
+        [index] => Ok(index),
+        _ => fail_incorrectusage_clierror!("\"{column}\" must select exactly one column."),
+    }
+}
+
+fn parse_compare(spec: &str, headers: &ByteRecord, no_headers: bool) -> CliResult<Vec<Comparison>> {
+    let mut comparisons = Vec::new();
+    for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let mut parts: Vec<&str> = entry.split(':').collect();
+        let weight = if parts.len() >= 3 {
+            // safety: there are at least 3 parts
+            let weight = parts.pop().unwrap();
+            match fast_float2::parse::<f64, _>(weight) {
+                Ok(w) if w.is_finite() && w > 0.0 => w,
+                _ => {
+                    return fail_incorrectusage_clierror!(
+                        "Invalid weight \"{weight}\" in --compare entry \"{entry}\". It must be a \
+                         positive number."
+                    );
+                },
+            }
+        } else {
+            1.0
+        };
+        let metric = if parts.len() >= 2 {
+            // safety: there are at least 2 parts
+            let name = parts.pop().unwrap();
+            let Some(metric) = Metric::from_name(&name.to_ascii_lowercase()) else {
+                return fail_incorrectusage_clierror!(
+                    "Invalid metric \"{name}\" in --compare entry \"{entry}\". Valid metrics are: \
+                     jaro_winkler, levenshtein, sorensen_dice, eudex and exact."
+                );
+            };
+            metric
+        } else {
+            Metric::JaroWinkler
+        };
+        comparisons.push(Comparison {
+            column: column_index(&parts.join(":"), headers, no_headers)?,
+            metric,
+            weight,
+        });
+    }
+    if comparisons.is_empty() {
+        return fail_incorrectusage_clierror!("--compare requires at least one column.");
+    }
+    Ok(comparisons)
+}
+
+/// The weighted similarity of two rows, from their normalized compared values.
+fn score(comparisons: &[Comparison], a: &[String], b: &[String]) -> f64 {
+    let (mut total, mut weights) = (0.0, 0.0);
+    for (cmp, (x, y)) in comparisons.iter().zip(a.iter().zip(b)) {
+        if x.is_empty() && y.is_empty() {
+            continue;
+        }
+        weights += cmp.weight;
+        if !x.is_empty() && !y.is_empty() {
+            total += cmp.weight * cmp.metric.similarity(x, y);
+        }
+    }
+    if weights > 0.0 { total / weights } else { 0.0 }
+}
+
+enum Survivorship {
+    First,
+    MostComplete,
+    MostRecent(usize),
+}
+
+impl Survivorship {
+    fn parse(rule: &str, headers: &ByteRecord, no_headers: bool) -> CliResult<Self> {
+        match rule.split_once(':') {
+            None if rule == "first" => Ok(Self::First),
+            None if rule == "most_complete" => Ok(Self::MostComplete),
+            Some(("most_recent", column)) => {
+                Ok(Self::MostRecent(column_index(column, headers, no_headers)?))
+            },
+            _ => fail_incorrectusage_clierror!(
+                "Invalid --survivorship rule \"{rule}\". Valid rules are: first, most_complete \
+                 and most_recent:<column>."
+            ),
+        }
+    }
+
+    /// Sort the rows of a cluster from the most to the least preferred.
+    fn order(&self, members: &mut [usize], records: &[ByteRecord], prefer_dmy: bool) {
+        // the sorts are stable, so ties keep the input order
+        match self {
+            Self::First => {},
+            Self::MostComplete => members
+                .sort_by_key(|&row| Reverse(records[row].iter().filter(|f| !f.is_empty()).count())),
+            Self::MostRecent(column) => members.sort_by_cached_key(|&row| {
+                let date = records[row].get(*column).and_then(|value| {
+                    qsv_dateparser::parse_with_preference(
+                        &String::from_utf8_lossy(value),
+                        prefer_dmy,
+                    )
+                    .ok()
+                });
+                // rows without a date go last
+                Reverse(date)
+            }),
+        }
+    }
+}
+
+/// A union-find of the row indices, whose roots are the first row of each cluster.
+struct Clusters {
+    parent: Vec<usize>,
+}
+
+impl Clusters {
+    fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+        }
+    }
+
+    fn find(&mut self, mut row: usize) -> usize {
+        while self.parent[row] != row {
+            self.parent[row] = self.parent[self.parent[row]];
+            row = self.parent[row];
+        }
+        row
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (root_a, root_b) = (self.find(a), self.find(b));
+        if root_a != root_b {
+            self.parent[root_a.max(root_b)] = root_a.min(root_b);
+        }
+    }
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    if !(0.0..=1.0).contains(&args.flag_threshold) {
+        return fail_incorrectusage_clierror!("--threshold must be between 0 and 1.");
+    }
+    if args.flag_fill_empty && !args.flag_collapse {
+        return fail_incorrectusage_clierror!("--fill-empty requires --collapse.");
+    }
+    let normalize = if args.flag_normalize.eq_ignore_ascii_case("none") {
+        None
+    } else {
+        util::KeyTransform::from_flag(Some(&args.flag_normalize))?
+    };
+    let block_transform = util::KeyTransform::from_flag(args.flag_block_transform.as_deref())?;
+
+    let rconfig = Config::new(args.arg_input.as_ref())
+        .delimiter(args.flag_delimiter)
+        .no_headers_flag(args.flag_no_headers);
+
+    let mut rdr = rconfig.reader()?;
+    let headers = rdr.byte_headers()?.clone();
+    let comparisons = parse_compare(&args.flag_compare, &headers, args.flag_no_headers)?;
+    let survivorship =
+        Survivorship::parse(&args.flag_survivorship, &headers, args.flag_no_headers)?;
+    let block_sel: Option<Selection> = match args.flag_block {
+        Some(ref block) => Some(block.selection(&headers, !args.flag_no_headers)?),
+        None => None,
+    };
+
+    // we're loading the entire file into memory, we need to check avail mem
+    if let Some(path) = rconfig.resolved_path()? {
+        util::mem_file_check(&path, false, args.flag_memcheck)?;
+    }
+    util::njobs(args.flag_jobs);
+
+    let records: Vec<ByteRecord> = rdr.byte_records().collect::<Result<_, _>>()?;
+
+    // the normalized compared values of each row
+    let values: Vec<Vec<String>> = records
+        .par_iter()
+        .map(|record| {
+            comparisons
+                .iter()
+                .map(|cmp| {
+                    let value = record.get(cmp.column).unwrap_or_default();
+                    match normalize {
+                        Some(ref kt) => String::from_utf8_lossy(&kt.apply(value)).into_owned(),
+                        None => String::from_utf8_lossy(value).into_owned(),
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    let blocks: Vec<Vec<usize>> = match block_sel {
+        Some(ref sel) => {
+            let mut blocks: HashMap<Vec<u8>, Vec<usize>> = HashMap::new();
+            for (row, record) in records.iter().enumerate() {
+                let mut key = Vec::new();
+                for field in sel.select(record) {
+                    match block_transform {
+                        Some(ref kt) => key.extend_from_slice(&kt.apply(field)),
+                        None => key.extend_from_slice(field),
+                    }
+                    key.push(b'\x1f');
+                }
+                if key.iter().all(|&b| b == b'\x1f') {
+                    continue;
+                }
+                blocks.entry(key).or_default().push(row);
+            }
+            blocks.into_values().filter(|rows| rows.len() > 1).collect()
+        },
+        None => vec![(0..records.len()).collect()],
+    };
+
+    let threshold = args.flag_threshold;
+    let (comparisons, values) = (&comparisons, &values);
+    let matches: Vec<(usize, usize)> = blocks
+        .par_iter()
+        .flat_map(|rows| {
+            (0..rows.len()).into_par_iter().flat_map_iter(move |i| {
+                let a = rows[i];
+                rows[i + 1..].iter().filter_map(move |&b| {
+                    (score(comparisons, &values[a], &values[b]) >= threshold).then_some((a, b))
+                })
+            })
+        })
+        .collect();
+
+    let mut clusters = Clusters::new(records.len());
+    for (a, b) in matches {
+        clusters.union(a, b);
+    }
+
+    // the rows of each cluster, numbered in the order of their first row
+    let mut cluster_ids: HashMap<usize, usize> = HashMap::new();
+    let mut members: Vec<Vec<usize>> = Vec::new();
+    let mut row_cluster: Vec<usize> = Vec::with_capacity(records.len());
+    for row in 0..records.len() {
+        let root = clusters.find(row);
+        let id = *cluster_ids.entry(root).or_insert_with(|| {
+            members.push(Vec::new());
+            members.len() - 1
+        });
+        members[id].push(row);
+        row_cluster.push(id);
+    }
+    let prefer_dmy = util::get_envvar_flag("QSV_PREFER_DMY");
+    for cluster in &mut members {
+        survivorship.order(cluster, &records, prefer_dmy);
+    }
+
+    let mut wtr = Config::new(args.flag_output.as_ref()).writer()?;
+    if !args.flag_no_headers {
+        let mut headers = headers;
+        headers.push_field(b"cluster_id");
+        headers.push_field(b"cluster_size");
+        if !args.flag_collapse {
+            headers.push_field(b"representative");
+        }
+        wtr.write_byte_record(&headers)?;
+    }
+
+    let mut itoa_buf = itoa::Buffer::new();
+    if args.flag_collapse {
+        for (id, cluster) in members.iter().enumerate() {
+            let mut survivor = records[cluster[0]].clone();
+            if args.flag_fill_empty {
+                survivor = fill_empty(&survivor, &cluster[1..], &records);
+            }
+            survivor.push_field(itoa_buf.format(id + 1).as_bytes());
+            survivor.push_field(itoa_buf.format(cluster.len()).as_bytes());
+            wtr.write_byte_record(&survivor)?;
+        }
+    } else {
+        let mut record = ByteRecord::new();
+        for (row, &id) in row_cluster.iter().enumerate() {
+            record.clone_from(&records[row]);
+            record.push_field(itoa_buf.format(id + 1).as_bytes());
+            record.push_field(itoa_buf.format(members[id].len()).as_bytes());
+            record.push_field(if members[id][0] == row {
+                b"true"
+            } else {
+                b"false"
+            });
+            wtr.write_byte_record(&record)?;
+        }
+    }
+    wtr.flush()?;
+
+    if !args.flag_quiet {
+        eprintln!("{}", records.len() - members.len());
+    }
+    Ok(())
+}
+
+/// Fill the empty fields of the representative of a cluster from its other rows.
+fn fill_empty(survivor: &ByteRecord, others: &[usize], records: &[ByteRecord]) -> ByteRecord {
+    survivor
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            if field.is_empty() {
+                others
+                    .iter()
+                    .filter_map(|&row| records[row].get(i))
+                    .find(|f| !f.is_empty())
+                    .unwrap_or_default()
+            } else {
+                field
+            }
+        })
+        .collect()
+}
@@ -18,7 +18,7 @@ By default, the prebuilt index uses the Geonames Gazeteer cities15000.zip file u
 English names. It contains cities with populations > 15,000 (about ~26k cities). 
 See https://download.geonames.org/export/dump/ for more information.
 
-It has twelve major subcommands:
+It has fourteen major subcommands:
  * suggest        - given a partial City name, return the closest City's location metadata
                     per the local Geonames cities index (Jaro-Winkler distance)
  * suggestnow     - same as suggest, but using a partial City name from the command line,
@@ -41,6 +41,10 @@ It has twelve major subcommands:
                     "lat, long" coordinate. Requires an OpenCage API key.
  * opencagenow    - same as opencage, but using an address/coordinate from the
                     command line, instead of CSV data.
+ * parse-address  - OFFLINE parsing of free-form street addresses into their components
+                    (house number, street, unit, city, state, postcode & country).
+ * parse-addressnow - same as parse-address, but using an address from the command line,
+                    instead of CSV data.
  * index-*        - operations to update the local Geonames cities index.
                     (index-check, index-update, index-load & index-reset)
  * cache-*        - operations to manage the persistent on-disk OpenCage result cache.
@@ -172,6 +176,11 @@ persistent on-disk cache (see --cache-ttl & --no-cache). Re-runs and duplicate
 queries do NOT re-hit the API. The free tier allows 2,500 requests/day at 1
 request/second; rows are processed sequentially and rate-limited (see --rate-limit).
 
+For long runs, use --checkpoint to save the progress of the run every few seconds.
+If it dies - or stops because the API quota is exhausted - rerun it with the same
+arguments to append to the --output file from the last row written:
+  $ qsv geocode opencage address --checkpoint file.ckpt file.csv -o geocoded.csv
+
 The --country option, if set, restricts results to the given ISO 3166-1 alpha-2
 country code(s). The --timeout, --language, --invalid-result, --new-column, --rename
 and --output options behave as they do for the other subcommands.
@@ -213,6 +222,47 @@ Accepts the same options as opencage, but does not require an input file.
   $ qsv geocode opencagenow "40.71427, -74.00597"
   $ qsv geocode opencagenow -f '%pretty-json' "Eiffel Tower, Paris"
 
+PARSE-ADDRESS
+Offline parsing of free-form street addresses - no index, API key or network access needed.
+Using embedded rules in the style of libpostal (USPS street suffixes, directionals & unit
+designators, and US state & Canadian province names/codes), each address is split into its
+house_number, street, unit, city, state, postcode and country components. US and Canadian
+addresses are supported. Comma-separated addresses parse best, but single-line addresses
+like "123 Main St Apt 4 Springfield IL 62704" are handled too.
+
+The components are normalised per --address-style:
+  * usps     - USPS Publication 28 standardisation: uppercase, with standard abbreviations
+               and state codes, e.g. "123 N MAIN ST APT 4, SPRINGFIELD, IL 62704" (default)
+  * expanded - Title Case, with abbreviations expanded and state names spelled out, e.g.
+               "123 North Main Street Apartment 4, Springfield, Illinois 62704"
+  * raw      - the components as found in the address
+In the usps & expanded styles, ZIP+4 and Canadian postal codes are normalised as well.
+
+The --formatstr option supports these PARSE-ADDRESS formats:
+  * '%+'           - add a column for each component, named house_number, street, unit,
+                     city, state, postcode and country (default)
+  * '%address'     - the normalised address on a single line
+  * '%json'        - the components as JSON
+  * '%pretty-json' - the components as pretty JSON
+Dynamic formatting is also supported, using the component names, e.g.
+  "{house_number} {street}" or "{city}, {state} {postcode}".
+The "%dyncols:" format adds only the chosen components as columns, e.g.
+  "%dyncols: {zip:postcode}, {st:state}"
+'%+' and "%dyncols:" cannot be combined with --new-column.
+
+When none of the street, state and postcode are found, the address is left as is (the
+added columns are left empty) - or set to --invalid-result, if specified.
+
+  $ qsv geocode parse-address address file.csv
+  $ qsv geocode parse-address address --address-style expanded -f '%address' -c clean file.csv
+  $ qsv geocode parse-address address -f '%dyncols: {zip:postcode}, {st:state}' file.csv
+
+PARSE-ADDRESSNOW
+Accepts the same options as parse-address, but does not require an input file.
+
+  $ qsv geocode parse-addressnow "1600 Pennsylvania Ave NW, Washington, DC 20500"
+  $ qsv geocode parse-addressnow -f '%pretty-json' "350 5th Ave Fl 34 New York NY 10118"
+
 INDEX-<operation>
 Manage the local Geonames cities index used by the geocode command.
 
@@ -302,6 +352,8 @@ qsv geocode iplookup [options] <column> [<input>]
 qsv geocode iplookupnow [options] <location>
 qsv geocode opencage [--formatstr=<string>] [options] <column> [<input>]
 qsv geocode opencagenow [options] <location>
+qsv geocode parse-address [--formatstr=<string>] [options] <column> [<input>]
+qsv geocode parse-addressnow [options] <location>
 qsv geocode index-load <index-file>
 qsv geocode index-check
 qsv geocode index-update [--languages=<lang>] [--cities-url=<url>] [--force] [--timeout=<seconds>]
@@ -322,6 +374,7 @@ geocode arguments:
                                 For countryinfo, it must be a column with a ISO 3166-1 alpha-2 country code.
                                 For iplookup, it must be a column with an IP address or a URL.
                                 For opencage, it may be a free-form address OR a WGS 84 coordinate.
+                                For parse-address, it must be a column with a free-form street address.
                                 Note that you can use column selector syntax to select the column, but only
                                 the first column will be used. See `select --help` for more information.
 
@@ -332,6 +385,7 @@ geocode arguments:
                                   For countryinfonow, it must be a ISO 3166-1 alpha-2 code.
                                   For iplookupnow, it must be an IP address or a URL.
                                   For opencagenow, it must be an address OR a WGS 84 coordinate.
+                                  For parse-addressnow, it must be a free-form street address.
 
     <index-file>                The alternate geonames index file to use. It must be a .rkyv file.
                                 For convenience, if this is set to a published population floor
@@ -406,6 +460,16 @@ geocode options:
                                 [default: 1209600]
     --no-cache                  Disable the persistent on-disk OpenCage cache. Duplicate
                                 queries within a run are still de-duplicated.
+    --checkpoint <file>         Save the progress of an opencage run to <file>, so it can be
+                                resumed by rerunning it with the same arguments. Requires an
+                                <input> file and --output. The file is removed once the run
+                                completes.
+
+                                PARSE-ADDRESS only option:
+    --address-style <style>     How the parse-address subcommands normalise the address
+                                components - usps, expanded or raw.
+                                See PARSE-ADDRESS above.
+                                [default: usps]
 
     -f, --formatstr=<string>    The place format to use. It has three options:
                                 1. Use one of the predefined formats.
@@ -586,6 +650,7 @@ use uuid::Uuid;
 
 use crate::{
     CliResult,
+    checkpoint::{self, Checkpoint},
     clitypes::CliError,
     config::{Config, Delimiter},
     regex_oncelock,
@@ -594,6 +659,8 @@ use crate::{
     util::replace_column_value,
 };
 
+mod address;
+
 // Cached regex patterns used throughout the geocode module
 // Using module-level statics for better performance
 static ADMIN1_CODE_REGEX: fn() -> &'static Regex = || regex_oncelock!(r"^[A-Z]{2}\.[A-Z0-9]{1,8}$");
@@ -613,53 +680,57 @@ static RELATIVE_AGE_REGEX: fn() -> &'static Regex = || regex_oncelock!(r"(?i)^(\
 
 #[derive(Deserialize)]
 struct Args {
-    arg_column:          String,
-    arg_location:        String,
-    cmd_suggest:         bool,
-    cmd_suggestnow:      bool,
-    cmd_reverse:         bool,
-    cmd_reversenow:      bool,
-    cmd_countryinfo:     bool,
-    cmd_countryinfonow:  bool,
-    cmd_iplookup:        bool,
-    cmd_iplookupnow:     bool,
-    cmd_opencage:        bool,
-    cmd_opencagenow:     bool,
-    cmd_index_check:     bool,
-    cmd_index_update:    bool,
-    cmd_index_load:      bool,
-    cmd_index_reset:     bool,
-    cmd_cache_clear:     bool,
-    cmd_cache_prune:     bool,
-    cmd_cache_info:      bool,
-    arg_input:           Option<String>,
-    arg_index_file:      Option<String>,
-    flag_rename:         Option<String>,
-    flag_country:        Option<String>,
-    flag_min_score:      Option<f32>,
-    flag_admin1:         Option<String>,
-    flag_k_weight:       Option<f32>,
-    flag_formatstr:      String,
-    flag_language:       String,
-    flag_invalid_result: Option<String>,
-    flag_batch:          usize,
-    flag_timeout:        u16,
-    flag_cache_dir:      String,
-    flag_languages:      String,
-    flag_cities_url:     String,
-    flag_force:          bool,
-    flag_jobs:           Option<usize>,
-    flag_new_column:     Option<String>,
-    flag_output:         Option<String>,
-    flag_delimiter:      Option<Delimiter>,
-    flag_progressbar:    bool,
-    flag_api_key:        Option<String>,
-    flag_rate_limit:     u32,
-    flag_reverse:        bool,
-    flag_no_annotations: bool,
-    flag_cache_ttl:      u64,
-    flag_no_cache:       bool,
-    flag_older_than:     Option<String>,
+    arg_column:           String,
+    arg_location:         String,
+    cmd_suggest:          bool,
+    cmd_suggestnow:       bool,
+    cmd_reverse:          bool,
+    cmd_reversenow:       bool,
+    cmd_countryinfo:      bool,
+    cmd_countryinfonow:   bool,
+    cmd_iplookup:         bool,
+    cmd_iplookupnow:      bool,
+    cmd_opencage:         bool,
+    cmd_opencagenow:      bool,
+    cmd_parse_address:    bool,
+    cmd_parse_addressnow: bool,
+    cmd_index_check:      bool,
+    cmd_index_update:     bool,
+    cmd_index_load:       bool,
+    cmd_index_reset:      bool,
+    cmd_cache_clear:      bool,
+    cmd_cache_prune:      bool,
+    cmd_cache_info:       bool,
+    arg_input:            Option<String>,
+    arg_index_file:       Option<String>,
+    flag_rename:          Option<String>,
+    flag_country:         Option<String>,
+    flag_min_score:       Option<f32>,
+    flag_admin1:          Option<String>,
+    flag_k_weight:        Option<f32>,
+    flag_formatstr:       String,
+    flag_language:        String,
+    flag_invalid_result:  Option<String>,
+    flag_batch:           usize,
+    flag_timeout:         u16,
+    flag_cache_dir:       String,
+    flag_languages:       String,
+    flag_cities_url:      String,
+    flag_force:           bool,
+    flag_jobs:            Option<usize>,
+    flag_new_column:      Option<String>,
+    flag_output:          Option<String>,
+    flag_delimiter:       Option<Delimiter>,
+    flag_progressbar:     bool,
+    flag_api_key:         Option<String>,
+    flag_rate_limit:      u32,
+    flag_reverse:         bool,
+    flag_no_annotations:  bool,
+    flag_cache_ttl:       u64,
+    flag_no_cache:        bool,
+    flag_checkpoint:      Option<String>,
+    flag_address_style:   String,
+    flag_older_than:      Option<String>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -927,6 +998,8 @@ enum GeocodeSubCmd {
     IplookupNow,
     Opencage,
     OpencageNow,
+    ParseAddress,
+    ParseAddressNow,
     IndexCheck,
     IndexUpdate,
     IndexLoad,
@@ -1020,16 +1093,30 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         resolve_older_than(older_than)?;
     }
 
+    // --checkpoint is only supported by opencage, the subcommand making (metered) API calls
+    let checkpoint = match args.flag_checkpoint {
+        Some(ref path) if args.cmd_opencage => {
+            checkpoint::check_output(args.flag_output.as_ref())?;
+            Some(Checkpoint::open(path, argv, args.arg_input.as_deref())?)
+        },
+        Some(_) => {
+            return fail_incorrectusage_clierror!(
+                "--checkpoint is only supported by the opencage subcommand."
+            );
+        },
+        None => None,
+    };
+
     // we need to use tokio runtime as geosuggest uses async
     let rt = tokio::runtime::Runtime::new()?;
-    rt.block_on(geocode_main(args))?;
+    rt.block_on(geocode_main(args, checkpoint))?;
 
     Ok(())
 }
 
 // main async geocode function that does the actual work
 #[allow(clippy::future_not_send)]
-async fn geocode_main(args: Args) -> CliResult<()> {
+async fn geocode_main(args: Args, checkpoint: Option<Checkpoint>) -> CliResult<()> {
     let mut index_cmd = false;
     let mut now_cmd = false;
     let mut iplookup_cmd = false;
@@ -1060,6 +1147,10 @@ async fn geocode_main(args: Args) -> CliResult<()> {
     } else if args.cmd_opencagenow {
         now_cmd = true;
         GeocodeSubCmd::OpencageNow
+    } else if args.cmd_parse_address {
+        GeocodeSubCmd::ParseAddress
+    } else if args.cmd_parse_addressnow {
+        GeocodeSubCmd::ParseAddressNow
     } else if args.cmd_index_check {
         index_cmd = true;
         GeocodeSubCmd::IndexCheck
@@ -1083,6 +1174,15 @@ async fn geocode_main(args: Args) -> CliResult<()> {
         unreachable!();
     };
 
+    // parse-address subcommands parse addresses offline with embedded rules. They use
+    // neither the Geonames index nor a cache, so dispatch before setting up the cache dir.
+    if matches!(
+        geocode_cmd,
+        GeocodeSubCmd::ParseAddress | GeocodeSubCmd::ParseAddressNow
+    ) {
+        return run_parse_address(args, geocode_cmd);
+    }
+
     // setup cache directory
     let geocode_cache_dir = resolve_geocode_cache_dir(&args.flag_cache_dir)?;
 
@@ -1094,7 +1194,7 @@ async fn geocode_main(args: Args) -> CliResult<()> {
         geocode_cmd,
         GeocodeSubCmd::Opencage | GeocodeSubCmd::OpencageNow
     ) {
-        return run_opencage(args, geocode_cmd, &geocode_cache_dir).await;
+        return run_opencage(args, geocode_cmd, &geocode_cache_dir, checkpoint).await;
     }
 
     // cache-* subcommands manage the persistent on-disk OpenCage result cache.
@@ -1689,12 +1789,277 @@ async fn geocode_main(args: Args) -> CliResult<()> {
     Ok(wtr.flush()?)
 }
 
+/// Run the `parse-address` / `parse-addressnow` subcommands: offline parsing of free-form
+/// street addresses into their components, with the embedded rules of the `address` module.
+/// Like `geocode_main`, rows are parsed in batches via a rayon parallel iterator.
+fn run_parse_address(args: Args, mode: GeocodeSubCmd) -> CliResult<()> {
+    let now_cmd = mode == GeocodeSubCmd::ParseAddressNow;
+    let style = args
+        .flag_address_style
+        .parse::<address::AddressStyle>()
+        .map_err(CliError::IncorrectUsage)?;
+    let formatstr = args.flag_formatstr.as_str();
+
+    // '%+' adds a column per component, "%dyncols:" a column per "{col_name:component}"
+    // pair. The other formats put the parsed address in a single column.
+    let mut column_names: Vec<String> = Vec::new();
+    let mut column_components: Vec<&str> = Vec::new();
+    if let Some(dyncols) = formatstr.strip_prefix("%dyncols:") {
+        for column in dyncols.split(',') {
+            let column = column.trim();
+            if column.is_empty() {
+                // tolerate a trailing/empty comma-delimited entry
+                continue;
+            }
+            let Some((column_name, component)) = column.trim_matches(['{', '}']).split_once(':')
+            else {
+                return fail_incorrectusage_clierror!(
+                    "Invalid '%dyncols:' pair: {column:?}. Expected a '{{col_name:component}}' \
+                     pair."
+                );
+            };
+            let component = component.trim();
+            if !address::COMPONENT_NAMES.contains(&component) {
+                return fail_incorrectusage_clierror!(
+                    "Invalid '%dyncols:' component: {component}. Valid components are: {:?}",
+                    address::COMPONENT_NAMES
+                );
+            }
+            column_names.push(column_name.trim().to_string());
+            column_components.push(component);
+        }
+        if column_components.is_empty() {
+            return fail_incorrectusage_clierror!(
+                "Invalid '%dyncols:' format - expected one or more 'col_name:component' pairs \
+                 enclosed in curly braces."
+            );
+        }
+    } else if formatstr.starts_with('%')
+        && !matches!(formatstr, "%address" | "%json" | "%pretty-json")
+    {
+        // '%+' - or an invalid format, which is treated as '%+'
+        column_names = address::COMPONENT_NAMES
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        column_components = address::COMPONENT_NAMES.to_vec();
+    }
+    let Ok(columns_len) = u8::try_from(column_components.len()) else {
+        return fail_incorrectusage_clierror!(
+            "Too many %dyncols columns: {} (max 255).",
+            column_components.len()
+        );
+    };
+    if columns_len > 0 && args.flag_new_column.is_some() {
+        return fail_incorrectusage_clierror!(
+            "Cannot use --new-column with the '%+' or '%dyncols:' parse-address formats."
+        );
+    }
+
+    // for parse-addressnow, write the CLI address to a one-row temp CSV, as opencagenow does
+    let tempdir = tempfile::Builder::new().prefix("qsv-geocode").tempdir()?;
+    let input = if now_cmd {
+        let temp_csv_path = format!(
+            "{}/{}.csv",
+            tempdir.path().to_string_lossy(),
+            Uuid::new_v4()
+        );
+        let mut temp_csv_wtr = csv::WriterBuilder::new().from_path(&temp_csv_path)?;
+        temp_csv_wtr.write_record(["Location"])?;
+        temp_csv_wtr.write_record([&args.arg_location])?;
+        temp_csv_wtr.flush()?;
+        Some(temp_csv_path)
+    } else {
+        args.arg_input.clone()
+    };
+
+    let rconfig = Config::new(input.as_ref())
+        .delimiter(args.flag_delimiter)
+        .select(SelectColumns::parse(&args.arg_column)?);
+
+    #[cfg(feature = "datapusher_plus")]
+    let show_progress = false;
+    #[cfg(not(feature = "datapusher_plus"))]
+    let show_progress =
+        (args.flag_progressbar || util::get_envvar_flag("QSV_PROGRESSBAR")) && !rconfig.is_stdin();
+
+    let progress = ProgressBar::with_draw_target(None, ProgressDrawTarget::stderr_with_hz(5));
+    if show_progress {
+        util::prep_progress(&progress, util::count_rows(&rconfig)?);
+    } else {
+        progress.set_draw_target(ProgressDrawTarget::hidden());
+    }
+
+    // when a now subcommand outputs JSON, the CSV writer must not quote the output
+    // (and the "Location" header is omitted) so the result is valid JSON
+    let json_output = now_cmd && (formatstr == "%json" || formatstr == "%pretty-json");
+
+    let mut rdr = rconfig.reader()?;
+    let mut wtr = Config::new(args.flag_output.as_ref())
+        .quote_style(if json_output {
+            csv::QuoteStyle::Never
+        } else {
+            csv::QuoteStyle::Necessary
+        })
+        .writer()?;
+
+    let header_record = rdr.byte_headers()?.clone();
+    let sel = rconfig.selection(&header_record)?;
+    let column_index = *sel.iter().next().unwrap();
+
+    let mut headers = rdr.headers()?.clone();
+    if let Some(new_name) = &args.flag_rename {
+        let new_col_names = util::ColumnNameParser::new(new_name).parse()?;
+        if new_col_names.len() != sel.len() {
+            return fail_incorrectusage_clierror!(
+                "Number of new columns does not match input column selection."
+            );
+        }
+        for (i, col_index) in sel.iter().enumerate() {
+            headers = replace_column_value(&headers, *col_index, &new_col_names[i]);
+        }
+    }
+    if let Some(new_column) = &args.flag_new_column {
+        headers.push_field(new_column);
+    }
+    for column_name in &column_names {
+        headers.push_field(column_name);
+    }
+    if !json_output {
+        wtr.write_record(&headers)?;
+    }
+
+    // amortize memory allocation by reusing record
+    let mut batch_record = csv::StringRecord::new();
+
+    // reuse batch buffers
+    let batchsize: usize = if args.flag_batch == 0 {
+        std::cmp::max(1000, util::count_rows_regular(&rconfig)? as usize)
+    } else {
+        args.flag_batch
+    };
+    let mut batch = Vec::with_capacity(batchsize);
+    let mut batch_results = Vec::with_capacity(batchsize);
+
+    util::njobs(args.flag_jobs);
+
+    let invalid_result = args.flag_invalid_result.unwrap_or_default();
+
+    'batch_loop: loop {
+        for _ in 0..batchsize {
+            match rdr.read_record(&mut batch_record) {
+                Ok(has_data) => {
+                    if has_data {
+                        batch.push(std::mem::take(&mut batch_record));
+                    } else {
+                        // nothing else to add to batch
+                        break;
+                    }
+                },
+                Err(e) => {
+                    return fail_clierror!("Error reading file: {e}");
+                },
+            }
+        }
+
+        if batch.is_empty() {
+            // break out of infinite loop when at EOF
+            break 'batch_loop;
+        }
+
+        batch
+            .par_iter()
+            .map(|record_item| {
+                let mut record = record_item.clone();
+                let cell = record[column_index].to_owned();
+                let components = if cell.is_empty() {
+                    None
+                } else {
+                    Some(address::parse_address(&cell, style))
+                        .filter(address::AddressComponents::is_valid)
+                };
+
+                if columns_len > 0 {
+                    if let Some(components) = components {
+                        for component in &column_components {
+                            record.push_field(components.get(component).unwrap_or_default());
+                        }
+                    } else if cell.is_empty() {
+                        add_fields(&mut record, "", columns_len);
+                    } else {
+                        add_fields(&mut record, &invalid_result, columns_len);
+                    }
+                    return record;
+                }
+
+                let value = match components {
+                    Some(components) => format_address(&components, formatstr),
+                    // not an address - use --invalid-result if set, otherwise
+                    // leave the cell untouched
+                    None if !cell.is_empty() && !invalid_result.is_empty() => {
+                        invalid_result.clone()
+                    },
+                    None => cell,
+                };
+                if args.flag_new_column.is_some() {
+                    record.push_field(&value);
+                } else {
+                    record = replace_column_value(&record, column_index, &value);
+                }
+                record
+            })
+            .collect_into_vec(&mut batch_results);
+
+        // rayon collect() guarantees original order, so we can just append results each batch
+        for result_record in &batch_results {
+            wtr.write_record(result_record)?;
+        }
+
+        if show_progress {
+            progress.inc(batch.len() as u64);
+        }
+
+        batch.clear();
+    } // end batch loop
+
+    if show_progress {
+        util::finish_progress(&progress);
+    }
+    Ok(wtr.flush()?)
+}
+
+/// Format the components of a parsed address with a single-column parse-address
+/// --formatstr: '%address', '%json', '%pretty-json' or a dynfmt template.
+fn format_address(components: &address::AddressComponents, formatstr: &str) -> String {
+    match formatstr {
+        "%address" => components.single_line(),
+        "%json" => serde_json::to_string(components).unwrap_or_default(),
+        "%pretty-json" => serde_json::to_string_pretty(components).unwrap_or_default(),
+        _ => {
+            let component_map: HashMap<&str, String> = address::COMPONENT_NAMES
+                .iter()
+                .map(|name| (*name, components.get(name).unwrap_or_default().to_string()))
+                .collect();
+            if let Ok(formatted) = dynfmt2::SimpleCurlyFormat.format(formatstr, component_map) {
+                formatted.to_string()
+            } else {
+                INVALID_DYNFMT.to_string()
+            }
+        },
+    }
+}
+
 /// Run the `opencage` / `opencagenow` subcommands: online forward/reverse geocoding
 /// via the `OpenCage` API. Rows are processed sequentially behind a rate limiter, with
 /// a persistent on-disk result cache (`OpenCage`'s TOS explicitly permits caching).
 /// This is a separate path from `geocode_main`'s offline, rayon-parallel pipeline.
 #[allow(clippy::future_not_send)]
-async fn run_opencage(args: Args, mode: GeocodeSubCmd, cache_dir: &Path) -> CliResult<()> {
+async fn run_opencage(
+    args: Args,
+    mode: GeocodeSubCmd,
+    cache_dir: &Path,
+    mut checkpoint: Option<Checkpoint>,
+) -> CliResult<()> {
     let now_cmd = mode == GeocodeSubCmd::OpencageNow;
 
     // for opencagenow, write the single CLI value to a one-row temp CSV, so the rest
@@ -1792,14 +2157,21 @@ async fn run_opencage(args: Args, mode: GeocodeSubCmd, cache_dir: &Path) -> CliR
     let json_output =
         now_cmd && (args.flag_formatstr == "%json" || args.flag_formatstr == "%pretty-json");
 
+    // with --checkpoint, a run that died is resumed, appending to its output
+    let resume = checkpoint
+        .as_ref()
+        .filter(|checkpoint| checkpoint.is_resumed());
+
     let mut rdr = rconfig.reader()?;
-    let mut wtr = Config::new(args.flag_output.as_ref())
-        .quote_style(if json_output {
-            csv::QuoteStyle::Never
-        } else {
-            csv::QuoteStyle::Necessary
-        })
-        .writer()?;
+    let wtr_config = Config::new(args.flag_output.as_ref()).quote_style(if json_output {
+        csv::QuoteStyle::Never
+    } else {
+        csv::QuoteStyle::Necessary
+    });
+    let mut wtr = match resume {
+        Some(checkpoint) => wtr_config.resume_writer(checkpoint.output_len())?,
+        None => wtr_config.writer()?,
+    };
 
     let header_record = rdr.byte_headers()?.clone();
     let sel = rconfig.selection(&header_record)?;
@@ -1824,7 +2196,7 @@ async fn run_opencage(args: Args, mode: GeocodeSubCmd, cache_dir: &Path) -> CliR
     for column_name in &column_names {
         headers.push_field(column_name);
     }
-    if !json_output {
+    if !json_output && resume.is_none() {
         wtr.write_record(&headers)?;
     }
 
@@ -1885,6 +2257,22 @@ async fn run_opencage(args: Args, mode: GeocodeSubCmd, cache_dir: &Path) -> CliR
     let countrycode = args.flag_country.as_deref();
 
     let mut record = csv::StringRecord::new();
+
+    // skip the records already written by the run we're resuming
+    let mut rows_written = 0_u64;
+    if let Some(checkpoint) = resume {
+        while rows_written < checkpoint.rows() && rdr.read_record(&mut record)? {
+            rows_written += 1;
+        }
+        if show_progress {
+            progress.inc(rows_written);
+        }
+        winfo!(
+            "Resuming from checkpoint: {} records already geocoded.",
+            indicatif::HumanCount(rows_written)
+        );
+    }
+
     while rdr.read_record(&mut record)? {
         let cell = record.get(column_index).unwrap_or_default().to_string();
 
@@ -1911,7 +2299,20 @@ async fn run_opencage(args: Args, mode: GeocodeSubCmd, cache_dir: &Path) -> CliR
                 .await
                 {
                     Ok(values) => values,
-                    Err(OcError::Fatal(msg)) => return fail_clierror!("{msg}"),
+                    Err(OcError::Fatal(msg)) => {
+                        // e.g. the API quota is exhausted - save where we got to, so the
+                        // run can be resumed once the quota is reset
+                        if let Some(ref mut checkpoint) = checkpoint {
+                            save_opencage_checkpoint(
+                                checkpoint,
+                                rows_written,
+                                &mut wtr,
+                                disk_cache.as_ref(),
+                                &args,
+                            )?;
+                        }
+                        return fail_clierror!("{msg}");
+                    },
                     Err(OcError::Transient(msg)) => {
                         log::warn!("OpenCage lookup failed for {cell:?}: {msg}");
                         None
@@ -1953,7 +2354,20 @@ async fn run_opencage(args: Args, mode: GeocodeSubCmd, cache_dir: &Path) -> CliR
                     Ok(Some(result)) => result,
                     Ok(None) if invalid_result.is_empty() => cell.clone(),
                     Ok(None) => invalid_result.clone(),
-                    Err(OcError::Fatal(msg)) => return fail_clierror!("{msg}"),
+                    Err(OcError::Fatal(msg)) => {
+                        // e.g. the API quota is exhausted - save where we got to, so the
+                        // run can be resumed once the quota is reset
+                        if let Some(ref mut checkpoint) = checkpoint {
+                            save_opencage_checkpoint(
+                                checkpoint,
+                                rows_written,
+                                &mut wtr,
+                                disk_cache.as_ref(),
+                                &args,
+                            )?;
+                        }
+                        return fail_clierror!("{msg}");
+                    },
                     Err(OcError::Transient(msg)) => {
                         log::warn!("OpenCage lookup failed for {cell:?}: {msg}");
                         if invalid_result.is_empty() {
@@ -1982,12 +2396,50 @@ async fn run_opencage(args: Args, mode: GeocodeSubCmd, cache_dir: &Path) -> CliR
         if show_progress {
             progress.inc(1);
         }
+
+        rows_written += 1;
+        if let Some(ref mut checkpoint) = checkpoint
+            && checkpoint.is_due()
+        {
+            save_opencage_checkpoint(
+                checkpoint,
+                rows_written,
+                &mut wtr,
+                disk_cache.as_ref(),
+                &args,
+            )?;
+        }
     }
 
     if show_progress {
         util::finish_progress(&progress);
     }
-    Ok(wtr.flush()?)
+    wtr.flush()?;
+    if let Some(checkpoint) = checkpoint {
+        checkpoint.finish()?;
+    }
+    Ok(())
+}
+
+/// Flush the output & the `OpenCage` cache, so the --checkpoint only covers rows that are
+/// completely written - and cached - then save it.
+fn save_opencage_checkpoint(
+    checkpoint: &mut Checkpoint,
+    rows_written: u64,
+    wtr: &mut csv::Writer<Box<dyn std::io::Write>>,
+    disk_cache: Option<&RedbCache<String, String>>,
+    args: &Args,
+) -> CliResult<()> {
+    wtr.flush()?;
+    if let Some(dc) = disk_cache {
+        dc.flush()
+            .map_err(|e| CliError::Other(format!("Error flushing OpenCage cache: {e}")))?;
+    }
+    checkpoint.save(
+        rows_written,
+        args.flag_output.as_deref().unwrap_or_default(),
+        None,
+    )
 }
 
 // ─────────────────────── OpenCage disk-cache management ───────────────────────
@@ -0,0 +1,1024 @@
+//! Offline parsing of free-form street addresses for `geocode parse-address`.
+//!
+//! A rule-based parser in the spirit of libpostal, with embedded USPS Publication 28 tables
+//! (street suffixes, directionals and secondary unit designators) plus the US state/territory
+//! and Canadian province codes. It works right-to-left, peeling the country, postcode and state
+//! off the end of the address, then splits what's left into the street line (house number,
+//! street and unit) and the city. Comma-separated addresses parse best, but single-line
+//! addresses are handled too, by ending the street at its suffix - e.g.
+//! "123 Main St Apt 4 Springfield IL 62704".
+
+use std::str::FromStr;
+
+use phf::phf_map;
+use serde::Serialize;
+
+use crate::regex_oncelock;
+
+static HOUSE_NUMBER_REGEX: fn() -> &'static regex::Regex =
+    || regex_oncelock!(r"^\d+(?:-\d+)?[A-Z]?$");
+static ORDINAL_REGEX: fn() -> &'static regex::Regex = || regex_oncelock!(r"^\d+(?:ST|ND|RD|TH)$");
+static FRACTION_REGEX: fn() -> &'static regex::Regex = || regex_oncelock!(r"^\d/\d$");
+static ZIP_REGEX: fn() -> &'static regex::Regex = || regex_oncelock!(r"^(\d{5})(?:-?(\d{4}))?$");
+static CA_POSTCODE_REGEX: fn() -> &'static regex::Regex =
+    || regex_oncelock!(r"^([ABCEGHJ-NPRSTVXY]\d[ABCEGHJ-NPRSTV-Z])-?(\d[ABCEGHJ-NPRSTV-Z]\d)$");
+
+/// The components of a parsed address, in output column order.
+pub static COMPONENT_NAMES: [&str; 7] = [
+    "house_number",
+    "street",
+    "unit",
+    "city",
+    "state",
+    "postcode",
+    "country",
+];
+
+/// USPS street suffixes (Publication 28, Appendix C1): spelling => (abbreviation, name).
+static SUFFIXES: phf::Map<&'static str, (&'static str, &'static str)> = phf_map! {
+    "ALLEY" => ("ALY", "Alley"), "ALLEE" => ("ALY", "Alley"), "ALY" => ("ALY", "Alley"),
+    "ANNEX" => ("ANX", "Annex"), "ANX" => ("ANX", "Annex"),
+    "ARCADE" => ("ARC", "Arcade"), "ARC" => ("ARC", "Arcade"),
+    "AVENUE" => ("AVE", "Avenue"), "AVE" => ("AVE", "Avenue"), "AV" => ("AVE", "Avenue"),
+    "AVEN" => ("AVE", "Avenue"), "AVENU" => ("AVE", "Avenue"), "AVN" => ("AVE", "Avenue"),
+    "AVNUE" => ("AVE", "Avenue"),
+    "BAYOU" => ("BYU", "Bayou"), "BYU" => ("BYU", "Bayou"),
+    "BEACH" => ("BCH", "Beach"), "BCH" => ("BCH", "Beach"),
+    "BEND" => ("BND", "Bend"), "BND" => ("BND", "Bend"),
+    "BLUFF" => ("BLF", "Bluff"), "BLF" => ("BLF", "Bluff"),
+    "BOULEVARD" => ("BLVD", "Boulevard"), "BLVD" => ("BLVD", "Boulevard"),
+    "BOUL" => ("BLVD", "Boulevard"), "BOULV" => ("BLVD", "Boulevard"),
+    "BRANCH" => ("BR", "Branch"), "BR" => ("BR", "Branch"),
+    "BRIDGE" => ("BRG", "Bridge"), "BRG" => ("BRG", "Bridge"),
+    "BROOK" => ("BRK", "Brook"), "BRK" => ("BRK", "Brook"),
+    "BYPASS" => ("BYP", "Bypass"), "BYP" => ("BYP", "Bypass"),
+    "CANYON" => ("CYN", "Canyon"), "CYN" => ("CYN", "Canyon"),
+    "CAUSEWAY" => ("CSWY", "Causeway"), "CSWY" => ("CSWY", "Causeway"),
+    "CENTER" => ("CTR", "Center"), "CTR" => ("CTR", "Center"), "CENTRE" => ("CTR", "Center"),
+    "CNTR" => ("CTR", "Center"),
+    "CIRCLE" => ("CIR", "Circle"), "CIR" => ("CIR", "Circle"), "CIRC" => ("CIR", "Circle"),
+    "CRCL" => ("CIR", "Circle"),
+    "CLIFF" => ("CLF", "Cliff"), "CLF" => ("CLF", "Cliff"),
+    "COMMON" => ("CMN", "Common"), "CMN" => ("CMN", "Common"),
+    "CORNER" => ("COR", "Corner"), "COR" => ("COR", "Corner"),
+    "COURT" => ("CT", "Court"), "CT" => ("CT", "Court"),
+    "COVE" => ("CV", "Cove"), "CV" => ("CV", "Cove"),
+    "CREEK" => ("CRK", "Creek"), "CRK" => ("CRK", "Creek"),
+    "CRESCENT" => ("CRES", "Crescent"), "CRES" => ("CRES", "Crescent"),
+    "CROSSING" => ("XING", "Crossing"), "XING" => ("XING", "Crossing"),
+    "DRIVE" => ("DR", "Drive"), "DR" => ("DR", "Drive"), "DRV" => ("DR", "Drive"),
+    "ESTATES" => ("ESTS", "Estates"), "ESTS" => ("ESTS", "Estates"),
+    "EXPRESSWAY" => ("EXPY", "Expressway"), "EXPY" => ("EXPY", "Expressway"),
+    "EXPWY" => ("EXPY", "Expressway"),
+    "EXTENSION" => ("EXT", "Extension"), "EXT" => ("EXT", "Extension"),
+    "FALLS" => ("FLS", "Falls"), "FLS" => ("FLS", "Falls"),
+    "FERRY" => ("FRY", "Ferry"), "FRY" => ("FRY", "Ferry"),
+    "FIELD" => ("FLD", "Field"), "FLD" => ("FLD", "Field"),
+    "FIELDS" => ("FLDS", "Fields"), "FLDS" => ("FLDS", "Fields"),
+    "FOREST" => ("FRST", "Forest"), "FRST" => ("FRST", "Forest"),
+    "FORK" => ("FRK", "Fork"), "FRK" => ("FRK", "Fork"),
+    "FREEWAY" => ("FWY", "Freeway"), "FWY" => ("FWY", "Freeway"),
+    "GARDENS" => ("GDNS", "Gardens"), "GDNS" => ("GDNS", "Gardens"),
+    "GATEWAY" => ("GTWY", "Gateway"), "GTWY" => ("GTWY", "Gateway"),
+    "GLEN" => ("GLN", "Glen"), "GLN" => ("GLN", "Glen"),
+    "GREEN" => ("GRN", "Green"), "GRN" => ("GRN", "Green"),
+    "GROVE" => ("GRV", "Grove"), "GRV" => ("GRV", "Grove"),
+    "HARBOR" => ("HBR", "Harbor"), "HBR" => ("HBR", "Harbor"),
+    "HEIGHTS" => ("HTS", "Heights"), "HTS" => ("HTS", "Heights"),
+    "HIGHWAY" => ("HWY", "Highway"), "HWY" => ("HWY", "Highway"),
+    "HIWAY" => ("HWY", "Highway"), "HIWY" => ("HWY", "Highway"),
+    "HILL" => ("HL", "Hill"), "HL" => ("HL", "Hill"),
+    "HILLS" => ("HLS", "Hills"), "HLS" => ("HLS", "Hills"),
+    "HOLLOW" => ("HOLW", "Hollow"), "HOLW" => ("HOLW", "Hollow"),
+    "ISLAND" => ("IS", "Island"),
+    "JUNCTION" => ("JCT", "Junction"), "JCT" => ("JCT", "Junction"),
+    "KNOLL" => ("KNL", "Knoll"), "KNL" => ("KNL", "Knoll"),
+    "LAKE" => ("LK", "Lake"), "LK" => ("LK", "Lake"),
+    "LANDING" => ("LNDG", "Landing"), "LNDG" => ("LNDG", "Landing"),
+    "LANE" => ("LN", "Lane"), "LN" => ("LN", "Lane"),
+    "LOOP" => ("LOOP", "Loop"),
+    "MALL" => ("MALL", "Mall"),
+    "MANOR" => ("MNR", "Manor"), "MNR" => ("MNR", "Manor"),
+    "MEADOWS" => ("MDWS", "Meadows"), "MDWS" => ("MDWS", "Meadows"),
+    "MILL" => ("ML", "Mill"), "ML" => ("ML", "Mill"),
+    "MOTORWAY" => ("MTWY", "Motorway"), "MTWY" => ("MTWY", "Motorway"),
+    "MOUNT" => ("MT", "Mount"),
+    "MOUNTAIN" => ("MTN", "Mountain"), "MTN" => ("MTN", "Mountain"),
+    "ORCHARD" => ("ORCH", "Orchard"), "ORCH" => ("ORCH", "Orchard"),
+    "OVAL" => ("OVAL", "Oval"),
+    "PARK" => ("PARK", "Park"),
+    "PARKWAY" => ("PKWY", "Parkway"), "PKWY" => ("PKWY", "Parkway"),
+    "PKY" => ("PKWY", "Parkway"), "PARKWY" => ("PKWY", "Parkway"),
+    "PASS" => ("PASS", "Pass"),
+    "PATH" => ("PATH", "Path"),
+    "PIKE" => ("PIKE", "Pike"),
+    "PINES" => ("PNES", "Pines"), "PNES" => ("PNES", "Pines"),
+    "PLACE" => ("PL", "Place"), "PL" => ("PL", "Place"),
+    "PLAINS" => ("PLNS", "Plains"), "PLNS" => ("PLNS", "Plains"),
+    "PLAZA" => ("PLZ", "Plaza"), "PLZ" => ("PLZ", "Plaza"),
+    "POINT" => ("PT", "Point"), "PT" => ("PT", "Point"),
+    "PORT" => ("PRT", "Port"), "PRT" => ("PRT", "Port"),
+    "RANCH" => ("RNCH", "Ranch"), "RNCH" => ("RNCH", "Ranch"),
+    "RIDGE" => ("RDG", "Ridge"), "RDG" => ("RDG", "Ridge"),
+    "RIVER" => ("RIV", "River"), "RIV" => ("RIV", "River"),
+    "ROAD" => ("RD", "Road"), "RD" => ("RD", "Road"),
+    "ROUTE" => ("RTE", "Route"), "RTE" => ("RTE", "Route"),
+    "ROW" => ("ROW", "Row"),
+    "RUN" => ("RUN", "Run"),
+    "SHORE" => ("SHR", "Shore"), "SHR" => ("SHR", "Shore"),
+    "SKYWAY" => ("SKWY", "Skyway"), "SKWY" => ("SKWY", "Skyway"),
+    "SPRINGS" => ("SPGS", "Springs"), "SPGS" => ("SPGS", "Springs"),
+    "SQUARE" => ("SQ", "Square"), "SQ" => ("SQ", "Square"), "SQR" => ("SQ", "Square"),
+    "STATION" => ("STA", "Station"), "STA" => ("STA", "Station"),
+    "STREET" => ("ST", "Street"), "ST" => ("ST", "Street"), "STR" => ("ST", "Street"),
+    "STRT" => ("ST", "Street"),
+    "SUMMIT" => ("SMT", "Summit"), "SMT" => ("SMT", "Summit"),
+    "TERRACE" => ("TER", "Terrace"), "TER" => ("TER", "Terrace"), "TERR" => ("TER", "Terrace"),
+    "TRACE" => ("TRCE", "Trace"), "TRCE" => ("TRCE", "Trace"),
+    "TRAIL" => ("TRL", "Trail"), "TRL" => ("TRL", "Trail"),
+    "TUNNEL" => ("TUNL", "Tunnel"), "TUNL" => ("TUNL", "Tunnel"),
+    "TURNPIKE" => ("TPKE", "Turnpike"), "TPKE" => ("TPKE", "Turnpike"),
+    "VALLEY" => ("VLY", "Valley"), "VLY" => ("VLY", "Valley"),
+    "VIEW" => ("VW", "View"), "VW" => ("VW", "View"),
+    "VILLAGE" => ("VLG", "Village"), "VLG" => ("VLG", "Village"),
+    "VISTA" => ("VIS", "Vista"), "VIS" => ("VIS", "Vista"),
+    "WALK" => ("WALK", "Walk"),
+    "WAY" => ("WAY", "Way"), "WY" => ("WAY", "Way"),
+};
+
+/// Directionals: spelling => (abbreviation, name).
+static DIRECTIONALS: phf::Map<&'static str, (&'static str, &'static str)> = phf_map! {
+    "N" => ("N", "North"), "NORTH" => ("N", "North"),
+    "S" => ("S", "South"), "SOUTH" => ("S", "South"),
+    "E" => ("E", "East"), "EAST" => ("E", "East"),
+    "W" => ("W", "West"), "WEST" => ("W", "West"),
+    "NE" => ("NE", "Northeast"), "NORTHEAST" => ("NE", "Northeast"),
+    "NW" => ("NW", "Northwest"), "NORTHWEST" => ("NW", "Northwest"),
+    "SE" => ("SE", "Southeast"), "SOUTHEAST" => ("SE", "Southeast"),
+    "SW" => ("SW", "Southwest"), "SOUTHWEST" => ("SW", "Southwest"),
+};
+
+/// A secondary unit designator (USPS Publication 28, Appendix C2).
+struct Designator {
+    abbr:   &'static str,
+    name:   &'static str,
+    /// whether it is followed by a unit number, e.g. "APT 4B" but "REAR"
+    ranged: bool,
+    /// whether it is unambiguous enough to end a street mid-segment. The others
+    /// ("Front", "Lower", "Lot", ...) are only recognized at the start of a segment.
+    inline: bool,
+}
+
+macro_rules! designator {
+    ($abbr:literal, $name:literal, $ranged:literal, $inline:literal) => {
+        Designator {
+            abbr:   $abbr,
+            name:   $name,
+            ranged: $ranged,
+            inline: $inline,
+        }
+    };
+}
+
+static DESIGNATORS: phf::Map<&'static str, Designator> = phf_map! {
+    "APARTMENT" => designator!("APT", "Apartment", true, true),
+    "APT" => designator!("APT", "Apartment", true, true),
+    "BASEMENT" => designator!("BSMT", "Basement", false, true),
+    "BSMT" => designator!("BSMT", "Basement", false, true),
+    "BUILDING" => designator!("BLDG", "Building", true, true),
+    "BLDG" => designator!("BLDG", "Building", true, true),
+    "DEPARTMENT" => designator!("DEPT", "Department", true, true),
+    "DEPT" => designator!("DEPT", "Department", true, true),
+    "FLOOR" => designator!("FL", "Floor", true, true),
+    "FLR" => designator!("FL", "Floor", true, true),
+    "FL" => designator!("FL", "Floor", true, true),
+    "FRONT" => designator!("FRNT", "Front", false, false),
+    "FRNT" => designator!("FRNT", "Front", false, false),
+    "HANGAR" => designator!("HNGR", "Hangar", true, true),
+    "HNGR" => designator!("HNGR", "Hangar", true, true),
+    "LOBBY" => designator!("LBBY", "Lobby", false, false),
+    "LBBY" => designator!("LBBY", "Lobby", false, false),
+    "LOT" => designator!("LOT", "Lot", true, false),
+    "LOWER" => designator!("LOWR", "Lower", false, false),
+    "LOWR" => designator!("LOWR", "Lower", false, false),
+    "OFFICE" => designator!("OFC", "Office", false, false),
+    "OFC" => designator!("OFC", "Office", false, false),
+    "PENTHOUSE" => designator!("PH", "Penthouse", false, true),
+    "PH" => designator!("PH", "Penthouse", false, true),
+    "PIER" => designator!("PIER", "Pier", true, false),
+    "REAR" => designator!("REAR", "Rear", false, false),
+    "ROOM" => designator!("RM", "Room", true, true),
+    "RM" => designator!("RM", "Room", true, true),
+    "SIDE" => designator!("SIDE", "Side", false, false),
+    "SLIP" => designator!("SLIP", "Slip", true, false),
+    "SPACE" => designator!("SPC", "Space", true, false),
+    "SPC" => designator!("SPC", "Space", true, true),
+    "STOP" => designator!("STOP", "Stop", true, false),
+    "SUITE" => designator!("STE", "Suite", true, true),
+    "STE" => designator!("STE", "Suite", true, true),
+    "TRAILER" => designator!("TRLR", "Trailer", true, true),
+    "TRLR" => designator!("TRLR", "Trailer", true, true),
+    "UNIT" => designator!("UNIT", "Unit", true, true),
+    "UPPER" => designator!("UPPR", "Upper", false, false),
+    "UPPR" => designator!("UPPR", "Upper", false, false),
+    "#" => designator!("#", "#", true, true),
+};
+
+/// US states & territories and Canadian provinces & territories: code => name.
+static REGIONS: phf::Map<&'static str, &'static str> = phf_map! {
+    "AL" => "Alabama", "AK" => "Alaska", "AZ" => "Arizona", "AR" => "Arkansas",
+    "CA" => "California", "CO" => "Colorado", "CT" => "Connecticut", "DE" => "Delaware",
+    "DC" => "District of Columbia", "FL" => "Florida", "GA" => "Georgia", "HI" => "Hawaii",
+    "ID" => "Idaho", "IL" => "Illinois", "IN" => "Indiana", "IA" => "Iowa", "KS" => "Kansas",
+    "KY" => "Kentucky", "LA" => "Louisiana", "ME" => "Maine", "MD" => "Maryland",
+    "MA" => "Massachusetts", "MI" => "Michigan", "MN" => "Minnesota", "MS" => "Mississippi",
+    "MO" => "Missouri", "MT" => "Montana", "NE" => "Nebraska", "NV" => "Nevada",
+    "NH" => "New Hampshire", "NJ" => "New Jersey", "NM" => "New Mexico", "NY" => "New York",
+    "NC" => "North Carolina", "ND" => "North Dakota", "OH" => "Ohio", "OK" => "Oklahoma",
+    "OR" => "Oregon", "PA" => "Pennsylvania", "RI" => "Rhode Island", "SC" => "South Carolina",
+    "SD" => "South Dakota", "TN" => "Tennessee", "TX" => "Texas", "UT" => "Utah",
+    "VT" => "Vermont", "VA" => "Virginia", "WA" => "Washington", "WV" => "West Virginia",
+    "WI" => "Wisconsin", "WY" => "Wyoming",
+    "AS" => "American Samoa", "GU" => "Guam", "MP" => "Northern Mariana Islands",
+    "PR" => "Puerto Rico", "VI" => "Virgin Islands",
+    "AB" => "Alberta", "BC" => "British Columbia", "MB" => "Manitoba", "NB" => "New Brunswick",
+    "NL" => "Newfoundland and Labrador", "NS" => "Nova Scotia", "NT" => "Northwest Territories",
+    "NU" => "Nunavut", "ON" => "Ontario", "PE" => "Prince Edward Island", "QC" => "Quebec",
+    "SK" => "Saskatchewan", "YT" => "Yukon",
+};
+
+/// Spelled-out region names (uppercased) => code.
+static REGION_NAMES: phf::Map<&'static str, &'static str> = phf_map! {
+    "ALABAMA" => "AL", "ALASKA" => "AK", "ARIZONA" => "AZ", "ARKANSAS" => "AR",
+    "CALIFORNIA" => "CA", "COLORADO" => "CO", "CONNECTICUT" => "CT", "DELAWARE" => "DE",
+    "DISTRICT OF COLUMBIA" => "DC", "FLORIDA" => "FL", "GEORGIA" => "GA", "HAWAII" => "HI",
+    "IDAHO" => "ID", "ILLINOIS" => "IL", "INDIANA" => "IN", "IOWA" => "IA", "KANSAS" => "KS",
+    "KENTUCKY" => "KY", "LOUISIANA" => "LA", "MAINE" => "ME", "MARYLAND" => "MD",
+    "MASSACHUSETTS" => "MA", "MICHIGAN" => "MI", "MINNESOTA" => "MN", "MISSISSIPPI" => "MS",
+    "MISSOURI" => "MO", "MONTANA" => "MT", "NEBRASKA" => "NE", "NEVADA" => "NV",
+    "NEW HAMPSHIRE" => "NH", "NEW JERSEY" => "NJ", "NEW MEXICO" => "NM", "NEW YORK" => "NY",
+    "NORTH CAROLINA" => "NC", "NORTH DAKOTA" => "ND", "OHIO" => "OH", "OKLAHOMA" => "OK",
+    "OREGON" => "OR", "PENNSYLVANIA" => "PA", "RHODE ISLAND" => "RI",
+    "SOUTH CAROLINA" => "SC", "SOUTH DAKOTA" => "SD", "TENNESSEE" => "TN", "TEXAS" => "TX",
+    "UTAH" => "UT", "VERMONT" => "VT", "VIRGINIA" => "VA", "WASHINGTON" => "WA",
+    "WEST VIRGINIA" => "WV", "WISCONSIN" => "WI", "WYOMING" => "WY",
+    "AMERICAN SAMOA" => "AS", "GUAM" => "GU", "NORTHERN MARIANA ISLANDS" => "MP",
+    "PUERTO RICO" => "PR", "VIRGIN ISLANDS" => "VI",
+    "ALBERTA" => "AB", "BRITISH COLUMBIA" => "BC", "MANITOBA" => "MB", "NEW BRUNSWICK" => "NB",
+    "NEWFOUNDLAND AND LABRADOR" => "NL", "NEWFOUNDLAND" => "NL", "NOVA SCOTIA" => "NS",
+    "NORTHWEST TERRITORIES" => "NT", "NUNAVUT" => "NU", "ONTARIO" => "ON",
+    "PRINCE EDWARD ISLAND" => "PE", "QUEBEC" => "QC", "QUÉBEC" => "QC",
+    "SASKATCHEWAN" => "SK", "YUKON" => "YT",
+};
+
+/// Country spellings (uppercased) => ISO 3166-1 alpha-2 code.
+static COUNTRIES: phf::Map<&'static str, &'static str> = phf_map! {
+    "US" => "US", "USA" => "US", "UNITED STATES" => "US", "UNITED STATES OF AMERICA" => "US",
+    "CANADA" => "CA", "CAN" => "CA",
+};
+
+/// How the parsed components are normalised.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum AddressStyle {
+    /// USPS Publication 28 - uppercase, with standard abbreviations & state codes.
+    Usps,
+    /// Title Case, with abbreviations expanded & state names spelled out.
+    Expanded,
+    /// As found in the input.
+    Raw,
+}
+
+impl FromStr for AddressStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "usps" => Ok(Self::Usps),
+            "expanded" => Ok(Self::Expanded),
+            "raw" => Ok(Self::Raw),
+            _ => Err(format!(
+                "Invalid --address-style: {s}. Valid values are usps, expanded or raw."
+            )),
+        }
+    }
+}
+
+/// The normalised components of a parsed address. Missing components are empty.
+#[derive(Serialize, Default, Debug, PartialEq, Eq)]
+pub struct AddressComponents {
+    pub house_number: String,
+    pub street:       String,
+    pub unit:         String,
+    pub city:         String,
+    pub state:        String,
+    pub postcode:     String,
+    pub country:      String,
+}
+
+impl AddressComponents {
+    /// The component named `name` - one of [`COMPONENT_NAMES`].
+    pub fn get(&self, name: &str) -> Option<&str> {
+        Some(match name {
+            "house_number" => &self.house_number,
+            "street" => &self.street,
+            "unit" => &self.unit,
+            "city" => &self.city,
+            "state" => &self.state,
+            "postcode" => &self.postcode,
+            "country" => &self.country,
+            _ => return None,
+        })
+    }
+
+    /// Whether anything address-like was recognized. When none of the street, state
+    /// and postcode were found, the value was not parsed - it is all "city".
+    pub const fn is_valid(&self) -> bool {
+        !(self.street.is_empty() && self.state.is_empty() && self.postcode.is_empty())
+    }
+
+    /// The address on a single line, e.g. "123 MAIN ST APT 4, SPRINGFIELD, IL 62704".
+    pub fn single_line(&self) -> String {
+        let join = |parts: &[&str], sep: &str| {
+            parts
+                .iter()
+                .filter(|part| !part.is_empty())
+                .copied()
+                .collect::<Vec<_>>()
+                .join(sep)
+        };
+        let street_line = join(&[&self.house_number, &self.street, &self.unit], " ");
+        let region_line = join(&[&self.state, &self.postcode], " ");
+        join(
+            &[&street_line, &self.city, &region_line, &self.country],
+            ", ",
+        )
+    }
+}
+
+struct Token<'a> {
+    /// the word as found, without surrounding punctuation
+    raw: &'a str,
+    /// the uppercased word, without periods - used for all the table lookups
+    key: String,
+    /// the index of the comma-separated segment the word is in
+    seg: usize,
+}
+
+#[derive(Default)]
+struct Parsed<'t> {
+    house_number: &'t [Token<'t>],
+    street:       &'t [Token<'t>],
+    po_box:       bool,
+    unit:         Vec<&'t Token<'t>>,
+    city:         &'t [Token<'t>],
+    state:        Option<(&'t [Token<'t>], &'static str)>,
+    postcode:     Option<(&'t [Token<'t>], String)>,
+    country:      Option<(&'t [Token<'t>], &'static str)>,
+}
+
+/// Parse a free-form address into its components, normalised per `style`.
+pub fn parse_address(address: &str, style: AddressStyle) -> AddressComponents {
+    let tokens = tokenize(address);
+    let mut parsed = Parsed::default();
+    let mut end = tokens.len();
+
+    // the country, if it is its own segment or follows the postcode
+    if let Some((n, code)) = phrase_at_end(&tokens[..end], 4, &COUNTRIES) {
+        let start = end - n;
+        if starts_segment(&tokens, start) || postcode_at_end(&tokens[..start]).is_some() {
+            parsed.country = Some((&tokens[start..end], code));
+            end = start;
+        }
+    }
+
+    if let Some((n, postcode)) = postcode_at_end(&tokens[..end]) {
+        let start = end - n;
+        parsed.postcode = Some((&tokens[start..end], postcode));
+        end = start;
+    }
+
+    // the state, by code or by name
+    let by_code = tokens[..end]
+        .last()
+        .and_then(|token| REGIONS.get_key(token.key.as_str()))
+        .map(|code| (1, *code));
+    let by_name = phrase_at_end(&tokens[..end], 3, &REGION_NAMES);
+    if let Some((n, code)) = by_name.or(by_code) {
+        let start = end - n;
+        // a spelled-out name could just as well end a city ("Fort Washington"), so
+        // it needs a segment of its own or a postcode/country after it
+        let allowed = if by_name.is_some() {
+            starts_segment(&tokens, start) || parsed.postcode.is_some() || parsed.country.is_some()
+        } else {
+            state_code_allowed(&tokens, start, parsed.postcode.is_some())
+        };
+        if allowed {
+            parsed.state = Some((&tokens[start..end], code));
+            end = start;
+        }
+    }
+
+    // what's left is the street line & the city, possibly with a venue name in front
+    let segments: Vec<&[Token]> = tokens[..end].chunk_by(|a, b| a.seg == b.seg).collect();
+    let street_idx = segments
+        .iter()
+        .position(|segment| street_start(segment).is_some())
+        .or_else(|| {
+            // without a house number, a segment is only a street if a city follows it
+            segments
+                .iter()
+                .take(segments.len().saturating_sub(1))
+                .position(|segment| is_street_name(segment))
+        });
+
+    let mut trailing: &[Token] = &[];
+    let mut city: Option<&[Token]> = None;
+    for (i, &segment) in segments.iter().enumerate() {
+        if Some(i) == street_idx {
+            trailing = parse_street(segment, i + 1 == segments.len(), &mut parsed);
+        } else if is_unit_segment(segment) {
+            parsed.unit.extend(segment.iter());
+        } else if street_idx.is_none_or(|street_idx| i > street_idx) {
+            // the last of several segments is the city, e.g. "..., Downtown, Springfield"
+            city = Some(segment);
+        }
+    }
+    parsed.city = city.unwrap_or(trailing);
+
+    parsed.render(style)
+}
+
+fn tokenize(address: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    for (seg, segment) in address.split([',', ';', '\n', '\r']).enumerate() {
+        for word in segment.split_whitespace() {
+            let mut word = word.trim_matches(|c: char| !(c.is_alphanumeric() || c == '#'));
+            // "#4B" is the "#" unit designator followed by the unit number
+            if word.starts_with('#') {
+                tokens.push(Token {
+                    raw: &word[..1],
+                    key: "#".to_string(),
+                    seg,
+                });
+                word = word.trim_start_matches('#');
+            }
+            let key: String = word
+                .chars()
+                .filter(|c| *c != '.')
+                .flat_map(char::to_uppercase)
+                .collect();
+            if !key.is_empty() {
+                tokens.push(Token {
+                    raw: word,
+                    key,
+                    seg,
+                });
+            }
+        }
+    }
+    tokens
+}
+
+fn starts_segment(tokens: &[Token], i: usize) -> bool {
+    i == 0 || tokens[i - 1].seg != tokens[i].seg
+}
+
+/// The longest phrase of up to `max_words` words within the last segment of `tokens`
+/// found in `table`, with its word count.
+fn phrase_at_end(
+    tokens: &[Token],
+    max_words: usize,
+    table: &phf::Map<&'static str, &'static str>,
+) -> Option<(usize, &'static str)> {
+    let last_seg = tokens.last()?.seg;
+    (1..=max_words.min(tokens.len())).rev().find_map(|n| {
+        let words = &tokens[tokens.len() - n..];
+        if words[0].seg != last_seg {
+            return None;
+        }
+        let phrase = words
+            .iter()
+            .map(|token| token.key.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+        table.get(phrase.as_str()).map(|code| (n, *code))
+    })
+}
+
+/// A US ZIP (ZIP+4) or Canadian postal code at the end of `tokens`, with its word
+/// count and normalised form.
+fn postcode_at_end(tokens: &[Token]) -> Option<(usize, String)> {
+    let last = tokens.last()?;
+    let before = tokens
+        .len()
+        .checked_sub(2)
+        .map(|i| &tokens[i])
+        .filter(|token| token.seg == last.seg);
+
+    if let Some(caps) = ZIP_REGEX().captures(&last.key) {
+        // the number of a PO box or a unit is not a ZIP code
+        if before.is_some_and(|token| {
+            matches!(token.key.as_str(), "BOX" | "POB")
+                || DESIGNATORS.contains_key(token.key.as_str())
+        }) {
+            return None;
+        }
+        let zip = match caps.get(2) {
+            Some(plus4) => format!("{}-{}", &caps[1], plus4.as_str()),
+            None => caps[1].to_string(),
+        };
+        return Some((1, zip));
+    }
+    if let Some(before) = before
+        && before.key.len() == 3
+        && let Some(caps) = CA_POSTCODE_REGEX().captures(&format!("{}{}", before.key, last.key))
+    {
+        return Some((2, format!("{} {}", &caps[1], &caps[2])));
+    }
+    CA_POSTCODE_REGEX()
+        .captures(&last.key)
+        .map(|caps| (1, format!("{} {}", &caps[1], &caps[2])))
+}
+
+/// Whether the state code starting at `start` really is a state. In the street segment
+/// it must come after the street and a city - in "123 Elm Ct", "CT" is the suffix -
+/// unless it is followed by a postcode and can't be read as a street word.
+fn state_code_allowed(tokens: &[Token], start: usize, before_postcode: bool) -> bool {
+    let seg_start = (0..=start)
+        .rev()
+        .find(|&i| starts_segment(tokens, i))
+        .unwrap_or(0);
+    let segment = &tokens[seg_start..start];
+    match street_start(segment) {
+        Some(name_start) if po_box_prefix(segment).is_some() => segment.len() > name_start,
+        Some(name_start) => {
+            let code = tokens[start].key.as_str();
+            (before_postcode
+                && !SUFFIXES.contains_key(code)
+                && !DIRECTIONALS.contains_key(code)
+                && !DESIGNATORS.contains_key(code))
+                || (name_start + 1..segment.len().saturating_sub(1))
+                    .any(|k| SUFFIXES.contains_key(segment[k].key.as_str()))
+        },
+        None => true,
+    }
+}
+
+/// The number of words of a "PO Box" prefix at the start of `segment`.
+fn po_box_prefix(segment: &[Token]) -> Option<usize> {
+    let keys: Vec<&str> = segment.iter().take(3).map(|t| t.key.as_str()).collect();
+    let n = match keys.as_slice() {
+        ["POST", "OFFICE", "BOX", ..] | ["P", "O", "BOX", ..] => 3,
+        ["PO", "BOX", ..] => 2,
+        ["POB" | "POBOX", ..] => 1,
+        // a bare "Box" needs its number, so "Box Elder" stays a place name
+        ["BOX", number, ..] if is_house_number(number) => 1,
+        _ => return None,
+    };
+    Some(n)
+}
+
+/// If `segment` starts with a house number or a PO box, the index after it.
+fn street_start(segment: &[Token]) -> Option<usize> {
+    if let Some(n) = po_box_prefix(segment) {
+        return Some((n + 1).min(segment.len()));
+    }
+    (segment.len() > 1 && is_house_number(&segment[0].key)).then_some(1)
+}
+
+fn is_house_number(key: &str) -> bool {
+    HOUSE_NUMBER_REGEX().is_match(key)
+}
+
+/// Whether `segment` looks like a street without a house number, e.g. "Main St NW".
+fn is_street_name(segment: &[Token]) -> bool {
+    let keys: Vec<&str> = segment.iter().map(|t| t.key.as_str()).collect();
+    let suffix_at = match keys.as_slice() {
+        [.., last] if DIRECTIONALS.contains_key(*last) => keys.len().checked_sub(2),
+        _ => keys.len().checked_sub(1),
+    };
+    suffix_at.is_some_and(|i| i > 0 && SUFFIXES.contains_key(keys[i])) && !is_unit_segment(segment)
+}
+
+/// "Apt 4B", "Suite 100", "34th Floor", ...
+fn is_unit_segment(segment: &[Token]) -> bool {
+    match segment {
+        [first, ..] if DESIGNATORS.contains_key(first.key.as_str()) => true,
+        [number, designator] => {
+            DESIGNATORS.contains_key(designator.key.as_str())
+                && (is_house_number(&number.key) || ORDINAL_REGEX().is_match(&number.key))
+        },
+        _ => false,
+    }
+}
+
+/// Whether `segment[j]` is a unit designator that ends the street before it.
+fn is_inline_unit(segment: &[Token], j: usize) -> bool {
+    DESIGNATORS
+        .get(segment[j].key.as_str())
+        .is_some_and(|d| d.inline && (!d.ranged || j + 1 < segment.len()))
+}
+
+/// "Highway 50", "Route 66", ...
+fn is_numbered_route(abbr: &str) -> bool {
+    matches!(abbr, "HWY" | "RTE" | "FWY" | "EXPY" | "TPKE")
+}
+
+/// Suffixes that end a street on a single-line address. Weaker ones ("Park", "Springs",
+/// "Village", ...) are too often part of a city name.
+fn is_strong_suffix(abbr: &str) -> bool {
+    matches!(
+        abbr,
+        "ALY"
+            | "AVE"
+            | "BLVD"
+            | "BYP"
+            | "CIR"
+            | "CRES"
+            | "CSWY"
+            | "CT"
+            | "DR"
+            | "EXPY"
+            | "FWY"
+            | "HWY"
+            | "LN"
+            | "LOOP"
+            | "PIKE"
+            | "PKWY"
+            | "PL"
+            | "PLZ"
+            | "RD"
+            | "ROW"
+            | "SQ"
+            | "ST"
+            | "TER"
+            | "TPKE"
+            | "TRL"
+            | "WAY"
+            | "XING"
+    )
+}
+
+fn is_directional_abbr(key: &str) -> bool {
+    DIRECTIONALS.get(key).is_some_and(|(abbr, _)| *abbr == key)
+}
+
+/// Split the street segment into its house number, street & unit, returning the tokens
+/// left over - on a single-line address (`single`), those are the city.
+fn parse_street<'t>(
+    segment: &'t [Token<'t>],
+    single: bool,
+    parsed: &mut Parsed<'t>,
+) -> &'t [Token<'t>] {
+    let len = segment.len();
+    if let Some(n) = po_box_prefix(segment) {
+        let box_end = (n + 1).min(len);
+        parsed.po_box = true;
+        parsed.street = &segment[..box_end];
+        return split_unit(segment, box_end, single, parsed);
+    }
+
+    let mut i = 0;
+    if street_start(segment).is_some() {
+        // "123 1/2 Main St"
+        let fraction = len > 2 && FRACTION_REGEX().is_match(&segment[1].key);
+        i = if fraction { 2 } else { 1 };
+        parsed.house_number = &segment[..i];
+    }
+    let limit = (i + 1..len)
+        .find(|&j| is_inline_unit(segment, j))
+        .unwrap_or(len);
+    let street_end = if single {
+        single_line_street_end(segment, i, limit).unwrap_or(limit)
+    } else {
+        limit
+    };
+    parsed.street = &segment[i..street_end];
+    split_unit(segment, street_end, single, parsed)
+}
+
+/// Where the street starting at `start` ends on a single-line address: after its
+/// first strong suffix (and any post-directional), or after a route number.
+fn single_line_street_end(segment: &[Token], start: usize, limit: usize) -> Option<usize> {
+    for (j, token) in segment.iter().enumerate().take(limit).skip(start) {
+        let Some((abbr, _)) = SUFFIXES.get(token.key.as_str()) else {
+            continue;
+        };
+        let mut end =
+            if is_numbered_route(abbr) && j + 1 < limit && is_house_number(&segment[j + 1].key) {
+                j + 2
+            } else if is_strong_suffix(abbr)
+                && segment[start..j]
+                    .iter()
+                    .any(|t| !DIRECTIONALS.contains_key(t.key.as_str()))
+            {
+                // "Court St" - the street ends at the last of consecutive strong suffixes
+                let mut end = j + 1;
+                while end < limit
+                    && SUFFIXES
+                        .get(segment[end].key.as_str())
+                        .is_some_and(|(abbr, _)| is_strong_suffix(abbr))
+                {
+                    end += 1;
+                }
+                end
+            } else {
+                continue;
+            };
+        if end < limit && is_directional_abbr(&segment[end].key) {
+            end += 1;
+        }
+        return Some(end);
+    }
+    None
+}
+
+/// Take the unit off the street segment from `at`, returning the tokens left over.
+fn split_unit<'t>(
+    segment: &'t [Token<'t>],
+    at: usize,
+    single: bool,
+    parsed: &mut Parsed<'t>,
+) -> &'t [Token<'t>] {
+    let rest = &segment[at..];
+    if !single {
+        parsed.unit.extend(rest.iter());
+        return &[];
+    }
+    let Some(designator) = rest
+        .first()
+        .and_then(|token| DESIGNATORS.get(token.key.as_str()))
+    else {
+        return rest;
+    };
+    let n = if designator.ranged && rest.len() > 1 {
+        2
+    } else {
+        1
+    };
+    parsed.unit.extend(rest[..n].iter());
+    &rest[n..]
+}
+
+impl Parsed<'_> {
+    fn render(&self, style: AddressStyle) -> AddressComponents {
+        let state = self
+            .state
+            .map_or_else(String::new, |(tokens, code)| match style {
+                AddressStyle::Raw => join_raw(tokens),
+                AddressStyle::Usps => code.to_string(),
+                AddressStyle::Expanded => REGIONS.get(code).copied().unwrap_or(code).to_string(),
+            });
+        let postcode = self
+            .postcode
+            .as_ref()
+            .map_or_else(String::new, |(tokens, postcode)| match style {
+                AddressStyle::Raw => join_raw(*tokens),
+                _ => postcode.clone(),
+            });
+        let country = self
+            .country
+            .map_or_else(String::new, |(tokens, code)| match style {
+                AddressStyle::Raw => join_raw(tokens),
+                AddressStyle::Usps => code.to_string(),
+                AddressStyle::Expanded => if code == "US" {
+                    "United States"
+                } else {
+                    "Canada"
+                }
+                .to_string(),
+            });
+
+        AddressComponents {
+            house_number: match style {
+                AddressStyle::Raw => join_raw(self.house_number),
+                _ => join_keys(self.house_number),
+            },
+            street: self.render_street(style),
+            unit: match style {
+                AddressStyle::Raw => join_raw(self.unit.iter().copied()),
+                _ => render_unit(&self.unit, style),
+            },
+            city: match style {
+                AddressStyle::Raw => join_raw(self.city),
+                AddressStyle::Usps => join_keys(self.city),
+                AddressStyle::Expanded => self
+                    .city
+                    .iter()
+                    .map(|t| title_case(&t.key))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            },
+            state,
+            postcode,
+            country,
+        }
+    }
+
+    fn render_street(&self, style: AddressStyle) -> String {
+        let tokens = self.street;
+        if style == AddressStyle::Raw {
+            return join_raw(tokens);
+        }
+        if self.po_box {
+            let prefix_len = po_box_prefix(tokens).unwrap_or(0);
+            let po_box = if style == AddressStyle::Usps {
+                "PO BOX"
+            } else {
+                "PO Box"
+            };
+            return std::iter::once(po_box)
+                .chain(tokens[prefix_len..].iter().map(|t| t.key.as_str()))
+                .collect::<Vec<_>>()
+                .join(" ");
+        }
+
+        // the roles of the words: [pre-directional] name [suffix] [post-directional]
+        let n = tokens.len();
+        let is_dir = |i: usize| DIRECTIONALS.contains_key(tokens[i].key.as_str());
+        let is_suffix = |i: usize| SUFFIXES.contains_key(tokens[i].key.as_str());
+        let mut name_end = n;
+        let postdir = n >= 2 && is_dir(n - 1) && !is_dir(n - 2);
+        if postdir {
+            name_end -= 1;
+        }
+        let suffix = name_end >= 2 && is_suffix(name_end - 1);
+        if suffix {
+            name_end -= 1;
+        }
+        let predir = name_end >= 2 && is_dir(0);
+
+        let pick = |(abbr, name): (&'static str, &'static str)| {
+            (if style == AddressStyle::Usps {
+                abbr
+            } else {
+                name
+            })
+            .to_string()
+        };
+        tokens
+            .iter()
+            .enumerate()
+            .map(|(i, token)| {
+                let key = token.key.as_str();
+                if (i == 0 && predir) || (i == n - 1 && postdir) {
+                    pick(DIRECTIONALS[key])
+                } else if (i == name_end && suffix)
+                    // "Highway 50", "County Road 12"
+                    || (SUFFIXES.contains_key(key)
+                        && tokens.get(i + 1).is_some_and(|t| is_house_number(&t.key)))
+                {
+                    pick(SUFFIXES[key])
+                } else if style == AddressStyle::Usps {
+                    key.to_string()
+                } else {
+                    title_case(key)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+fn render_unit(tokens: &[&Token], style: AddressStyle) -> String {
+    tokens
+        .iter()
+        .map(|token| match DESIGNATORS.get(token.key.as_str()) {
+            Some(d) if style == AddressStyle::Usps => d.abbr.to_string(),
+            Some(d) => d.name.to_string(),
+            None if style == AddressStyle::Expanded => title_case(&token.key),
+            None => token.key.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn join_raw<'a>(tokens: impl IntoIterator<Item = &'a Token<'a>>) -> String {
+    tokens
+        .into_iter()
+        .map(|t| t.raw)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn join_keys(tokens: &[Token]) -> String {
+    tokens
+        .iter()
+        .map(|t| t.key.as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// "MAIN" => "Main", "O-NEIL" => "O-Neil". Words starting with a digit are lowercased,
+/// so ordinals read "42nd".
+fn title_case(word: &str) -> String {
+    let lower = word.to_lowercase();
+    if lower.starts_with(|c: char| c.is_ascii_digit()) {
+        return lower;
+    }
+    lower
+        .split('-')
+        .map(|part| {
+            let mut chars = part.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_uppercase().chain(chars).collect()
+            })
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usps(address: &str) -> AddressComponents {
+        parse_address(address, AddressStyle::Usps)
+    }
+
+    #[test]
+    fn parse_comma_separated() {
+        let parsed = usps("123 North Main Street, Apt. 4B, Springfield, IL 62704-1234, USA");
+        assert_eq!(parsed.house_number, "123");
+        assert_eq!(parsed.street, "N MAIN ST");
+        assert_eq!(parsed.unit, "APT 4B");
+        assert_eq!(parsed.city, "SPRINGFIELD");
+        assert_eq!(parsed.state, "IL");
+        assert_eq!(parsed.postcode, "62704-1234");
+        assert_eq!(parsed.country, "US");
+    }
+
+    #[test]
+    fn parse_single_line() {
+        let parsed = usps("456 Oak Avenue Suite 200 Portland OR 97201");
+        assert_eq!(parsed.house_number, "456");
+        assert_eq!(parsed.street, "OAK AVE");
+        assert_eq!(parsed.unit, "STE 200");
+        assert_eq!(parsed.city, "PORTLAND");
+        assert_eq!(parsed.state, "OR");
+        assert_eq!(parsed.postcode, "97201");
+
+        let parsed = usps("12 Court Street West Springfield MA");
+        assert_eq!(parsed.street, "COURT ST");
+        assert_eq!(parsed.city, "WEST SPRINGFIELD");
+        assert_eq!(parsed.state, "MA");
+    }
+
+    #[test]
+    fn parse_suffix_state_ambiguity() {
+        // "Ct" is the street suffix, not Connecticut
+        let parsed = usps("77 Elm Ct 06103");
+        assert_eq!(parsed.street, "ELM CT");
+        assert_eq!(parsed.state, "");
+        assert_eq!(parsed.postcode, "06103");
+
+        let parsed = usps("77 Elm St Hartford CT 06103");
+        assert_eq!(parsed.street, "ELM ST");
+        assert_eq!(parsed.city, "HARTFORD");
+        assert_eq!(parsed.state, "CT");
+    }
+
+    #[test]
+    fn parse_po_box_and_canada() {
+        let parsed = usps("PO Box 1234, Ottawa, Ontario K1A 0B1, Canada");
+        assert_eq!(parsed.street, "PO BOX 1234");
+        assert_eq!(parsed.city, "OTTAWA");
+        assert_eq!(parsed.state, "ON");
+        assert_eq!(parsed.postcode, "K1A 0B1");
+        assert_eq!(parsed.country, "CA");
+    }
+
+    #[test]
+    fn parse_styles() {
+        let address = "500 e 42nd st. #12, new york, ny 10017";
+        let parsed = parse_address(address, AddressStyle::Expanded);
+        assert_eq!(parsed.street, "East 42nd Street");
+        assert_eq!(parsed.unit, "# 12");
+        assert_eq!(parsed.city, "New York");
+        assert_eq!(parsed.state, "New York");
+
+        let parsed = parse_address(address, AddressStyle::Raw);
+        assert_eq!(parsed.street, "e 42nd st");
+        assert_eq!(parsed.city, "new york");
+        assert_eq!(parsed.state, "ny");
+        assert_eq!(
+            usps(address).single_line(),
+            "500 E 42ND ST # 12, NEW YORK, NY 10017"
+        );
+    }
+
+    #[test]
+    fn parse_highway() {
+        let parsed = usps("9000 State Highway 50 Carson City NV");
+        assert_eq!(parsed.street, "STATE HWY 50");
+        assert_eq!(parsed.city, "CARSON CITY");
+        assert_eq!(parsed.state, "NV");
+    }
+
+    #[test]
+    fn parse_unrecognized() {
+        let parsed = usps("somewhere over the rainbow");
+        assert!(!parsed.is_valid());
+        assert_eq!(parsed.city, "SOMEWHERE OVER THE RAINBOW");
+    }
+}
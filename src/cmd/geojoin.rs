@@ -0,0 +1,424 @@
+static USAGE: &str = r#"
+Spatially join a CSV of points to polygon boundaries - e.g. census tracts, school districts or
+service areas - appending the attributes of the polygon that contains each point as new columns.
+A point in no polygon can instead be joined to the nearest polygon within --max-distance.
+
+The boundaries are a GeoJSON FeatureCollection, or a Shapefile (a .shp file, with its .shx & .dbf
+files alongside). Only their Polygon & MultiPolygon features are used, and their coordinates must
+be WGS84 longitudes & latitudes (EPSG:4326). The polygons are indexed in an R-tree, so each point
+is only tested against the few polygons near it - joining millions of points to tens of thousands
+of polygons is quick.
+
+The points are given either by latitude & longitude columns, or by a column of WKT points
+(e.g. "POINT (-73.9857 40.7484)"). Rows whose point is empty or invalid, and rows that are not
+joined to any polygon, get empty attribute columns - or are dropped with --inner.
+
+Examples:
+
+Append the GEOID & name of the census tract that contains each point:
+
+  $ qsv geojoin tracts.geojson places.csv --latitude lat --longitude lon --properties GEOID,NAMELSAD
+
+Append all the attributes of the district that contains each WKT point, prefixed with "district_":
+
+  $ qsv geojoin districts.shp stops.csv --geometry wkt --prefix district_
+
+Join points up to 2 km offshore to the nearest county, with the distance to it in km:
+
+  $ qsv geojoin counties.geojson sightings.csv -y lat -x lon --max-distance 2 --distance-column dist_km
+
+Only keep the events inside a zone:
+
+  $ qsv geojoin zones.geojson events.csv -y lat -x lon --inner
+
+For more examples, see https://github.com/dathere/qsv/blob/master/tests/test_geojoin.rs.
+
+Usage:
+    qsv geojoin [options] <boundaries> [<input>]
+    qsv geojoin --help
+
+geojoin arguments:
+    <boundaries>               The polygon boundaries: a GeoJSON file, or a .shp Shapefile.
+    <input>                    The CSV of points. If not given, reads from stdin.
+
+geojoin options:
+    -y, --latitude <col>       The column with the latitudes of the points.
+    -x, --longitude <col>      The column with the longitudes of the points.
+    -g, --geometry <col>       The column with the points in WKT. Alternative to --latitude
+                               and --longitude.
+    --properties <list>        Comma-separated list of the polygon properties to append, in order.
+                               Defaults to all the properties of the first polygon.
+    --prefix <string>          Prefix the names of the appended property columns with <string>.
+    -m, --max-distance <km>    Join a point that is in no polygon to the polygon whose boundary
+                               is nearest, if it is within <km> kilometers.
+                               0 only joins the polygon that contains the point.
+                               [default: 0]
+    --distance-column <name>   Also append a column <name> with the distance in km from the point
+                               to the boundary of its polygon - 0 when the point is inside it.
+    -i, --inner                Only keep the rows that are joined to a polygon.
+    -j, --jobs <arg>           The number of jobs to run in parallel.
+                               When not set, the number of jobs is set to the number of CPUs detected.
+    -b, --batch <size>         The number of rows per batch to load into memory, before running in parallel.
+                               Set to 0 to load all rows in one batch.
+                               [default: 50000]
+
+Common options:
+    -h, --help                 Display this message
+    -o, --output <file>        Write output to <file> instead of stdout.
+    -n, --no-headers           When set, the first row will not be interpreted
+                               as headers. Columns are then selected by their
+                               1-based index, and no header row is written.
+    -d, --delimiter <arg>      The field delimiter for reading CSV data.
+                               Must be a single character. (default: ,)
+    -p, --progressbar          Show progress bars. Not valid for stdin.
+"#;
+
+use std::{fs::File, io::BufReader, path::Path};
+
+use csv::ByteRecord;
+use geozero::geojson::GeoJsonWriter;
+use indicatif::{ProgressBar, ProgressDrawTarget};
+use rayon::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    CliError, CliResult,
+    config::{Config, Delimiter},
+    pip::{PipFeature, PipIndex, geojson_value_to_polygons},
+    select::SelectColumns,
+    util,
+};
+
+#[derive(Deserialize)]
+struct Args {
+    arg_boundaries:       String,
+    arg_input:            Option<String>,
+    flag_latitude:        Option<String>,
+    flag_longitude:       Option<String>,
+    flag_geometry:        Option<String>,
+    flag_properties:      Option<String>,
+    flag_prefix:          Option<String>,
+    flag_max_distance:    f64,
+    flag_distance_column: Option<String>,
+    flag_inner:           bool,
+    flag_jobs:            Option<usize>,
+    flag_batch:           usize,
+    flag_output:          Option<String>,
+    flag_no_headers:      bool,
+    flag_delimiter:       Option<Delimiter>,
+    flag_progressbar:     bool,
+}
+
+/// Where the points are read from.
+enum PointColumns {
+    LatLon { lat: usize, lon: usize },
+    Wkt(usize),
+}
+
+impl PointColumns {
+    /// The `(lat, lon)` of a record's point, if it is valid.
+    fn point(&self, record: &csv::StringRecord) -> Option<(f64, f64)> {
+        let (lat, lon) = match *self {
+            Self::LatLon { lat, lon } => (
+                record.get(lat)?.trim().parse::<f64>().ok()?,
+                record.get(lon)?.trim().parse::<f64>().ok()?,
+            ),
+            Self::Wkt(column) => {
+                let (lon, lat) = parse_wkt_point(record.get(column)?)?;
+                (lat, lon)
+            },
+        };
+        ((-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lon)).then_some((lat, lon))
+    }
+}
+
+/// Parse a WKT point - "POINT (x y)", with an optional Z/M/ZM tag - into its `(x, y)`, i.e.
+/// its `(lon, lat)`.
+fn parse_wkt_point(wkt: &str) -> Option<(f64, f64)> {
+    let wkt = wkt.trim();
+    if !wkt.get(..5)?.eq_ignore_ascii_case("POINT") {
+        return None;
+    }
+    let coords = wkt
+        .get(5..)?
+        .trim_start()
+        .trim_start_matches(['Z', 'M', 'z', 'm'])
+        .trim()
+        .strip_prefix('(')?
+        .strip_suffix(')')?;
+    let mut coords = coords.split_whitespace().map(str::parse::<f64>);
+    Some((coords.next()?.ok()?, coords.next()?.ok()?))
+}
+
+/// The index of a single column, selected by name or 1-based index.
+fn column_index(column: &str, headers: &ByteRecord, no_headers: bool) -> CliResult<usize> {
+    let sel = SelectColumns::parse(column)?.selection(headers, !no_headers)?;
+    match *sel {
+        [index] => Ok(index),
+        _ => fail_incorrectusage_clierror!("\"{column}\" must select exactly one column."),
+    }
+}
+
+/// Read the features of the boundaries file - a Shapefile, converted to GeoJSON with geozero,
+/// or a GeoJSON `FeatureCollection`.
+fn read_boundaries(path: &str) -> CliResult<Vec<geojson::Feature>> {
+    let shp_path = Path::new(path);
+    let is_shp = shp_path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("shp"));
+    let geojson = if is_shp {
+        let mut shp_reader = BufReader::new(File::open(shp_path)?);
+        let mut reader = geozero::shp::ShpReader::new(&mut shp_reader)?;
+        let mut shx_reader = BufReader::new(File::open(shp_path.with_extension("shx"))?);
+        let mut dbf_reader = BufReader::new(File::open(shp_path.with_extension("dbf"))?);
+        reader.add_index_source(&mut shx_reader)?;
+        reader.add_dbf_source(&mut dbf_reader)?;
+        let mut geojson: Vec<u8> = Vec::new();
+        for feature in reader.iter_features(&mut GeoJsonWriter::new(&mut geojson))? {
+            feature?;
+        }
+        geojson
+    } else {
+        std::fs::read(path)
+            .map_err(|e| CliError::Other(format!("Cannot read boundaries '{path}': {e}")))?
+    };
+    let fc: geojson::FeatureCollection = serde_json::from_slice(&geojson).map_err(|e| {
+        CliError::Other(format!(
+            "Boundaries '{path}' are not a valid GeoJSON FeatureCollection: {e}"
+        ))
+    })?;
+    Ok(fc.features)
+}
+
+/// A property value as a CSV cell: strings as is, null as empty, anything else as JSON.
+fn property_cell(value: Option<&serde_json::Value>) -> String {
+    match value {
+        None | Some(serde_json::Value::Null) => String::new(),
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(value) => value.to_string(),
+    }
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    if args.flag_geometry.is_some()
+        && (args.flag_latitude.is_some() || args.flag_longitude.is_some())
+    {
+        return fail_incorrectusage_clierror!(
+            "Cannot use --geometry with --latitude or --longitude."
+        );
+    }
+    if args.flag_geometry.is_none()
+        && (args.flag_latitude.is_none() || args.flag_longitude.is_none())
+    {
+        return fail_incorrectusage_clierror!(
+            "Either --latitude and --longitude, or --geometry, is required."
+        );
+    }
+    if !(args.flag_max_distance >= 0.0 && args.flag_max_distance.is_finite()) {
+        return fail_incorrectusage_clierror!("--max-distance must be a non-negative number.");
+    }
+
+    // the polygon features, with the attributes each appends
+    let boundary_features = read_boundaries(&args.arg_boundaries)?;
+    let mut features: Vec<PipFeature> = Vec::with_capacity(boundary_features.len());
+    let mut properties: Vec<Option<&serde_json::Map<String, serde_json::Value>>> =
+        Vec::with_capacity(boundary_features.len());
+    for (i, feature) in boundary_features.iter().enumerate() {
+        let polygons = match &feature.geometry {
+            Some(geometry) => geojson_value_to_polygons(&geometry.value),
+            None => Vec::new(),
+        };
+        if polygons.is_empty() {
+            continue;
+        }
+        features.push(PipFeature::new(i.to_string(), None, polygons));
+        properties.push(feature.properties.as_ref());
+    }
+    if features.is_empty() {
+        return fail_clierror!(
+            "Boundaries '{}' have no Polygon or MultiPolygon features.",
+            args.arg_boundaries
+        );
+    }
+
+    let property_names: Vec<String> = if let Some(list) = &args.flag_properties {
+        let names: Vec<String> = list
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(String::from)
+            .collect();
+        // like a misspelled column, a property that no polygon has is almost certainly a typo
+        for name in &names {
+            if !properties
+                .iter()
+                .flatten()
+                .any(|props| props.contains_key(name))
+            {
+                return fail_incorrectusage_clierror!(
+                    "No polygon in '{}' has the property \"{name}\".",
+                    args.arg_boundaries
+                );
+            }
+        }
+        names
+    } else {
+        properties
+            .first()
+            .copied()
+            .flatten()
+            .map(|props| props.keys().cloned().collect())
+            .unwrap_or_default()
+    };
+    let attributes: Vec<Vec<String>> = properties
+        .iter()
+        .map(|props| {
+            property_names
+                .iter()
+                .map(|name| property_cell(props.and_then(|props| props.get(name))))
+                .collect()
+        })
+        .collect();
+    // the attributes are all that's needed of the features from here on
+    drop(boundary_features);
+
+    let index = PipIndex::new(&features);
+
+    let rconfig = Config::new(args.arg_input.as_ref())
+        .delimiter(args.flag_delimiter)
+        .no_headers_flag(args.flag_no_headers);
+
+    #[cfg(feature = "datapusher_plus")]
+    let show_progress = false;
+    #[cfg(not(feature = "datapusher_plus"))]
+    let show_progress =
+        (args.flag_progressbar || util::get_envvar_flag("QSV_PROGRESSBAR")) && !rconfig.is_stdin();
+
+    let progress = ProgressBar::with_draw_target(None, ProgressDrawTarget::stderr_with_hz(5));
+    if show_progress {
+        util::prep_progress(&progress, util::count_rows(&rconfig)?);
+    } else {
+        progress.set_draw_target(ProgressDrawTarget::hidden());
+    }
+
+    let mut rdr = rconfig.reader()?;
+    let mut wtr = Config::new(args.flag_output.as_ref()).writer()?;
+
+    let byte_headers = rdr.byte_headers()?.clone();
+    let point_columns = if let Some(geometry) = &args.flag_geometry {
+        PointColumns::Wkt(column_index(geometry, &byte_headers, rconfig.no_headers)?)
+    } else {
+        // safety: --latitude & --longitude were checked above
+        PointColumns::LatLon {
+            lat: column_index(
+                args.flag_latitude.as_deref().unwrap(),
+                &byte_headers,
+                rconfig.no_headers,
+            )?,
+            lon: column_index(
+                args.flag_longitude.as_deref().unwrap(),
+                &byte_headers,
+                rconfig.no_headers,
+            )?,
+        }
+    };
+
+    if !rconfig.no_headers {
+        let mut headers = rdr.headers()?.clone();
+        let prefix = args.flag_prefix.as_deref().unwrap_or_default();
+        for name in &property_names {
+            headers.push_field(&format!("{prefix}{name}"));
+        }
+        if let Some(distance_column) = &args.flag_distance_column {
+            headers.push_field(distance_column);
+        }
+        wtr.write_record(&headers)?;
+    }
+    let empty_columns = property_names.len() + usize::from(args.flag_distance_column.is_some());
+
+    // amortize memory allocation by reusing record
+    let mut batch_record = csv::StringRecord::new();
+
+    // reuse batch buffers
+    let batchsize: usize = if args.flag_batch == 0 {
+        std::cmp::max(1000, util::count_rows_regular(&rconfig)? as usize)
+    } else {
+        args.flag_batch
+    };
+    let mut batch = Vec::with_capacity(batchsize);
+    let mut batch_results = Vec::with_capacity(batchsize);
+
+    util::njobs(args.flag_jobs);
+
+    'batch_loop: loop {
+        for _ in 0..batchsize {
+            match rdr.read_record(&mut batch_record) {
+                Ok(has_data) => {
+                    if has_data {
+                        batch.push(std::mem::take(&mut batch_record));
+                    } else {
+                        // nothing else to add to batch
+                        break;
+                    }
+                },
+                Err(e) => {
+                    return fail_clierror!("Error reading file: {e}");
+                },
+            }
+        }
+
+        if batch.is_empty() {
+            // break out of infinite loop when at EOF
+            break 'batch_loop;
+        }
+
+        batch
+            .par_iter()
+            .map(|record| {
+                let located = point_columns.point(record).and_then(|(lat, lon)| {
+                    index.locate(&features, lat, lon, args.flag_max_distance)
+                });
+                let Some((i, distance)) = located else {
+                    if args.flag_inner {
+                        return None;
+                    }
+                    let mut record = record.clone();
+                    for _ in 0..empty_columns {
+                        record.push_field("");
+                    }
+                    return Some(record);
+                };
+                let mut record = record.clone();
+                for value in &attributes[i] {
+                    record.push_field(value);
+                }
+                if args.flag_distance_column.is_some() {
+                    if distance > 0.0 {
+                        record.push_field(&format!("{distance:.3}"));
+                    } else {
+                        record.push_field("0");
+                    }
+                }
+                Some(record)
+            })
+            .collect_into_vec(&mut batch_results);
+
+        // rayon collect() guarantees original order, so we can just append results each batch
+        for result_record in batch_results.iter().flatten() {
+            wtr.write_record(result_record)?;
+        }
+
+        if show_progress {
+            progress.inc(batch.len() as u64);
+        }
+
+        batch.clear();
+    } // end batch loop
+
+    if show_progress {
+        util::finish_progress(&progress);
+    }
+    Ok(wtr.flush()?)
+}
@@ -12,9 +12,18 @@ the QSV_GET_PART_SIZE and QSV_GET_CONCURRENCY env vars).
 Once cached, a resource can be read by ANY qsv command using the `dc:` prefix,
 e.g. `qsv stats dc:data.csv`. Stale `dc:` entries are auto-refreshed.
 
+With --keep-versions N, an entry keeps its last N versions - the current one
+included - when refreshes change its content. An earlier version is read by date,
+as `dc:data.csv@2026-09-01` (the latest version fetched by the end of that UTC day;
+an RFC 3339 timestamp also works), or by content, as `dc:data.csv@<blake3-prefix>`.
+Such reads are offline: the entry is never refreshed. cache-history lists the
+versions of an entry.
+
 A glob (e.g. data/*.csv) or directory source fetches every matching tabular file
-(.csv/.tsv/.tab/.ssv) — supported for local paths and (with the get_cloud feature)
-cloud buckets/prefixes. --name is ignored when a source expands to multiple files.
+(.csv/.tsv/.tab/.ssv) — supported for local paths, (with the get_cloud feature)
+cloud buckets/prefixes and (with the get_sftp feature) sftp/ftp directories, where
+only the file name may be a glob. --name is ignored when a source expands to
+multiple files.
 
 Supported sources:
     local file path, directory, or glob (e.g. /data/*.csv)
@@ -25,9 +34,23 @@ Supported sources:
     s3://<bucket>/<key>       AWS S3 / S3-compatible       (get_cloud feature)
     gs://<bucket>/<key>       Google Cloud Storage         (get_cloud feature)
     az://<container>/<key>    Azure Blob Storage           (get_cloud feature)
+    sftp://[user@]host/<path> SSH File Transfer Protocol   (get_sftp feature)
+    ftp://[user@]host/<path>  FTP                          (get_sftp feature)
+    ftps://[user@]host/<path> FTP over explicit TLS        (get_sftp feature)
 Cloud credentials are read from the standard AWS_*/AZURE_*/GOOGLE_* environment
 variables (and IAM roles); use --cloud-opt for one-off overrides such as region
-or endpoint. (sftp:// is planned for a later release.)
+or endpoint.
+
+An sftp/ftp <path> is absolute; start it with /~/ to make it relative to the login
+directory (e.g. sftp://host/~/exports/data.csv). SFTP authenticates with the
+QSV_SFTP_KEY private key, the ssh-agent, then the default ~/.ssh/id_ed25519,
+id_ecdsa and id_rsa keys (QSV_SFTP_KEY_PASSPHRASE unlocks an encrypted key), falling
+back to the QSV_SFTP_PASSWORD env var. Its host key must be in ~/.ssh/known_hosts,
+unless QSV_SFTP_ACCEPT_NEW_HOSTS is set. FTP logs in as anonymous without a user,
+with the QSV_FTP_PASSWORD env var as the password. Passwords are never accepted in
+the URL, as it is stored in the cache. Neither protocol has ETags, so re-fetches
+compare the remote file's size & modification time instead, and only download it
+again when they changed.
 
 `--sample` PREVIEW vs the `sample` command: `get --sample N` is a cheap PEEK — it
 streams just the first N rows from the head (stopping early, so a huge remote file
@@ -57,6 +80,11 @@ Examples:
         $ qsv get gs://my-bucket/data.csv --cloud-opt skip_signature=true
         $ qsv get 's3://my-bucket/exports/*.csv'
 
+    Fetch from an SFTP or FTP server (requires the get_sftp feature):
+        $ qsv get sftp://etl@files.example.gov/~/exports/permits.csv
+        $ qsv get 'sftp://etl@files.example.gov/outgoing/*.csv'
+        $ qsv get ftps://files.example.gov/pub/data/
+
     Show what's in the cache, then prune old entries:
         $ qsv get cache-list
         $ qsv get cache-prune --older-than=30d
@@ -65,10 +93,23 @@ Examples:
         $ qsv get cache-fetch data.csv --output /tmp/data.csv
         $ qsv get cache-fetch data.csv | qsv stats
 
+    Keep the last 12 versions of a monthly dataset, then see what changed since
+    September, offline:
+        $ qsv get https://example.com/permits.csv --name permits.csv --keep-versions 12
+        $ qsv get cache-history permits.csv
+        $ qsv diff dc:permits.csv@2026-09-30 dc:permits.csv
+        $ qsv get cache-fetch permits.csv@3fa9c2 --output /tmp/permits-sep.csv
+
+    Revalidate every stale entry (4 at a time), e.g. from cron, and rebuild the
+    stats cache of those whose content changed:
+        $ qsv get cache-refresh --all --hook 'qsv stats --everything --stats-jsonl "$QSV_GET_DC"'
+        $ qsv get cache-refresh data.csv vax.csv --jobs 2 --json
+
     Verify cached blob integrity, then retune an entry's TTL & policy:
         $ qsv get cache-list --verify
         $ qsv get cache-set-ttl data.csv --ttl=86400
         $ qsv get cache-set-policy data.csv --refresh=never
+        $ qsv get cache-set-versions data.csv --keep-versions=5
 
     The `dc:` handle prefix is accepted (and ignored) wherever a cached <name> is
     expected, so a `dc:` reference copied from another command works as-is:
@@ -86,19 +127,45 @@ Usage:
     qsv get cache-prune --older-than=<val> [options]
     qsv get cache-set-ttl <name> --ttl=<secs> [options]
     qsv get cache-set-policy <name> --refresh=<policy> [options]
+    qsv get cache-set-versions <name> --keep-versions=<n> [options]
+    qsv get cache-history <name> [options]
+    qsv get cache-refresh (--all | <names>...) [options]
     qsv get [--cloud-opt <kv>...] [options] <source>...
     qsv get --help
 
 get arguments:
     <source>...            One or more sources to fetch into the cache.
-    <name>                 For cache-fetch / cache-set-ttl / cache-set-policy: the
-                           cached logical name (`dc:` handle) to read or modify.
-                           A leading `dc:` prefix is accepted and ignored.
+    <name>                 For cache-fetch / cache-set-ttl / cache-set-policy /
+                           cache-set-versions / cache-history: the cached logical
+                           name (`dc:` handle) to read, modify or list. A leading `dc:`
+                           prefix is accepted and ignored. cache-fetch also accepts
+                           an @<date> or @<blake3-prefix> version.
+    <names>...             For cache-refresh: the cached logical names to refresh.
 
 cache-fetch writes an ALREADY-cached entry's (decompressed) contents to the --output
 file (or stdout if omitted or `-`). It is offline: it reads the cached blob directly and
 never re-fetches the source. Errors if <name> is not in the cache.
 
+cache-history lists the stored versions of an entry, oldest first, with when each
+was first fetched (in UTC), its BLAKE3 hash, record count and size. The current
+version is marked with a `*`.
+
+cache-refresh revalidates cache entries against their sources in parallel - with --all,
+every entry past its TTL (entries with the `never` policy or a -1 TTL are never stale);
+otherwise the named entries, even when fresh. Unchanged sources are revalidated, not
+re-downloaded (use --force to re-fetch them). It reports each entry as changed,
+unchanged, skipped (named entries with the `never` policy) or failed, with its BLAKE3
+hash before & after, and exits non-zero if any fetch or hook failed.
+
+cache-refresh options:
+    --all                  Refresh every stale entry.
+    -j, --jobs <arg>       The number of entries refreshed at a time. [default: 4]
+    --hook <command>       Run <command> for each entry whose content changed, once all
+                           entries are refreshed. It runs in a shell (sh -c, or cmd /C
+                           on Windows) with the env vars QSV_GET_DC (the `dc:` handle),
+                           QSV_GET_NAME, QSV_GET_BLAKE3, QSV_GET_PREV_BLAKE3 and
+                           QSV_CACHE_DIR set. Its output goes to stderr.
+
 get options:
     --name <name>          Logical cache name (the `dc:` handle) for the fetched
                            entry. Defaults to the source's terminal path segment.
@@ -112,7 +179,13 @@ get options:
                            Also the value applied by cache-set-policy. [default: on-stale]
     --compress <algo>      Transparent blob compression: zstd or none.
                            [default: zstd]
-    --force                Re-fetch even if a fresh cached copy exists.
+    --keep-versions <n>    How many versions of the entry to keep, the current one
+                           included. When omitted, an existing entry keeps its
+                           setting and a new one keeps 1. Also the value applied
+                           by cache-set-versions, which reclaims the versions
+                           beyond it.
+    --force                Re-fetch even if a fresh cached copy exists. For cache-refresh,
+                           re-download unchanged sources instead of revalidating them.
     --sample <n>           PREVIEW: stream the first N data records of <source> to
                            stdout (or the --output file) WITHOUT caching. No `dc:`
                            entry is created. The sniffed header row is re-attached.
@@ -131,15 +204,24 @@ get options:
     --ckan-api <url>       CKAN Action API base URL. Overrides the QSV_CKAN_API
                            env var. [default: https://data.dathere.com/api/3/action]
     --ckan-token <token>   CKAN API token. Overrides the QSV_CKAN_TOKEN env var.
+    --auth <profile>       Authenticate http(s) downloads with the named auth profile
+                           (bearer, basic, api-key, oauth2 or aws-sigv4), read from the
+                           QSV_AUTH_FILE file (default: ~/.qsv/auth.toml). See
+                           "USING AUTH PROFILES" in `qsv fetch --help`. The entry
+                           remembers the profile's name - never its credentials - so
+                           `dc:` auto-refreshes & cache-refresh use it too.
     --timeout <secs>       HTTP timeout in seconds. For cache downloads this is an INACTIVITY
                            timeout: the transfer aborts only if no data is received from the
                            server for this long, so a slow-but-steady download is NOT cut off.
                            Preview mode (--sample / --offset / --random) instead uses it as a
-                           total-request timeout. 0 = no timeout. [default: 60]
+                           total-request timeout. sftp:// sources use it as an inactivity
+                           timeout throughout, ftp(s):// ones only to connect.
+                           0 = no timeout. [default: 60]
     --older-than <val>     For cache-prune: remove entries older than this age.
                            Accepts seconds, or a value with an s/m/h/d/w suffix
                            (e.g. 3600, 90m, 30d, 2w).
-    --json                 For cache-list/cache-info: output JSON instead of a table.
+    --json                 For cache-list/cache-info/cache-refresh/cache-history: output
+                           JSON instead of a table.
     --verify               For cache-list: recompute each cached blob's BLAKE3 and
                            report OK/FAIL per name (exits non-zero on any failure).
 
@@ -165,38 +247,52 @@ const DEFAULT_PREVIEW_SAMPLE: u64 = 10;
 
 #[derive(Deserialize)]
 struct Args {
-    arg_source:           Vec<String>,
-    arg_name:             Option<String>,
-    flag_name:            Option<String>,
-    flag_cache_dir:       String,
-    flag_ttl:             i64,
-    flag_refresh:         String,
-    flag_compress:        String,
-    flag_force:           bool,
-    flag_sample:          Option<u64>,
-    flag_offset:          Option<u64>,
-    flag_random:          bool,
-    flag_cloud_opt:       Vec<String>,
-    flag_ckan_api:        Option<String>,
-    flag_ckan_token:      Option<String>,
-    flag_timeout:         u16,
-    flag_older_than:      Option<String>,
-    flag_json:            bool,
-    flag_verify:          bool,
-    flag_output:          Option<String>,
-    flag_quiet:           bool,
-    cmd_cache_list:       bool,
-    cmd_cache_info:       bool,
-    cmd_cache_fetch:      bool,
-    cmd_cache_clear:      bool,
-    cmd_cache_prune:      bool,
-    cmd_cache_set_ttl:    bool,
-    cmd_cache_set_policy: bool,
+    arg_source:             Vec<String>,
+    arg_name:               Option<String>,
+    arg_names:              Vec<String>,
+    flag_name:              Option<String>,
+    flag_cache_dir:         String,
+    flag_ttl:               i64,
+    flag_refresh:           String,
+    flag_compress:          String,
+    flag_force:             bool,
+    flag_keep_versions:     Option<usize>,
+    flag_sample:            Option<u64>,
+    flag_offset:            Option<u64>,
+    flag_random:            bool,
+    flag_cloud_opt:         Vec<String>,
+    flag_ckan_api:          Option<String>,
+    flag_ckan_token:        Option<String>,
+    flag_auth:              Option<String>,
+    flag_timeout:           u16,
+    flag_older_than:        Option<String>,
+    flag_json:              bool,
+    flag_all:               bool,
+    flag_jobs:              usize,
+    flag_hook:              Option<String>,
+    flag_verify:            bool,
+    flag_output:            Option<String>,
+    flag_quiet:             bool,
+    cmd_cache_list:         bool,
+    cmd_cache_info:         bool,
+    cmd_cache_fetch:        bool,
+    cmd_cache_clear:        bool,
+    cmd_cache_prune:        bool,
+    cmd_cache_set_ttl:      bool,
+    cmd_cache_set_policy:   bool,
+    cmd_cache_set_versions: bool,
+    cmd_cache_history:      bool,
+    cmd_cache_refresh:      bool,
 }
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
     let cache_dir = diskcache::set_qsv_cache_dir(&args.flag_cache_dir)?;
+    if args.flag_keep_versions == Some(0) {
+        return Err(CliError::Other(
+            "get: --keep-versions must be at least 1.".to_string(),
+        ));
+    }
 
     // ---- cache-management subcommands ----
     if args.cmd_cache_list || args.cmd_cache_info {
@@ -257,6 +353,30 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         }
         return Ok(());
     }
+    if args.cmd_cache_set_versions {
+        let name = args.arg_name.as_deref().unwrap_or_default();
+        // convenience: accept (and ignore) a leading `dc:` prefix, like cache-fetch
+        let name = name.strip_prefix("dc:").unwrap_or(name);
+        // docopt requires --keep-versions for cache-set-versions
+        let keep_versions = args.flag_keep_versions.unwrap_or_default();
+        diskcache::set_keep_versions(&cache_dir, name, keep_versions)?;
+        if !args.flag_quiet {
+            eprintln!(
+                "Set '{name}' to keep {keep_versions} version{}.",
+                if keep_versions == 1 { "" } else { "s" }
+            );
+        }
+        return Ok(());
+    }
+    if args.cmd_cache_history {
+        let name = args.arg_name.as_deref().unwrap_or_default();
+        // convenience: accept (and ignore) a leading `dc:` prefix, like cache-fetch
+        let name = name.strip_prefix("dc:").unwrap_or(name);
+        return run_cache_history(&cache_dir, name, args.flag_json);
+    }
+    if args.cmd_cache_refresh {
+        return run_cache_refresh(&cache_dir, &args);
+    }
 
     let ckan_api_url = args
         .flag_ckan_api
@@ -285,6 +405,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             ckan_token,
             timeout_secs: args.flag_timeout,
             cloud_opts: args.flag_cloud_opt.clone(),
+            auth_profile: args.flag_auth.clone(),
         };
         return diskcache::preview_resource(&preview, args.flag_output.as_deref());
     }
@@ -296,7 +417,8 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     // Expand any glob/directory sources (local and, with get_cloud, cloud) into
     // concrete sources; non-glob sources pass through unchanged.
     let expand_ctx = diskcache::ExpandCtx {
-        cloud_opts: args.flag_cloud_opt.clone(),
+        cloud_opts:   args.flag_cloud_opt.clone(),
+        timeout_secs: args.flag_timeout,
     };
     let mut sources = Vec::with_capacity(args.arg_source.len());
     for s in &args.arg_source {
@@ -318,10 +440,12 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             refresh_policy,
             compression,
             force: args.flag_force,
+            keep_versions: args.flag_keep_versions,
             ckan_api_url: ckan_api_url.clone(),
             ckan_token: ckan_token.clone(),
             timeout_secs: args.flag_timeout,
             cloud_opts: args.flag_cloud_opt.clone(),
+            auth_profile: args.flag_auth.clone(),
         };
         let meta = diskcache::get_resource(&opts)?;
 
@@ -445,6 +569,158 @@ fn run_cache_list(cache_dir: &str, json: bool, info: bool, verify: bool) -> CliR
     Ok(())
 }
 
+fn run_cache_history(cache_dir: &str, name: &str, json: bool) -> CliResult<()> {
+    let versions = diskcache::history(cache_dir, name)?;
+
+    if json {
+        let s = serde_json::to_string_pretty(&versions)
+            .map_err(|e| CliError::Other(format!("get: failed to serialize cache history: {e}")))?;
+        println!("{s}");
+        return Ok(());
+    }
+
+    println!(
+        "  {:<19} {:<16} {:>10} {:>12} {:>12}",
+        "FETCHED (UTC)", "BLAKE3", "RECORDS", "COMP", "UNCOMP"
+    );
+    let current = versions.len() - 1;
+    for (i, v) in versions.iter().enumerate() {
+        let fetched = chrono::DateTime::from_timestamp(v.fetched_at, 0).map_or_else(
+            || v.fetched_at.to_string(),
+            |dt| dt.format("%Y-%m-%d %H:%M:%S").to_string(),
+        );
+        let records = v
+            .record_count
+            .map_or_else(|| "?".to_string(), |c| c.to_string());
+        println!(
+            "{} {:<19} {:<16} {:>10} {:>12} {:>12}",
+            if i == current { "*" } else { " " },
+            fetched,
+            &v.blake3[..v.blake3.len().min(16)],
+            records,
+            v.size_compressed,
+            v.size_uncompressed,
+        );
+    }
+    Ok(())
+}
+
+fn run_cache_refresh(cache_dir: &str, args: &Args) -> CliResult<()> {
+    let names = (!args.flag_all).then_some(args.arg_names.as_slice());
+    let outcomes = diskcache::refresh(cache_dir, names, args.flag_force, args.flag_jobs)?;
+
+    if args.flag_json {
+        let s = serde_json::to_string_pretty(&outcomes).map_err(|e| {
+            CliError::Other(format!("get: failed to serialize refresh results: {e}"))
+        })?;
+        println!("{s}");
+    } else if outcomes.is_empty() {
+        println!("(no stale entries)");
+    } else {
+        println!(
+            "{:<24} {:<9} {:<16} BLAKE3",
+            "NAME", "STATUS", "PREV BLAKE3"
+        );
+        for o in &outcomes {
+            println!(
+                "{:<24} {:<9} {:<16} {}",
+                truncate(&o.logical_name, 24),
+                refresh_status_label(o.status),
+                &o.prev_blake3[..o.prev_blake3.len().min(16)],
+                &o.blake3[..o.blake3.len().min(16)],
+            );
+        }
+    }
+
+    let mut failed = 0;
+    for o in &outcomes {
+        if let Some(e) = &o.error {
+            failed += 1;
+            eprintln!("✗ dc:{}: {e}", o.logical_name);
+        }
+    }
+    if let Some(hook) = &args.flag_hook {
+        for o in outcomes
+            .iter()
+            .filter(|o| o.status == diskcache::RefreshStatus::Changed)
+        {
+            if let Err(e) = run_refresh_hook(hook, cache_dir, o) {
+                failed += 1;
+                eprintln!("✗ hook for dc:{}: {e}", o.logical_name);
+            }
+        }
+    }
+    if !args.flag_quiet {
+        let changed = outcomes
+            .iter()
+            .filter(|o| o.status == diskcache::RefreshStatus::Changed)
+            .count();
+        eprintln!(
+            "Refreshed {} cache entr{} ({changed} changed).",
+            outcomes.len(),
+            plural(outcomes.len())
+        );
+    }
+    if failed > 0 {
+        return Err(CliError::Other(format!(
+            "get: {failed} refresh{} failed.",
+            if failed == 1 { "" } else { "es" }
+        )));
+    }
+    Ok(())
+}
+
+/// Run a `cache-refresh --hook` command for an entry whose content changed.
+fn run_refresh_hook(
+    hook: &str,
+    cache_dir: &str,
+    outcome: &diskcache::RefreshOutcome,
+) -> CliResult<()> {
+    use std::process::{Command, Stdio};
+
+    // Like `split --filter`, hand the whole command line to cmd.exe verbatim on
+    // Windows, so Rust's argument escaping doesn't corrupt its quoting.
+    #[cfg(windows)]
+    let mut cmd = {
+        use std::os::windows::process::CommandExt;
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").raw_arg(hook);
+        cmd
+    };
+    #[cfg(not(windows))]
+    let mut cmd = {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(hook);
+        cmd
+    };
+    // the report owns stdout; `dc:` inputs of the hook resolve via QSV_CACHE_DIR
+    let status = cmd
+        .env("QSV_GET_DC", format!("dc:{}", outcome.logical_name))
+        .env("QSV_GET_NAME", &outcome.logical_name)
+        .env("QSV_GET_BLAKE3", &outcome.blake3)
+        .env("QSV_GET_PREV_BLAKE3", &outcome.prev_blake3)
+        .env("QSV_CACHE_DIR", cache_dir)
+        .stdout(Stdio::from(std::io::stderr()))
+        .status()
+        .map_err(|e| CliError::Other(format!("cannot run the hook: {e}")))?;
+    if !status.success() {
+        return Err(CliError::Other(format!(
+            "the hook failed with exit code {}",
+            status.code().unwrap_or(-1)
+        )));
+    }
+    Ok(())
+}
+
+fn refresh_status_label(status: diskcache::RefreshStatus) -> &'static str {
+    match status {
+        diskcache::RefreshStatus::Changed => "changed",
+        diskcache::RefreshStatus::Unchanged => "unchanged",
+        diskcache::RefreshStatus::Skipped => "skipped",
+        diskcache::RefreshStatus::Failed => "failed",
+    }
+}
+
 fn truncate(s: &str, max: usize) -> String {
     if s.chars().count() <= max {
         s.to_string()
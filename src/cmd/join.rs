@@ -74,12 +74,46 @@ join options:
                            is found.
                            Cross joins do not write keys.
 
+                           JOIN DIAGNOSTICS OPTIONS:
+    --report <file>        Write a join diagnostics report to <file> as JSON.
+                           For each input, it has the number of rows, null-key
+                           rows (see --nulls), matched & unmatched rows, distinct,
+                           unmatched & duplicate keys, and the match rate.
+                           It also has the number of matched keys, the observed
+                           key cardinality (one_to_one, one_to_many, many_to_one
+                           or many_to_many) and the many-to-many keys that multiply
+                           rows in the output, with the top offending keys.
+                           Note that the distinct keys of <input1> are kept in
+                           memory to compile the report.
+                           Not supported for cross joins.
+    --report-top <n>       The number of top many-to-many keys to list in the
+                           report. [default: 10]
+    --unmatched-left <file>
+                           Write the rows of <input1> that have no match in
+                           <input2>, including null-key rows, to <file>.
+    --unmatched-right <file>
+                           Write the rows of <input2> that have no match in
+                           <input1>, including null-key rows, to <file>.
+
                            JOIN KEY TRANSFORMATION OPTIONS:
                            Note that transformations are applied to TEMPORARY
                            join key columns. The original columns are not modified
                            and the TEMPORARY columns are removed after the join.
 -i, --ignore-case           When set, joins are done case insensitively.
 -z, --ignore-leading-zeros  When set, leading zeros are ignored in join keys.
+    --key-transform <ops>  A comma-separated pipeline of key normalization
+                           operations, applied in order to each join key field
+                           (after trimming and the two options above), e.g.
+                           "squeeze,lower,digits". The same operations are
+                           available in exclude, dedup & diff.
+                           Valid operations are:
+                             trim, ltrim, rtrim, lower, upper, squeeze, squeeze0
+                               - same as the apply operations of the same name.
+                             digits - keep only the digits (e.g. phone numbers).
+                             alnum - keep only the alphanumeric characters.
+                             strip_zeros - strip leading zeros.
+                             date - normalize dates to ISO 8601 (YYYY-MM-DD).
+                               Honors the QSV_PREFER_DMY environment variable.
 
 Common options:
     -h, --help             Display this message
@@ -95,7 +129,7 @@ use std::{collections::hash_map::Entry, fmt, io, iter::repeat_n, mem::swap, str}
 
 use byteorder::{BigEndian, WriteBytesExt};
 use foldhash::{HashMap, HashMapExt};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     CliResult,
@@ -127,10 +161,20 @@ struct Args {
     flag_keys_output:          Option<String>,
     flag_ignore_case:          bool,
     flag_ignore_leading_zeros: bool,
+    flag_key_transform:        Option<String>,
+    flag_report:               Option<String>,
+    flag_report_top:           usize,
+    flag_unmatched_left:       Option<String>,
+    flag_unmatched_right:      Option<String>,
 }
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
+    if args.flag_cross && args.diagnostics_requested() {
+        return fail_incorrectusage_clierror!(
+            "--report, --unmatched-left and --unmatched-right are not supported for cross joins."
+        );
+    }
     let mut state = args.new_io_state()?;
     match (
         args.flag_left,
@@ -171,8 +215,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         // swap left and right data sets and run left anti join
         (false, false, false, false, true, false, false, false) => {
             let mut swapped_join = state;
-            swap(&mut swapped_join.rdr1, &mut swapped_join.rdr2);
-            swap(&mut swapped_join.sel1, &mut swapped_join.sel2);
+            swapped_join.swap_inputs();
             swapped_join.write_headers1()?;
             swapped_join.left_join(true)
         },
@@ -180,8 +223,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         // swap left and right data sets and run left semi join
         (false, false, false, false, false, true, false, false) => {
             let mut swapped_join = state;
-            swap(&mut swapped_join.rdr1, &mut swapped_join.rdr2);
-            swap(&mut swapped_join.sel1, &mut swapped_join.sel2);
+            swapped_join.swap_inputs();
             swapped_join.write_headers1()?;
             swapped_join.left_join(false)
         },
@@ -200,16 +242,16 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 }
 
 struct IoState<R, W: io::Write> {
-    wtr:        csv::Writer<W>,
-    rdr1:       csv::Reader<R>,
-    sel1:       Selection,
-    rdr2:       csv::Reader<R>,
-    sel2:       Selection,
-    no_headers: bool,
-    casei:      bool,
-    zerosi:     bool,
-    nulls:      bool,
-    keys_wtr:   KeysWriter,
+    wtr:         csv::Writer<W>,
+    rdr1:        csv::Reader<R>,
+    sel1:        Selection,
+    rdr2:        csv::Reader<R>,
+    sel2:        Selection,
+    no_headers:  bool,
+    key_opts:    KeyOpts,
+    nulls:       bool,
+    keys_wtr:    KeysWriter,
+    diagnostics: Option<JoinDiagnostics>,
 }
 
 impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
@@ -230,16 +272,28 @@ impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
         Ok(())
     }
 
+    /// Swap the two inputs, so the join streams <input2> and indexes <input1>.
+    fn swap_inputs(&mut self) {
+        swap(&mut self.rdr1, &mut self.rdr2);
+        swap(&mut self.sel1, &mut self.sel2);
+        if let Some(diag) = self.diagnostics.as_mut() {
+            diag.swapped = !diag.swapped;
+        }
+    }
+
     fn inner_join(mut self) -> CliResult<()> {
         let mut scratch = csv::ByteRecord::new();
-        let mut validx =
-            ValueIndex::new(self.rdr2, &self.sel2, self.casei, self.zerosi, self.nulls)?;
+        let mut validx = ValueIndex::new(self.rdr2, &self.sel2, &self.key_opts, self.nulls)?;
         let mut row = csv::ByteRecord::new();
         let mut key;
 
         while self.rdr1.read_byte_record(&mut row)? {
-            key = get_row_key(&self.sel1, &row, self.casei, self.zerosi);
-            if let Some(rows) = validx.values.get(&key) {
+            key = get_row_key(&self.sel1, &row, &self.key_opts);
+            let rows = validx.values.get(&key);
+            if let Some(diag) = self.diagnostics.as_mut() {
+                diag.observe(&key, rows.is_some(), &row)?;
+            }
+            if let Some(rows) = rows {
                 self.keys_wtr.write_key(&key)?;
 
                 for &rowi in rows {
@@ -252,6 +306,9 @@ impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
                 }
             }
         }
+        if let Some(diag) = self.diagnostics.take() {
+            diag.finish(&mut validx)?;
+        }
         self.wtr.flush()?;
         self.keys_wtr.flush()?;
         Ok(())
@@ -259,20 +316,22 @@ impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
 
     fn outer_join(mut self, right: bool) -> CliResult<()> {
         if right {
-            swap(&mut self.rdr1, &mut self.rdr2);
-            swap(&mut self.sel1, &mut self.sel2);
+            self.swap_inputs();
         }
 
         let mut scratch = csv::ByteRecord::new();
         let (_, pad2) = self.get_padding()?;
-        let mut validx =
-            ValueIndex::new(self.rdr2, &self.sel2, self.casei, self.zerosi, self.nulls)?;
+        let mut validx = ValueIndex::new(self.rdr2, &self.sel2, &self.key_opts, self.nulls)?;
         let mut row = csv::ByteRecord::new();
         let mut key;
 
         while self.rdr1.read_byte_record(&mut row)? {
-            key = get_row_key(&self.sel1, &row, self.casei, self.zerosi);
-            match validx.values.get(&key) {
+            key = get_row_key(&self.sel1, &row, &self.key_opts);
+            let rows = validx.values.get(&key);
+            if let Some(diag) = self.diagnostics.as_mut() {
+                diag.observe(&key, rows.is_some(), &row)?;
+            }
+            match rows {
                 Some(rows) => {
                     self.keys_wtr.write_key(&key)?;
 
@@ -296,20 +355,26 @@ impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
                 },
             }
         }
+        if let Some(diag) = self.diagnostics.take() {
+            diag.finish(&mut validx)?;
+        }
         self.wtr.flush()?;
         self.keys_wtr.flush()?;
         Ok(())
     }
 
     fn left_join(mut self, anti: bool) -> CliResult<()> {
-        let validx = ValueIndex::new(self.rdr2, &self.sel2, self.casei, self.zerosi, self.nulls)?;
+        let mut validx = ValueIndex::new(self.rdr2, &self.sel2, &self.key_opts, self.nulls)?;
         let mut row = csv::ByteRecord::new();
         let mut key;
 
         while self.rdr1.read_byte_record(&mut row)? {
-            key = get_row_key(&self.sel1, &row, self.casei, self.zerosi);
-            #[allow(clippy::map_entry)]
-            if !validx.values.contains_key(&key) {
+            key = get_row_key(&self.sel1, &row, &self.key_opts);
+            let matched = validx.values.contains_key(&key);
+            if let Some(diag) = self.diagnostics.as_mut() {
+                diag.observe(&key, matched, &row)?;
+            }
+            if !matched {
                 if anti {
                     self.keys_wtr.write_key(&key)?;
                     self.wtr.write_record(&row)?;
@@ -319,6 +384,9 @@ impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
                 self.wtr.write_record(&row)?;
             }
         }
+        if let Some(diag) = self.diagnostics.take() {
+            diag.finish(&mut validx)?;
+        }
         self.wtr.flush()?;
         self.keys_wtr.flush()?;
         Ok(())
@@ -327,8 +395,7 @@ impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
     fn full_outer_join(mut self) -> CliResult<()> {
         let mut scratch = csv::ByteRecord::new();
         let (pad1, pad2) = self.get_padding()?;
-        let mut validx =
-            ValueIndex::new(self.rdr2, &self.sel2, self.casei, self.zerosi, self.nulls)?;
+        let mut validx = ValueIndex::new(self.rdr2, &self.sel2, &self.key_opts, self.nulls)?;
 
         // Keep track of which rows we've written from rdr2.
         let mut rdr2_written: Vec<_> = repeat_n(false, validx.num_rows).collect();
@@ -336,8 +403,12 @@ impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
         let mut key;
 
         while self.rdr1.read_byte_record(&mut row1)? {
-            key = get_row_key(&self.sel1, &row1, self.casei, self.zerosi);
-            match validx.values.get(&key) {
+            key = get_row_key(&self.sel1, &row1, &self.key_opts);
+            let rows = validx.values.get(&key);
+            if let Some(diag) = self.diagnostics.as_mut() {
+                diag.observe(&key, rows.is_some(), &row1)?;
+            }
+            match rows {
                 Some(rows) => {
                     self.keys_wtr.write_key(&key)?;
 
@@ -364,6 +435,9 @@ impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
                 self.wtr.write_record(pad1.iter().chain(&scratch))?;
             }
         }
+        if let Some(diag) = self.diagnostics.take() {
+            diag.finish(&mut validx)?;
+        }
         self.wtr.flush()?;
         self.keys_wtr.flush()?;
         Ok(())
@@ -430,6 +504,31 @@ impl Args {
             KeysWriter::new(self.flag_keys_output.as_ref())?
         };
 
+        let diagnostics = if self.diagnostics_requested() {
+            Some(JoinDiagnostics {
+                report_path:   self.flag_report.clone(),
+                builder:       JoinReportBuilder::new(self.flag_report_top),
+                join_type:     self.join_type(),
+                swapped:       false,
+                nulls:         self.flag_nulls,
+                matched_keys:  HashMap::new(),
+                other_keys:    HashMap::new(),
+                null_key_rows: 0,
+                left_wtr:      unmatched_writer(
+                    self.flag_unmatched_left.as_ref(),
+                    &rconf1,
+                    &mut rdr1,
+                )?,
+                right_wtr:     unmatched_writer(
+                    self.flag_unmatched_right.as_ref(),
+                    &rconf2,
+                    &mut rdr2,
+                )?,
+            })
+        } else {
+            None
+        };
+
         Ok(IoState {
             wtr: Config::new(self.flag_output.as_ref()).writer()?,
             rdr1,
@@ -437,13 +536,43 @@ impl Args {
             rdr2,
             sel2,
             no_headers: rconf1.no_headers,
-            casei: self.flag_ignore_case,
-            zerosi: self.flag_ignore_leading_zeros,
+            key_opts: KeyOpts {
+                casei:     self.flag_ignore_case,
+                zerosi:    self.flag_ignore_leading_zeros,
+                transform: util::KeyTransform::from_flag(self.flag_key_transform.as_deref())?,
+            },
             nulls: self.flag_nulls,
             keys_wtr,
+            diagnostics,
         })
     }
 
+    fn diagnostics_requested(&self) -> bool {
+        self.flag_report.is_some()
+            || self.flag_unmatched_left.is_some()
+            || self.flag_unmatched_right.is_some()
+    }
+
+    fn join_type(&self) -> &'static str {
+        if self.flag_left {
+            "left"
+        } else if self.flag_left_anti {
+            "left_anti"
+        } else if self.flag_left_semi {
+            "left_semi"
+        } else if self.flag_right {
+            "right"
+        } else if self.flag_right_anti {
+            "right_anti"
+        } else if self.flag_right_semi {
+            "right_semi"
+        } else if self.flag_full {
+            "full"
+        } else {
+            "inner"
+        }
+    }
+
     #[allow(clippy::unused_self)]
     fn get_selections<R: io::Read>(
         &self,
@@ -487,8 +616,7 @@ impl<R: io::Read + io::Seek> ValueIndex<R> {
     ///
     /// * `rdr` - A CSV reader that implements Read + Seek
     /// * `sel` - A Selection that specifies which columns to index
-    /// * `casei` - If true, indexed values are compared case-insensitively
-    /// * `zerosi` - If true, indexed values are compared without leading zeros
+    /// * `key_opts` - The join key normalization options (case, leading zeros & key transforms)
     /// * `nulls` - If true, indexed rows with empty values are included
     ///
     /// # Returns
@@ -506,8 +634,7 @@ impl<R: io::Read + io::Seek> ValueIndex<R> {
     fn new(
         mut rdr: csv::Reader<R>,
         sel: &Selection,
-        casei: bool,
-        zerosi: bool,
+        key_opts: &KeyOpts,
         nulls: bool,
     ) -> CliResult<ValueIndex<R>> {
         // Initial capacities chosen for typical lookup-table cardinality;
@@ -544,7 +671,7 @@ impl<R: io::Read + io::Seek> ValueIndex<R> {
                 .byte();
             row_idx.write_u64::<BigEndian>(byte_pos)?;
 
-            let fields = get_row_key(sel, &row, casei, zerosi);
+            let fields = get_row_key(sel, &row, key_opts);
             if nulls || !fields.iter().any(std::vec::Vec::is_empty) {
                 match val_idx.entry(fields) {
                     Entry::Vacant(v) => {
@@ -625,17 +752,23 @@ fn transform_field(v: &[u8], casei: bool, zerosi: bool) -> ByteString {
     buf
 }
 
+/// Join key normalization options.
+struct KeyOpts {
+    casei:     bool,
+    zerosi:    bool,
+    transform: Option<util::KeyTransform>,
+}
+
 #[inline]
 /// Extracts key values from a CSV row based on the given selection and options.
-fn get_row_key(
-    sel: &Selection,
-    row: &csv::ByteRecord,
-    casei: bool,
-    zerosi: bool,
-) -> Vec<ByteString> {
-    sel.select(row)
-        .map(|v| transform_field(v, casei, zerosi))
-        .collect()
+fn get_row_key(sel: &Selection, row: &csv::ByteRecord, key_opts: &KeyOpts) -> Vec<ByteString> {
+    let key = sel
+        .select(row)
+        .map(|v| transform_field(v, key_opts.casei, key_opts.zerosi));
+    match key_opts.transform {
+        Some(ref kt) => key.map(|v| kt.apply(&v)).collect(),
+        None => key.collect(),
+    }
 }
 
 struct KeysWriter(Option<csv::Writer<Box<dyn io::Write>>>);
@@ -663,3 +796,310 @@ impl KeysWriter {
         Ok(())
     }
 }
+
+type UnmatchedWriter = Option<csv::Writer<Box<dyn io::Write>>>;
+
+/// Create a writer for the unmatched rows of an input, with the input's headers.
+fn unmatched_writer<R: io::Read>(
+    path: Option<&String>,
+    rconf: &Config,
+    rdr: &mut csv::Reader<R>,
+) -> CliResult<UnmatchedWriter> {
+    let Some(path) = path else {
+        return Ok(None);
+    };
+    let mut wtr = Config::new(Some(path)).writer()?;
+    rconf.write_headers(rdr, &mut wtr)?;
+    Ok(Some(wtr))
+}
+
+/// Collects the join diagnostics for `--report` and the unmatched rows sidecar files.
+///
+/// The streamed input's keys are counted as its rows are joined. The indexed input's
+/// keys are already counted in its `ValueIndex`, so it is only scanned once at the end.
+struct JoinDiagnostics {
+    report_path:   Option<String>,
+    builder:       JoinReportBuilder,
+    join_type:     &'static str,
+    // true when the join streams <input2> and indexes <input1>
+    swapped:       bool,
+    nulls:         bool,
+    // streamed row counts of the keys found in the index...
+    matched_keys:  HashMap<Vec<ByteString>, u64>,
+    // ... and of the keys that were not
+    other_keys:    HashMap<Vec<ByteString>, u64>,
+    null_key_rows: u64,
+    left_wtr:      UnmatchedWriter,
+    right_wtr:     UnmatchedWriter,
+}
+
+impl JoinDiagnostics {
+    #[inline]
+    fn observe(
+        &mut self,
+        key: &[ByteString],
+        matched: bool,
+        row: &csv::ByteRecord,
+    ) -> CliResult<()> {
+        if matched {
+            match self.matched_keys.get_mut(key) {
+                Some(count) => *count += 1,
+                None => {
+                    self.matched_keys.insert(key.to_vec(), 1);
+                },
+            }
+            return Ok(());
+        }
+
+        if !self.nulls && key.iter().any(Vec::is_empty) {
+            self.null_key_rows += 1;
+        } else {
+            match self.other_keys.get_mut(key) {
+                Some(count) => *count += 1,
+                None => {
+                    self.other_keys.insert(key.to_vec(), 1);
+                },
+            }
+        }
+        let stream_wtr = if self.swapped {
+            &mut self.right_wtr
+        } else {
+            &mut self.left_wtr
+        };
+        if let Some(w) = stream_wtr {
+            w.write_byte_record(row)?;
+        }
+        Ok(())
+    }
+
+    fn finish<R: io::Read + io::Seek>(mut self, validx: &mut ValueIndex<R>) -> CliResult<()> {
+        let key_strings = |key: &[ByteString]| -> Vec<String> {
+            key.iter()
+                .map(|k| util::bytes_to_cow_str(k).into_owned())
+                .collect()
+        };
+
+        // the streamed side's null-key rows are counted as they're streamed,
+        // the indexed side's null-key rows are the ones left out of its ValueIndex
+        let indexed_rows: usize = validx.values.values().map(Vec::len).sum();
+        self.builder
+            .add_null_key_rows(self.null_key_rows, (validx.num_rows - indexed_rows) as u64);
+
+        let mut index_matched = vec![false; validx.num_rows];
+        for (key, rows) in &validx.values {
+            let stream_rows = self.matched_keys.get(key).copied().unwrap_or_default();
+            if stream_rows > 0 {
+                for &rowi in rows {
+                    index_matched[rowi] = true;
+                }
+            }
+            self.builder
+                .add_key(|| key_strings(key), stream_rows, rows.len() as u64);
+        }
+        for (key, &stream_rows) in &self.other_keys {
+            self.builder.add_key(|| key_strings(key), stream_rows, 0);
+        }
+
+        // write the indexed side's unmatched rows, in input order
+        let index_wtr = if self.swapped {
+            &mut self.left_wtr
+        } else {
+            &mut self.right_wtr
+        };
+        if let Some(w) = index_wtr {
+            let mut row = csv::ByteRecord::new();
+            for (rowi, _) in index_matched.iter().enumerate().filter(|(_, m)| !**m) {
+                validx.idx.seek(rowi as u64)?;
+                validx.idx.read_byte_record(&mut row)?;
+                w.write_byte_record(&row)?;
+            }
+        }
+        for w in [&mut self.left_wtr, &mut self.right_wtr]
+            .into_iter()
+            .flatten()
+        {
+            w.flush()?;
+        }
+
+        let mut report = self.builder.finish(self.join_type);
+        if self.swapped {
+            report.swap_sides();
+        }
+        if let Some(path) = self.report_path {
+            report.write(&path)?;
+        }
+        Ok(())
+    }
+}
+
+/// The per-input statistics of a join diagnostics `--report`.
+#[derive(Serialize, Default)]
+pub struct JoinSideReport {
+    rows:           u64,
+    null_key_rows:  u64,
+    matched_rows:   u64,
+    unmatched_rows: u64,
+    distinct_keys:  u64,
+    unmatched_keys: u64,
+    duplicate_keys: u64,
+    match_rate:     f64,
+}
+
+/// A many-to-many key, with the number of rows it has in each input
+/// and the number of rows it produces in the join output.
+#[derive(Serialize)]
+pub struct ManyToManyKey {
+    key:         Vec<String>,
+    left_rows:   u64,
+    right_rows:  u64,
+    output_rows: u64,
+}
+
+#[derive(Serialize, Default)]
+pub struct ManyToManyReport {
+    keys:        u64,
+    output_rows: u64,
+    top_keys:    Vec<ManyToManyKey>,
+}
+
+/// A join diagnostics `--report`, shared by `join` and `joinp`.
+#[derive(Serialize)]
+pub struct JoinReport {
+    join_type:    String,
+    matched_keys: u64,
+    cardinality:  &'static str,
+    left:         JoinSideReport,
+    right:        JoinSideReport,
+    many_to_many: ManyToManyReport,
+}
+
+impl JoinReport {
+    /// Swap the left & right statistics, for joins that were run with swapped inputs.
+    pub fn swap_sides(&mut self) {
+        swap(&mut self.left, &mut self.right);
+        self.cardinality = match self.cardinality {
+            "one_to_many" => "many_to_one",
+            "many_to_one" => "one_to_many",
+            other => other,
+        };
+        for m2m in &mut self.many_to_many.top_keys {
+            swap(&mut m2m.left_rows, &mut m2m.right_rows);
+        }
+    }
+
+    pub fn write(&self, path: &str) -> CliResult<()> {
+        let mut wtr = io::BufWriter::new(std::fs::File::create(path)?);
+        serde_json::to_writer_pretty(&mut wtr, self)?;
+        io::Write::flush(&mut wtr)?;
+        Ok(())
+    }
+}
+
+/// Compiles a `JoinReport` from the row counts of each distinct join key.
+pub struct JoinReportBuilder {
+    top_n:        usize,
+    left:         JoinSideReport,
+    right:        JoinSideReport,
+    matched_keys: u64,
+    many_to_many: ManyToManyReport,
+}
+
+impl JoinReportBuilder {
+    pub fn new(top_n: usize) -> Self {
+        Self {
+            top_n,
+            left: JoinSideReport::default(),
+            right: JoinSideReport::default(),
+            matched_keys: 0,
+            many_to_many: ManyToManyReport::default(),
+        }
+    }
+
+    /// Add the number of rows with a null join key in each input.
+    /// These rows never match.
+    pub fn add_null_key_rows(&mut self, left_rows: u64, right_rows: u64) {
+        self.left.rows += left_rows;
+        self.left.null_key_rows += left_rows;
+        self.right.rows += right_rows;
+        self.right.null_key_rows += right_rows;
+    }
+
+    /// Add a distinct, non-null join key with the number of rows it has in each input.
+    /// `key` is only called for many-to-many keys, to keep the common case allocation-free.
+    pub fn add_key(&mut self, key: impl FnOnce() -> Vec<String>, left_rows: u64, right_rows: u64) {
+        for (side, rows) in [(&mut self.left, left_rows), (&mut self.right, right_rows)] {
+            if rows == 0 {
+                continue;
+            }
+            side.rows += rows;
+            side.distinct_keys += 1;
+            if rows > 1 {
+                side.duplicate_keys += 1;
+            }
+            if left_rows > 0 && right_rows > 0 {
+                side.matched_rows += rows;
+            } else {
+                side.unmatched_rows += rows;
+                side.unmatched_keys += 1;
+            }
+        }
+        if left_rows == 0 || right_rows == 0 {
+            return;
+        }
+
+        self.matched_keys += 1;
+        if left_rows > 1 && right_rows > 1 {
+            let output_rows = left_rows * right_rows;
+            self.many_to_many.keys += 1;
+            self.many_to_many.output_rows += output_rows;
+            self.many_to_many.top_keys.push(ManyToManyKey {
+                key: key(),
+                left_rows,
+                right_rows,
+                output_rows,
+            });
+            // bound the memory used by the top keys candidates
+            if self.many_to_many.top_keys.len() > self.top_n.max(1) * 8 {
+                self.truncate_top_keys();
+            }
+        }
+    }
+
+    fn truncate_top_keys(&mut self) {
+        self.many_to_many.top_keys.sort_unstable_by(|a, b| {
+            b.output_rows
+                .cmp(&a.output_rows)
+                .then_with(|| a.key.cmp(&b.key))
+        });
+        self.many_to_many.top_keys.truncate(self.top_n);
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    pub fn finish(mut self, join_type: &str) -> JoinReport {
+        self.truncate_top_keys();
+        for side in [&mut self.left, &mut self.right] {
+            side.match_rate = if side.rows == 0 {
+                0.0
+            } else {
+                side.matched_rows as f64 / side.rows as f64
+            };
+        }
+        let cardinality = match (
+            self.left.duplicate_keys == 0,
+            self.right.duplicate_keys == 0,
+        ) {
+            (true, true) => "one_to_one",
+            (true, false) => "one_to_many",
+            (false, true) => "many_to_one",
+            (false, false) => "many_to_many",
+        };
+        JoinReport {
+            join_type: join_type.to_string(),
+            matched_keys: self.matched_keys,
+            cardinality,
+            left: self.left,
+            right: self.right,
+            many_to_many: self.many_to_many,
+        }
+    }
+}
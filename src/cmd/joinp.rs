@@ -92,8 +92,34 @@ joinp options:
                              onetomany - join keys are unique in the left data set.
                              manytoone - join keys are unique in the right data set.
                              onetoone - join keys are unique in both left & right data sets.
+                           Use --report to see the observed cardinality and the
+                           keys that break it.
                            [default: none]
 
+                           JOIN DIAGNOSTICS OPTIONS:
+    --report <file>        Write a join diagnostics report to <file> as JSON,
+                           computed on the join keys BEFORE the join (after the
+                           --filter-left/--filter-right filters and the join key
+                           transformations).
+                           For each input, it has the number of rows, null-key
+                           rows, matched & unmatched rows, distinct, unmatched &
+                           duplicate keys, and the match rate.
+                           It also has the number of matched keys, the observed
+                           key cardinality (one_to_one, one_to_many, many_to_one
+                           or many_to_many) and the many-to-many keys that multiply
+                           rows in the output, with the top offending keys.
+                           Uses the same report format as `qsv join --report`.
+                           Only supported for equi joins (not cross, asof or
+                           non-equi joins).
+    --report-top <n>       The number of top many-to-many keys to list in the
+                           report. [default: 10]
+    --unmatched-left <file>
+                           Write the rows of <input1> that have no match in
+                           <input2>, including null-key rows, to <file>.
+    --unmatched-right <file>
+                           Write the rows of <input2> that have no match in
+                           <input1>, including null-key rows, to <file>.
+
                             JOIN OPTIONS:
     --maintain-order <arg>  Which row order to preserve, if any. Valid values are:
                               none, left, right, left_right, right_left
@@ -274,7 +300,10 @@ use serde::Deserialize;
 use tempfile::tempdir;
 
 use crate::{
-    CliResult, cmd::sqlp::compress_output_if_needed, config::Delimiter, util,
+    CliResult,
+    cmd::{join::JoinReportBuilder, sqlp::compress_output_if_needed},
+    config::Delimiter,
+    util,
     util::get_stats_records,
 };
 
@@ -297,6 +326,10 @@ struct Args {
     flag_filter_left:          Option<String>,
     flag_filter_right:         Option<String>,
     flag_validate:             Option<String>,
+    flag_report:               Option<String>,
+    flag_report_top:           usize,
+    flag_unmatched_left:       Option<String>,
+    flag_unmatched_right:      Option<String>,
     flag_maintain_order:       Option<String>,
     flag_nulls:                bool,
     flag_streaming:            bool,
@@ -350,6 +383,16 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         args.flag_try_parsedates = true;
     }
 
+    if (args.flag_report.is_some()
+        || args.flag_unmatched_left.is_some()
+        || args.flag_unmatched_right.is_some())
+        && (args.flag_cross || args.flag_asof || args.flag_non_equi.is_some())
+    {
+        return fail_incorrectusage_clierror!(
+            "--report, --unmatched-left and --unmatched-right are only supported for equi joins."
+        );
+    }
+
     let tmpdir = tempdir()?;
     let join = args.new_join(&tmpdir)?;
 
@@ -454,8 +497,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         // underlying run() below is operating on inverted semantics.
         (false, false, false, false, true, false, false, false, false, false) => {
             let mut swapped_join = join;
-            swap(&mut swapped_join.left_lf, &mut swapped_join.right_lf);
-            swap(&mut swapped_join.left_sel, &mut swapped_join.right_sel);
+            swapped_join.swap_inputs();
             swapped_join.run(
                 JoinType::Anti,
                 validation,
@@ -469,8 +511,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         // (see NOTE above about inverted field names after the swap)
         (false, false, false, false, false, true, false, false, false, false) => {
             let mut swapped_join = join;
-            swap(&mut swapped_join.left_lf, &mut swapped_join.right_lf);
-            swap(&mut swapped_join.left_sel, &mut swapped_join.right_sel);
+            swapped_join.swap_inputs();
             swapped_join.run(
                 JoinType::Semi,
                 validation,
@@ -587,9 +628,151 @@ struct JoinStruct {
     decimal_comma:        bool,
     ignore_case:          bool,
     ignore_leading_zeros: bool,
+    join_type:            &'static str,
+    report:               Option<String>,
+    report_top:           usize,
+    unmatched_left:       Option<String>,
+    unmatched_right:      Option<String>,
+    // true when the left_* fields hold the right data set (see swap_inputs)
+    swapped:              bool,
 }
 
 impl JoinStruct {
+    /// Swap the left and right data sets, to run right anti & semi joins as left joins.
+    fn swap_inputs(&mut self) {
+        swap(&mut self.left_lf, &mut self.right_lf);
+        swap(&mut self.left_sel, &mut self.right_sel);
+        self.swapped = !self.swapped;
+    }
+
+    /// The `JoinArgs` of a diagnostics join. Null keys never match each other, as in
+    /// the join itself, which leaves `nulls_equal` at its default.
+    fn join_args(how: JoinType) -> JoinArgs {
+        let mut join_args = JoinArgs::new(how);
+        join_args.nulls_equal = false;
+        join_args
+    }
+
+    /// Write the join diagnostics `--report` and the unmatched rows sidecar files,
+    /// if requested, using the (possibly transformed) join key expressions.
+    fn write_diagnostics(&self, left_selcols: &[Expr], right_selcols: &[Expr]) -> CliResult<()> {
+        if let Some(ref report_path) = self.report {
+            let key_names: Vec<String> = (0..left_selcols.len())
+                .map(|i| format!("_qsv-report-key-{i}"))
+                .collect();
+            let key_cols: Vec<Expr> = key_names
+                .iter()
+                .map(|name| polars::lazy::dsl::col(name.as_str()))
+                .collect();
+            let key_counts = |lf: &LazyFrame, selcols: &[Expr], count_name: &str| {
+                let keys: Vec<Expr> = selcols
+                    .iter()
+                    .zip(&key_names)
+                    .map(|(expr, name)| expr.clone().cast(DataType::String).alias(name.as_str()))
+                    .collect();
+                lf.clone()
+                    .select(keys)
+                    .group_by(key_cols.clone())
+                    .agg([len().alias(count_name)])
+            };
+
+            // null keys never match, so they come out of this full join as rows
+            // with a null key and the row count of one side only
+            let counts = key_counts(&self.left_lf, left_selcols, "left_rows")
+                .join(
+                    key_counts(&self.right_lf, right_selcols, "right_rows"),
+                    key_cols.clone(),
+                    key_cols,
+                    Self::join_args(JoinType::Full).with_coalesce(JoinCoalesce::CoalesceColumns),
+                )
+                .collect()?;
+
+            let keys = key_names
+                .iter()
+                .map(|name| counts.column(name)?.as_materialized_series().str().cloned())
+                .collect::<Result<Vec<_>, _>>()?;
+            let left_rows = counts.column("left_rows")?.cast(&DataType::UInt64)?;
+            let left_rows = left_rows.as_materialized_series().u64()?;
+            let right_rows = counts.column("right_rows")?.cast(&DataType::UInt64)?;
+            let right_rows = right_rows.as_materialized_series().u64()?;
+
+            let mut builder = JoinReportBuilder::new(self.report_top);
+            for i in 0..counts.height() {
+                let (left, right) = (
+                    left_rows.get(i).unwrap_or_default(),
+                    right_rows.get(i).unwrap_or_default(),
+                );
+                if keys.iter().any(|key| key.get(i).is_none()) {
+                    builder.add_null_key_rows(left, right);
+                } else {
+                    let key = || -> Vec<String> {
+                        keys.iter()
+                            .map(|key| key.get(i).unwrap_or_default().to_string())
+                            .collect()
+                    };
+                    builder.add_key(key, left, right);
+                }
+            }
+            let mut report = builder.finish(self.join_type);
+            if self.swapped {
+                report.swap_sides();
+            }
+            report.write(report_path)?;
+        }
+
+        let (left_path, right_path) = if self.swapped {
+            (&self.unmatched_right, &self.unmatched_left)
+        } else {
+            (&self.unmatched_left, &self.unmatched_right)
+        };
+        for (path, lf, selcols, other_lf, other_selcols) in [
+            (
+                left_path,
+                &self.left_lf,
+                left_selcols,
+                &self.right_lf,
+                right_selcols,
+            ),
+            (
+                right_path,
+                &self.right_lf,
+                right_selcols,
+                &self.left_lf,
+                left_selcols,
+            ),
+        ] {
+            let Some(path) = path else {
+                continue;
+            };
+            // an anti join keeps the rows with no match, including the null-key rows
+            let mut unmatched = lf
+                .clone()
+                .join(
+                    other_lf.clone(),
+                    selcols.to_vec(),
+                    other_selcols.to_vec(),
+                    Self::join_args(JoinType::Anti),
+                )
+                .collect()?;
+            let transformed_cols: Vec<PlSmallStr> = unmatched
+                .get_column_names()
+                .into_iter()
+                .filter(|name| name.contains("-transformed"))
+                .cloned()
+                .collect();
+            if !transformed_cols.is_empty() {
+                unmatched = unmatched.drop_many(transformed_cols);
+            }
+            CsvWriter::new(BufWriter::new(File::create(path)?))
+                .include_header(true)
+                .with_separator(tsvssv_delim(path, self.delim))
+                .with_null_value(self.null_value.clone().into())
+                .with_decimal_comma(self.decimal_comma)
+                .finish(&mut unmatched)?;
+        }
+        Ok(())
+    }
+
     fn run(
         mut self,
         jointype: JoinType,
@@ -670,6 +853,10 @@ impl JoinStruct {
             );
         }
 
+        if special_join == SpecialJoin::None && jointype != JoinType::Cross {
+            self.write_diagnostics(&left_selcols, &right_selcols)?;
+        }
+
         let coalesce_flag = if self.coalesce {
             JoinCoalesce::CoalesceColumns
         } else {
@@ -1227,8 +1414,34 @@ impl Args {
             decimal_comma: self.flag_decimal_comma,
             ignore_case: self.flag_ignore_case,
             ignore_leading_zeros: self.flag_ignore_leading_zeros,
+            join_type: self.join_type(),
+            report: self.flag_report.clone(),
+            report_top: self.flag_report_top,
+            unmatched_left: self.flag_unmatched_left.clone(),
+            unmatched_right: self.flag_unmatched_right.clone(),
+            swapped: false,
         })
     }
+
+    fn join_type(&self) -> &'static str {
+        if self.flag_left {
+            "left"
+        } else if self.flag_left_anti {
+            "left_anti"
+        } else if self.flag_left_semi {
+            "left_semi"
+        } else if self.flag_right {
+            "right"
+        } else if self.flag_right_anti {
+            "right_anti"
+        } else if self.flag_right_semi {
+            "right_semi"
+        } else if self.flag_full {
+            "full"
+        } else {
+            "inner"
+        }
+    }
 }
 
 /// Build a `.pschema.json` sidecar path without going through UTF-8 string formatting,
@@ -464,7 +464,7 @@ fn flatten_json_value(
 /// This is a focused reimplementation of the parts of the unmaintained `json-objects-to-csv`
 /// crate that qsv relied on, so we no longer depend on it (see issue #3523). qsv builds
 /// `serde_json` with the `preserve_order` feature, so object keys are already in insertion order.
-fn json_objects_to_csv<W: Write>(
+pub(crate) fn json_objects_to_csv<W: Write>(
     objects: &[Value],
     mut csv_writer: csv::Writer<W>,
 ) -> CliResult<()> {
@@ -555,7 +555,7 @@ fn json_objects_to_csv<W: Write>(
 /// intended to feed `qsv json`'s CSV writer (where every cell is text, so
 /// quoting is invisible). Do not lift it to a JSON sink without revisiting
 /// these branches.
-fn val_to_json_value(v: Val) -> Result<serde_json::Value, String> {
+pub(crate) fn val_to_json_value(v: Val) -> Result<serde_json::Value, String> {
     match v {
         Val::Null => Ok(serde_json::Value::Null),
         Val::Bool(b) => Ok(serde_json::Value::Bool(b)),
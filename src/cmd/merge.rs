@@ -0,0 +1,256 @@
+static USAGE: &str = r#"
+Merges two or more CSV files that are already sorted on the selected key columns
+into a single sorted CSV, using a streaming k-way merge (i.e. constant memory).
+
+This is far cheaper than concatenating the files and sorting the result again,
+and is meant for combining sorted partitions (e.g. the output of `partition`
+or of several `extsort` runs).
+
+The inputs must have the same headers, and must be sorted in ascending order per
+the selected comparison mode. The order of each input is verified as it is merged,
+with the same logic as `sortcheck`, and merge aborts if an input is not sorted,
+without writing the --output file.
+Records with equal keys are output in input order - all the matching records of
+the first input, then those of the second, and so on.
+
+The comparison modes match `sort`, `sortcheck` & `dedup`: --natural takes
+precedence over --numeric, which takes precedence over --ignore-case.
+
+Examples:
+
+  # Merge two sorted partitions on the id column
+  qsv merge --select id part1.csv part2.csv > merged.csv
+
+  # Merge numerically sorted files on the year & month columns
+  qsv merge -s year,month --numeric 2023.csv 2024.csv 2025.csv -o all.csv
+
+  # Merge the files of a directory, sorted in natural order on all columns
+  qsv merge --natural sorted/*.csv
+
+For more examples, see https://github.com/dathere/qsv/blob/master/tests/test_merge.rs.
+
+Usage:
+    qsv merge [options] <inputs>...
+    qsv merge --help
+
+merge arguments:
+    <inputs>                The sorted CSV files to merge. Stdin is not supported.
+
+merge options:
+    -s, --select <arg>      Select the key columns the inputs are sorted on.
+                            The same selection is applied to each input.
+                            See 'qsv select --help' for the format details.
+                            (default: all columns)
+    -N, --numeric           Compare according to string numerical value.
+    --natural               Compare strings using natural sort order
+                            (e.g. "item2" < "item10").
+    -i, --ignore-case       Compare strings disregarding case.
+
+Common options:
+    -h, --help              Display this message
+    -o, --output <file>     Write output to <file> instead of stdout.
+    -n, --no-headers        When set, the first row will not be interpreted
+                            as headers. Otherwise, the first row of each input
+                            is a header row and must be the same for all inputs.
+    -d, --delimiter <arg>   The field delimiter for reading CSV data.
+                            Must be a single character. (default: ,)
+"#;
+
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+    io,
+    path::Path,
+};
+
+use csv::ByteRecord;
+use serde::Deserialize;
+
+use crate::{
+    CliResult,
+    cmd::sortcheck::ComparisonMode,
+    config::{Config, Delimiter},
+    select::{SelectColumns, Selection},
+    util,
+    util::ByteString,
+};
+
+#[derive(Deserialize)]
+struct Args {
+    arg_inputs:       Vec<String>,
+    flag_select:      SelectColumns,
+    flag_numeric:     bool,
+    flag_natural:     bool,
+    flag_ignore_case: bool,
+    flag_output:      Option<String>,
+    flag_no_headers:  bool,
+    flag_delimiter:   Option<Delimiter>,
+}
+
+/// The current record of an input, ordered by its key, then by input order.
+struct Head {
+    key:    Vec<ByteString>,
+    input:  usize,
+    record: ByteRecord,
+    mode:   ComparisonMode,
+}
+
+impl Ord for Head {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare_keys(self.mode, &self.key, &other.key).then_with(|| self.input.cmp(&other.input))
+    }
+}
+
+impl PartialOrd for Head {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Head {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Head {}
+
+#[inline]
+fn compare_keys(mode: ComparisonMode, a: &[ByteString], b: &[ByteString]) -> Ordering {
+    mode.compare(a.iter().map(Vec::as_slice), b.iter().map(Vec::as_slice))
+}
+
+struct Input {
+    path:      String,
+    rdr:       csv::Reader<Box<dyn io::Read + Send>>,
+    sel:       Selection,
+    // the data record number of the last record read, for error messages
+    record_no: u64,
+}
+
+impl Input {
+    /// Read the next record of this input, with its key.
+    fn next_head(&mut self, input: usize, mode: ComparisonMode) -> CliResult<Option<Head>> {
+        let mut record = ByteRecord::new();
+        if !self.rdr.read_byte_record(&mut record)? {
+            return Ok(None);
+        }
+        self.record_no += 1;
+        let key = self.sel.select(&record).map(<[u8]>::to_vec).collect();
+        Ok(Some(Head {
+            key,
+            input,
+            record,
+            mode,
+        }))
+    }
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+    if args.arg_inputs.len() < 2 {
+        return fail_incorrectusage_clierror!("merge requires at least two inputs.");
+    }
+
+    let mode =
+        ComparisonMode::from_flags(args.flag_natural, args.flag_numeric, args.flag_ignore_case);
+
+    // an --output file is written to a temp file next to it & renamed into place once the
+    // merge succeeds, so an input that turns out not to be sorted leaves no partial output.
+    // The temp file keeps the output's file name as its suffix, so it is written with the
+    // same delimiter & compression.
+    let tmp_output = match args.flag_output.as_deref() {
+        Some(output) if output != "sink" => {
+            let output = Path::new(output);
+            let dir = output
+                .parent()
+                .filter(|d| !d.as_os_str().is_empty())
+                .unwrap_or_else(|| Path::new("."));
+            let suffix = output
+                .file_name()
+                .map(|name| format!(".{}", name.to_string_lossy()))
+                .unwrap_or_default();
+            Some(tempfile::Builder::new().suffix(&suffix).tempfile_in(dir)?)
+        },
+        _ => None,
+    };
+    let tmp_path = tmp_output
+        .as_ref()
+        .map(|tmp| tmp.path().to_string_lossy().into_owned());
+    let mut wtr = Config::new(tmp_path.as_ref().or(args.flag_output.as_ref())).writer()?;
+    let mut inputs: Vec<Input> = Vec::with_capacity(args.arg_inputs.len());
+    let mut first_headers: Option<ByteRecord> = None;
+    for path in &args.arg_inputs {
+        let rconfig = Config::new(Some(path))
+            .delimiter(args.flag_delimiter)
+            .no_headers_flag(args.flag_no_headers)
+            .select(args.flag_select.clone());
+        if rconfig.is_stdin() {
+            return fail_incorrectusage_clierror!("merge does not support stdin.");
+        }
+        let mut rdr = rconfig.reader()?;
+        let headers = rdr.byte_headers()?.clone();
+        let sel = rconfig.selection(&headers)?;
+
+        match first_headers {
+            None => {
+                rconfig.write_headers(&mut rdr, &mut wtr)?;
+                first_headers = Some(headers);
+            },
+            Some(ref first) => {
+                if !rconfig.no_headers && first != &headers {
+                    return fail_incorrectusage_clierror!(
+                        "All inputs must have the same headers. The headers of {path} differ from \
+                         those of {}.",
+                        args.arg_inputs[0]
+                    );
+                }
+            },
+        }
+
+        inputs.push(Input {
+            path: path.clone(),
+            rdr,
+            sel,
+            record_no: 0,
+        });
+    }
+
+    // a min-heap of the current record of each input
+    let mut heap: BinaryHeap<Reverse<Head>> = BinaryHeap::with_capacity(inputs.len());
+    for (i, input) in inputs.iter_mut().enumerate() {
+        if let Some(head) = input.next_head(i, mode)? {
+            heap.push(Reverse(head));
+        }
+    }
+
+    while let Some(Reverse(head)) = heap.pop() {
+        wtr.write_byte_record(&head.record)?;
+
+        let input = &mut inputs[head.input];
+        if let Some(next) = input.next_head(head.input, mode)? {
+            // verify the input is sorted, like sortcheck does
+            if compare_keys(mode, &next.key, &head.key) == Ordering::Less {
+                return fail_clierror!(
+                    "Aborting! Input {} is not sorted! Record {} is less than the previous \
+                     record.\n  Compare mode: {mode:?};  Select columns index/es (0-based): \
+                     {:?}\n  Previous: {:?}\n   Current: {:?}\nUse `qsv sortcheck` with the same \
+                     options to check your inputs.",
+                    input.path,
+                    input.record_no,
+                    input.sel,
+                    head.record,
+                    next.record
+                );
+            }
+            heap.push(Reverse(next));
+        }
+    }
+
+    wtr.flush()?;
+    drop(wtr);
+    if let (Some(tmp), Some(output)) = (tmp_output, args.flag_output) {
+        tmp.persist(output).map_err(|e| e.error)?;
+    }
+    Ok(())
+}
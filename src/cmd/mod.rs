@@ -53,10 +53,14 @@ pub mod fmt;
 #[cfg(all(feature = "foreach", not(feature = "lite")))]
 pub mod foreach;
 pub mod frequency;
+#[cfg(all(feature = "fuzzydedup", feature = "feature_capable"))]
+pub mod fuzzydedup;
 #[cfg(feature = "geocode")]
 pub mod geocode;
 #[cfg(feature = "geocode")]
 pub mod geoconvert;
+#[cfg(all(feature = "geocode", feature = "feature_capable"))]
+pub mod geojoin;
 #[cfg(feature = "get")]
 pub mod get;
 pub mod headers;
@@ -80,6 +84,8 @@ pub mod lens;
 pub mod log;
 #[cfg(feature = "luau")]
 pub mod luau;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+pub mod merge;
 pub mod moarstats;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 pub mod partition;
@@ -137,6 +143,8 @@ pub mod to;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 pub mod tojsonl;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
+pub mod top;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
 pub mod transpose;
 pub mod validate;
 // `viz` (and therefore `viz_static`, which enables it) does not work on big-endian
@@ -158,3 +166,5 @@ pub mod viz;
 pub mod viz_census;
 #[cfg(all(feature = "viz", feature = "feature_capable"))]
 pub mod viz_i18n;
+#[cfg(all(feature = "polars", feature = "feature_capable"))]
+pub mod window;
@@ -15,12 +15,30 @@ GROUP-BY MODE (without <on-cols>):
   The none aggregation is not supported in group-by mode.
   If --values is omitted, a single "count" column is produced.
 
+UNPIVOT MODE (with --unpivot):
+  The inverse of a pivot - reshapes wide data to long format (aka "melt").
+  - The --index column(s) are kept as identifier columns and repeated for each value column.
+  - The --values column(s) (and/or the columns matching --values-regex) are stacked into
+    two columns: one with the original column name (--variable-name) and one with the
+    cell value (--value-name).
+  If neither --values nor --values-regex is given, all non-index columns are unpivoted.
+  If the value columns have different data types, they are unpivoted as strings.
+  <on-cols> and the aggregation, total and validation options are not used in unpivot mode.
+
+MULTIPLE AGGREGATIONS:
+  In pivot and group-by mode, --agg accepts a comma-separated list of aggregations
+  (e.g. --agg sum,mean,count). Each aggregation is computed separately and the
+  aggregation name is appended to the generated column names using --col-separator,
+  e.g. "sales_sum", "sales_mean" & "sales_count" in group-by mode.
+  smart and none cannot be combined with other aggregations.
+
 For examples, see https://github.com/dathere/qsv/blob/master/tests/test_pivotp.rs.
 See also https://github.com/dathere/qsv/wiki/SQL-and-Polars#pivotp
 
 Usage:
     qsv pivotp [options] <on-cols> <input>
     qsv pivotp [options] <input>
+    qsv pivotp --unpivot [options] <input>
     qsv pivotp --help
 
 pivotp arguments:
@@ -45,7 +63,10 @@ pivotp options:
                             In pivot mode, if None, all remaining columns not specified on --on and --index
                             will be used; at least one of --index and --values must be specified.
                             In group-by mode, if omitted, a single "count" column is produced.
-    -a, --agg <func>        The aggregation function to use:
+                            In unpivot mode, the columns to unpivot. If omitted, all
+                            non-index columns are unpivoted.
+    -a, --agg <func>        The aggregation function(s) to use.
+                            Specify multiple aggregations by separating them with a comma.
                               first - First value encountered
                               last - Last value encountered
                               sum - Sum of values
@@ -57,6 +78,7 @@ pivotp options:
                                              Alias: q@<p>. Examples: quantile@0.95, q@0.5
                                              (q@0.5 is equivalent to median for even-length groups).
                               len - Count of values
+                              count - Count of non-null values
                               item - Get single value from group. Raises error if there are multiple values.
                               smart - use value column data type & statistics to pick an aggregation.
                                       Always uses type, cardinality, sparsity, CV, sign
@@ -77,8 +99,9 @@ pivotp options:
     --sort-columns          Sort the transposed columns by name. (pivot mode only)
     --maintain-order        Maintain output order: preserve input column order in pivot mode,
                             and preserve group/row order in group-by mode.
-    --col-separator <arg>   The separator in generated column names in case of multiple --values columns.
-                            (pivot mode only; ignored in group-by mode) [default: _]
+    --col-separator <arg>   The separator in generated column names in case of multiple --values
+                            columns or multiple --agg aggregations. In group-by mode, only used
+                            with multiple aggregations. [default: _]
     --validate              Validate a pivot by checking the pivot column(s)' cardinality. (pivot mode only)
     --try-parsedates        When set, will attempt to parse columns as dates.
     --infer-len <arg>       Number of rows to scan when inferring schema.
@@ -93,6 +116,15 @@ pivotp options:
                             Requires 2+ index columns. (pivot mode only)
     --total-label <arg>     Custom label for total rows. [default: Total]
 
+unpivot options:
+    --unpivot               Unpivot the input from wide to long format. See UNPIVOT MODE above.
+    --values-regex <regex>  Unpivot the columns whose names match <regex>, in addition to
+                            any --values columns.
+    --variable-name <arg>   The name of the output column holding the unpivoted column names.
+                            [default: variable]
+    --value-name <arg>      The name of the output column holding the unpivoted values.
+                            [default: value]
+
 Common options:
     -h, --help              Display this message
     -o, --output <file>     Write output to <file> instead of stdout.
@@ -101,13 +133,7 @@ Common options:
     -q, --quiet             Do not return smart aggregation chosen nor pivot result shape to stderr.
 "#;
 
-use std::{
-    fs::File,
-    io,
-    io::{BufReader, Read, Write},
-    path::{Path, PathBuf},
-    sync::OnceLock,
-};
+use std::{fs::File, io, io::Write, path::Path, sync::OnceLock};
 
 use csv::ByteRecord;
 use foldhash::HashSet;
@@ -139,6 +165,50 @@ fn parse_quantile_agg(s: &str) -> Option<f64> {
     (p.is_finite() && (0.0..=1.0).contains(&p)).then_some(p)
 }
 
+/// Map an explicit aggregation name (anything but `smart` and `none`) to the
+/// generic expression pivot applies to each value column.
+/// NOTE: This must stay in sync with `groupby_agg_expr` below.
+fn pivot_agg_expr(agg_name: &str) -> Option<Expr> {
+    Some(match agg_name {
+        "first" => Expr::Element.first(),
+        "last" => Expr::Element.last(),
+        "sum" => Expr::Element.sum(),
+        "min" => Expr::Element.min(),
+        "max" => Expr::Element.max(),
+        "mean" => Expr::Element.mean(),
+        "median" => Expr::Element.median(),
+        "len" => Expr::Element.len(),
+        "count" => Expr::Element.count(),
+        "item" => Expr::Element.item(true),
+        s if let Some(p) = parse_quantile_agg(s) => {
+            Expr::Element.quantile(lit(p), QuantileMethod::Linear)
+        },
+        _ => return None,
+    })
+}
+
+/// Map an aggregation name to its group-by expression on column `c`.
+/// `smart` resolves to `len` in group-by mode. `none` and `item` are rejected
+/// during validation, so they return None here.
+fn groupby_agg_expr(c: Expr, agg_name: &str) -> Option<Expr> {
+    // Use len() (counts all rows including nulls) instead of count()
+    // (counts non-null only) for consistency with pivot mode's
+    // Expr::Element.len().
+    Some(match agg_name {
+        "first" => c.first(),
+        "last" => c.last(),
+        "sum" => c.sum(),
+        "min" => c.min(),
+        "max" => c.max(),
+        "mean" => c.mean(),
+        "median" => c.median(),
+        "len" | "smart" => len(),
+        "count" => c.count(),
+        s if let Some(p) = parse_quantile_agg(s) => c.quantile(lit(p), QuantileMethod::Linear),
+        _ => return None,
+    })
+}
+
 /// Build the `SchemaArgs` used for stats/frequency lookups.
 /// Extracted to avoid duplicated construction at multiple call sites.
 fn build_schema_args(args: &Args) -> util::SchemaArgs {
@@ -206,6 +276,10 @@ struct Args {
     flag_grand_total:    bool,
     flag_subtotal:       bool,
     flag_total_label:    String,
+    flag_unpivot:        bool,
+    flag_values_regex:   Option<String>,
+    flag_variable_name:  String,
+    flag_value_name:     String,
     flag_output:         Option<String>,
     flag_delimiter:      Option<Delimiter>,
     flag_quiet:          bool,
@@ -869,6 +943,247 @@ fn log_mixed_sign(stats: &StatsData) {
     );
 }
 
+/// Resolve the field delimiter and check it against --decimal-comma.
+fn get_delimiter(args: &Args) -> CliResult<u8> {
+    let delim = if let Some(delimiter) = args.flag_delimiter {
+        delimiter.as_byte()
+    } else {
+        b','
+    };
+
+    if args.flag_decimal_comma && delim == b',' {
+        return fail_incorrectusage_clierror!(
+            "You need to specify an alternate --delimiter when using --decimal-comma."
+        );
+    }
+    Ok(delim)
+}
+
+/// Create the input `LazyFrame`, using the pschema.json file if it exists and is current.
+fn read_input(args: &Args, input_path_str: &str, delim: u8) -> CliResult<LazyFrame> {
+    // Create CSV reader config
+    let mut csv_reader = LazyCsvReader::new(PlRefPath::new(input_path_str))
+        .with_has_header(true)
+        .with_try_parse_dates(args.flag_try_parsedates)
+        .with_decimal_comma(args.flag_decimal_comma)
+        .with_separator(delim)
+        .with_ignore_errors(args.flag_ignore_errors);
+
+    // Use the schema from the pschema.json file if it exists and is current,
+    // and the user did not specify a custom inference length
+    let schema = if args.flag_infer_len == DEFAULT_INFER_LEN {
+        util::load_current_pschema(Path::new(input_path_str))?
+    } else {
+        None
+    };
+    if let Some(schema) = schema {
+        csv_reader = csv_reader.with_schema(Some(Arc::new(schema)));
+    } else {
+        // Otherwise we infer the schema using inference length (default or user-specified)
+        csv_reader = csv_reader.with_infer_schema_length(Some(args.flag_infer_len));
+    }
+
+    Ok(csv_reader.finish()?)
+}
+
+/// Write the result `DataFrame` to --output or stdout, and print its shape to stderr.
+fn write_output(args: &Args, df: &mut DataFrame, delim: u8) -> CliResult<()> {
+    let mut writer = match args.flag_output {
+        Some(ref output_file) => {
+            // no need to use buffered writer here, as CsvWriter already does that
+            let path = Path::new(&output_file);
+            Box::new(File::create(path)?) as Box<dyn Write>
+        },
+        None => Box::new(io::stdout()) as Box<dyn Write>,
+    };
+    let datetime_fmt: PlSmallStr = PlSmallStr::from_str("%Y-%m-%d %H:%M:%S");
+    CsvWriter::new(&mut writer)
+        .include_header(true)
+        .with_datetime_format(Some(datetime_fmt))
+        .with_separator(delim)
+        .finish(df)?;
+
+    // Print shape to stderr
+    if !args.flag_quiet {
+        eprintln!("{:?}", df.shape());
+    }
+
+    Ok(())
+}
+
+/// Unpivot (melt) the input from wide to long format.
+fn run_unpivot(args: &Args, input_path_str: &str) -> CliResult<()> {
+    if args.flag_subtotal || args.flag_grand_total {
+        return fail_incorrectusage_clierror!(
+            "--subtotal and --grand-total are not supported in unpivot mode."
+        );
+    }
+    if args.flag_validate {
+        return fail_incorrectusage_clierror!("--validate is not supported in unpivot mode.");
+    }
+    if args.flag_sort_columns {
+        return fail_incorrectusage_clierror!("--sort-columns is not supported in unpivot mode.");
+    }
+    if args.flag_variable_name == args.flag_value_name {
+        return fail_incorrectusage_clierror!(
+            "--variable-name and --value-name must be different."
+        );
+    }
+
+    let values_regex = if let Some(ref re) = args.flag_values_regex {
+        match regex::Regex::new(re) {
+            Ok(re) => Some(re),
+            Err(e) => return fail_incorrectusage_clierror!("Invalid --values-regex: {e}"),
+        }
+    } else {
+        None
+    };
+
+    let delim = get_delimiter(args)?;
+    let mut lf = read_input(args, input_path_str, delim)?;
+    let schema = lf.collect_schema()?;
+
+    let index_cols: Vec<String> = args
+        .flag_index
+        .as_deref()
+        .map(|s| s.split(',').map(std::string::ToString::to_string).collect())
+        .unwrap_or_default();
+    let index_set: HashSet<&str> = index_cols.iter().map(String::as_str).collect();
+
+    for idx_col in &index_cols {
+        if schema.get(idx_col).is_none() {
+            return fail_incorrectusage_clierror!("--index column \"{idx_col}\" not found.");
+        }
+    }
+
+    // value columns: explicit --values first, then any --values-regex matches
+    // in input column order, skipping index and already selected columns
+    let mut value_cols: Vec<String> = Vec::new();
+    if let Some(ref flag_values) = args.flag_values {
+        for vc in flag_values.split(',') {
+            if schema.get(vc).is_none() {
+                return fail_incorrectusage_clierror!("--values column \"{vc}\" not found.");
+            }
+            if index_set.contains(vc) {
+                return fail_incorrectusage_clierror!(
+                    "Column \"{vc}\" cannot be both an --index and a --values column."
+                );
+            }
+            if !value_cols.iter().any(|c| c == vc) {
+                value_cols.push(vc.to_string());
+            }
+        }
+    }
+    if let Some(ref re) = values_regex {
+        for name in schema.iter_names() {
+            let name = name.as_str();
+            if re.is_match(name)
+                && !index_set.contains(name)
+                && !value_cols.iter().any(|c| c == name)
+            {
+                value_cols.push(name.to_string());
+            }
+        }
+        if value_cols.is_empty() {
+            return fail_incorrectusage_clierror!("--values-regex did not match any column.");
+        }
+    }
+    if args.flag_values.is_none() && values_regex.is_none() {
+        value_cols = schema
+            .iter_names()
+            .filter(|name| !index_set.contains(name.as_str()))
+            .map(PlSmallStr::to_string)
+            .collect();
+    }
+    if value_cols.is_empty() {
+        return fail_incorrectusage_clierror!("No columns to unpivot.");
+    }
+    for out_col in [&args.flag_variable_name, &args.flag_value_name] {
+        if index_set.contains(out_col.as_str()) {
+            return fail_incorrectusage_clierror!(
+                "Output column \"{out_col}\" conflicts with an --index column."
+            );
+        }
+    }
+
+    // unpivot requires a common supertype for all the value columns.
+    // If they differ, unpivot them as strings so mixed numeric/text columns still work.
+    let first_dtype = schema.get(&value_cols[0]);
+    let mixed_dtypes = value_cols.iter().any(|vc| schema.get(vc) != first_dtype);
+    let lf = if mixed_dtypes {
+        lf.with_columns(
+            value_cols
+                .iter()
+                .map(|vc| col(PlSmallStr::from_str(vc)).cast(DataType::String))
+                .collect::<Vec<_>>(),
+        )
+    } else {
+        lf
+    };
+
+    let mut unpivot_result = lf
+        .unpivot(UnpivotArgsDSL {
+            on:            cols(value_cols.iter().map(String::as_str)),
+            index:         cols(index_cols.iter().map(String::as_str)),
+            variable_name: Some(PlSmallStr::from_str(&args.flag_variable_name)),
+            value_name:    Some(PlSmallStr::from_str(&args.flag_value_name)),
+        })
+        .collect()?;
+
+    write_output(args, &mut unpivot_result, delim)
+}
+
+/// Run a single pivot with `agg` and restore the discovery order of the index rows.
+#[allow(clippy::too_many_arguments)]
+fn pivot_with_agg(
+    lf: LazyFrame,
+    on_cols: &[String],
+    on_columns: Arc<DataFrame>,
+    index_cols: &[String],
+    value_cols: &[String],
+    agg: Expr,
+    maintain_order: bool,
+    separator: PlSmallStr,
+    index_order: Option<&DataFrame>,
+    row_order_col: &str,
+) -> CliResult<DataFrame> {
+    // Perform pivot operation using the new LazyFrame.pivot API
+    let on_selector = cols(on_cols.iter().map(std::string::String::as_str));
+    let index_selector = cols(index_cols.iter().map(std::string::String::as_str));
+    let values_selector = cols(value_cols.iter().map(std::string::String::as_str));
+    let column_naming = PivotColumnNaming::default();
+
+    let mut pivot_result = lf
+        .pivot(
+            on_selector,
+            on_columns,
+            index_selector,
+            values_selector,
+            agg,
+            maintain_order,
+            separator,
+            column_naming,
+        )
+        .collect()?;
+
+    // Restore discovery order by joining with index_order and sorting
+    if let Some(index_order_df) = index_order {
+        pivot_result = pivot_result
+            .lazy()
+            .join(
+                index_order_df.clone().lazy(),
+                &index_cols.iter().map(col).collect::<Vec<_>>(),
+                &index_cols.iter().map(col).collect::<Vec<_>>(),
+                JoinArgs::new(JoinType::Left),
+            )
+            .sort([row_order_col], SortMultipleOptions::default())
+            .drop(cols([row_order_col]))
+            .collect()?;
+    }
+
+    Ok(pivot_result)
+}
+
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
 
@@ -897,6 +1212,16 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         },
     };
 
+    if args.flag_unpivot {
+        if on_cols.is_some() {
+            return fail_incorrectusage_clierror!("<on-cols> is not supported in unpivot mode.");
+        }
+        return run_unpivot(&args, &input_path_str);
+    }
+    if args.flag_values_regex.is_some() {
+        return fail_incorrectusage_clierror!("--values-regex requires --unpivot.");
+    }
+
     let is_groupby_mode = on_cols.is_none();
 
     // Parse index column(s)
@@ -923,6 +1248,15 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         None
     };
 
+    // Parse the aggregation function name(s) (default: "smart", which resolves to "len" in
+    // group-by mode)
+    let agg_names: Vec<String> = if let Some(ref agg) = args.flag_agg {
+        agg.split(',').map(|a| a.trim().to_lowercase()).collect()
+    } else {
+        vec!["smart".to_string()]
+    };
+    let multi_agg = agg_names.len() > 1;
+
     // Validate mode-specific constraints
     if is_groupby_mode {
         if index_cols.is_none() {
@@ -941,136 +1275,94 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                 "--sort-columns is not supported in group-by mode."
             );
         }
-        if let Some(ref agg) = args.flag_agg
-            && agg.eq_ignore_ascii_case("item")
-        {
+        if agg_names.iter().any(|a| a == "item") {
             return fail_incorrectusage_clierror!("--agg item is not supported in group-by mode.");
         }
+        // Reject --agg none AFTER lowercasing so "None"/"NONE" are also caught
+        if agg_names.iter().any(|a| a == "none") {
+            return fail_incorrectusage_clierror!(
+                "--agg \"none\" is not supported in group-by mode."
+            );
+        }
+        if multi_agg && value_cols.is_none() {
+            return fail_incorrectusage_clierror!(
+                "Multiple aggregations require --values <cols> in group-by mode."
+            );
+        }
     } else if index_cols.is_none() && value_cols.is_none() {
         return fail_incorrectusage_clierror!(
             "Either --index <cols> or --values <cols> must be specified."
         );
     }
 
-    // Parse the aggregation function name (default: "smart", which resolves to "len" in group-by
-    // mode)
-    let agg_name = if let Some(ref agg) = args.flag_agg {
-        agg.to_lowercase()
-    } else {
-        "smart".to_string()
-    };
-
-    // Reject --agg none in group-by mode AFTER lowercasing so "None"/"NONE" are also caught
-    if is_groupby_mode && agg_name == "none" {
-        return fail_incorrectusage_clierror!("--agg \"none\" is not supported in group-by mode.");
+    if multi_agg {
+        let mut seen: HashSet<&str> = HashSet::default();
+        for agg_name in &agg_names {
+            if agg_name == "smart" || agg_name == "none" {
+                return fail_incorrectusage_clierror!(
+                    "--agg {agg_name} cannot be combined with other aggregations."
+                );
+            }
+            if !seen.insert(agg_name.as_str()) {
+                return fail_incorrectusage_clierror!("Duplicate aggregation in --agg: {agg_name}");
+            }
+        }
     }
 
     // Reject malformed quantile probabilities with a precise error so users see *why*
     // their probability is bad rather than the generic "Invalid pivot aggregation function".
     // Works in both pivot and group-by mode; no mode-conditional check needed.
-    if (agg_name.starts_with("quantile@") || agg_name.starts_with("q@"))
-        && parse_quantile_agg(&agg_name).is_none()
-    {
-        return fail_incorrectusage_clierror!(
-            "Invalid quantile probability in --agg {agg_name}: must be a float in [0, 1] (e.g. \
-             quantile@0.95)"
-        );
+    for agg_name in &agg_names {
+        if (agg_name.starts_with("quantile@") || agg_name.starts_with("q@"))
+            && parse_quantile_agg(agg_name).is_none()
+        {
+            return fail_incorrectusage_clierror!(
+                "Invalid quantile probability in --agg {agg_name}: must be a float in [0, 1] \
+                 (e.g. quantile@0.95)"
+            );
+        }
     }
 
-    // Get aggregation function - using generic expressions that pivot will apply to value columns
-    // NOTE: This match must stay in sync with the group-by agg_exprs match below.
-    let agg_expr = if agg_name == "none" {
-        None
-    } else {
-        Some(match agg_name.as_str() {
-            "first" => Expr::Element.first(),
-            "last" => Expr::Element.last(),
-            "sum" => Expr::Element.sum(),
-            "min" => Expr::Element.min(),
-            "max" => Expr::Element.max(),
-            "mean" => Expr::Element.mean(),
-            "median" => Expr::Element.median(),
-            "len" => Expr::Element.len(),
-            "item" => Expr::Element.item(true),
-            s if let Some(p) = parse_quantile_agg(s) => {
-                Expr::Element.quantile(lit(p), QuantileMethod::Linear)
-            },
-            "smart" => {
-                if is_groupby_mode {
-                    // In group-by mode, smart defaults to len (count)
-                    Expr::Element.len()
-                } else if let Some(value_cols) = &value_cols {
-                    // Try to suggest an appropriate aggregation function
-                    let on_cols_ref = on_cols.as_ref().unwrap();
-                    if let Some(suggested_agg) =
-                        suggest_agg_function(&args, on_cols_ref, index_cols.as_deref(), value_cols)
-                    {
-                        suggested_agg
-                    } else {
-                        // fallback to first, which always works
-                        Expr::Element.first()
-                    }
+    // Get aggregation function(s) - using generic expressions that pivot will apply to value
+    // columns. Each entry is the aggregation name and its pivot expression (None for "none").
+    let mut aggs: Vec<(String, Option<Expr>)> = Vec::with_capacity(agg_names.len());
+    for agg_name in agg_names {
+        let agg_expr = if agg_name == "none" {
+            None
+        } else if agg_name == "smart" {
+            Some(if is_groupby_mode {
+                // In group-by mode, smart defaults to len (count)
+                Expr::Element.len()
+            } else if let Some(value_cols) = &value_cols {
+                // Try to suggest an appropriate aggregation function
+                let on_cols_ref = on_cols.as_ref().unwrap();
+                if let Some(suggested_agg) =
+                    suggest_agg_function(&args, on_cols_ref, index_cols.as_deref(), value_cols)
+                {
+                    suggested_agg
                 } else {
-                    // Default to Len if no value columns specified
-                    Expr::Element.len()
+                    // fallback to first, which always works
+                    Expr::Element.first()
                 }
-            },
-            _ => {
-                let agg_mode = if is_groupby_mode { "group-by" } else { "pivot" };
-                return fail_incorrectusage_clierror!(
-                    "Invalid {agg_mode} aggregation function: {agg_name}"
-                );
-            },
-        })
-    };
-
-    // Set delimiter if specified
-    let delim = if let Some(delimiter) = args.flag_delimiter {
-        delimiter.as_byte()
-    } else {
-        b','
-    };
-
-    if args.flag_decimal_comma && delim == b',' {
-        return fail_incorrectusage_clierror!(
-            "You need to specify an alternate --delimiter when using --decimal-comma."
-        );
+            } else {
+                // Default to Len if no value columns specified
+                Expr::Element.len()
+            })
+        } else if let Some(expr) = pivot_agg_expr(&agg_name) {
+            Some(expr)
+        } else {
+            let agg_mode = if is_groupby_mode { "group-by" } else { "pivot" };
+            return fail_incorrectusage_clierror!(
+                "Invalid {agg_mode} aggregation function: {agg_name}"
+            );
+        };
+        aggs.push((agg_name, agg_expr));
     }
 
-    // Create CSV reader config
-    let mut csv_reader = LazyCsvReader::new(PlRefPath::new(&input_path_str))
-        .with_has_header(true)
-        .with_try_parse_dates(args.flag_try_parsedates)
-        .with_decimal_comma(args.flag_decimal_comma)
-        .with_separator(delim)
-        .with_ignore_errors(args.flag_ignore_errors);
-
-    // check if the pschema.json file exists and is newer or created at the same time
-    // as the table file
-    let input_path = Path::new(&input_path_str);
-    let schema_file = PathBuf::from(format!(
-        "{}.pschema.json",
-        input_path.canonicalize()?.display()
-    ));
-    let valid_schema_exists = schema_file.exists()
-        && schema_file.metadata()?.modified()? >= input_path.metadata()?.modified()?;
-
-    if valid_schema_exists && args.flag_infer_len == DEFAULT_INFER_LEN {
-        // Use schema from pschema.json file if it exists and is valid
-        // and the user did not specify a custom inference length
-        let file = File::open(&schema_file)?;
-        let mut buf_reader = BufReader::new(file);
-        let mut schema_json = String::with_capacity(100);
-        buf_reader.read_to_string(&mut schema_json)?;
-        let schema: Schema = serde_json::from_str(&schema_json)?;
-        csv_reader = csv_reader.with_schema(Some(Arc::new(schema)));
-    } else {
-        // Otherwise we infer the schema using inference length (default or user-specified)
-        csv_reader = csv_reader.with_infer_schema_length(Some(args.flag_infer_len));
-    }
+    let delim = get_delimiter(&args)?;
 
     // Read the CSV into a LazyFrame
-    let mut lf = csv_reader.finish()?;
+    let mut lf = read_input(&args, &input_path_str, delim)?;
 
     // Add a row index to track discovery order
     let row_order_col = "__qsv_row_order__";
@@ -1129,46 +1421,41 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         None
     };
 
+    let index_cols_set: HashSet<&str> = actual_index_cols
+        .iter()
+        .map(std::string::String::as_str)
+        .collect();
+
     // Branch into group-by or pivot path
     let mut pivot_result = if is_groupby_mode {
         // === GROUP-BY MODE ===
-        // Build aggregation expressions for each value column
-        // NOTE: This match must stay in sync with the agg_expr match above.
-        // "none" is rejected during validation; if it somehow reaches here, treat as error.
+        // Build aggregation expressions for each value column and aggregation.
+        // With multiple aggregations, the output columns are named
+        // <value><col-separator><agg>.
         let agg_exprs: Vec<Expr> = if let Some(ref val_cols) = actual_value_cols {
-            val_cols
-                .iter()
-                .map(|vc| {
-                    let c = col(PlSmallStr::from_str(vc));
-                    // Use len() (counts all rows including nulls) instead of count()
-                    // (counts non-null only) for consistency with pivot mode's
-                    // Expr::Element.len(). "item" is rejected during validation.
-                    match agg_name.as_str() {
-                        "first" => c.first().alias(PlSmallStr::from_str(vc)),
-                        "last" => c.last().alias(PlSmallStr::from_str(vc)),
-                        "sum" => c.sum().alias(PlSmallStr::from_str(vc)),
-                        "min" => c.min().alias(PlSmallStr::from_str(vc)),
-                        "max" => c.max().alias(PlSmallStr::from_str(vc)),
-                        "mean" => c.mean().alias(PlSmallStr::from_str(vc)),
-                        "median" => c.median().alias(PlSmallStr::from_str(vc)),
-                        "len" | "smart" => len().alias(PlSmallStr::from_str(vc)),
-                        s if let Some(p) = parse_quantile_agg(s) => c
-                            .quantile(lit(p), QuantileMethod::Linear)
-                            .alias(PlSmallStr::from_str(vc)),
-                        // Unreachable because:
-                        //   - "none" and "item" are rejected in the group-by validation block in
-                        //     `run` (see the `is_groupby_mode` checks).
-                        //   - malformed quantile@/q@ names are rejected in the same validation
-                        //     block.
-                        //   - any other unknown name is rejected in the pivot `agg_expr` match
-                        //     above, where `agg_expr` is first constructed.
-                        _ => unreachable!(
+            let mut agg_exprs = Vec::with_capacity(val_cols.len() * aggs.len());
+            for vc in val_cols {
+                for (agg_name, _) in &aggs {
+                    // Unreachable None because:
+                    //   - "none" and "item" are rejected in the group-by validation block above.
+                    //   - malformed quantile@/q@ names are rejected in the same validation block.
+                    //   - any other unknown name is rejected when `aggs` is constructed.
+                    let Some(agg_expr) = groupby_agg_expr(col(PlSmallStr::from_str(vc)), agg_name)
+                    else {
+                        unreachable!(
                             "Invalid agg_name '{agg_name}' should have been caught during \
                              validation"
-                        ),
-                    }
-                })
-                .collect()
+                        );
+                    };
+                    let out_name = if multi_agg {
+                        format!("{vc}{}{agg_name}", args.flag_col_separator)
+                    } else {
+                        vc.clone()
+                    };
+                    agg_exprs.push(agg_expr.alias(PlSmallStr::from_str(&out_name)));
+                }
+            }
+            agg_exprs
         } else {
             // No value columns: just count rows per group
             vec![len().alias(PlSmallStr::from_str("count"))]
@@ -1212,7 +1499,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         // === PIVOT MODE ===
         // Safety: on_cols is always Some in pivot mode (is_groupby_mode is false iff on_cols is
         // Some). actual_value_cols is always Some because the pivot-mode fallback
-        // (lines ~940-950) computes remaining columns when --values is omitted.
+        // above computes remaining columns when --values is omitted.
         let on_cols = on_cols.unwrap();
         let actual_value_cols = actual_value_cols.unwrap();
 
@@ -1248,10 +1535,6 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             Arc::new(unique_df)
         };
 
-        // Create the aggregation expression
-        // If agg_expr is None, we need a default
-        let agg = agg_expr.unwrap_or_else(|| Expr::Element.first());
-
         // Convert separator to PlSmallStr
         let separator = PlSmallStr::from_str(&args.flag_col_separator);
 
@@ -1276,39 +1559,62 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             Some(order_df)
         };
 
-        // Perform pivot operation using the new LazyFrame.pivot API
-        let on_selector = cols(on_cols.iter().map(std::string::String::as_str));
-        let index_selector = cols(actual_index_cols.iter().map(std::string::String::as_str));
-        let values_selector = cols(actual_value_cols.iter().map(std::string::String::as_str));
-        let column_naming = PivotColumnNaming::default();
-
-        let mut pivot_result = lf
-            .pivot(
-                on_selector,
-                on_columns,
-                index_selector,
-                values_selector,
+        // Pivot once per aggregation. With multiple aggregations, the aggregation name
+        // is appended to each pivoted column name and the pivoted columns of every
+        // aggregation are stacked horizontally. As the rows of each pivot are sorted by
+        // discovery order, they line up across aggregations.
+        let mut pivot_result: Option<DataFrame> = None;
+        for (agg_name, agg_expr) in aggs {
+            // If agg_expr is None, we need a default
+            let agg = agg_expr.unwrap_or_else(|| Expr::Element.first());
+
+            let mut agg_result = pivot_with_agg(
+                lf.clone(),
+                &on_cols,
+                on_columns.clone(),
+                &actual_index_cols,
+                &actual_value_cols,
                 agg,
                 args.flag_maintain_order,
-                separator,
-                column_naming,
-            )
-            .collect()?;
+                separator.clone(),
+                index_order.as_ref(),
+                row_order_col,
+            )?;
+
+            if !multi_agg {
+                pivot_result = Some(agg_result);
+                break;
+            }
 
-        // Restore discovery order by joining with index_order and sorting
-        if let Some(index_order_df) = index_order {
-            pivot_result = pivot_result
-                .lazy()
-                .join(
-                    index_order_df.lazy(),
-                    &actual_index_cols.iter().map(col).collect::<Vec<_>>(),
-                    &actual_index_cols.iter().map(col).collect::<Vec<_>>(),
-                    JoinArgs::new(JoinType::Left),
-                )
-                .sort([row_order_col], SortMultipleOptions::default())
-                .drop(cols([row_order_col]))
-                .collect()?;
+            let new_names: Vec<String> = agg_result
+                .get_column_names()
+                .into_iter()
+                .map(|name| {
+                    if index_cols_set.contains(name.as_str()) {
+                        name.to_string()
+                    } else {
+                        format!("{name}{separator}{agg_name}")
+                    }
+                })
+                .collect();
+            agg_result.set_column_names(new_names)?;
+
+            pivot_result = Some(match pivot_result {
+                None => agg_result,
+                Some(mut result) => {
+                    let pivoted: Vec<Column> = agg_result
+                        .columns()
+                        .iter()
+                        .filter(|c| !index_cols_set.contains(c.name().as_str()))
+                        .cloned()
+                        .collect();
+                    result.hstack_mut(&pivoted)?;
+                    result
+                },
+            });
         }
+        // Safety: aggs always has at least one entry
+        let mut pivot_result = pivot_result.unwrap();
 
         // Sort columns if requested
         if args.flag_sort_columns {
@@ -1316,10 +1622,6 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                 .get_column_names()
                 .into_iter()
                 .map(polars::prelude::PlSmallStr::to_string);
-            let index_cols_set: HashSet<_> = actual_index_cols
-                .iter()
-                .map(std::string::String::as_str)
-                .collect();
 
             // Separate index and pivoted columns
             let (index_names, mut pivot_names): (Vec<_>, Vec<_>) = columns
@@ -1387,25 +1689,5 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     }
 
     // Write output
-    let mut writer = match args.flag_output {
-        Some(ref output_file) => {
-            // no need to use buffered writer here, as CsvWriter already does that
-            let path = Path::new(&output_file);
-            Box::new(File::create(path)?) as Box<dyn Write>
-        },
-        None => Box::new(io::stdout()) as Box<dyn Write>,
-    };
-    let datetime_fmt: PlSmallStr = PlSmallStr::from_str("%Y-%m-%d %H:%M:%S");
-    CsvWriter::new(&mut writer)
-        .include_header(true)
-        .with_datetime_format(Some(datetime_fmt))
-        .with_separator(delim)
-        .finish(&mut pivot_result)?;
-
-    // Print shape to stderr
-    if !args.flag_quiet {
-        eprintln!("{:?}", pivot_result.shape());
-    }
-
-    Ok(())
+    write_output(&args, &mut pivot_result, delim)
 }
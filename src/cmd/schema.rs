@@ -1,5 +1,7 @@
 static USAGE: &str = r#"
 Generate JSON Schema or Polars Schema (with the `--polars` option) from CSV data.
+The inferred schema can also be exported as SQL DDL, an Avro or Arrow schema, or
+Pydantic/TypeScript types (with the `--format` option).
 
 JSON Schema Validation:
 =======================
@@ -47,11 +49,95 @@ skip schema inferencing which may fail when the inferencing sample is too low, i
 Polars to optimize the query and gives the user the option to tailor the schema to their specific
 query needs (e.g. using a Decimal type with explicit precision and scale instead of a Float type).
 
+Other Schema Formats:
+=====================
+`--format` exports the inferred schema in other formats, so the database tables, data
+pipelines & application types derived from a CSV all agree on its column types. All the
+formats are translated from the same stats-cache driven JSON Schema, with date & datetime
+columns inferred as if --strict-dates was set:
+
+  * postgres, sqlite & duckdb - a `CREATE TABLE` statement, with NOT NULL for columns
+    without empty values, and CHECK constraints for enums and for the value & length ranges.
+  * avro - an Avro record schema (.avsc), with nullable columns as a union with null and
+    enums as Avro enums when their values are valid Avro names.
+  * arrow - an Arrow schema in the Arrow JSON format.
+  * pydantic - a Pydantic model, with enums as Literal types and range constraints as
+    Field() arguments. Column names that aren't Python identifiers are aliased.
+  * typescript - a TypeScript interface of the records, as output by `qsv tojsonl`.
+
+The output is written to <input>.<format suffix> (e.g. mydata.csv.postgres.sql, .avsc,
+.arrow.json, .py or .ts), or to --output or --stdout. The table, record & type names are
+derived from the input filename, or set with --table. <input> can also be a JSON Schema file.
+
+  # Generate PostgreSQL DDL for a CSV file
+  qsv schema --format postgres --table suppliers data.csv
+
+  # Generate TypeScript types from a fine-tuned JSON Schema
+  qsv schema --format typescript --stdout data.csv.schema.json
+
+Merging Schemas:
+================
+A schema inferred from one sample file often rejects the next one, e.g. January's schema
+rejecting February's new enum values. `--merge` generates a single JSON Schema from
+several inputs - CSV files whose schema is inferred with the options below, and/or
+existing JSON Schema files - that accepts the data of all of them:
+
+  * types are unioned & widened - e.g. integer & number to number. As CSV values are
+    text, any other mix of types widens to string.
+  * enums (and consts) are unioned, and dropped if they end up with more than
+    --enum-threshold values or if an input has no enum for the column.
+  * minimum/maximum & minLength/maxLength ranges are widened.
+  * other constraints (e.g. pattern & format) are only kept if all inputs agree.
+  * columns missing from any input are optional.
+
+The inputs that contributed are recorded in the schema's `$comment`. The merged schema is
+written to stdout, or to --output.
+
+  # Merge the schemas of monthly supplier files
+  qsv schema --merge jan.csv feb.csv mar.csv -o supplier.schema.json
+
+  # Extend an existing schema with a new sample file
+  qsv schema --merge supplier.schema.json apr.csv -o supplier.schema.json
+
+Schema Comparison:
+==================
+When a supplier changes their file, `--compare` compares the old & new schemas - either
+JSON Schema files, or CSV files whose schema is inferred with the options below - and
+writes a CSV report of the changes, with one row per change:
+
+  * column_added/column_removed
+  * column_renamed - a removed & an added column with similar headers (per
+    --rename-threshold) and compatible types
+  * type_widened/type_narrowed/type_changed - e.g. integer to number is widening. As CSV
+    values are text, any type widens to string.
+  * enum_values_added/enum_values_removed/enum_added/enum_removed
+  * constraint_tightened/constraint_loosened/constraint_changed - for minimum, maximum,
+    exclusiveMinimum, exclusiveMaximum, minLength, maxLength, pattern, format, const,
+    multipleOf & dynamicEnum
+  * required_added/required_removed
+
+Each change is rated by its compatibility:
+  * backward - data valid under the old schema is also valid under the new one
+  * forward  - data valid under the new schema is also valid under the old one
+  * full     - both backward & forward compatible
+  * breaking - neither
+
+The command returns a non-zero exit code if the changes don't have the --compat
+compatibility, so it can gate a pipeline.
+
+  # Compare two JSON Schemas, failing on changes that break existing data
+  qsv schema --compare old.schema.json new.schema.json
+
+  # Check that this month's file is fully compatible with last month's schema
+  qsv schema --compare --compat full data-2024-01.csv.schema.json data-2024-02.csv
+
 For examples, see https://github.com/dathere/qsv/blob/master/tests/test_schema.rs.
 See also https://github.com/dathere/qsv/wiki/Validation-and-Schema#schema
 
 Usage:
     qsv schema [options] [<input>]
+    qsv schema --compare [options] <old> <new>
+    qsv schema --merge [options] <inputs>...
     qsv schema --help
 
 Schema options:
@@ -88,6 +174,11 @@ Schema options:
                                Otherwise, use mdy format.
     --force                    Force recomputing cardinality and unique values
                                even if stats cache file exists and is current.
+    --format <format>          The schema format to generate. One of jsonschema, postgres,
+                               sqlite, duckdb, avro, arrow, pydantic or typescript.
+                               See "Other Schema Formats" above. [default: jsonschema]
+    --table <name>             The table, record or type name for --format. Defaults to
+                               the input filename without its extensions.
     --stdout                   Send generated JSON schema file to stdout instead.
     -j, --jobs <arg>           The number of jobs to run in parallel.
                                When not set, the number of jobs is set to the
@@ -95,13 +186,27 @@ Schema options:
     -o, --output <file>        Write output to <file> instead of using the default
                                filename. For JSON Schema, the default is
                                <input>.schema.json. For Polars schema, the default
-                               is <input>.pschema.json.
+                               is <input>.pschema.json. For the other --format
+                               formats, see "Other Schema Formats" above.
 
     --polars                   Infer a Polars schema instead of a JSON Schema.
                                This option is only available if the `polars` feature is enabled.
                                The generated Polars schema will be written to a file with the
                                `.pschema.json` suffix appended to the input filename.
 
+Schema merge & comparison options:
+    --merge                    Merge the schemas of <inputs> instead of generating one.
+                               See "Merging Schemas" above.
+    --compare                  Compare the <old> & <new> schemas instead of generating one,
+                               writing the report to stdout, or to --output.
+                               See "Schema Comparison" above.
+    --compat <mode>            The compatibility the changes must have for a zero exit code.
+                               One of backward, forward, full or none. [default: backward]
+    --rename-threshold <num>   The minimum header similarity (0-1) of a removed & an added
+                               column to be reported as a rename. Headers are compared
+                               disregarding case & non-alphanumeric characters.
+                               [default: 0.8]
+
 Common options:
     -h, --help                 Display this message
     -n, --no-headers           When set, the first row will not be interpreted
@@ -140,11 +245,29 @@ use crate::{
     util::{self, StatsMode},
 };
 
+mod compare;
+mod export;
+mod merge;
+
 const STDIN_CSV: &str = "stdin.csv";
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let mut args: util::SchemaArgs = util::get_args(USAGE, argv)?;
 
+    let compare_args: compare::Args = util::get_args(USAGE, argv)?;
+    if compare_args.flag_compare {
+        return compare::run(&args, &compare_args);
+    }
+    let merge_args: merge::Args = util::get_args(USAGE, argv)?;
+    if merge_args.flag_merge {
+        return merge::run(&args, &merge_args);
+    }
+    let export_args: export::Args = util::get_args(USAGE, argv)?;
+    let format = export::Format::from_flag(export_args.flag_format.as_deref())?;
+    if format != export::Format::JsonSchema && args.flag_polars {
+        return fail_incorrectusage_clierror!("--format cannot be used with --polars.");
+    }
+
     #[cfg(feature = "polars")]
     if args.flag_polars {
         if let Some(input) = args.arg_input {
@@ -213,9 +336,59 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         winfo!("Prefer DMY set.");
     }
 
+    if format != export::Format::JsonSchema {
+        return export::run(&args, &export_args, format, &input_path);
+    }
+
+    let schema = infer_json_schema(
+        &args,
+        &input_filename,
+        &format!("Inferred JSON Schema with `qsv {}`", argv[1..].join(" ")),
+    )?;
+
+    // Use platform-appropriate JSON serialization
+    let schema_pretty = match cfg_select! {
+        target_endian = "little" => simd_json::to_string_pretty(&schema),
+        _ => serde_json::to_string_pretty(&schema),
+    } {
+        Ok(s) => s,
+        Err(e) => return fail_clierror!("Cannot prettify schema json: {e}"),
+    };
+
+    if args.flag_stdout {
+        let stdout = std::io::stdout();
+        let mut handle = stdout.lock();
+
+        handle.write_all(schema_pretty.as_bytes())?;
+        handle.flush()?;
+
+        info!("Schema written to stdout");
+    } else {
+        let schema_output_filename = if let Some(output) = args.flag_output {
+            output
+        } else {
+            input_path + ".schema.json"
+        };
+        let mut schema_output_file = File::create(&schema_output_filename)?;
+
+        schema_output_file.write_all(schema_pretty.as_bytes())?;
+        schema_output_file.flush()?;
+
+        woutinfo!("Schema written to {schema_output_filename}");
+    }
+
+    Ok(())
+}
+
+/// Infer the JSON Schema of the CSV file `args.arg_input`.
+fn infer_json_schema(
+    args: &util::SchemaArgs,
+    input_filename: &str,
+    description: &str,
+) -> CliResult<Value> {
     // build schema for each field by their inferred type, min/max value/length, and unique values
     let mut properties_map: Map<String, Value> =
-        match infer_schema_from_stats(&args, &input_filename, false) {
+        match infer_schema_from_stats(args, input_filename, false) {
             Ok(map) => map,
             Err(e) => {
                 return fail_clierror!(
@@ -225,7 +398,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         };
 
     // generate regex pattern for selected String columns
-    let pattern_map = generate_string_patterns(&args, &properties_map)?;
+    let pattern_map = generate_string_patterns(args, &properties_map)?;
 
     // enrich properties map with pattern constraint for String fields
     for (field_name, field_def) in &mut properties_map {
@@ -242,47 +415,41 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     let required_fields = get_required_fields(&properties_map);
 
     // create final JSON object for output
-    let schema = json!({
+    Ok(json!({
         "$schema": "https://json-schema.org/draft/2020-12/schema",
         "title": format!("JSON Schema for {input_filename}"),
-        "description": format!("Inferred JSON Schema with `qsv {}`", argv[1..].join(" ")),
+        "description": description,
         "type": "object",
         "properties": Value::Object(properties_map),
         "required": Value::Array(required_fields)
-    });
-
-    // Use platform-appropriate JSON serialization
-    let schema_pretty = match cfg_select! {
-        target_endian = "little" => simd_json::to_string_pretty(&schema),
-        _ => serde_json::to_string_pretty(&schema),
-    } {
-        Ok(s) => s,
-        Err(e) => return fail_clierror!("Cannot prettify schema json: {e}"),
-    };
-
-    if args.flag_stdout {
-        let stdout = std::io::stdout();
-        let mut handle = stdout.lock();
-
-        handle.write_all(schema_pretty.as_bytes())?;
-        handle.flush()?;
+    }))
+}
 
-        info!("Schema written to stdout");
-    } else {
-        let schema_output_filename = if let Some(output) = args.flag_output {
-            output
-        } else {
-            input_path + ".schema.json"
+/// Load a JSON Schema file, or infer the schema of a CSV file with the `schema` options.
+fn load_schema(schema_args: &util::SchemaArgs, path: &str) -> CliResult<Value> {
+    let is_json = Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    if is_json {
+        let schema_json = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read JSON Schema {path}: {e}"))?;
+        return match serde_json::from_str(&schema_json) {
+            Ok(schema) => Ok(schema),
+            Err(e) => fail_clierror!("Cannot parse JSON Schema {path}: {e}"),
         };
-        let mut schema_output_file = File::create(&schema_output_filename)?;
-
-        schema_output_file.write_all(schema_pretty.as_bytes())?;
-        schema_output_file.flush()?;
-
-        woutinfo!("Schema written to {schema_output_filename}");
     }
 
-    Ok(())
+    util::mem_file_check(Path::new(path), false, schema_args.flag_memcheck)?;
+    let mut csv_args = schema_args.clone();
+    csv_args.arg_input = Some(path.to_string());
+    let filename = Path::new(path)
+        .file_name()
+        .map_or_else(|| path.to_string(), |f| f.to_string_lossy().to_string());
+    infer_json_schema(
+        &csv_args,
+        &filename,
+        &format!("Inferred JSON Schema of {filename}"),
+    )
 }
 
 /// Builds JSON MAP object that corresponds to the "properties" object of JSON Schema (Draft
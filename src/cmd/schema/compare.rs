@@ -0,0 +1,458 @@
+//! `schema --compare`: schema evolution & compatibility checking.
+//!
+//! Compares the `properties` of two JSON Schemas column by column and rates each change by
+//! whether data valid under one schema stays valid under the other - "backward" if the old
+//! data is valid under the new schema, "forward" if the new data is valid under the old one.
+
+use std::fmt;
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::{CliResult, config::Config, util};
+
+#[derive(Deserialize)]
+pub struct Args {
+    pub flag_compare:      bool,
+    arg_old:               Option<String>,
+    arg_new:               Option<String>,
+    flag_compat:           String,
+    flag_rename_threshold: f64,
+}
+
+/// The compatibility of a change, or of all the changes between two schemas.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Compatibility {
+    // old & new data are valid under both schemas
+    Full,
+    // old data is valid under the new schema
+    Backward,
+    // new data is valid under the old schema
+    Forward,
+    Breaking,
+}
+
+impl Compatibility {
+    const fn from_directions(backward: bool, forward: bool) -> Self {
+        match (backward, forward) {
+            (true, true) => Self::Full,
+            (true, false) => Self::Backward,
+            (false, true) => Self::Forward,
+            (false, false) => Self::Breaking,
+        }
+    }
+
+    const fn backward(self) -> bool {
+        matches!(self, Self::Full | Self::Backward)
+    }
+
+    const fn forward(self) -> bool {
+        matches!(self, Self::Full | Self::Forward)
+    }
+
+    const fn and(self, other: Self) -> Self {
+        Self::from_directions(
+            self.backward() && other.backward(),
+            self.forward() && other.forward(),
+        )
+    }
+}
+
+impl fmt::Display for Compatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Full => "full",
+            Self::Backward => "backward",
+            Self::Forward => "forward",
+            Self::Breaking => "breaking",
+        })
+    }
+}
+
+struct Change {
+    column:        String,
+    change:        &'static str,
+    detail:        String,
+    old:           String,
+    new:           String,
+    compatibility: Compatibility,
+}
+
+pub fn run(schema_args: &util::SchemaArgs, args: &Args) -> CliResult<()> {
+    // the compatibility the changes must have, or None for --compat none
+    let required = match args.flag_compat.to_ascii_lowercase().as_str() {
+        "backward" => Some(Compatibility::Backward),
+        "forward" => Some(Compatibility::Forward),
+        "full" => Some(Compatibility::Full),
+        "none" => None,
+        _ => {
+            return fail_incorrectusage_clierror!(
+                "Invalid --compat mode: {}. Valid values are backward, forward, full and none.",
+                args.flag_compat
+            );
+        },
+    };
+    if !(0.0..=1.0).contains(&args.flag_rename_threshold) {
+        return fail_incorrectusage_clierror!("--rename-threshold must be between 0 and 1.");
+    }
+
+    // safety: docopt requires <old> & <new> with --compare
+    let old = super::load_schema(schema_args, args.arg_old.as_deref().unwrap())?;
+    let new = super::load_schema(schema_args, args.arg_new.as_deref().unwrap())?;
+    let changes = compare(&old, &new, args.flag_rename_threshold)?;
+
+    let mut wtr = Config::new(schema_args.flag_output.as_ref()).writer()?;
+    wtr.write_record(["column", "change", "detail", "old", "new", "compatibility"])?;
+    let mut overall = Compatibility::Full;
+    for change in &changes {
+        overall = overall.and(change.compatibility);
+        wtr.write_record([
+            change.column.as_str(),
+            change.change,
+            &change.detail,
+            &change.old,
+            &change.new,
+            &change.compatibility.to_string(),
+        ])?;
+    }
+    wtr.flush()?;
+
+    winfo!(
+        "{} schema change(s). Compatibility: {overall}.",
+        changes.len()
+    );
+    if let Some(required) = required {
+        let compatible = (!required.backward() || overall.backward())
+            && (!required.forward() || overall.forward());
+        if !compatible {
+            return fail_clierror!(
+                "Schema changes are not {required} compatible (compatibility: {overall})."
+            );
+        }
+    }
+    Ok(())
+}
+
+fn properties<'a>(schema: &'a Value, which: &str) -> CliResult<&'a Map<String, Value>> {
+    match schema.get("properties").and_then(Value::as_object) {
+        Some(properties) => Ok(properties),
+        None => fail_clierror!("The {which} schema has no \"properties\" object."),
+    }
+}
+
+fn required_fields(schema: &Value) -> Vec<&str> {
+    schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|required| required.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+fn compare(old: &Value, new: &Value, rename_threshold: f64) -> CliResult<Vec<Change>> {
+    let old_props = properties(old, "old")?;
+    let new_props = properties(new, "new")?;
+    let old_required = required_fields(old);
+    let new_required = required_fields(new);
+
+    // columns removed from the old schema & added to the new one, in schema order
+    let removed: Vec<&String> = old_props
+        .keys()
+        .filter(|name| !new_props.contains_key(*name))
+        .collect();
+    let added: Vec<&String> = new_props
+        .keys()
+        .filter(|name| !old_props.contains_key(*name))
+        .collect();
+
+    // a removed & an added column with similar headers & compatible types are a rename.
+    // Candidates are paired greedily, most similar first.
+    let mut candidates: Vec<(f64, usize, usize)> = Vec::new();
+    for (r, old_name) in removed.iter().enumerate() {
+        for (a, new_name) in added.iter().enumerate() {
+            let similarity = header_similarity(old_name, new_name);
+            let old_types = types(&old_props[*old_name]);
+            let new_types = types(&new_props[*new_name]);
+            if similarity >= rename_threshold
+                && (covers(&new_types, &old_types) || covers(&old_types, &new_types))
+            {
+                candidates.push((similarity, r, a));
+            }
+        }
+    }
+    candidates.sort_by(|x, y| y.0.total_cmp(&x.0));
+    let mut renamed_to: Vec<Option<(usize, f64)>> = vec![None; removed.len()];
+    let mut renamed_from = vec![false; added.len()];
+    for (similarity, r, a) in candidates {
+        if renamed_to[r].is_none() && !renamed_from[a] {
+            renamed_to[r] = Some((a, similarity));
+            renamed_from[a] = true;
+        }
+    }
+
+    let mut changes = Vec::new();
+    for (old_name, old_def) in old_props {
+        let was_required = old_required.contains(&old_name.as_str());
+        if let Some(new_def) = new_props.get(old_name) {
+            let is_required = new_required.contains(&old_name.as_str());
+            compare_required(&mut changes, old_name, was_required, is_required);
+            compare_column(&mut changes, old_name, old_def, new_def);
+            continue;
+        }
+
+        // safety: old_name is in removed, as it is not in the new schema
+        let r = removed.iter().position(|name| *name == old_name).unwrap();
+        if let Some((a, similarity)) = renamed_to[r] {
+            let new_name = added[a];
+            let is_required = new_required.contains(&new_name.as_str());
+            // old data lacks the new column & new data lacks the old one
+            changes.push(Change {
+                column:        new_name.clone(),
+                change:        "column_renamed",
+                detail:        format!("header similarity {similarity:.2}"),
+                old:           old_name.clone(),
+                new:           new_name.clone(),
+                compatibility: Compatibility::from_directions(!is_required, !was_required),
+            });
+            compare_column(&mut changes, new_name, old_def, &new_props[new_name]);
+        } else {
+            // old data has the extra column, which the new schema doesn't forbid
+            changes.push(Change {
+                column:        old_name.clone(),
+                change:        "column_removed",
+                detail:        String::new(),
+                old:           type_list(old_def),
+                new:           String::new(),
+                compatibility: Compatibility::from_directions(true, !was_required),
+            });
+        }
+    }
+    for (a, new_name) in added.iter().enumerate() {
+        if renamed_from[a] {
+            continue;
+        }
+        let is_required = new_required.contains(&new_name.as_str());
+        changes.push(Change {
+            column:        (*new_name).clone(),
+            change:        "column_added",
+            detail:        String::new(),
+            old:           String::new(),
+            new:           type_list(&new_props[*new_name]),
+            compatibility: Compatibility::from_directions(!is_required, true),
+        });
+    }
+    Ok(changes)
+}
+
+fn compare_required(changes: &mut Vec<Change>, column: &str, was: bool, is: bool) {
+    if was == is {
+        return;
+    }
+    changes.push(Change {
+        column:        column.to_string(),
+        change:        if is {
+            "required_added"
+        } else {
+            "required_removed"
+        },
+        detail:        String::new(),
+        old:           was.to_string(),
+        new:           is.to_string(),
+        compatibility: Compatibility::from_directions(was, is),
+    });
+}
+
+// constraints whose larger values are tighter, whose smaller values are tighter,
+// and that are only either present or not
+const LOWER_BOUNDS: [&str; 3] = ["minimum", "exclusiveMinimum", "minLength"];
+const UPPER_BOUNDS: [&str; 3] = ["maximum", "exclusiveMaximum", "maxLength"];
+const EXACT_CONSTRAINTS: [&str; 5] = ["pattern", "format", "const", "multipleOf", "dynamicEnum"];
+
+/// Compare the definitions of a column present in both schemas.
+fn compare_column(changes: &mut Vec<Change>, column: &str, old_def: &Value, new_def: &Value) {
+    let mut push = |change: &'static str, detail: String, old: String, new: String, compat| {
+        changes.push(Change {
+            column: column.to_string(),
+            change,
+            detail,
+            old,
+            new,
+            compatibility: compat,
+        });
+    };
+
+    // type - as CSV values are text, every type widens to string, and integer to number
+    let old_types = types(old_def);
+    let new_types = types(new_def);
+    let widened = covers(&new_types, &old_types);
+    let narrowed = covers(&old_types, &new_types);
+    if !(widened && narrowed) {
+        let change = match (widened, narrowed) {
+            (true, _) => "type_widened",
+            (_, true) => "type_narrowed",
+            _ => "type_changed",
+        };
+        push(
+            change,
+            String::new(),
+            type_list(old_def),
+            type_list(new_def),
+            Compatibility::from_directions(widened, narrowed),
+        );
+    }
+
+    // enum
+    match (
+        old_def.get("enum").and_then(Value::as_array),
+        new_def.get("enum").and_then(Value::as_array),
+    ) {
+        (Some(old_enum), Some(new_enum)) => {
+            let added: Vec<&Value> = new_enum.iter().filter(|v| !old_enum.contains(v)).collect();
+            let removed: Vec<&Value> = old_enum.iter().filter(|v| !new_enum.contains(v)).collect();
+            if !added.is_empty() {
+                push(
+                    "enum_values_added",
+                    display_values(&added),
+                    old_enum.len().to_string(),
+                    new_enum.len().to_string(),
+                    Compatibility::Backward,
+                );
+            }
+            if !removed.is_empty() {
+                push(
+                    "enum_values_removed",
+                    display_values(&removed),
+                    old_enum.len().to_string(),
+                    new_enum.len().to_string(),
+                    Compatibility::Forward,
+                );
+            }
+        },
+        (None, Some(new_enum)) => push(
+            "enum_added",
+            display_values(&new_enum.iter().collect::<Vec<_>>()),
+            String::new(),
+            new_enum.len().to_string(),
+            Compatibility::Forward,
+        ),
+        (Some(old_enum), None) => push(
+            "enum_removed",
+            display_values(&old_enum.iter().collect::<Vec<_>>()),
+            old_enum.len().to_string(),
+            String::new(),
+            Compatibility::Backward,
+        ),
+        (None, None) => {},
+    }
+
+    // constraints
+    for keyword in LOWER_BOUNDS
+        .iter()
+        .chain(UPPER_BOUNDS.iter())
+        .chain(EXACT_CONSTRAINTS.iter())
+    {
+        let old_value = old_def.get(*keyword);
+        let new_value = new_def.get(*keyword);
+        // true if tightened, false if loosened, None if changed (or unchanged)
+        let tightened = match (old_value, new_value) {
+            (None, None) => continue,
+            (Some(o), Some(n)) if o == n => continue,
+            (None, Some(_)) => Some(true),
+            (Some(_), None) => Some(false),
+            (Some(o), Some(n)) => match (o.as_f64(), n.as_f64()) {
+                (Some(o), Some(n)) if LOWER_BOUNDS.contains(keyword) => Some(n > o),
+                (Some(o), Some(n)) if UPPER_BOUNDS.contains(keyword) => Some(n < o),
+                _ => None,
+            },
+        };
+        let (change, compatibility) = match tightened {
+            Some(true) => ("constraint_tightened", Compatibility::Forward),
+            Some(false) => ("constraint_loosened", Compatibility::Backward),
+            None => ("constraint_changed", Compatibility::Breaking),
+        };
+        push(
+            change,
+            (*keyword).to_string(),
+            old_value.map(display_value).unwrap_or_default(),
+            new_value.map(display_value).unwrap_or_default(),
+            compatibility,
+        );
+    }
+}
+
+/// The JSON types of a column definition. Empty if unconstrained.
+fn types(def: &Value) -> Vec<&str> {
+    match def.get("type") {
+        Some(Value::String(t)) => vec![t.as_str()],
+        Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn type_list(def: &Value) -> String {
+    let types = types(def);
+    if types.is_empty() {
+        "any".to_string()
+    } else {
+        types.join("|")
+    }
+}
+
+/// Whether every value valid for the `narrow` types is also valid for the `wide` types.
+fn covers(wide: &[&str], narrow: &[&str]) -> bool {
+    if wide.is_empty() {
+        return true;
+    }
+    if narrow.is_empty() {
+        return false;
+    }
+    narrow.iter().all(|t| {
+        wide.contains(t)
+            || (*t == "integer" && wide.contains(&"number"))
+            || (*t != "null" && wide.contains(&"string"))
+    })
+}
+
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        v => v.to_string(),
+    }
+}
+
+fn display_values(values: &[&Value]) -> String {
+    values
+        .iter()
+        .map(|v| display_value(v))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The similarity (0-1) of two headers, ignoring case & non-alphanumeric characters,
+/// as 1 - their normalized Levenshtein distance.
+#[allow(clippy::cast_precision_loss)]
+fn header_similarity(a: &str, b: &str) -> f64 {
+    let normalize = |s: &str| -> Vec<char> {
+        s.chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+    let a = normalize(a);
+    let b = normalize(b);
+    let max_len = a.len().max(b.len());
+    if max_len == 0 {
+        return 1.0;
+    }
+
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        curr[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    1.0 - prev[b.len()] as f64 / max_len as f64
+}
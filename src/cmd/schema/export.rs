@@ -0,0 +1,597 @@
+//! `schema --format`: the inferred schema as SQL DDL, an Avro or Arrow schema, or Pydantic
+//! & TypeScript types.
+//!
+//! All the formats are translated from the same JSON Schema - inferred from the stats cache,
+//! with date & date-time columns - so they agree on the column types, nullability and the
+//! enum & range constraints.
+
+use std::{fmt::Write as _, fs::File, io::Write, path::Path};
+
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+
+use crate::{CliResult, util};
+
+#[derive(Deserialize)]
+pub struct Args {
+    pub flag_format: Option<String>,
+    flag_table:      Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    JsonSchema,
+    Postgres,
+    Sqlite,
+    Duckdb,
+    Avro,
+    Arrow,
+    Pydantic,
+    Typescript,
+}
+
+impl Format {
+    pub fn from_flag(flag: Option<&str>) -> CliResult<Self> {
+        let Some(flag) = flag else {
+            return Ok(Self::JsonSchema);
+        };
+        match flag.to_ascii_lowercase().as_str() {
+            "jsonschema" => Ok(Self::JsonSchema),
+            "postgres" | "postgresql" => Ok(Self::Postgres),
+            "sqlite" => Ok(Self::Sqlite),
+            "duckdb" => Ok(Self::Duckdb),
+            "avro" => Ok(Self::Avro),
+            "arrow" => Ok(Self::Arrow),
+            "pydantic" => Ok(Self::Pydantic),
+            "typescript" | "ts" => Ok(Self::Typescript),
+            _ => fail_incorrectusage_clierror!(
+                "Invalid --format: {flag}. Valid values are jsonschema, postgres, sqlite, duckdb, \
+                 avro, arrow, pydantic and typescript."
+            ),
+        }
+    }
+
+    const fn name(self) -> &'static str {
+        match self {
+            Self::JsonSchema => "jsonschema",
+            Self::Postgres => "postgres",
+            Self::Sqlite => "sqlite",
+            Self::Duckdb => "duckdb",
+            Self::Avro => "avro",
+            Self::Arrow => "arrow",
+            Self::Pydantic => "pydantic",
+            Self::Typescript => "typescript",
+        }
+    }
+
+    /// the suffix appended to the input filename for the default output file
+    const fn suffix(self) -> &'static str {
+        match self {
+            Self::JsonSchema => "schema.json",
+            Self::Postgres => "postgres.sql",
+            Self::Sqlite => "sqlite.sql",
+            Self::Duckdb => "duckdb.sql",
+            Self::Avro => "avsc",
+            Self::Arrow => "arrow.json",
+            Self::Pydantic => "py",
+            Self::Typescript => "ts",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ColumnType {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    Date,
+    DateTime,
+    // a column with only empty values
+    Null,
+}
+
+struct Column {
+    name:        String,
+    col_type:    ColumnType,
+    nullable:    bool,
+    // the non-null values of an enum or const constraint
+    enum_values: Vec<Value>,
+    minimum:     Option<Value>,
+    maximum:     Option<Value>,
+    min_length:  Option<u64>,
+    max_length:  Option<u64>,
+}
+
+pub fn run(
+    schema_args: &util::SchemaArgs,
+    args: &Args,
+    format: Format,
+    input_path: &str,
+) -> CliResult<()> {
+    // the date & date-time formats are needed for the date/timestamp types
+    let mut strict_args = schema_args.clone();
+    strict_args.flag_strict_dates = true;
+    let schema = super::load_schema(&strict_args, input_path)?;
+    let columns = columns(&schema)?;
+
+    let input_filename = Path::new(input_path).file_name().map_or_else(
+        || input_path.to_string(),
+        |f| f.to_string_lossy().to_string(),
+    );
+    let table = args.flag_table.clone().unwrap_or_else(|| {
+        // the file name without its extensions, e.g. data for data.csv.schema.json
+        input_filename
+            .split('.')
+            .next()
+            .filter(|stem| !stem.is_empty())
+            .unwrap_or("data")
+            .to_string()
+    });
+    let generated_by = format!(
+        "Generated with `qsv schema --format {}` from {input_filename}",
+        format.name()
+    );
+
+    let output = match format {
+        Format::Postgres | Format::Sqlite | Format::Duckdb => {
+            sql_ddl(&columns, &table, format, &generated_by)
+        },
+        Format::Avro => json_output(&avro_schema(&columns, &table, &generated_by))?,
+        Format::Arrow => json_output(&arrow_schema(&columns, &generated_by))?,
+        Format::Pydantic => pydantic_model(&columns, &table, &generated_by),
+        Format::Typescript => typescript_interface(&columns, &table, &generated_by),
+        Format::JsonSchema => json_output(&schema)?,
+    };
+
+    if schema_args.flag_stdout {
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(output.as_bytes())?;
+        stdout.flush()?;
+    } else {
+        let output_filename = schema_args
+            .flag_output
+            .clone()
+            .unwrap_or_else(|| format!("{input_path}.{}", format.suffix()));
+        let mut output_file = File::create(&output_filename)?;
+        output_file.write_all(output.as_bytes())?;
+        output_file.flush()?;
+        woutinfo!("Schema written to {output_filename}");
+    }
+    Ok(())
+}
+
+fn json_output(value: &Value) -> CliResult<String> {
+    match serde_json::to_string_pretty(value) {
+        Ok(s) => Ok(s + "\n"),
+        Err(e) => fail_clierror!("Cannot prettify schema json: {e}"),
+    }
+}
+
+/// The columns of a JSON Schema's `properties`.
+fn columns(schema: &Value) -> CliResult<Vec<Column>> {
+    let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+        return fail_clierror!("The schema has no \"properties\" object.");
+    };
+    Ok(properties
+        .iter()
+        .map(|(name, def)| column(name, def.as_object().unwrap_or(&Map::new())))
+        .collect())
+}
+
+fn column(name: &str, def: &Map<String, Value>) -> Column {
+    let types: Vec<&str> = match def.get("type") {
+        Some(Value::String(t)) => vec![t.as_str()],
+        Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    let non_null: Vec<&str> = types.iter().copied().filter(|t| *t != "null").collect();
+    let col_type = match non_null.as_slice() {
+        [] if !types.is_empty() => ColumnType::Null,
+        ["integer"] => ColumnType::Integer,
+        ["number"] => ColumnType::Number,
+        ["boolean"] => ColumnType::Boolean,
+        ["string"] => match def.get("format").and_then(Value::as_str) {
+            Some("date") => ColumnType::Date,
+            Some("date-time") => ColumnType::DateTime,
+            _ => ColumnType::String,
+        },
+        mixed if !mixed.is_empty() && mixed.iter().all(|t| matches!(*t, "integer" | "number")) => {
+            ColumnType::Number
+        },
+        // no type, or a mix of types
+        _ => ColumnType::String,
+    };
+
+    let enum_values = match (def.get("enum"), def.get("const")) {
+        (Some(Value::Array(values)), _) => {
+            values.iter().filter(|v| !v.is_null()).cloned().collect()
+        },
+        (_, Some(value)) if !value.is_null() => vec![value.clone()],
+        _ => Vec::new(),
+    };
+    let numeric = |key: &str| def.get(key).filter(|v| v.is_number()).cloned();
+
+    Column {
+        name: name.to_string(),
+        col_type,
+        nullable: types.contains(&"null") || types.is_empty(),
+        enum_values,
+        minimum: numeric("minimum"),
+        maximum: numeric("maximum"),
+        min_length: def.get("minLength").and_then(Value::as_u64),
+        max_length: def.get("maxLength").and_then(Value::as_u64),
+    }
+}
+
+/// A `CREATE TABLE` statement with NOT NULL & CHECK constraints.
+fn sql_ddl(columns: &[Column], table: &str, dialect: Format, generated_by: &str) -> String {
+    let mut ddl = format!(
+        "-- {generated_by}\nCREATE TABLE {} (\n",
+        sql_identifier(table)
+    );
+    let length_fn = if dialect == Format::Postgres {
+        "char_length"
+    } else {
+        "length"
+    };
+
+    for (i, col) in columns.iter().enumerate() {
+        let name = sql_identifier(&col.name);
+        let sql_type = match (dialect, col.col_type) {
+            (Format::Postgres | Format::Duckdb, ColumnType::Integer) => "BIGINT",
+            (Format::Postgres, ColumnType::Number) => "DOUBLE PRECISION",
+            (Format::Duckdb, ColumnType::Number) => "DOUBLE",
+            (Format::Postgres | Format::Duckdb, ColumnType::Boolean) => "BOOLEAN",
+            (Format::Postgres | Format::Duckdb, ColumnType::Date) => "DATE",
+            (Format::Postgres, ColumnType::DateTime) => "TIMESTAMPTZ",
+            (Format::Duckdb, ColumnType::DateTime) => "TIMESTAMP",
+            (Format::Duckdb, _) => "VARCHAR",
+            // SQLite stores booleans as 0/1 & dates as ISO 8601 text
+            (Format::Sqlite, ColumnType::Integer | ColumnType::Boolean) => "INTEGER",
+            (Format::Sqlite, ColumnType::Number) => "REAL",
+            _ => "TEXT",
+        };
+        let _ = write!(ddl, "    {name} {sql_type}");
+        if !col.nullable {
+            ddl.push_str(" NOT NULL");
+        }
+
+        if !col.enum_values.is_empty() {
+            let values = col
+                .enum_values
+                .iter()
+                .map(sql_literal)
+                .collect::<Vec<_>>()
+                .join(", ");
+            let _ = write!(ddl, " CHECK ({name} IN ({values}))");
+        } else if let Some(check) = range_check(
+            &name,
+            col.minimum.as_ref().map(Value::to_string),
+            col.maximum.as_ref().map(Value::to_string),
+        ) {
+            let _ = write!(ddl, " CHECK ({check})");
+        } else if let Some(check) = range_check(
+            &format!("{length_fn}({name})"),
+            col.min_length.map(|l| l.to_string()),
+            col.max_length.map(|l| l.to_string()),
+        ) {
+            let _ = write!(ddl, " CHECK ({check})");
+        }
+        ddl.push_str(if i + 1 < columns.len() { ",\n" } else { "\n" });
+    }
+    ddl.push_str(");\n");
+    ddl
+}
+
+fn range_check(expr: &str, min: Option<String>, max: Option<String>) -> Option<String> {
+    match (min, max) {
+        (Some(min), Some(max)) => Some(format!("{expr} BETWEEN {min} AND {max}")),
+        (Some(min), None) => Some(format!("{expr} >= {min}")),
+        (None, Some(max)) => Some(format!("{expr} <= {max}")),
+        (None, None) => None,
+    }
+}
+
+fn sql_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn sql_literal(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("'{}'", s.replace('\'', "''")),
+        other => other.to_string(),
+    }
+}
+
+/// An Avro record schema, with nullable columns as a union with null.
+fn avro_schema(columns: &[Column], table: &str, generated_by: &str) -> Value {
+    let names = unique_identifiers(columns, avro_name);
+    let fields: Vec<Value> = columns
+        .iter()
+        .zip(&names)
+        .map(|(col, name)| {
+            let symbols: Vec<&str> = col.enum_values.iter().filter_map(Value::as_str).collect();
+            let avro_type = match col.col_type {
+                ColumnType::String
+                    if !symbols.is_empty()
+                        && symbols.len() == col.enum_values.len()
+                        && symbols.iter().all(|s| avro_name(s) == *s) =>
+                {
+                    json!({"type": "enum", "name": format!("{name}_enum"), "symbols": symbols})
+                },
+                ColumnType::String => json!("string"),
+                ColumnType::Integer => json!("long"),
+                ColumnType::Number => json!("double"),
+                ColumnType::Boolean => json!("boolean"),
+                ColumnType::Date => json!({"type": "int", "logicalType": "date"}),
+                ColumnType::DateTime => json!({"type": "long", "logicalType": "timestamp-micros"}),
+                ColumnType::Null => json!("null"),
+            };
+            let mut field = Map::new();
+            field.insert("name".to_string(), json!(name));
+            if *name != col.name {
+                field.insert("doc".to_string(), json!(col.name));
+            }
+            if col.nullable && col.col_type != ColumnType::Null {
+                field.insert("type".to_string(), json!(["null", avro_type]));
+                field.insert("default".to_string(), Value::Null);
+            } else {
+                field.insert("type".to_string(), avro_type);
+            }
+            Value::Object(field)
+        })
+        .collect();
+
+    json!({
+        "type": "record",
+        "name": avro_name(table),
+        "doc": generated_by,
+        "fields": fields,
+    })
+}
+
+/// An Avro name: `[A-Za-z_][A-Za-z0-9_]*`
+fn avro_name(name: &str) -> String {
+    let mut avro: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !avro.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        avro.insert(0, '_');
+    }
+    avro
+}
+
+/// An Arrow schema in the Arrow JSON format.
+fn arrow_schema(columns: &[Column], generated_by: &str) -> Value {
+    let fields: Vec<Value> = columns
+        .iter()
+        .map(|col| {
+            let arrow_type = match col.col_type {
+                ColumnType::String => json!({"name": "utf8"}),
+                ColumnType::Integer => json!({"name": "int", "bitWidth": 64, "isSigned": true}),
+                ColumnType::Number => json!({"name": "floatingpoint", "precision": "DOUBLE"}),
+                ColumnType::Boolean => json!({"name": "bool"}),
+                ColumnType::Date => json!({"name": "date", "unit": "DAY"}),
+                ColumnType::DateTime => {
+                    json!({"name": "timestamp", "unit": "MICROSECOND", "timezone": "UTC"})
+                },
+                ColumnType::Null => json!({"name": "null"}),
+            };
+            json!({
+                "name": col.name,
+                "nullable": col.nullable,
+                "type": arrow_type,
+                "children": [],
+            })
+        })
+        .collect();
+
+    json!({
+        "fields": fields,
+        "metadata": [{"key": "description", "value": generated_by}],
+    })
+}
+
+const PYTHON_KEYWORDS: [&str; 35] = [
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+/// A Pydantic model, with the original column names as field aliases where needed.
+fn pydantic_model(columns: &[Column], table: &str, generated_by: &str) -> String {
+    let names = unique_identifiers(columns, python_identifier);
+    let mut uses_datetime = false;
+    let mut uses_literal = false;
+    let mut uses_optional = false;
+    let mut uses_field = false;
+
+    let mut fields = String::new();
+    for (col, name) in columns.iter().zip(&names) {
+        let mut annotation = if col.enum_values.is_empty() {
+            match col.col_type {
+                ColumnType::String => "str",
+                ColumnType::Integer => "int",
+                ColumnType::Number => "float",
+                ColumnType::Boolean => "bool",
+                ColumnType::Date => "datetime.date",
+                ColumnType::DateTime => "datetime.datetime",
+                ColumnType::Null => "None",
+            }
+            .to_string()
+        } else {
+            uses_literal = true;
+            let values = col
+                .enum_values
+                .iter()
+                .map(Value::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("Literal[{values}]")
+        };
+        uses_datetime |= matches!(col.col_type, ColumnType::Date | ColumnType::DateTime);
+        if col.nullable && col.col_type != ColumnType::Null {
+            uses_optional = true;
+            annotation = format!("Optional[{annotation}]");
+        }
+
+        let mut field_args: Vec<String> = Vec::new();
+        if col.nullable {
+            field_args.push("default=None".to_string());
+        }
+        if *name != col.name {
+            field_args.push(format!("alias={}", Value::String(col.name.clone())));
+        }
+        if col.enum_values.is_empty() {
+            if let Some(ref minimum) = col.minimum {
+                field_args.push(format!("ge={minimum}"));
+            }
+            if let Some(ref maximum) = col.maximum {
+                field_args.push(format!("le={maximum}"));
+            }
+            if let Some(min_length) = col.min_length {
+                field_args.push(format!("min_length={min_length}"));
+            }
+            if let Some(max_length) = col.max_length {
+                field_args.push(format!("max_length={max_length}"));
+            }
+        }
+
+        let _ = write!(fields, "    {name}: {annotation}");
+        match field_args.as_slice() {
+            [] => {},
+            [default] if default == "default=None" => fields.push_str(" = None"),
+            _ => {
+                uses_field = true;
+                let _ = write!(fields, " = Field({})", field_args.join(", "));
+            },
+        }
+        fields.push('\n');
+    }
+    if columns.is_empty() {
+        fields.push_str("    pass\n");
+    }
+
+    let mut model = format!("# {generated_by}\n\n");
+    if uses_datetime {
+        model.push_str("import datetime\n");
+    }
+    let typing: Vec<&str> = [("Literal", uses_literal), ("Optional", uses_optional)]
+        .into_iter()
+        .filter_map(|(name, used)| used.then_some(name))
+        .collect();
+    if !typing.is_empty() {
+        let _ = writeln!(model, "from typing import {}", typing.join(", "));
+    }
+    if uses_datetime || !typing.is_empty() {
+        model.push('\n');
+    }
+    let _ = writeln!(
+        model,
+        "from pydantic import BaseModel{}\n\n",
+        if uses_field { ", Field" } else { "" }
+    );
+    let _ = writeln!(model, "class {}(BaseModel):", type_name(table));
+    model.push_str(&fields);
+    model
+}
+
+/// A Python identifier that Pydantic doesn't treat as private, i.e. no leading underscore.
+fn python_identifier(name: &str) -> String {
+    let ident: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let mut ident = ident.trim_start_matches('_').to_string();
+    if ident.is_empty() {
+        ident = "field".to_string();
+    } else if ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert_str(0, "field_");
+    }
+    if PYTHON_KEYWORDS.contains(&ident.as_str()) {
+        ident.push('_');
+    }
+    ident
+}
+
+/// A TypeScript interface of the records, as read by `qsv tojsonl`.
+fn typescript_interface(columns: &[Column], table: &str, generated_by: &str) -> String {
+    let mut interface = format!(
+        "// {generated_by}\n\nexport interface {} {{\n",
+        type_name(table)
+    );
+    for col in columns {
+        let mut ts_type = if col.enum_values.is_empty() {
+            match col.col_type {
+                // dates are ISO 8601 strings
+                ColumnType::String | ColumnType::Date | ColumnType::DateTime => "string",
+                ColumnType::Integer | ColumnType::Number => "number",
+                ColumnType::Boolean => "boolean",
+                ColumnType::Null => "null",
+            }
+            .to_string()
+        } else {
+            col.enum_values
+                .iter()
+                .map(Value::to_string)
+                .collect::<Vec<_>>()
+                .join(" | ")
+        };
+        if col.nullable && col.col_type != ColumnType::Null {
+            ts_type.push_str(" | null");
+        }
+
+        let is_identifier = col
+            .name
+            .starts_with(|c: char| c.is_ascii_alphabetic() || c == '_' || c == '$')
+            && col
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+        let key = if is_identifier {
+            col.name.clone()
+        } else {
+            Value::String(col.name.clone()).to_string()
+        };
+        let _ = writeln!(interface, "  {key}: {ts_type};");
+    }
+    interface.push_str("}\n");
+    interface
+}
+
+/// A PascalCase type name, e.g. `SupplierData` for `supplier_data`.
+fn type_name(table: &str) -> String {
+    let mut name: String = table
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            // safety: parts are not empty
+            let first = chars.next().unwrap().to_ascii_uppercase();
+            std::iter::once(first).chain(chars).collect::<String>()
+        })
+        .collect();
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        name.insert_str(0, "Model");
+    }
+    name
+}
+
+/// The column names as identifiers, de-duplicated with a numeric suffix.
+fn unique_identifiers(columns: &[Column], to_identifier: fn(&str) -> String) -> Vec<String> {
+    let mut identifiers: Vec<String> = Vec::with_capacity(columns.len());
+    for col in columns {
+        let ident = to_identifier(&col.name);
+        let mut unique = ident.clone();
+        let mut n = 2;
+        while identifiers.contains(&unique) {
+            unique = format!("{ident}_{n}");
+            n += 1;
+        }
+        identifiers.push(unique);
+    }
+    identifiers
+}
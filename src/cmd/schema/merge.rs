@@ -0,0 +1,225 @@
+//! `schema --merge`: a JSON Schema that accepts all of several sample files.
+//!
+//! Each input is a JSON Schema file, or a CSV file whose schema is inferred. The column
+//! definitions are merged by widening them: types are unioned, enums are unioned up to
+//! --enum-threshold, ranges & lengths are widened, and other constraints are only kept
+//! if all the inputs agree on them.
+
+use std::{fs::File, io::Write};
+
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+
+use crate::{CliResult, util};
+
+#[derive(Deserialize)]
+pub struct Args {
+    pub flag_merge: bool,
+    arg_inputs:     Vec<String>,
+}
+
+// keywords widened to the smaller & to the larger value of the inputs
+const LOWER_BOUNDS: [&str; 3] = ["minimum", "exclusiveMinimum", "minLength"];
+const UPPER_BOUNDS: [&str; 3] = ["maximum", "exclusiveMaximum", "maxLength"];
+
+pub fn run(schema_args: &util::SchemaArgs, args: &Args) -> CliResult<()> {
+    if args.arg_inputs.len() < 2 {
+        return fail_incorrectusage_clierror!("--merge requires at least two inputs.");
+    }
+    let enum_threshold = usize::try_from(schema_args.flag_enum_threshold).unwrap_or(usize::MAX);
+
+    let mut merged: Option<(Map<String, Value>, Vec<String>)> = None;
+    for input in &args.arg_inputs {
+        let schema = super::load_schema(schema_args, input)?;
+        let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+            return fail_clierror!("{input} has no \"properties\" object.");
+        };
+        let required: Vec<String> = schema
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|required| {
+                required
+                    .iter()
+                    .filter_map(|r| r.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        merged = Some(match merged {
+            None => (properties.clone(), required),
+            Some((mut merged_properties, merged_required)) => {
+                // columns missing from any input are optional
+                let merged_required = merged_required
+                    .into_iter()
+                    .filter(|name| required.contains(name) && properties.contains_key(name))
+                    .collect();
+                for (name, def) in properties {
+                    match merged_properties.get_mut(name) {
+                        Some(merged_def) => {
+                            *merged_def = merge_definitions(merged_def, def, enum_threshold);
+                        },
+                        None => {
+                            merged_properties.insert(name.clone(), def.clone());
+                        },
+                    }
+                }
+                (merged_properties, merged_required)
+            },
+        });
+    }
+    // safety: there are at least two inputs
+    let (properties, required) = merged.unwrap();
+
+    let schema = json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": format!("JSON Schema merged from {} inputs", args.arg_inputs.len()),
+        "$comment": format!(
+            "Merged with `qsv schema --merge` from: {}",
+            args.arg_inputs.join(", ")
+        ),
+        "type": "object",
+        "properties": Value::Object(properties),
+        "required": required,
+    });
+    let schema_pretty = match serde_json::to_string_pretty(&schema) {
+        Ok(s) => s,
+        Err(e) => return fail_clierror!("Cannot prettify schema json: {e}"),
+    };
+
+    if let Some(ref output) = schema_args.flag_output {
+        let mut schema_output_file = File::create(output)?;
+        schema_output_file.write_all(schema_pretty.as_bytes())?;
+        schema_output_file.flush()?;
+        woutinfo!(
+            "Schema merged from {} inputs written to {output}",
+            args.arg_inputs.len()
+        );
+    } else {
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(schema_pretty.as_bytes())?;
+        stdout.flush()?;
+    }
+    Ok(())
+}
+
+/// Merge two column definitions into one that accepts the values of both.
+fn merge_definitions(a: &Value, b: &Value, enum_threshold: usize) -> Value {
+    let (Some(a), Some(b)) = (a.as_object(), b.as_object()) else {
+        // a boolean schema, e.g. `true`
+        return if a == b { a.clone() } else { Value::Bool(true) };
+    };
+    let mut merged = Map::with_capacity(a.len());
+
+    let types = merge_types(&types(a), &types(b));
+    if !types.is_empty() {
+        merged.insert(
+            "type".to_string(),
+            if types.len() == 1 {
+                Value::String(types[0].to_string())
+            } else {
+                Value::from(types.clone())
+            },
+        );
+    }
+
+    // enum & const are both a list of allowed values - an unconstrained input unconstrains
+    if let (Some(a_values), Some(b_values)) = (allowed_values(a), allowed_values(b)) {
+        // e.g. an integer column widened to string only accepts string values
+        let stringify = types.first() == Some(&"string");
+        let mut values: Vec<Value> = Vec::with_capacity(a_values.len() + b_values.len());
+        for mut value in a_values.into_iter().chain(b_values) {
+            if stringify && let Value::Number(n) = value {
+                value = Value::String(n.to_string());
+            }
+            if !values.contains(&value) {
+                values.push(value);
+            }
+        }
+        if values.len() == 1 {
+            merged.insert("const".to_string(), values.swap_remove(0));
+        } else if values.len() <= enum_threshold {
+            merged.insert("enum".to_string(), Value::Array(values));
+        }
+    }
+
+    for (key, a_value) in a {
+        if matches!(key.as_str(), "type" | "enum" | "const") {
+            continue;
+        }
+        let Some(b_value) = b.get(key) else {
+            // the description is informative, the other keywords constrain
+            if key == "description" {
+                merged.insert(key.clone(), a_value.clone());
+            }
+            continue;
+        };
+        let value = if LOWER_BOUNDS.contains(&key.as_str()) {
+            numeric_bound(a_value, b_value, true)
+        } else if UPPER_BOUNDS.contains(&key.as_str()) {
+            numeric_bound(a_value, b_value, false)
+        } else if key == "description" || a_value == b_value {
+            Some(a_value.clone())
+        } else {
+            // e.g. different patterns or formats
+            None
+        };
+        if let Some(value) = value {
+            merged.insert(key.clone(), value);
+        }
+    }
+    Value::Object(merged)
+}
+
+fn types(def: &Map<String, Value>) -> Vec<&str> {
+    match def.get("type") {
+        Some(Value::String(t)) => vec![t.as_str()],
+        Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Union two type lists, widening integer to number and anything but null to string,
+/// as CSV values are text. Empty if either is unconstrained.
+fn merge_types<'a>(a: &[&'a str], b: &[&'a str]) -> Vec<&'a str> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut types: Vec<&str> = a.to_vec();
+    for t in b {
+        if !types.contains(t) {
+            types.push(t);
+        }
+    }
+    let nullable = types.contains(&"null");
+    types.retain(|t| *t != "null");
+    if types.len() > 1 {
+        if types.iter().all(|t| matches!(*t, "integer" | "number")) {
+            types = vec!["number"];
+        } else {
+            types = vec!["string"];
+        }
+    }
+    if nullable {
+        types.push("null");
+    }
+    types
+}
+
+fn allowed_values(def: &Map<String, Value>) -> Option<Vec<Value>> {
+    if let Some(Value::Array(values)) = def.get("enum") {
+        return Some(values.clone());
+    }
+    def.get("const").map(|value| vec![value.clone()])
+}
+
+/// The looser of two numeric bounds - the smaller lower bound or the larger upper bound.
+fn numeric_bound(a: &Value, b: &Value, lower: bool) -> Option<Value> {
+    let (a_num, b_num) = (a.as_f64()?, b.as_f64()?);
+    // keep the original JSON number, so integer bounds stay integers
+    let pick_a = if lower {
+        a_num <= b_num
+    } else {
+        a_num >= b_num
+    };
+    Some(if pick_a { a.clone() } else { b.clone() })
+}
@@ -106,6 +106,9 @@ sniff options:
                              It supports the following variables - $QSV_VERSION, $QSV_TARGET,
                              $QSV_BIN_NAME, $QSV_KIND and $QSV_COMMAND. Try to follow the syntax here -
                              https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/User-Agent
+    --auth <profile>         Authenticate the request when sniffing a URL with the named auth
+                             profile. See "USING AUTH PROFILES" in `qsv fetch --help`.
+                             Not available in qsvlite.
     --stats-types            Use the same data type names as `stats`.
                              (Unsigned, Signed => Integer, Text => String, everything else the same)
     --no-infer               Do not infer the schema. Only return the file's mime type, size and
@@ -266,6 +269,7 @@ struct Args {
     flag_progressbar:    bool,
     flag_timeout:        u16,
     flag_user_agent:     Option<String>,
+    flag_auth:           Option<String>,
     flag_stats_types:    bool,
     flag_no_infer:       bool,
     flag_just_mime:      bool,
@@ -468,13 +472,25 @@ async fn get_file_to_sniff(args: &Args, tmpdir: &tempfile::TempDir) -> CliResult
                     Some(url.clone()),
                 )?;
 
-                let res = client
-                    .get(url.clone())
-                    .timeout(Duration::from_secs(
-                        util::timeout_secs(args.flag_timeout).unwrap_or(30),
-                    ))
-                    .send()
-                    .await?;
+                let req = client.get(url.clone()).timeout(Duration::from_secs(
+                    util::timeout_secs(args.flag_timeout).unwrap_or(30),
+                ));
+                #[cfg(not(feature = "lite"))]
+                let req = {
+                    use crate::auth::{self, Authorize};
+
+                    let profile = args
+                        .flag_auth
+                        .as_deref()
+                        .map(auth::load_profile)
+                        .transpose()?;
+                    req.auth(profile.as_ref())?
+                };
+                #[cfg(feature = "lite")]
+                if args.flag_auth.is_some() {
+                    return fail_incorrectusage_clierror!("--auth is not supported by qsvlite.");
+                }
+                let res = req.send().await?;
 
                 // Check Content-Type header to detect HTML responses early
                 // This helps catch cases where URLs serve HTML pages instead of raw files
@@ -1409,6 +1425,7 @@ pub(crate) fn date_columns(input_path: &std::path::Path) -> CliResult<Vec<String
         flag_progressbar:    false,
         flag_timeout:        30,
         flag_user_agent:     None,
+        flag_auth:           None,
         flag_stats_types:    true,
         flag_no_infer:       false,
         flag_just_mime:      false,
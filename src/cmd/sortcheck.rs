@@ -126,9 +126,10 @@ struct SortCheckStruct {
 }
 
 // Mirrors `SortMode` in `cmd/sort.rs` so sortcheck verifies the same ordering
-// the user would get from `sort` / `dedup`.
-#[derive(Clone, Copy)]
-enum ComparisonMode {
+// the user would get from `sort` / `dedup`. Also used by `merge` to verify its
+// inputs are sorted, and by `top` to rank records.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum ComparisonMode {
     Lex,
     LexIgnoreCase,
     Numeric,
@@ -136,25 +137,48 @@ enum ComparisonMode {
     NaturalIgnoreCase,
 }
 
+impl ComparisonMode {
+    /// Resolution order matches `sort` and `dedup`: --natural beats --numeric
+    /// beats --ignore-case.
+    pub(crate) const fn from_flags(natural: bool, numeric: bool, ignore_case: bool) -> Self {
+        if natural {
+            if ignore_case {
+                ComparisonMode::NaturalIgnoreCase
+            } else {
+                ComparisonMode::Natural
+            }
+        } else if numeric {
+            ComparisonMode::Numeric
+        } else if ignore_case {
+            ComparisonMode::LexIgnoreCase
+        } else {
+            ComparisonMode::Lex
+        }
+    }
+
+    #[inline]
+    pub(crate) fn compare<'a, L, R>(self, a: L, b: R) -> Ordering
+    where
+        L: Iterator<Item = &'a [u8]>,
+        R: Iterator<Item = &'a [u8]>,
+    {
+        match self {
+            ComparisonMode::Lex => iter_cmp(a, b),
+            ComparisonMode::LexIgnoreCase => iter_cmp_ignore_case(a, b),
+            ComparisonMode::Numeric => iter_cmp_num(a, b),
+            ComparisonMode::Natural => iter_cmp_natural(a, b),
+            ComparisonMode::NaturalIgnoreCase => iter_cmp_natural_ignore_case(a, b),
+        }
+    }
+}
+
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
 
-    // Resolution order matches `sort` and `dedup`: --natural beats --numeric
-    // beats --ignore-case. Done once before the loop so the dispatch `match`
+    // Done once before the loop so the dispatch `match` in `compare`
     // monomorphizes to a single comparator per row.
-    let compare_mode = if args.flag_natural {
-        if args.flag_ignore_case {
-            ComparisonMode::NaturalIgnoreCase
-        } else {
-            ComparisonMode::Natural
-        }
-    } else if args.flag_numeric {
-        ComparisonMode::Numeric
-    } else if args.flag_ignore_case {
-        ComparisonMode::LexIgnoreCase
-    } else {
-        ComparisonMode::Lex
-    };
+    let compare_mode =
+        ComparisonMode::from_flags(args.flag_natural, args.flag_numeric, args.flag_ignore_case);
 
     let rconfig = Config::new(args.arg_input.as_ref())
         .delimiter(args.flag_delimiter)
@@ -235,15 +259,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             if !more_records {
                 break;
             }
-            let a = sel.select(&record);
-            let b = sel.select(&next_record);
-            let comparison = match compare_mode {
-                ComparisonMode::Lex => iter_cmp(a, b),
-                ComparisonMode::LexIgnoreCase => iter_cmp_ignore_case(a, b),
-                ComparisonMode::Numeric => iter_cmp_num(a, b),
-                ComparisonMode::Natural => iter_cmp_natural(a, b),
-                ComparisonMode::NaturalIgnoreCase => iter_cmp_natural_ignore_case(a, b),
-            };
+            let comparison = compare_mode.compare(sel.select(&record), sel.select(&next_record));
 
             match comparison {
                 Ordering::Equal => {
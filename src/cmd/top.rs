@@ -0,0 +1,235 @@
+static USAGE: &str = r#"
+Returns the N largest (or smallest) records of a CSV, ranked by the selected columns.
+
+Unlike `sort`, which loads the entire CSV into memory, and `extsort`, which sorts
+the whole file, top streams the input and only keeps the current N best records
+in memory - making it well suited for leaderboards over arbitrarily large files.
+
+The records are returned in rank order - largest first, or smallest first with
+--smallest. Ties are broken by input order, i.e. the record that came first wins.
+
+The comparison modes match `sort`, `sortcheck` and `merge`: --natural takes
+precedence over --numeric, which takes precedence over --ignore-case.
+With --date, the selected columns are parsed as dates (see `qsv datefmt --help`
+for the recognized formats). Values that are not dates rank below all dates,
+whether or not --smallest is set.
+
+Examples:
+
+  # Get the 10 records with the highest sales
+  qsv top 10 --select sales --numeric data.csv
+
+  # Get the 5 most recent records
+  qsv top 5 --select updated_at --date data.csv
+
+  # Get the 3 cheapest products per the price & weight columns
+  qsv top 3 -s price,weight -N --smallest products.csv
+
+  # Get the 20 latest versions by version string (e.g. v1.9 < v1.10)
+  qsv top 20 -s version --natural releases.csv
+
+For more examples, see https://github.com/dathere/qsv/blob/master/tests/test_top.rs.
+
+Usage:
+    qsv top [options] <n> [<input>]
+    qsv top --help
+
+top arguments:
+    <n>                     The number of records to return.
+    <input>                 The CSV file to read. If not given, reads from stdin.
+
+top options:
+    -s, --select <arg>      Select the columns to rank records by.
+                            See 'qsv select --help' for the format details.
+                            (default: all columns)
+    -N, --numeric           Compare according to string numerical value.
+    --natural               Compare strings using natural sort order
+                            (e.g. "item2" < "item10").
+    --date                  Compare values as dates. Honors the QSV_PREFER_DMY
+                            environment variable when parsing ambiguous dates.
+    -i, --ignore-case       Compare strings disregarding case.
+    --smallest              Return the N smallest records instead of the largest.
+
+Common options:
+    -h, --help              Display this message
+    -o, --output <file>     Write output to <file> instead of stdout.
+    -n, --no-headers        When set, the first row will not be interpreted
+                            as headers. Namely, it will be ranked with the rest
+                            of the rows. Otherwise, the first row will always
+                            appear as the header row in the output.
+    -d, --delimiter <arg>   The field delimiter for reading CSV data.
+                            Must be a single character. (default: ,)
+"#;
+
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+};
+
+use csv::ByteRecord;
+use serde::Deserialize;
+
+use crate::{
+    CliResult,
+    cmd::sortcheck::ComparisonMode,
+    config::{Config, Delimiter},
+    select::SelectColumns,
+    util,
+    util::ByteString,
+};
+
+#[derive(Deserialize)]
+struct Args {
+    arg_n:            usize,
+    arg_input:        Option<String>,
+    flag_select:      SelectColumns,
+    flag_numeric:     bool,
+    flag_natural:     bool,
+    flag_date:        bool,
+    flag_ignore_case: bool,
+    flag_smallest:    bool,
+    flag_output:      Option<String>,
+    flag_no_headers:  bool,
+    flag_delimiter:   Option<Delimiter>,
+}
+
+/// A candidate record with its ranking key.
+/// Entries are ordered so that the "better" entry is greater, whichever the
+/// direction, so the heap only has to keep the N greatest entries.
+struct Entry {
+    key:      Vec<ByteString>,
+    seq:      u64,
+    record:   ByteRecord,
+    mode:     ComparisonMode,
+    dates:    bool,
+    smallest: bool,
+}
+
+impl Entry {
+    /// Compare date keys column by column. A value that is not a date has an empty key
+    /// and ranks last in either direction.
+    fn compare_dates(&self, other: &Self) -> Ordering {
+        for (a, b) in self.key.iter().zip(&other.key) {
+            let ord = match (a.is_empty(), b.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Less,
+                (false, true) => Ordering::Greater,
+                (false, false) if self.smallest => b.cmp(a),
+                (false, false) => a.cmp(b),
+            };
+            if ord != Ordering::Equal {
+                return ord;
+            }
+        }
+        Ordering::Equal
+    }
+}
+
+impl Ord for Entry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let ord = if self.dates {
+            self.compare_dates(other)
+        } else {
+            let ord = self.mode.compare(
+                self.key.iter().map(Vec::as_slice),
+                other.key.iter().map(Vec::as_slice),
+            );
+            if self.smallest { ord.reverse() } else { ord }
+        };
+        // on ties, the earlier record is better
+        ord.then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Entry {}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    let rconfig = Config::new(args.arg_input.as_ref())
+        .delimiter(args.flag_delimiter)
+        .no_headers_flag(args.flag_no_headers)
+        .select(args.flag_select);
+
+    let mut rdr = rconfig.reader()?;
+    let headers = rdr.byte_headers()?.clone();
+    let sel = rconfig.selection(&headers)?;
+
+    // dates are normalized to sortable ISO 8601 strings and compared lexicographically
+    let mode = if args.flag_date {
+        ComparisonMode::Lex
+    } else {
+        ComparisonMode::from_flags(args.flag_natural, args.flag_numeric, args.flag_ignore_case)
+    };
+    let prefer_dmy = util::get_envvar_flag("QSV_PREFER_DMY");
+    let get_key = |record: &ByteRecord| -> Vec<ByteString> {
+        if args.flag_date {
+            sel.select(record)
+                .map(|field| date_key(field, prefer_dmy))
+                .collect()
+        } else {
+            sel.select(record).map(<[u8]>::to_vec).collect()
+        }
+    };
+
+    // a min-heap of the N best entries seen so far, with the worst one on top
+    let mut heap: BinaryHeap<Reverse<Entry>> = BinaryHeap::with_capacity(args.arg_n + 1);
+    let mut record = ByteRecord::new();
+    let mut seq = 0_u64;
+    if args.arg_n > 0 {
+        while rdr.read_byte_record(&mut record)? {
+            let entry = Entry {
+                key: get_key(&record),
+                seq,
+                record: ByteRecord::new(),
+                mode,
+                dates: args.flag_date,
+                smallest: args.flag_smallest,
+            };
+            seq += 1;
+            if heap.len() == args.arg_n {
+                // safety: the heap is full, so it has a worst entry
+                if entry <= heap.peek().unwrap().0 {
+                    continue;
+                }
+                heap.pop();
+            }
+            heap.push(Reverse(Entry {
+                record: record.clone(),
+                ..entry
+            }));
+        }
+    }
+
+    let mut wtr = Config::new(args.flag_output.as_ref()).writer()?;
+    rconfig.write_headers(&mut rdr, &mut wtr)?;
+    // sorting the Reverse entries ascending puts the best entry first
+    for Reverse(entry) in heap.into_sorted_vec() {
+        wtr.write_byte_record(&entry.record)?;
+    }
+    Ok(wtr.flush()?)
+}
+
+/// Normalize a date to a lexicographically sortable ISO 8601 string.
+/// Values that are not dates map to an empty key, which ranks below all dates.
+fn date_key(field: &[u8], prefer_dmy: bool) -> ByteString {
+    let Ok(s) = simdutf8::basic::from_utf8(field) else {
+        return Vec::new();
+    };
+    match qsv_dateparser::parse_with_preference(s.trim(), prefer_dmy) {
+        Ok(dt) => dt.format("%Y-%m-%dT%H:%M:%S%.9f").to_string().into_bytes(),
+        Err(_) => Vec::new(),
+    }
+}
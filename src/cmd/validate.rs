@@ -18,6 +18,18 @@ A "validation-errors.tsv" report is also created with the following columns:
   * field: the field name of the invalid field
   * error: a validation error message detailing why the field is invalid
 
+For dashboards & CI, --report-format also writes an aggregated JSON or JUnit XML summary.
+
+Common data entry problems can be repaired with deterministic fixes. Fields that fail
+validation are trimmed of stray whitespace, integers & numbers are stripped of thousands
+separators, enum values are matched case-insensitively & `format: date`/`date-time`
+values are reformatted to ISO 8601. The repaired row is then re-validated.
+--suggest-fixes writes the fixes per error class - the field & fix, the number of rows,
+an example, and the `apply`/`replace`/`datefmt` command to apply it to the whole file -
+to "<input>.fix-suggestions.tsv". --auto-fix applies the fixes directly: repaired rows
+that pass re-validation are written to the "valid" file, and each fix is logged to
+"<input>.fixes.tsv" with the columns row_number, field, fix, old_value & new_value.
+
 It uses the JSON Schema Validation Specification (draft 2020-12) to validate the CSV.
 It validates the structure of the file, as well as the data types and domain/range of the fields.
 See https://json-schema.org/draft/2020-12/json-schema-validation.html
@@ -140,6 +152,71 @@ If piped from stdin, the filenames will use `stdin.csv` as the base filename. Fo
    * stdin.csv.invalid
    * stdin.csv.validation-errors.tsv
 
+TABLE SCHEMA & CSVW DESCRIPTORS:
+-------------------------------
+Instead of a JSON Schema, a Frictionless Table Schema (https://specs.frictionlessdata.io/table-schema/)
+or CSVW metadata (https://www.w3.org/TR/tabular-metadata/) JSON file can be used. The descriptor
+kind is detected from its contents: a "fields" array for Table Schema, a "tableSchema" or "tables"
+key for CSVW. It is translated to an equivalent JSON Schema and validated in JSON Schema mode,
+with the errors reported in the same "validation-errors.tsv" file.
+
+Supported are field types & formats (including strftime (Table Schema) and UTS #35 (CSVW)
+date/time patterns), required, unique, minLength/maxLength, minimum/maximum (numeric types only),
+pattern, enum, primary keys (checked with `uniqueCombinedWith`) and single-field foreign keys
+(checked with `dynamicEnum` against another CSV file, or against the input itself if the
+reference resource is empty). Relative foreign key resources are resolved against the directory
+of the descriptor. Only empty values are treated as missing values, and unique constraints
+ignore them. For a CSVW table group, the table whose url matches the input's file name is used.
+
+  # Validate a CSV against a Frictionless Table Schema
+  qsv validate data.csv tableschema.json
+
+  # Validate a CSV against its CSVW metadata
+  qsv validate data.csv data.csv-metadata.json
+
+BUSINESS RULES:
+--------------
+Cross-field & conditional row-level rules that JSON Schema cannot express can be checked with
+--rules, alone or together with a JSON Schema. The rules file is a JSON file with a list of
+rules, each a MiniJinja expression that must be true for a row to pass, e.g.:
+
+  {
+    "rules": [
+      {
+        "id": "dates_ordered",
+        "expression": "end_date >= start_date",
+        "field": "end_date",
+        "message": "end_date {{ end_date }} is before start_date {{ start_date }}"
+      },
+      {
+        "id": "closed_has_date",
+        "expression": "status != 'closed' or closed_at is not none",
+        "severity": "warning"
+      }
+    ]
+  }
+
+Columns are available as variables, with their names sanitized as with `qsv apply` (e.g. the
+"Start Date" column is `Start_Date`), and by their original name through `row`
+(e.g. row["Start Date"]). Empty fields are `none`, and integers & floats are numbers.
+All the MiniJinja filters of `qsv template` are available.
+
+Each rule has:
+  * id:         a unique identifier, included in the error report.
+  * expression: the MiniJinja expression to evaluate.
+  * severity:   "error" (default) or "warning".
+  * message:    an optional MiniJinja template for the error report message.
+  * field:      an optional field the violation is reported against (default: <RULE>).
+
+Violations are written to the "validation-errors.tsv" report as "<severity> <id>: <message>".
+Only violations at or above the --fail-on severity make a row invalid. Rows with only lower
+severity violations are still reported, but are written to the valid file.
+
+  # Validate a CSV against a JSON Schema and business rules
+  qsv validate --rules rules.json data.csv schema.json
+
+  # Only check business rules, failing on warnings too
+  qsv validate --rules rules.json --fail-on warning data.csv
 
 JSON SCHEMA SCHEMA VALIDATION SUBMODE:
 ---------------------------------------
@@ -148,6 +225,51 @@ JSON SCHEMA SCHEMA VALIDATION SUBMODE:
      // ignore format validation
      `qsv validate schema --no-format-validation myjsonschema.json`
 
+RELATIONS SUBMODE:
+-----------------
+`validate relations` checks the referential integrity of a multi-table release - composite
+foreign keys, orphans in either direction and relation cardinality - across the CSV files
+declared in a JSON config file. E.g. `qsv validate relations release.relations.json` with:
+
+  {
+    "tables": {
+      "customers": "customers.csv",
+      "orders": "orders.csv",
+      "regions": { "path": "https://example.com/regions.tsv", "delimiter": "\t" }
+    },
+    "relations": [
+      {
+        "name": "order_customer",
+        "from": { "table": "orders", "columns": ["customer_id", "region"] },
+        "to": { "table": "customers", "columns": ["id", "region"] },
+        "cardinality": "N:1",
+        "orphans": "both"
+      },
+      {
+        "from": { "table": "customers", "columns": "region" },
+        "to": { "table": "regions", "columns": "code" },
+        "key_transform": "trim,upper"
+      }
+    ]
+  }
+
+Tables are loaded with the same loader as dynamicEnum, so they can be local files (relative to
+the config file), URLs, or dathere:// & ckan:// resources, and are cached in --cache-dir.
+
+For each relation:
+  * cardinality: how many rows of each side may share a key, as "from:to" - one of
+                 "1:1", "1:N", "N:1" (a typical foreign key to a primary key) & "N:M" (default).
+  * orphans:     which side must not have keys missing from the other side - "from" (default),
+                 "to", "both" or "none".
+  * key_transform: an optional key normalization pipeline, applied to both sides.
+                 See `qsv join --help` for the --key-transform operations.
+
+Keys with an empty column are null and are neither orphans nor duplicates.
+
+All violations are written to a single "<config>.validation-errors.tsv" report with the
+columns table, row_number, field & error, and a non-zero exit code is returned.
+Not available on qsvlite.
+
 RFC 4180 VALIDATION MODE:
 ========================
 
@@ -193,12 +315,17 @@ Examples:
   # Validate CSV files listed in a '.infile-list' file against a JSON Schema
   qsv validate files.infile-list schema.json
 
+  # Suggest fixes for the invalid records, and repair them where possible
+  qsv validate --suggest-fixes data.csv schema.json
+  qsv validate --auto-fix data.csv schema.json
+
 For more examples, see the tests included in this file (denoted by '#[test]') or see
 https://github.com/dathere/qsv/blob/master/tests/test_validate.rs.
 See also https://github.com/dathere/qsv/wiki/Validation-and-Schema#validate
 
 Usage:
     qsv validate schema [--no-format-validation] [<json-schema>]
+    qsv validate relations [options] <relations-config>
     qsv validate [options] [<input>...]
     qsv validate [options] [<input>] <json-schema>
     qsv validate --help
@@ -213,6 +340,7 @@ Validate arguments:
     <json-schema>              JSON Schema file to validate against. If not provided, `validate`
                                will run in RFC 4180 validation mode. The file can be a local file
                                or a URL (http and https schemes supported).
+                               Can also be a Table Schema or CSVW metadata file (see above).
 
 Validate options:
     --trim                     Trim leading and trailing whitespace from fields before validating.
@@ -221,6 +349,29 @@ Validate options:
                                useful when you want to validate the structure of the CSV file
                                w/o worrying about the data types and domain/range of the fields.
     --fail-fast                Stops on first error.
+    --rules <file>             A JSON file of row-level business rules to check, with or
+                               without a JSON Schema. The file can be a local file or a URL.
+                               See BUSINESS RULES above.
+    --fail-on <severity>       The minimum severity of rule violations that make a row
+                               invalid. Either error or warning. [default: error]
+    --report-format <format>   Also write a machine-readable summary of the validation, for
+                               dashboards & CI, to "<input>.validation-report.<json|xml>".
+                               The valid/invalid files & the validation-errors.tsv report are
+                               still written as usual. Either:
+                                 json - the valid, invalid & warning record counts, the
+                                        BLAKE3 fingerprint of the JSON Schema, and the error
+                                        count of each error class (a field & the JSON Schema
+                                        keyword or rule it failed) with example row numbers.
+                                 junit - the same as JUnit XML, with a test case per error
+                                        class, failed unless it only has rule warnings.
+    --report-examples <n>      The number of example row numbers per error class in the
+                               --report-format summary. [default: 5]
+    --suggest-fixes            Write the deterministic fixes for the invalid records, with the
+                               qsv command line for each, to "<input>.fix-suggestions.tsv".
+                               See the fixes above.
+    --auto-fix                 Apply the deterministic fixes to the invalid records. Repaired
+                               records that pass re-validation are written to the valid file,
+                               with the fixes logged to "<input>.fixes.tsv".
     --valid <suffix>           Valid record output file suffix. [default: valid]
     --invalid <suffix>         Invalid record output file suffix. [default: invalid]
     --split-ragged             Opt-in mode: instead of aborting on the first row with the wrong
@@ -328,7 +479,7 @@ use std::{
 
 use bitvec::prelude::*;
 use csv::ByteRecord;
-use foldhash::{HashSet, HashSetExt};
+use foldhash::{HashMap, HashMapExt, HashSet, HashSetExt};
 use indicatif::HumanCount;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 use indicatif::{ProgressBar, ProgressDrawTarget};
@@ -356,6 +507,18 @@ use crate::{
     util,
 };
 
+mod fixes;
+#[cfg(not(feature = "lite"))]
+mod relations;
+mod report;
+mod rules;
+mod tableschema;
+
+use fixes::{FixLog, Fixer, Repair};
+use report::{ReportFormat, ReportSummary, ValidationReport};
+use rules::{RuleSet, Severity};
+use tableschema::DescriptorKind;
+
 // to save on repeated init/allocs
 static NULL_TYPE: OnceLock<Value> = OnceLock::new();
 
@@ -371,6 +534,9 @@ static CKAN_API: OnceLock<String> = OnceLock::new();
 static CKAN_TOKEN: OnceLock<Option<String>> = OnceLock::new();
 static DELIMITER: OnceLock<Option<Delimiter>> = OnceLock::new();
 
+// the schema used when only --rules is given
+const RULES_ONLY_SCHEMA: &str = r#"{"type": "object", "properties": {}}"#;
+
 /// write to stderr and `log::error`, using `ValidationError`
 macro_rules! fail_validation_error {
     ($($t:tt)*) => {{
@@ -385,6 +551,7 @@ macro_rules! fail_validation_error {
 #[allow(dead_code)]
 struct Args {
     cmd_schema:                bool,
+    cmd_relations:             bool,
     flag_trim:                 bool,
     flag_no_format_validation: bool,
     flag_fail_fast:            bool,
@@ -402,6 +569,7 @@ struct Args {
     flag_quiet:                bool,
     arg_input:                 Vec<std::path::PathBuf>,
     arg_json_schema:           Option<String>,
+    arg_relations_config:      Option<String>,
     flag_fancy_regex:          bool,
     flag_backtrack_limit:      usize,
     flag_size_limit:           usize,
@@ -414,6 +582,12 @@ struct Args {
     flag_email_display_text:   bool,
     flag_email_min_subdomains: usize,
     flag_email_domain_literal: bool,
+    flag_rules:                Option<String>,
+    flag_fail_on:              String,
+    flag_report_format:        Option<String>,
+    flag_report_examples:      usize,
+    flag_suggest_fixes:        bool,
+    flag_auto_fix:             bool,
 }
 
 enum JSONtypes {
@@ -466,9 +640,7 @@ impl DynEnumValidator {
 impl<'i> Keyword<'i> for DynEnumValidator {
     #[inline]
     fn validate(&self, instance: &'i Value) -> Result<(), ValidationError<'i>> {
-        if let Value::String(s) = instance
-            && self.dynenum_set.contains(s)
-        {
+        if self.is_valid(instance) {
             return Ok(());
         }
         Err(ValidationError::custom(format!(
@@ -478,10 +650,12 @@ impl<'i> Keyword<'i> for DynEnumValidator {
 
     #[inline]
     fn is_valid(&self, instance: &'i Value) -> bool {
-        if let Value::String(s) = instance {
-            self.dynenum_set.contains(s)
-        } else {
-            false
+        match instance {
+            Value::String(s) => self.dynenum_set.contains(s),
+            // numeric columns (e.g. integer foreign keys) are looked up by their
+            // canonical string representation
+            Value::Number(n) => self.dynenum_set.contains(&n.to_string()),
+            _ => false,
         }
     }
 }
@@ -1169,6 +1343,20 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         Ordering::Relaxed,
     );
 
+    let fail_on = Severity::from_flag(&args.flag_fail_on)?;
+    let report_format = args
+        .flag_report_format
+        .as_deref()
+        .map(ReportFormat::from_flag)
+        .transpose()?;
+
+    if args.cmd_relations {
+        #[cfg(not(feature = "lite"))]
+        return relations::run(&args);
+        #[cfg(feature = "lite")]
+        return fail_incorrectusage_clierror!("`validate relations` is not available in qsvlite.");
+    }
+
     // Check if the last argument is a JSON schema file
     let has_json_schema = if let Some(last_input) = args.arg_input.last() {
         last_input
@@ -1180,7 +1368,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     };
 
     // if no JSON Schema supplied, only let csv reader RFC4180-validate csv file
-    if !has_json_schema && args.arg_json_schema.is_none() {
+    if !has_json_schema && args.arg_json_schema.is_none() && args.flag_rules.is_none() {
         // Warn when a .json file appears earlier in the input list — schema detection
         // only looks at the last positional, so a misordered argument silently falls
         // into RFC 4180 mode.
@@ -1304,12 +1492,19 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         let _ = CKAN_TOKEN.set(ckan_token);
     }
 
-    // parse and compile supplied JSON Schema
-    let json_schema_path =
-        json_schema_path.unwrap_or_else(|| PathBuf::from(json_schema_arg.as_ref().unwrap()));
+    // parse and compile supplied JSON Schema. With only --rules, a permissive schema that
+    // every row satisfies is used, so only the business rules are checked.
+    let json_schema_path = json_schema_path
+        .or_else(|| json_schema_arg.as_ref().map(PathBuf::from))
+        .unwrap_or_default();
+    let schema_source = if json_schema_arg.is_some() {
+        load_json(&json_schema_path.to_string_lossy())
+    } else {
+        Ok(RULES_ONLY_SCHEMA.to_string())
+    };
     let (schema_json, schema_compiled, has_unique_combined): (Value, Validator, bool) =
-            // safety: we know the schema is_some() because we checked above
-            match load_json(&json_schema_path.to_string_lossy()) {
+            // with only --rules, schema_source is the permissive RULES_ONLY_SCHEMA
+            match schema_source {
             Ok(s) => {
                 // parse JSON string - use platform-appropriate JSON deserialization
                 // simd_json mutates its input buffer; serde_json reads from the str directly.
@@ -1322,6 +1517,23 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
                 match json_result {
                     Ok(json) => {
+                        // Table Schema & CSVW descriptors are translated to an equivalent
+                        // JSON Schema, so the rest of the pipeline only deals with JSON Schema
+                        let descriptor_kind = DescriptorKind::detect(&json);
+                        let json = if descriptor_kind == DescriptorKind::JsonSchema {
+                            json
+                        } else {
+                            let translated = tableschema::to_json_schema(
+                                &json,
+                                descriptor_kind,
+                                &json_schema_path,
+                                input_path,
+                                &headers,
+                            )?;
+                            debug!("{} translated to JSON Schema: {translated}", descriptor_kind.name());
+                            translated
+                        };
+
                         // Detect custom formats/keywords by walking the parsed schema.
                         // This is robust to whitespace and avoids false matches on
                         // descriptions/titles that mention these strings as text.
@@ -1433,6 +1645,58 @@ Try running `qsv validate schema {}` to check the JSON Schema file.", json_schem
     // get JSON types for each column in CSV file
     let header_types = get_json_types(&headers, &schema_json)?;
 
+    // compile the --rules business rules, if any
+    let rule_defs = match args.flag_rules {
+        Some(ref rules_uri) => rules::load(rules_uri)?,
+        None => Vec::new(),
+    };
+    let rules_env = (!rule_defs.is_empty()).then(rules::environment);
+    let rule_set = match rules_env {
+        Some(ref env) => Some(RuleSet::compile(env, &rule_defs, &headers, fail_on)?),
+        None => None,
+    };
+    // rows with only rule violations below --fail-on severity
+    let mut warning_count: u64 = 0;
+    let mut validation_report =
+        report_format.map(|format| ValidationReport::new(format, args.flag_report_examples));
+
+    // --suggest-fixes & --auto-fix try deterministic fixes on invalid rows
+    let auto_fix = args.flag_auto_fix;
+    let fixer = if (args.flag_suggest_fixes || auto_fix) && has_unique_combined {
+        wwarn!(
+            "--suggest-fixes/--auto-fix are not supported with uniqueCombinedWith, as repaired \
+             rows cannot be re-validated. No fixes will be attempted."
+        );
+        None
+    } else if args.flag_suggest_fixes || auto_fix {
+        Some(Fixer::new(
+            &header_types,
+            &schema_json,
+            rconfig.get_dmy_preference(),
+        ))
+    } else {
+        None
+    };
+    let mut fix_log = FixLog::default();
+    // the repaired rows by --auto-fix, by row index
+    let mut repaired_records: HashMap<usize, ByteRecord> = HashMap::new();
+    // a repaired row is valid if it casts to its JSON types & passes the schema & --rules
+    let revalidate = |repaired: &ByteRecord| {
+        let Ok(json_instance) = to_json_instance(&header_types, header_len, repaired) else {
+            return false;
+        };
+        if !schema_compiled.is_valid(&json_instance) {
+            return false;
+        }
+        rule_set.as_ref().is_none_or(|rule_set| {
+            // safety: the row number is the last appended field, so it is valid ASCII
+            let row_number_string = simdutf8::basic::from_utf8(&repaired[header_len]).unwrap();
+            let mut rule_errors = RowErrors::default();
+            rule_set.evaluate(repaired, row_number_string, &mut rule_errors);
+            !rule_errors.invalid
+        })
+    };
+
     // how many rows read and processed as batches
     let mut row_number: u64 = 0;
     // how many invalid rows found
@@ -1447,7 +1711,7 @@ Try running `qsv validate schema {}` to check the JSON Schema file.", json_schem
     let mut valid_flags: BitVec = BitVec::with_capacity(record_count as usize);
     let batch_size = util::optimal_batch_size(&rconfig, args.flag_batch, num_jobs);
     let mut batch = Vec::with_capacity(batch_size);
-    let mut batch_validation_results: Vec<Option<String>> = Vec::with_capacity(batch_size);
+    let mut batch_validation_results: Vec<Option<RowErrors>> = Vec::with_capacity(batch_size);
     let mut validation_error_messages: Vec<String> = Vec::with_capacity(50);
     let flag_trim = args.flag_trim;
     let flag_fail_fast = args.flag_fail_fast;
@@ -1505,10 +1769,18 @@ Try running `qsv validate schema {}` to check the JSON Schema file.", json_schem
                     // safety: row number was appended via itoa, so it is always valid ASCII
                     let row_number_string =
                         simdutf8::basic::from_utf8(&record[record.len() - 1]).unwrap();
-                    return Some(format!(
-                        "{row_number_string}\t<RECORD>\tfound {} fields, but expected {header_len}",
-                        record.len() - 1
-                    ));
+                    let mut errors = RowErrors::invalid();
+                    errors.push(
+                        row_number_string,
+                        "<RECORD>",
+                        "ragged",
+                        true,
+                        &format!(
+                            "found {} fields, but expected {header_len}",
+                            record.len() - 1
+                        ),
+                    );
+                    return Some(errors);
                 }
 
                 // convert CSV record to JSON instance
@@ -1520,7 +1792,12 @@ Try running `qsv validate schema {}` to check the JSON Schema file.", json_schem
                         // always valid ASCII; the unwrap can never fire in practice.
                         let row_number_string =
                             simdutf8::basic::from_utf8(&record[header_len]).unwrap();
-                        return Some(format!("{row_number_string}\t<RECORD>\t{e}"));
+                        let mut errors = RowErrors::invalid();
+                        errors.push(row_number_string, "<RECORD>", "type", true, &e.to_string());
+                        if let Some(ref fixer) = fixer {
+                            errors.repair = fixer.repair(record, &errors, &revalidate);
+                        }
+                        return Some(errors);
                     },
                 };
 
@@ -1531,40 +1808,59 @@ Try running `qsv validate schema {}` to check the JSON Schema file.", json_schem
                 // is_valid() and short-circuit valid records; only the rare invalid
                 // case falls through to the full evaluate.
                 let evaluation = if has_unique_combined {
-                    schema_compiled.evaluate(&json_instance)
+                    Some(schema_compiled.evaluate(&json_instance))
                 } else if schema_compiled.is_valid(&json_instance) {
-                    return None;
+                    if rule_set.is_none() {
+                        return None;
+                    }
+                    None
                 } else {
                     std::hint::cold_path();
-                    schema_compiled.evaluate(&json_instance)
+                    Some(schema_compiled.evaluate(&json_instance))
                 };
 
-                if evaluation.flag().valid {
-                    None
-                } else {
-                    std::hint::cold_path();
-                    // safety: row number was appended as the last field via itoa, so it is
-                    // always valid ASCII; the unwrap can never fire in practice.
-                    let row_number_string =
-                        simdutf8::basic::from_utf8(&record[header_len]).unwrap();
+                // safety: row number was appended as the last field via itoa, so it is
+                // always valid ASCII; the unwrap can never fire in practice.
+                let row_number_string = simdutf8::basic::from_utf8(&record[header_len]).unwrap();
 
-                    // Collect errors into a vector
-                    let errors: Vec<_> = evaluation.iter_errors().collect();
+                // there can be multiple validation errors for a single record,
+                // each is reported on its own line, classed by the keyword that failed
+                let mut errors = RowErrors::default();
+                if let Some(evaluation) = evaluation
+                    && !evaluation.flag().valid
+                {
+                    std::hint::cold_path();
+                    errors.invalid = true;
+                    for e in evaluation.iter_errors() {
+                        errors.push(
+                            row_number_string,
+                            e.instance_location.as_str().trim_start_matches('/'),
+                            e.schema_location
+                                .as_str()
+                                .rsplit('/')
+                                .next()
+                                .unwrap_or_default(),
+                            true,
+                            &e.error.to_string(),
+                        );
+                    }
+                }
 
-                    // Preallocate the vector with the known size
-                    let mut error_messages = Vec::with_capacity(errors.len());
+                // then check the business rules. Violations below the --fail-on severity
+                // are reported, but don't make the row invalid.
+                if let Some(ref rule_set) = rule_set {
+                    rule_set.evaluate(record, row_number_string, &mut errors);
+                }
 
-                    // there can be multiple validation errors for a single record,
-                    // squash multiple errors into one long String with linebreaks
-                    for e in errors {
-                        error_messages.push(format!(
-                            "{row_number_string}\t{field}\t{error}",
-                            field = e.instance_location.as_str().trim_start_matches('/'),
-                            error = e.error
-                        ));
-                    }
-                    Some(error_messages.join("\n"))
+                if errors.lines.is_empty() {
+                    return None;
+                }
+                if errors.invalid
+                    && let Some(ref fixer) = fixer
+                {
+                    errors.repair = fixer.repair(record, &errors, &revalidate);
                 }
+                Some(errors)
             })
             .collect_into_vec(&mut batch_validation_results);
 
@@ -1575,12 +1871,34 @@ Try running `qsv validate schema {}` to check the JSON Schema file.", json_schem
         // extend by the actual batch length, not batch_size — the last batch may be partial,
         // and over-extending would leave trailing `true` flags for nonexistent rows.
         valid_flags.extend(std::iter::repeat_n(true, batch.len()));
-        for (i, result) in batch_validation_results.iter().enumerate() {
-            if let Some(validation_error_msg) = result {
-                invalid_count += 1;
-                // safe set(): negligible cost on this path (dominated by validator work)
-                valid_flags.set(start_idx + i, false);
-                validation_error_messages.push(validation_error_msg.to_owned());
+        for (i, result) in batch_validation_results.iter_mut().enumerate() {
+            if let Some(mut errors) = result.take() {
+                if let Some(repair) = errors.repair.take() {
+                    let row = (start_idx + i + 1) as u64;
+                    let applied = auto_fix && repair.valid;
+                    // safety: fixer is set when there's a repair
+                    fix_log.add(fixer.as_ref().unwrap(), row, &repair, applied);
+                    if applied {
+                        // the repaired row is valid, so it goes to the valid output
+                        // without the appended row number
+                        repaired_records.insert(
+                            start_idx + i,
+                            repair.record.iter().take(header_len).collect(),
+                        );
+                        continue;
+                    }
+                }
+                if errors.invalid {
+                    invalid_count += 1;
+                    // safe set(): negligible cost on this path (dominated by validator work)
+                    valid_flags.set(start_idx + i, false);
+                } else {
+                    warning_count += 1;
+                }
+                if let Some(ref mut validation_report) = validation_report {
+                    validation_report.add((start_idx + i + 1) as u64, &errors);
+                }
+                validation_error_messages.extend(errors.lines);
             }
         }
 
@@ -1605,8 +1923,63 @@ Try running `qsv validate schema {}` to check the JSON Schema file.", json_schem
         util::finish_progress(&progress);
     }
 
+    let input_path = args.arg_input.first().map_or_else(
+        || "stdin.csv".to_string(),
+        |p| p.to_string_lossy().to_string(),
+    );
+
+    if let Some(validation_report) = validation_report {
+        let schema = json_schema_arg.as_deref();
+        validation_report.write(&ReportSummary {
+            input: &input_path,
+            schema,
+            schema_fingerprint: schema.map(|_| {
+                format!(
+                    "blake3:{}",
+                    blake3::hash(schema_json.to_string().as_bytes())
+                )
+            }),
+            rules: args.flag_rules.as_deref(),
+            total_records: row_number,
+            invalid_records: invalid_count,
+            warning_records: warning_count,
+            fail_fast_stopped: args.flag_fail_fast && invalid_count > 0,
+        })?;
+    }
+
+    if let Some(ref fixer) = fixer {
+        if args.flag_suggest_fixes {
+            fix_log.write_suggestions(fixer, &input_path)?;
+            if !args.flag_quiet {
+                winfo!(
+                    "{} of {} invalid records can be repaired with the suggested fixes, see \
+                     {input_path}.fix-suggestions.tsv",
+                    HumanCount(fix_log.repairable),
+                    HumanCount(invalid_count + repaired_records.len() as u64)
+                );
+            }
+        }
+        if auto_fix {
+            fix_log.write_applied(&input_path)?;
+            if !args.flag_quiet {
+                winfo!(
+                    "{} records repaired by --auto-fix with {} fixes, see {input_path}.fixes.tsv",
+                    HumanCount(repaired_records.len() as u64),
+                    HumanCount(fix_log.applied_count() as u64)
+                );
+            }
+        }
+    }
+
+    let valid_suffix = args.flag_valid.unwrap_or_else(|| "valid".to_string());
+    let invalid_suffix = args.flag_invalid.unwrap_or_else(|| "invalid".to_string());
+
     if invalid_count == 0 {
-        // no invalid records found
+        // no invalid records found, but there may be rule warnings to report
+        if warning_count > 0 {
+            write_error_report(&input_path, validation_error_messages)?;
+        }
+
         // see if we need to pass all valid records to output
         if let Some(valid_output) = args.flag_valid_output {
             // pass all valid records to output and return exit code 1
@@ -1622,35 +1995,43 @@ Try running `qsv validate schema {}` to check the JSON Schema file.", json_schem
 
             let mut rdr = rconfig.reader()?;
             let mut record = csv::ByteRecord::new();
+            let mut row_idx: usize = 0;
             while rdr.read_byte_record(&mut record)? {
-                valid_wtr.write_byte_record(&record)?;
+                valid_wtr.write_byte_record(repaired_records.get(&row_idx).unwrap_or(&record))?;
+                row_idx += 1;
             }
             valid_wtr.flush()?;
             // return 1 as an exitcode and the number of valid rows to stderr
             return fail_clierror!("{row_number}");
         }
+
+        // all the invalid records were repaired by --auto-fix, write out the repaired file
+        if !repaired_records.is_empty() {
+            split_invalid_records(
+                &rconfig,
+                &valid_flags[..],
+                &repaired_records,
+                &headers,
+                &input_path,
+                &valid_suffix,
+                None,
+            )?;
+        }
     } else {
         // there are invalid records. write out invalid/valid/errors output files.
         // if 100% invalid, valid file isn't needed, but this is rare so OK creating empty file.
         woutinfo!("Writing invalid/valid/error files...");
 
-        let input_path = args.arg_input.first().map_or_else(
-            || "stdin.csv".to_string(),
-            |p| p.to_string_lossy().to_string(),
-        );
-
         write_error_report(&input_path, validation_error_messages)?;
 
-        let valid_suffix = args.flag_valid.unwrap_or_else(|| "valid".to_string());
-        let invalid_suffix = args.flag_invalid.unwrap_or_else(|| "invalid".to_string());
-
         split_invalid_records(
             &rconfig,
             &valid_flags[..],
+            &repaired_records,
             &headers,
             &input_path,
             &valid_suffix,
-            &invalid_suffix,
+            Some(invalid_suffix.as_str()),
         )?;
 
         // done with validation; print output
@@ -1671,7 +2052,21 @@ Try running `qsv validate schema {}` to check the JSON Schema file.", json_schem
     }
 
     if !args.flag_quiet {
-        winfo!("All {} records valid.", HumanCount(row_number));
+        if !repaired_records.is_empty() {
+            winfo!(
+                "All {} records valid after --auto-fix, written to {input_path}.{valid_suffix}",
+                HumanCount(row_number)
+            );
+        } else if warning_count > 0 {
+            winfo!(
+                "All {} records valid. {} records with rule warnings, see \
+                 {input_path}.validation-errors.tsv",
+                HumanCount(row_number),
+                HumanCount(warning_count)
+            );
+        } else {
+            winfo!("All {} records valid.", HumanCount(row_number));
+        }
     }
     Ok(())
 }
@@ -2242,14 +2637,18 @@ Alternatively, transcode your data to UTF-8 first using `iconv` or `recode`."
 /// - Memory is bounded — we keep only the `BitVec` of valid/invalid flags, not the records
 ///   themselves.
 ///
-/// Only called when there is at least one invalid record.
+/// The rows repaired by --auto-fix are written to `.valid` in place of the originals.
+///
+/// Only called when there is at least one invalid record, or when --auto-fix repaired all of
+/// them - then with no `invalid_suffix`, so only the `.valid` file is written.
 fn split_invalid_records(
     rconfig: &Config,
     valid_flags: &BitSlice,
+    repaired_records: &HashMap<usize, ByteRecord>,
     headers: &ByteRecord,
     input_path: &str,
     valid_suffix: &str,
-    invalid_suffix: &str,
+    invalid_suffix: Option<&str>,
 ) -> CliResult<()> {
     // track how many rows read for splitting into valid/invalid
     // should not exceed row_number when aborted early due to fail-fast
@@ -2262,10 +2661,16 @@ fn split_invalid_records(
 
     // the invalid writer must be flexible when quarantining ragged rows (--split-ragged),
     // since those rows have field counts that differ from the header width.
-    let mut invalid_wtr = Config::new(Some(input_path.to_owned() + "." + invalid_suffix).as_ref())
-        .flexible(rconfig.flexible)
-        .writer()?;
-    invalid_wtr.write_byte_record(headers)?;
+    let mut invalid_wtr = match invalid_suffix {
+        Some(invalid_suffix) => {
+            let mut wtr = Config::new(Some(input_path.to_owned() + "." + invalid_suffix).as_ref())
+                .flexible(rconfig.flexible)
+                .writer()?;
+            wtr.write_byte_record(headers)?;
+            Some(wtr)
+        },
+        None => None,
+    };
 
     let mut rdr = rconfig.reader()?;
 
@@ -2279,19 +2684,48 @@ fn split_invalid_records(
         }
 
         if valid_flags[split_row_num] {
-            valid_wtr.write_byte_record(&record)?;
-        } else {
+            valid_wtr.write_byte_record(repaired_records.get(&split_row_num).unwrap_or(&record))?;
+        } else if let Some(ref mut invalid_wtr) = invalid_wtr {
             invalid_wtr.write_byte_record(&record)?;
         }
         split_row_num += 1;
     }
 
     valid_wtr.flush()?;
-    invalid_wtr.flush()?;
+    if let Some(ref mut invalid_wtr) = invalid_wtr {
+        invalid_wtr.flush()?;
+    }
 
     Ok(())
 }
 
+/// The validation errors of a row: its `validation-errors.tsv` lines, with the
+/// (field, keyword, failing) class of each for the --report-format summary.
+#[derive(Default)]
+struct RowErrors {
+    lines:   Vec<String>,
+    classes: Vec<(String, String, bool)>,
+    // false if the row only has rule violations below the --fail-on severity
+    invalid: bool,
+    // the --suggest-fixes/--auto-fix repair of an invalid row
+    repair:  Option<Repair>,
+}
+
+impl RowErrors {
+    fn invalid() -> Self {
+        Self {
+            invalid: true,
+            ..Self::default()
+        }
+    }
+
+    fn push(&mut self, row_number: &str, field: &str, keyword: &str, failing: bool, message: &str) {
+        self.lines.push(format!("{row_number}\t{field}\t{message}"));
+        self.classes
+            .push((field.to_string(), keyword.to_string(), failing));
+    }
+}
+
 fn write_error_report(input_path: &str, validation_error_messages: Vec<String>) -> CliResult<()> {
     let wtr_capacitys = env::var("QSV_WTR_BUFFER_CAPACITY")
         .unwrap_or_else(|_| DEFAULT_WTR_BUFFER_CAPACITY.to_string());
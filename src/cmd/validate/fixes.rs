@@ -0,0 +1,382 @@
+//! Deterministic repairs for `validate --suggest-fixes` & `--auto-fix`.
+//!
+//! The failing fields of an invalid row are run through a fixed sequence of repairs -
+//! stray whitespace, thousands separators, enum case mismatches & non-ISO 8601 dates - and
+//! the repaired row is re-validated. Each repair corresponds to an `apply`/`replace`/`datefmt`
+//! command line, so the fixes can also be applied to the whole file outside of `validate`.
+
+use std::sync::LazyLock;
+
+use csv::ByteRecord;
+use foldhash::HashMap;
+use regex::Regex;
+use serde_json::Value;
+
+use super::{JSONtypes, RowErrors};
+use crate::{CliResult, util};
+
+static THOUSANDS_SEPARATOR: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[+-]?\d{1,3}(?:,\d{3})+(?:\.\d+)?$").unwrap());
+static ISO_DATE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\d{4}-\d{2}-\d{2}$").unwrap());
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FixKind {
+    Trim,
+    ThousandsSeparator,
+    EnumCase,
+    DateFormat,
+}
+
+impl FixKind {
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Trim => "trim",
+            Self::ThousandsSeparator => "thousands_separator",
+            Self::EnumCase => "enum_case",
+            Self::DateFormat => "date_format",
+        }
+    }
+}
+
+pub struct Fix {
+    column: usize,
+    kind:   FixKind,
+    old:    String,
+    new:    String,
+}
+
+/// The fixes for an invalid row, the repaired row & whether it passed re-validation.
+pub struct Repair {
+    pub record: ByteRecord,
+    pub valid:  bool,
+    fixes:      Vec<Fix>,
+}
+
+#[derive(Clone, Copy)]
+enum DateFormat {
+    Date,
+    DateTime,
+}
+
+struct ColumnFixer {
+    name:        String,
+    numeric:     bool,
+    integer:     bool,
+    enum_values: Vec<String>,
+    date_format: Option<DateFormat>,
+}
+
+pub struct Fixer {
+    columns:    Vec<ColumnFixer>,
+    prefer_dmy: bool,
+}
+
+impl Fixer {
+    pub fn new(header_types: &[(String, JSONtypes)], schema: &Value, prefer_dmy: bool) -> Self {
+        let columns = header_types
+            .iter()
+            .map(|(name, json_type)| {
+                let def = schema.get("properties").and_then(|p| p.get(name));
+                let enum_values = def
+                    .and_then(|d| d.get("enum"))
+                    .and_then(Value::as_array)
+                    .map(|values| {
+                        values
+                            .iter()
+                            .filter_map(|v| v.as_str().map(String::from))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let date_format = match def.and_then(|d| d.get("format")).and_then(Value::as_str) {
+                    Some("date") => Some(DateFormat::Date),
+                    Some("date-time") => Some(DateFormat::DateTime),
+                    _ => None,
+                };
+                ColumnFixer {
+                    name: name.clone(),
+                    numeric: matches!(json_type, JSONtypes::Integer | JSONtypes::Number),
+                    integer: matches!(json_type, JSONtypes::Integer),
+                    enum_values,
+                    date_format,
+                }
+            })
+            .collect();
+        Self {
+            columns,
+            prefer_dmy,
+        }
+    }
+
+    /// Try the fixes on the failing fields of an invalid row, re-validating the repaired row
+    /// with `revalidate`. None if no fix applies.
+    pub fn repair(
+        &self,
+        record: &ByteRecord,
+        errors: &RowErrors,
+        revalidate: impl Fn(&ByteRecord) -> bool,
+    ) -> Option<Repair> {
+        // a row with a type cast error wasn't validated against the schema, so any of its
+        // fields may be invalid
+        let cast_error = errors
+            .classes
+            .iter()
+            .any(|(field, keyword, _)| field == "<RECORD>" && keyword == "type");
+
+        let mut fixes = Vec::new();
+        let mut fields: Vec<String> = Vec::with_capacity(record.len());
+        for (column, value) in record.iter().enumerate() {
+            let value = util::bytes_to_cow_str(value).into_owned();
+            // the row number appended to the record has no column
+            let Some(col) = self.columns.get(column) else {
+                fields.push(value);
+                continue;
+            };
+            let failing = errors
+                .classes
+                .iter()
+                .any(|(field, _, failing)| *failing && *field == col.name)
+                || (cast_error && col.numeric && !value.is_empty() && !Self::casts(col, &value));
+            if !failing && !cast_error {
+                fields.push(value);
+                continue;
+            }
+
+            let mut field_fixes = Vec::new();
+            let fixed = self.fix_value(column, col, value.clone(), &mut field_fixes);
+            // stray whitespace alone is only an error in a failing field
+            if failing || field_fixes.iter().any(|fix| fix.kind != FixKind::Trim) {
+                fixes.append(&mut field_fixes);
+                fields.push(fixed);
+            } else {
+                fields.push(value);
+            }
+        }
+        if fixes.is_empty() {
+            return None;
+        }
+
+        let record = ByteRecord::from(fields);
+        let valid = revalidate(&record);
+        Some(Repair {
+            record,
+            valid,
+            fixes,
+        })
+    }
+
+    fn casts(col: &ColumnFixer, value: &str) -> bool {
+        if col.integer {
+            atoi_simd::parse::<i64, false, false>(value.as_bytes()).is_ok()
+        } else {
+            fast_float2::parse::<f64, _>(value).is_ok_and(f64::is_finite)
+        }
+    }
+
+    fn fix_value(
+        &self,
+        column: usize,
+        col: &ColumnFixer,
+        mut value: String,
+        fixes: &mut Vec<Fix>,
+    ) -> String {
+        let mut apply = |kind: FixKind, value: &mut String, new: String| {
+            fixes.push(Fix {
+                column,
+                kind,
+                old: std::mem::take(value),
+                new: new.clone(),
+            });
+            *value = new;
+        };
+
+        let trimmed = value.trim();
+        if trimmed.len() != value.len() {
+            let new = trimmed.to_string();
+            apply(FixKind::Trim, &mut value, new);
+        }
+
+        if col.numeric && THOUSANDS_SEPARATOR.is_match(&value) {
+            let new = value.replace(',', "");
+            apply(FixKind::ThousandsSeparator, &mut value, new);
+        }
+
+        if !col.enum_values.is_empty() && !col.enum_values.contains(&value) {
+            let lower = value.to_lowercase();
+            let mut matches = col.enum_values.iter().filter(|v| v.to_lowercase() == lower);
+            // only fix an unambiguous case mismatch
+            if let (Some(canonical), None) = (matches.next(), matches.next()) {
+                let new = canonical.clone();
+                apply(FixKind::EnumCase, &mut value, new);
+            }
+        }
+
+        if let Some(date_format) = col.date_format
+            && !value.is_empty()
+            && !match date_format {
+                DateFormat::Date => ISO_DATE.is_match(&value),
+                DateFormat::DateTime => chrono::DateTime::parse_from_rfc3339(&value).is_ok(),
+            }
+            && let Ok(parsed) = qsv_dateparser::parse_with_preference(&value, self.prefer_dmy)
+        {
+            let new = match date_format {
+                DateFormat::Date => parsed.format("%Y-%m-%d").to_string(),
+                DateFormat::DateTime => parsed.format("%+").to_string(),
+            };
+            if new != value {
+                apply(FixKind::DateFormat, &mut value, new);
+            }
+        }
+        value
+    }
+}
+
+struct Suggestion {
+    column:        usize,
+    field:         String,
+    kind:          FixKind,
+    // the canonical value of an enum case fix
+    target:        String,
+    rows:          u64,
+    example:       String,
+    fixed_example: String,
+}
+
+/// The suggested fixes per error class & the log of the fixes applied by --auto-fix.
+#[derive(Default)]
+pub struct FixLog {
+    suggestions:    HashMap<(String, FixKind, String), Suggestion>,
+    applied:        Vec<[String; 5]>,
+    pub repairable: u64,
+}
+
+impl FixLog {
+    pub fn add(&mut self, fixer: &Fixer, row_number: u64, repair: &Repair, applied: bool) {
+        if repair.valid {
+            self.repairable += 1;
+        }
+        for fix in &repair.fixes {
+            let field = &fixer.columns[fix.column].name;
+            let target = if fix.kind == FixKind::EnumCase {
+                fix.new.clone()
+            } else {
+                String::new()
+            };
+            let suggestion = self
+                .suggestions
+                .entry((field.clone(), fix.kind, target.clone()))
+                .or_insert_with(|| Suggestion {
+                    column: fix.column,
+                    field: field.clone(),
+                    kind: fix.kind,
+                    target,
+                    rows: 0,
+                    example: fix.old.clone(),
+                    fixed_example: fix.new.clone(),
+                });
+            suggestion.rows += 1;
+
+            if applied {
+                self.applied.push([
+                    row_number.to_string(),
+                    field.clone(),
+                    fix.kind.name().to_string(),
+                    fix.old.clone(),
+                    fix.new.clone(),
+                ]);
+            }
+        }
+    }
+
+    pub fn applied_count(&self) -> usize {
+        self.applied.len()
+    }
+
+    /// Write the suggested fixes to `<input>.fix-suggestions.tsv`, most frequent first.
+    pub fn write_suggestions(&self, fixer: &Fixer, input_path: &str) -> CliResult<()> {
+        let mut suggestions: Vec<&Suggestion> = self.suggestions.values().collect();
+        suggestions.sort_unstable_by(|a, b| {
+            b.rows
+                .cmp(&a.rows)
+                .then_with(|| a.field.cmp(&b.field))
+                .then_with(|| a.kind.cmp(&b.kind))
+                .then_with(|| a.target.cmp(&b.target))
+        });
+
+        let mut wtr = csv::WriterBuilder::new()
+            .delimiter(b'\t')
+            .from_path(format!("{input_path}.fix-suggestions.tsv"))?;
+        wtr.write_record([
+            "field",
+            "fix",
+            "rows",
+            "example",
+            "fixed_example",
+            "command",
+        ])?;
+        let mut itoa_buf = itoa::Buffer::new();
+        for s in suggestions {
+            wtr.write_record([
+                s.field.as_str(),
+                s.kind.name(),
+                itoa_buf.format(s.rows),
+                s.example.as_str(),
+                s.fixed_example.as_str(),
+                command(fixer, s, input_path).as_str(),
+            ])?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+
+    /// Write the fixes applied by --auto-fix to `<input>.fixes.tsv`.
+    pub fn write_applied(&self, input_path: &str) -> CliResult<()> {
+        let mut wtr = csv::WriterBuilder::new()
+            .delimiter(b'\t')
+            .from_path(format!("{input_path}.fixes.tsv"))?;
+        wtr.write_record(["row_number", "field", "fix", "old_value", "new_value"])?;
+        for fix in &self.applied {
+            wtr.write_record(fix)?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+}
+
+/// the qsv command line that applies a suggested fix to the whole input
+fn command(fixer: &Fixer, s: &Suggestion, input_path: &str) -> String {
+    let field = shell_quote(&s.field);
+    let input = shell_quote(input_path);
+    match s.kind {
+        FixKind::Trim => format!("qsv apply operations trim {field} {input}"),
+        FixKind::ThousandsSeparator => format!("qsv replace --select {field} ',' '' {input}"),
+        FixKind::EnumCase => format!(
+            "qsv replace --select {field} {} {} {input}",
+            shell_quote(&format!(r"(?i)^\s*{}\s*$", regex::escape(&s.target))),
+            shell_quote(&s.target)
+        ),
+        FixKind::DateFormat => {
+            let prefer_dmy = if fixer.prefer_dmy {
+                " --prefer-dmy"
+            } else {
+                ""
+            };
+            // --formatstr defaults to RFC 3339, a valid date-time
+            let formatstr = match fixer.columns[s.column].date_format {
+                Some(DateFormat::Date) => " --formatstr '%Y-%m-%d'",
+                _ => "",
+            };
+            format!("qsv datefmt {field}{formatstr}{prefer_dmy} {input}")
+        },
+    }
+}
+
+fn shell_quote(s: &str) -> String {
+    if !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '/'))
+    {
+        s.to_string()
+    } else {
+        format!("'{}'", s.replace('\'', r"'\''"))
+    }
+}
@@ -0,0 +1,427 @@
+//! Cross-file referential integrity validation for `validate relations`.
+//!
+//! A relations config declares the tables of a multi-table release and the foreign key
+//! relations between them. For each relation, the (possibly composite) keys of both sides
+//! are checked for orphans and for the declared cardinality. All violations are written
+//! to a single consolidated `<config>.validation-errors.tsv` report.
+
+use std::{collections::BTreeMap, path::Path};
+
+use csv::ByteRecord;
+use foldhash::{HashMap, HashMapExt};
+use indicatif::HumanCount;
+use serde::Deserialize;
+
+use super::{Args, TIMEOUT_SECS, load_json};
+use crate::{
+    CliResult,
+    config::{Config, Delimiter},
+    lookup::{self, LookupTableOptions, load_lookup_table},
+    util::{self, ByteString, KeyTransform},
+};
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RelationsConfig {
+    tables:    BTreeMap<String, TableRef>,
+    relations: Vec<Relation>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TableRef {
+    Path(String),
+    Spec {
+        path:      String,
+        delimiter: Option<Delimiter>,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Relation {
+    name:          Option<String>,
+    from:          RelationSide,
+    to:            RelationSide,
+    #[serde(default)]
+    cardinality:   Cardinality,
+    #[serde(default)]
+    orphans:       OrphanCheck,
+    key_transform: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RelationSide {
+    table:   String,
+    columns: Columns,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Columns {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Columns {
+    fn names(&self) -> Vec<&str> {
+        match self {
+            Self::One(name) => vec![name.as_str()],
+            Self::Many(names) => names.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+/// How many rows of each side may share a key, as `from:to`.
+#[derive(Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+enum Cardinality {
+    #[serde(rename = "1:1")]
+    OneToOne,
+    #[serde(rename = "1:N")]
+    OneToMany,
+    #[serde(rename = "N:1")]
+    ManyToOne,
+    #[default]
+    #[serde(rename = "N:M", alias = "N:N")]
+    ManyToMany,
+}
+
+impl Cardinality {
+    const fn from_unique(self) -> bool {
+        matches!(self, Self::OneToOne | Self::OneToMany)
+    }
+
+    const fn to_unique(self) -> bool {
+        matches!(self, Self::OneToOne | Self::ManyToOne)
+    }
+
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::OneToOne => "1:1",
+            Self::OneToMany => "1:N",
+            Self::ManyToOne => "N:1",
+            Self::ManyToMany => "N:M",
+        }
+    }
+}
+
+/// Which side(s) of a relation must not have keys missing from the other side.
+#[derive(Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum OrphanCheck {
+    #[default]
+    From,
+    To,
+    Both,
+    None,
+}
+
+/// A table resolved to a local file by the lookup loader.
+struct ResolvedTable {
+    path:      String,
+    delimiter: Option<Delimiter>,
+}
+
+/// A relation violation, as a row of the consolidated error report.
+struct Violation {
+    table:      String,
+    row_number: u64,
+    field:      String,
+    error:      String,
+}
+
+/// The selected key columns of one side of a relation.
+struct KeyReader<'a> {
+    table:     &'a str,
+    columns:   Vec<&'a str>,
+    resolved:  &'a ResolvedTable,
+    transform: Option<&'a KeyTransform>,
+}
+
+impl KeyReader<'_> {
+    /// Stream the keys of the table, calling `f` with the 1-based row number and the key.
+    /// Keys with an empty column are null (as in SQL's MATCH SIMPLE) and are skipped.
+    fn for_each_key(&self, mut f: impl FnMut(u64, &[ByteString])) -> CliResult<()> {
+        let rconfig = Config::new(Some(&self.resolved.path)).delimiter(self.resolved.delimiter);
+        let mut rdr = rconfig.reader()?;
+        let headers = rdr.byte_headers()?.clone();
+        let mut indices = Vec::with_capacity(self.columns.len());
+        for column in &self.columns {
+            match headers.iter().position(|h| h == column.as_bytes()) {
+                Some(idx) => indices.push(idx),
+                None => {
+                    return fail_incorrectusage_clierror!(
+                        "Column \"{column}\" not found in table \"{}\" ({}).",
+                        self.table,
+                        self.resolved.path
+                    );
+                },
+            }
+        }
+
+        let mut record = ByteRecord::new();
+        let mut key: Vec<ByteString> = Vec::with_capacity(indices.len());
+        let mut row_number = 0_u64;
+        'rows: while rdr.read_byte_record(&mut record)? {
+            row_number += 1;
+            key.clear();
+            for &idx in &indices {
+                let field = record.get(idx).unwrap_or_default();
+                if field.is_empty() {
+                    continue 'rows;
+                }
+                key.push(match self.transform {
+                    Some(transform) => transform.apply(field),
+                    None => field.to_vec(),
+                });
+            }
+            f(row_number, &key);
+        }
+        Ok(())
+    }
+}
+
+fn display_key(key: &[ByteString]) -> String {
+    let values: Vec<_> = key.iter().map(|k| util::bytes_to_cow_str(k)).collect();
+    if values.len() == 1 {
+        values[0].to_string()
+    } else {
+        format!("({})", values.join(", "))
+    }
+}
+
+pub(super) fn run(args: &Args) -> CliResult<()> {
+    let Some(ref config_path) = args.arg_relations_config else {
+        return fail_incorrectusage_clierror!("No relations config file supplied.");
+    };
+    let config: RelationsConfig = match load_json(config_path) {
+        Ok(s) => match serde_json::from_str(&s) {
+            Ok(config) => config,
+            Err(e) => return fail_incorrectusage_clierror!("Invalid relations config: {e}"),
+        },
+        Err(e) => return fail_clierror!("Unable to retrieve relations config. error: {e}"),
+    };
+    if config.relations.is_empty() {
+        return fail_incorrectusage_clierror!("The relations config has no relations.");
+    }
+
+    // resolve every table once with the lookup loader, which also downloads & caches
+    // remote tables (http/https, dathere:// & ckan://)
+    let cache_dir = lookup::set_qsv_cache_dir(&args.flag_cache_dir)?;
+    let ckan_api = std::env::var("QSV_CKAN_API").unwrap_or_else(|_| args.flag_ckan_api.clone());
+    let ckan_token = std::env::var("QSV_CKAN_TOKEN")
+        .ok()
+        .or_else(|| args.flag_ckan_token.clone());
+    let base_dir = Path::new(config_path)
+        .parent()
+        .unwrap_or_else(|| Path::new(""));
+    let mut tables: HashMap<&str, ResolvedTable> = HashMap::with_capacity(config.tables.len());
+    for (name, table_ref) in &config.tables {
+        let (uri, delimiter) = match table_ref {
+            TableRef::Path(path) => (path, args.flag_delimiter),
+            TableRef::Spec { path, delimiter } => (path, delimiter.or(args.flag_delimiter)),
+        };
+        // relative paths are relative to the config file
+        let uri = if uri.contains("://") || Path::new(uri).is_absolute() {
+            uri.clone()
+        } else {
+            base_dir.join(uri).to_string_lossy().to_string()
+        };
+        let opts = LookupTableOptions {
+            name: name.clone(),
+            uri,
+            cache_age_secs: 3600,
+            cache_dir: cache_dir.clone(),
+            delimiter,
+            ckan_api_url: Some(ckan_api.clone()),
+            ckan_token: ckan_token.clone(),
+            timeout_secs: TIMEOUT_SECS.load(std::sync::atomic::Ordering::Relaxed),
+        };
+        let lookup_result = match load_lookup_table(&opts) {
+            Ok(result) => result,
+            Err(e) => return fail_clierror!("Error loading table \"{name}\": {e}"),
+        };
+        tables.insert(
+            name.as_str(),
+            ResolvedTable {
+                path: lookup_result.filepath,
+                delimiter,
+            },
+        );
+    }
+
+    let mut violations: Vec<Violation> = Vec::new();
+    for relation in &config.relations {
+        let relation_name = relation
+            .name
+            .clone()
+            .unwrap_or_else(|| format!("{}->{}", relation.from.table, relation.to.table));
+        let before = violations.len();
+        check_relation(relation, &relation_name, &tables, &mut violations)?;
+        if !args.flag_quiet {
+            winfo!(
+                "{relation_name}: {} violation(s).",
+                HumanCount((violations.len() - before) as u64)
+            );
+        }
+    }
+
+    if violations.is_empty() {
+        if !args.flag_quiet {
+            winfo!(
+                "All {} relation(s) valid.",
+                HumanCount(config.relations.len() as u64)
+            );
+        }
+        return Ok(());
+    }
+
+    let report_path = format!("{config_path}.validation-errors.tsv");
+    let mut wtr = csv::WriterBuilder::new()
+        .delimiter(b'\t')
+        .from_path(&report_path)?;
+    wtr.write_record(["table", "row_number", "field", "error"])?;
+    let mut itoa_buf = itoa::Buffer::new();
+    for v in &violations {
+        wtr.write_record([
+            v.table.as_str(),
+            itoa_buf.format(v.row_number),
+            v.field.as_str(),
+            v.error.as_str(),
+        ])?;
+    }
+    wtr.flush()?;
+
+    fail_clierror!(
+        "{} referential integrity violation(s) found in {} relation(s). See {report_path}.",
+        HumanCount(violations.len() as u64),
+        HumanCount(config.relations.len() as u64)
+    )
+}
+
+fn check_relation(
+    relation: &Relation,
+    relation_name: &str,
+    tables: &HashMap<&str, ResolvedTable>,
+    violations: &mut Vec<Violation>,
+) -> CliResult<()> {
+    let from_columns = relation.from.columns.names();
+    let to_columns = relation.to.columns.names();
+    if from_columns.len() != to_columns.len() {
+        return fail_incorrectusage_clierror!(
+            "[{relation_name}] from has {} key column(s), but to has {}.",
+            from_columns.len(),
+            to_columns.len()
+        );
+    }
+    let Some(from_table) = tables.get(relation.from.table.as_str()) else {
+        return fail_incorrectusage_clierror!(
+            "[{relation_name}] Unknown table \"{}\".",
+            relation.from.table
+        );
+    };
+    let Some(to_table) = tables.get(relation.to.table.as_str()) else {
+        return fail_incorrectusage_clierror!(
+            "[{relation_name}] Unknown table \"{}\".",
+            relation.to.table
+        );
+    };
+    let transform = KeyTransform::from_flag(relation.key_transform.as_deref())?;
+
+    let from = KeyReader {
+        table:     &relation.from.table,
+        columns:   from_columns,
+        resolved:  from_table,
+        transform: transform.as_ref(),
+    };
+    let to = KeyReader {
+        table:     &relation.to.table,
+        columns:   to_columns,
+        resolved:  to_table,
+        transform: transform.as_ref(),
+    };
+    let from_field = from.columns.join(",");
+    let to_field = to.columns.join(",");
+    let cardinality = relation.cardinality;
+
+    // key counts of the referenced side, with a cardinality check
+    let mut to_keys: HashMap<Vec<ByteString>, u64> = HashMap::new();
+    to.for_each_key(|row_number, key| {
+        let count = to_keys.entry(key.to_vec()).or_default();
+        *count += 1;
+        if *count > 1 && cardinality.to_unique() {
+            violations.push(Violation {
+                table: to.table.to_string(),
+                row_number,
+                field: to_field.clone(),
+                error: format!(
+                    "[{relation_name}] duplicate key {} in {}, but cardinality {} allows one {} \
+                     row per key",
+                    display_key(key),
+                    to.table,
+                    cardinality.as_str(),
+                    to.table
+                ),
+            });
+        }
+    })?;
+
+    let check_from_orphans = matches!(relation.orphans, OrphanCheck::From | OrphanCheck::Both);
+    let check_to_orphans = matches!(relation.orphans, OrphanCheck::To | OrphanCheck::Both);
+
+    let mut from_keys: HashMap<Vec<ByteString>, u64> = HashMap::new();
+    from.for_each_key(|row_number, key| {
+        if check_from_orphans && !to_keys.contains_key(key) {
+            violations.push(Violation {
+                table: from.table.to_string(),
+                row_number,
+                field: from_field.clone(),
+                error: format!(
+                    "[{relation_name}] orphan key {}: not found in {}.{to_field}",
+                    display_key(key),
+                    to.table
+                ),
+            });
+        }
+        if cardinality.from_unique() || check_to_orphans {
+            let count = from_keys.entry(key.to_vec()).or_default();
+            *count += 1;
+            if *count > 1 && cardinality.from_unique() {
+                violations.push(Violation {
+                    table: from.table.to_string(),
+                    row_number,
+                    field: from_field.clone(),
+                    error: format!(
+                        "[{relation_name}] duplicate key {} in {}, but cardinality {} allows one \
+                         {} row per key",
+                        display_key(key),
+                        from.table,
+                        cardinality.as_str(),
+                        from.table
+                    ),
+                });
+            }
+        }
+    })?;
+
+    if check_to_orphans {
+        // the row numbers of the referenced side are only needed for its orphans
+        to.for_each_key(|row_number, key| {
+            if !from_keys.contains_key(key) {
+                violations.push(Violation {
+                    table: to.table.to_string(),
+                    row_number,
+                    field: to_field.clone(),
+                    error: format!(
+                        "[{relation_name}] orphan key {}: not referenced by {}.{from_field}",
+                        display_key(key),
+                        from.table
+                    ),
+                });
+            }
+        })?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,245 @@
+//! Machine-readable validation summaries for `validate --report-format`.
+//!
+//! Errors are aggregated into classes - a field and the JSON Schema keyword (or business rule)
+//! it failed - with a count and the first example row numbers of each, and written next to
+//! the `validation-errors.tsv` report as JSON or as JUnit XML for CI systems.
+
+use std::{fmt::Write as _, fs};
+
+use foldhash::{HashMap, HashMapExt};
+use serde_json::json;
+
+use super::RowErrors;
+use crate::CliResult;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Json,
+    Junit,
+}
+
+impl ReportFormat {
+    pub fn from_flag(flag: &str) -> CliResult<Self> {
+        match flag.to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "junit" => Ok(Self::Junit),
+            _ => fail_incorrectusage_clierror!(
+                "Invalid --report-format: {flag}. Valid values are json and junit."
+            ),
+        }
+    }
+
+    const fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Junit => "xml",
+        }
+    }
+}
+
+/// The overall outcome of a validation run.
+pub struct ReportSummary<'a> {
+    pub input:              &'a str,
+    pub schema:             Option<&'a str>,
+    // BLAKE3 hash of the (translated) JSON Schema the data was validated against
+    pub schema_fingerprint: Option<String>,
+    pub rules:              Option<&'a str>,
+    pub total_records:      u64,
+    pub invalid_records:    u64,
+    pub warning_records:    u64,
+    pub fail_fast_stopped:  bool,
+}
+
+struct ErrorClass {
+    field:    String,
+    keyword:  String,
+    // whether the errors of this class make a row invalid (false for rule warnings)
+    failing:  bool,
+    count:    u64,
+    examples: Vec<u64>,
+}
+
+pub struct ValidationReport {
+    format:       ReportFormat,
+    max_examples: usize,
+    classes:      HashMap<(String, String), ErrorClass>,
+}
+
+impl ValidationReport {
+    pub fn new(format: ReportFormat, max_examples: usize) -> Self {
+        Self {
+            format,
+            max_examples,
+            classes: HashMap::new(),
+        }
+    }
+
+    /// Add the errors of a row to their error classes.
+    pub fn add(&mut self, row_number: u64, errors: &RowErrors) {
+        for (field, keyword, failing) in &errors.classes {
+            let class = self
+                .classes
+                .entry((field.clone(), keyword.clone()))
+                .or_insert_with(|| ErrorClass {
+                    field:    field.clone(),
+                    keyword:  keyword.clone(),
+                    failing:  *failing,
+                    count:    0,
+                    examples: Vec::new(),
+                });
+            class.count += 1;
+            // a row can fail the same class more than once, e.g. in an allOf
+            if class.examples.len() < self.max_examples
+                && class.examples.last() != Some(&row_number)
+            {
+                class.examples.push(row_number);
+            }
+        }
+    }
+
+    /// Write the report to `<input>.validation-report.<json|xml>`.
+    pub fn write(self, summary: &ReportSummary) -> CliResult<()> {
+        let format = self.format;
+        // most frequent error classes first
+        let mut classes: Vec<ErrorClass> = self.classes.into_values().collect();
+        classes.sort_unstable_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| a.field.cmp(&b.field))
+                .then_with(|| a.keyword.cmp(&b.keyword))
+        });
+
+        let report = match format {
+            ReportFormat::Json => to_json(&classes, summary),
+            ReportFormat::Junit => to_junit(&classes, summary),
+        };
+        fs::write(
+            format!("{}.validation-report.{}", summary.input, format.extension()),
+            report,
+        )?;
+        Ok(())
+    }
+}
+
+fn to_json(classes: &[ErrorClass], summary: &ReportSummary) -> String {
+    let errors: Vec<serde_json::Value> = classes
+        .iter()
+        .map(|class| {
+            json!({
+                "field": class.field,
+                "keyword": class.keyword,
+                "failing": class.failing,
+                "count": class.count,
+                "example_rows": class.examples,
+            })
+        })
+        .collect();
+    let report = json!({
+        "input": summary.input,
+        "schema": summary.schema,
+        "schema_fingerprint": summary.schema_fingerprint,
+        "rules": summary.rules,
+        "valid": summary.invalid_records == 0,
+        "fail_fast_stopped": summary.fail_fast_stopped,
+        "total_records": summary.total_records,
+        "valid_records": summary.total_records - summary.invalid_records,
+        "invalid_records": summary.invalid_records,
+        "warning_records": summary.warning_records,
+        "errors": errors,
+    });
+    // safety: a serde_json::Value always serializes
+    serde_json::to_string_pretty(&report).unwrap() + "\n"
+}
+
+fn to_junit(classes: &[ErrorClass], summary: &ReportSummary) -> String {
+    let input = xml_escape(summary.input);
+    let failures = classes.iter().filter(|class| class.failing).count();
+    // with no errors, a single passing test case
+    let tests = classes.len().max(1);
+
+    let mut xml = String::with_capacity(512 + classes.len() * 256);
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuites name=\"qsv validate\" tests=\"{tests}\" failures=\"{failures}\">"
+    );
+    let _ = writeln!(
+        xml,
+        "  <testsuite name=\"{input}\" tests=\"{tests}\" failures=\"{failures}\">"
+    );
+    xml.push_str("    <properties>\n");
+    let properties = [
+        ("schema", summary.schema.unwrap_or_default().to_string()),
+        (
+            "schema_fingerprint",
+            summary.schema_fingerprint.clone().unwrap_or_default(),
+        ),
+        ("rules", summary.rules.unwrap_or_default().to_string()),
+        ("total_records", summary.total_records.to_string()),
+        (
+            "valid_records",
+            (summary.total_records - summary.invalid_records).to_string(),
+        ),
+        ("invalid_records", summary.invalid_records.to_string()),
+        ("warning_records", summary.warning_records.to_string()),
+        ("fail_fast_stopped", summary.fail_fast_stopped.to_string()),
+    ];
+    for (name, value) in properties {
+        let _ = writeln!(
+            xml,
+            "      <property name=\"{name}\" value=\"{}\"/>",
+            xml_escape(&value)
+        );
+    }
+    xml.push_str("    </properties>\n");
+
+    if classes.is_empty() {
+        let _ = writeln!(
+            xml,
+            "    <testcase classname=\"{input}\" name=\"all records valid\"/>"
+        );
+    }
+    for class in classes {
+        let name = xml_escape(&format!("{} {}", class.field, class.keyword));
+        let examples = class
+            .examples
+            .iter()
+            .map(u64::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let _ = writeln!(xml, "    <testcase classname=\"{input}\" name=\"{name}\">");
+        if class.failing {
+            let _ = writeln!(
+                xml,
+                "      <failure type=\"{}\" message=\"{} errors\">example rows: \
+                 {examples}</failure>",
+                xml_escape(&class.keyword),
+                class.count
+            );
+        } else {
+            let _ = writeln!(
+                xml,
+                "      <system-out>{} warnings. example rows: {examples}</system-out>",
+                class.count
+            );
+        }
+        xml.push_str("    </testcase>\n");
+    }
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+fn xml_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
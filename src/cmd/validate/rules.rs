@@ -0,0 +1,231 @@
+//! Row-level business rules for `validate --rules`.
+//!
+//! A rules file is a JSON document with a list of rules, each a MiniJinja expression that
+//! must be truthy for a row to pass, e.g. `end_date >= start_date`. Rules and their message
+//! templates are compiled once and evaluated against every row in the same parallel loop as
+//! the JSON Schema validation, with violations reported in the same `.validation-errors.tsv`
+//! file.
+
+use csv::{ByteRecord, StringRecord};
+use minijinja::{Environment, Expression, Template, Value};
+use serde::Deserialize;
+
+use super::{RowErrors, load_json};
+use crate::{CliResult, util};
+
+/// The severity of a rule. Only violations at or above the `--fail-on` severity make a
+/// row invalid; the others are reported as warnings.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    #[default]
+    Error,
+}
+
+impl Severity {
+    pub fn from_flag(flag: &str) -> CliResult<Self> {
+        match flag.to_ascii_lowercase().as_str() {
+            "error" => Ok(Self::Error),
+            "warning" => Ok(Self::Warning),
+            _ => fail_incorrectusage_clierror!(
+                "Invalid --fail-on value: {flag}. Valid values are error and warning."
+            ),
+        }
+    }
+
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulesFile {
+    rules: Vec<RuleDef>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleDef {
+    id:         String,
+    expression: String,
+    #[serde(default)]
+    severity:   Severity,
+    message:    Option<String>,
+    // the field to attribute violations to in the error report
+    field:      Option<String>,
+}
+
+/// Load the rule definitions of a rules file (a local file or a URL).
+pub fn load(uri: &str) -> CliResult<Vec<RuleDef>> {
+    let rules_json = match load_json(uri) {
+        Ok(s) => s,
+        Err(e) => return fail_clierror!("Unable to retrieve rules file. error: {e}"),
+    };
+    let rules_file: RulesFile = match serde_json::from_str(&rules_json) {
+        Ok(rules_file) => rules_file,
+        Err(e) => return fail_incorrectusage_clierror!("Invalid rules file {uri}: {e}"),
+    };
+    if rules_file.rules.is_empty() {
+        return fail_incorrectusage_clierror!("Rules file {uri} has no rules.");
+    }
+    let mut ids: Vec<&str> = rules_file.rules.iter().map(|r| r.id.as_str()).collect();
+    ids.sort_unstable();
+    if let Some(dupe) = ids.windows(2).find(|w| w[0] == w[1]) {
+        return fail_incorrectusage_clierror!("Duplicate rule id \"{}\" in {uri}.", dupe[0]);
+    }
+    Ok(rules_file.rules)
+}
+
+/// The MiniJinja environment rules are compiled in, with qsv's usual filters.
+pub fn environment() -> Environment<'static> {
+    let mut env = Environment::new();
+    minijinja_contrib::add_to_environment(&mut env);
+    env.set_unknown_method_callback(minijinja_contrib::pycompat::unknown_method_callback);
+    #[cfg(not(feature = "lite"))]
+    crate::minijinja_filters::register(&mut env);
+    env
+}
+
+struct CompiledRule<'env, 'source> {
+    def:        &'source RuleDef,
+    expression: Expression<'env, 'source>,
+    message:    Option<Template<'env, 'source>>,
+}
+
+/// Rules compiled against the headers of the CSV being validated.
+pub struct RuleSet<'env, 'source> {
+    rules:     Vec<CompiledRule<'env, 'source>>,
+    // header names sanitized into MiniJinja identifiers, and the original headers
+    var_names: Vec<String>,
+    headers:   Vec<String>,
+    fail_on:   Severity,
+}
+
+impl<'env, 'source> RuleSet<'env, 'source> {
+    pub fn compile(
+        env: &'env Environment<'static>,
+        defs: &'source [RuleDef],
+        headers: &ByteRecord,
+        fail_on: Severity,
+    ) -> CliResult<Self> {
+        let mut rules = Vec::with_capacity(defs.len());
+        for def in defs {
+            let expression = match env.compile_expression(&def.expression) {
+                Ok(expression) => expression,
+                Err(e) => {
+                    return fail_incorrectusage_clierror!(
+                        "Cannot compile rule \"{}\" expression: {e}",
+                        def.id
+                    );
+                },
+            };
+            let message = match def.message {
+                Some(ref template) => match env.template_from_str(template) {
+                    Ok(template) => Some(template),
+                    Err(e) => {
+                        return fail_incorrectusage_clierror!(
+                            "Cannot compile rule \"{}\" message: {e}",
+                            def.id
+                        );
+                    },
+                },
+                None => None,
+            };
+            rules.push(CompiledRule {
+                def,
+                expression,
+                message,
+            });
+        }
+
+        let headers: Vec<String> = headers
+            .iter()
+            .map(|h| util::bytes_to_cow_str(h).into_owned())
+            .collect();
+        // like `apply`, headers are sanitized into valid MiniJinja identifiers. The original
+        // headers are also available through the `row` mapping, e.g. row["Start Date"].
+        let (var_names, _) = util::safe_header_names(
+            &StringRecord::from(headers.clone()),
+            true,
+            false,
+            None,
+            "",
+            true,
+            false,
+            false,
+        );
+
+        Ok(Self {
+            rules,
+            var_names,
+            headers,
+            fail_on,
+        })
+    }
+
+    /// Evaluate all rules against a record, whose first `headers.len()` fields are the row,
+    /// adding violations to the row's errors. The row is invalid if any violated rule has a
+    /// severity at or above `--fail-on`.
+    pub fn evaluate(&self, record: &ByteRecord, row_number: &str, errors: &mut RowErrors) {
+        let row: Vec<(&str, Value)> = self
+            .headers
+            .iter()
+            .zip(record.iter())
+            .map(|(h, field)| (h.as_str(), typed_value(field)))
+            .collect();
+        let mut context: Vec<(&str, Value)> = Vec::with_capacity(row.len() + 1);
+        context.push(("row", row.iter().cloned().collect::<Value>()));
+        context.extend(
+            self.var_names
+                .iter()
+                .zip(row.iter())
+                .map(|(name, (_, value))| (name.as_str(), value.clone())),
+        );
+        let context: Value = context.into_iter().collect();
+
+        for rule in &self.rules {
+            let def = rule.def;
+            let message = match rule.expression.eval(&context) {
+                Ok(result) if result.is_true() => continue,
+                Ok(_) => match rule.message {
+                    Some(ref template) => template
+                        .render(&context)
+                        .unwrap_or_else(|_| template.source().to_string()),
+                    None => format!("rule failed: {}", def.expression),
+                },
+                Err(e) => format!("rule could not be evaluated: {e}"),
+            };
+            let failing = def.severity >= self.fail_on;
+            errors.invalid |= failing;
+            errors.push(
+                row_number,
+                def.field.as_deref().unwrap_or("<RULE>"),
+                &format!("rule:{}", def.id),
+                failing,
+                &format!("{} {}: {message}", def.severity.as_str(), def.id),
+            );
+        }
+    }
+}
+
+/// Convert a CSV field to a typed MiniJinja value: empty fields are `none`, and integers &
+/// floats are numbers, so that e.g. `qty > 10` compares numerically.
+fn typed_value(field: &[u8]) -> Value {
+    if field.is_empty() {
+        return Value::from(());
+    }
+    if let Ok(int) = atoi_simd::parse::<i64, false, false>(field) {
+        return Value::from(int);
+    }
+    if let Ok(float) = fast_float2::parse::<f64, _>(field)
+        && float.is_finite()
+    {
+        return Value::from(float);
+    }
+    Value::from(util::bytes_to_cow_str(field).into_owned())
+}
@@ -0,0 +1,891 @@
+//! Frictionless Table Schema & CSVW metadata support for `validate`.
+//!
+//! Both descriptor flavors are first parsed into a common [`TableSpec`], which is then
+//! translated to an equivalent JSON Schema (draft 2020-12). Primary keys and `unique`
+//! constraints map to qsv's `uniqueCombinedWith` keyword and foreign keys map to
+//! `dynamicEnum`, so descriptors are validated by the same pipeline - and reported in the
+//! same `.validation-errors.tsv` file - as hand-written JSON Schemas.
+//!
+//! References:
+//! - <https://specs.frictionlessdata.io/table-schema/>
+//! - <https://www.w3.org/TR/tabular-metadata/>
+
+use std::path::Path;
+
+use csv::ByteRecord;
+use serde_json::{Map, Value, json};
+
+use crate::{CliResult, util};
+
+/// The kind of schema descriptor passed to `validate`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DescriptorKind {
+    JsonSchema,
+    TableSchema,
+    Csvw,
+}
+
+impl DescriptorKind {
+    /// Detect the descriptor kind from its top-level keys.
+    /// JSON Schemas have `properties`/`$schema`, Table Schemas have a `fields` array and
+    /// CSVW metadata has a `tableSchema` (single table) or `tables` (table group).
+    pub fn detect(descriptor: &Value) -> Self {
+        let Some(obj) = descriptor.as_object() else {
+            return Self::JsonSchema;
+        };
+        if obj.contains_key("properties") || obj.contains_key("$schema") {
+            Self::JsonSchema
+        } else if obj.get("fields").is_some_and(Value::is_array) {
+            Self::TableSchema
+        } else if obj.contains_key("tableSchema") || obj.get("tables").is_some_and(Value::is_array)
+        {
+            Self::Csvw
+        } else {
+            Self::JsonSchema
+        }
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::JsonSchema => "JSON Schema",
+            Self::TableSchema => "Table Schema",
+            Self::Csvw => "CSVW",
+        }
+    }
+}
+
+// RFC 3339 partial-time, which is also the Table Schema & CSVW default time format
+const TIME_PATTERN: &str = r"^([01]\d|2[0-3]):[0-5]\d:([0-5]\d|60)(\.\d+)?$";
+const YEARMONTH_PATTERN: &str = r"^\d{4}-(0[1-9]|1[0-2])$";
+const YEAR_PATTERN: &str = r"^-?\d{4}$";
+const BASE64_PATTERN: &str = r"^[A-Za-z0-9+/]*={0,2}$";
+
+#[derive(Default)]
+struct FieldSpec {
+    name:              String,
+    json_type:         &'static str,
+    required:          bool,
+    unique:            bool,
+    format:            Option<&'static str>,
+    // anchored regexes the value must match
+    patterns:          Vec<String>,
+    enum_values:       Option<Vec<Value>>,
+    min_length:        Option<u64>,
+    max_length:        Option<u64>,
+    minimum:           Option<Value>,
+    maximum:           Option<Value>,
+    exclusive_minimum: Option<Value>,
+    exclusive_maximum: Option<Value>,
+}
+
+struct ForeignKey {
+    field:           String,
+    resource:        String,
+    reference_field: String,
+}
+
+#[derive(Default)]
+struct TableSpec {
+    fields:       Vec<FieldSpec>,
+    primary_key:  Vec<String>,
+    foreign_keys: Vec<ForeignKey>,
+}
+
+/// Translate a Table Schema or CSVW descriptor to an equivalent JSON Schema.
+///
+/// `descriptor_path` is used to resolve relative foreign key resources, `input_path` is the
+/// CSV being validated (the target of self-referencing foreign keys and used to pick the
+/// table of a CSVW table group), and `headers` are its headers.
+pub fn to_json_schema(
+    descriptor: &Value,
+    kind: DescriptorKind,
+    descriptor_path: &Path,
+    input_path: &Path,
+    headers: &ByteRecord,
+) -> CliResult<Value> {
+    let base_dir = descriptor_path.parent().unwrap_or_else(|| Path::new(""));
+    let spec = match kind {
+        DescriptorKind::TableSchema => parse_table_schema(descriptor, base_dir, input_path)?,
+        DescriptorKind::Csvw => parse_csvw(descriptor, base_dir, input_path)?,
+        DescriptorKind::JsonSchema => return Ok(descriptor.clone()),
+    };
+
+    let header_names: Vec<String> = headers
+        .iter()
+        .map(|h| util::bytes_to_cow_str(h).into_owned())
+        .collect();
+    let missing: Vec<&str> = spec
+        .fields
+        .iter()
+        .filter(|f| !header_names.contains(&f.name))
+        .map(|f| f.name.as_str())
+        .collect();
+    if !missing.is_empty() {
+        return fail_clierror!(
+            "{} field(s) not found in the CSV headers: {}",
+            kind.name(),
+            missing.join(", ")
+        );
+    }
+
+    Ok(spec.into_json_schema(kind))
+}
+
+impl TableSpec {
+    fn into_json_schema(self, kind: DescriptorKind) -> Value {
+        let mut properties = Map::with_capacity(self.fields.len());
+        let mut unique_fields = Vec::new();
+
+        for mut field in self.fields {
+            if self.primary_key.contains(&field.name) {
+                field.required = true;
+            }
+            let mut prop = Map::new();
+            prop.insert(
+                "type".to_string(),
+                if field.required {
+                    json!(field.json_type)
+                } else {
+                    json!([field.json_type, "null"])
+                },
+            );
+            if let Some(format) = field.format {
+                prop.insert("format".to_string(), json!(format));
+            }
+            match field.patterns.len() {
+                0 => {},
+                1 => {
+                    prop.insert("pattern".to_string(), json!(field.patterns[0]));
+                },
+                _ => {
+                    let all_of: Vec<Value> = field
+                        .patterns
+                        .iter()
+                        .map(|p| json!({ "pattern": p }))
+                        .collect();
+                    prop.insert("allOf".to_string(), Value::Array(all_of));
+                },
+            }
+            if let Some(mut enum_values) = field.enum_values {
+                // an empty value is null, which enum must allow for optional fields
+                if !field.required {
+                    enum_values.push(Value::Null);
+                }
+                prop.insert("enum".to_string(), Value::Array(enum_values));
+            }
+            for (keyword, value) in [
+                ("minLength", field.min_length.map(Value::from)),
+                ("maxLength", field.max_length.map(Value::from)),
+                ("minimum", field.minimum),
+                ("maximum", field.maximum),
+                ("exclusiveMinimum", field.exclusive_minimum),
+                ("exclusiveMaximum", field.exclusive_maximum),
+            ] {
+                if let Some(value) = value {
+                    prop.insert(keyword.to_string(), value);
+                }
+            }
+            if let Some(fk) = self.foreign_keys.iter().find(|fk| fk.field == field.name) {
+                // null foreign keys are allowed, as in relational databases
+                prop.insert("if".to_string(), json!({ "not": { "type": "null" } }));
+                prop.insert(
+                    "then".to_string(),
+                    json!({ "dynamicEnum": dynenum_reference(&fk.resource, &fk.reference_field) }),
+                );
+            }
+            if field.unique {
+                unique_fields.push(field.name.clone());
+            }
+            properties.insert(field.name, Value::Object(prop));
+        }
+
+        let mut schema = Map::new();
+        schema.insert(
+            "$schema".to_string(),
+            json!("https://json-schema.org/draft/2020-12/schema"),
+        );
+        schema.insert(
+            "description".to_string(),
+            json!(format!("Translated by qsv validate from a {}", kind.name())),
+        );
+        schema.insert("type".to_string(), json!("object"));
+        schema.insert("properties".to_string(), Value::Object(properties));
+        if !self.primary_key.is_empty() {
+            schema.insert("uniqueCombinedWith".to_string(), json!(self.primary_key));
+        }
+        if !unique_fields.is_empty() {
+            // like in SQL, unique constraints don't apply to nulls
+            let all_of: Vec<Value> = unique_fields
+                .iter()
+                .map(|name| {
+                    let mut not_null = Map::with_capacity(1);
+                    not_null.insert(name.clone(), json!({ "not": { "type": "null" } }));
+                    json!({
+                        "if": { "properties": not_null },
+                        "then": { "uniqueCombinedWith": [name] }
+                    })
+                })
+                .collect();
+            schema.insert("allOf".to_string(), Value::Array(all_of));
+        }
+        Value::Object(schema)
+    }
+}
+
+/// Format a `dynamicEnum` reference to a column of a CSV resource.
+#[cfg(not(feature = "lite"))]
+fn dynenum_reference(resource: &str, column: &str) -> String {
+    // use the explicit cache config form, so the column is never mistaken for a URI
+    format!(";|{resource}|{column}")
+}
+
+#[cfg(feature = "lite")]
+fn dynenum_reference(resource: &str, column: &str) -> String {
+    format!("{resource}|{column}")
+}
+
+/// Resolve a foreign key resource: an empty resource references the input itself,
+/// URLs are used as is and relative paths are resolved against the descriptor's directory.
+fn resolve_resource(resource: &str, base_dir: &Path, input_path: &Path) -> String {
+    if resource.is_empty() {
+        return input_path.to_string_lossy().to_string();
+    }
+    if resource.contains("://") || Path::new(resource).is_absolute() {
+        return resource.to_string();
+    }
+    let relative = base_dir.join(resource);
+    if relative.exists() {
+        relative.to_string_lossy().to_string()
+    } else {
+        resource.to_string()
+    }
+}
+
+/// A Table Schema/CSVW value that is either a single string or an array of strings.
+fn string_or_array(value: Option<&Value>) -> Vec<String> {
+    match value {
+        Some(Value::String(s)) => vec![s.clone()],
+        Some(Value::Array(arr)) => arr
+            .iter()
+            .filter_map(|v| v.as_str().map(str::to_string))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Parse a numeric constraint, which may be given as a number or a string.
+fn numeric_constraint(value: &Value, json_type: &str) -> Option<Value> {
+    match value {
+        Value::Number(_) => Some(value.clone()),
+        Value::String(s) if json_type == "integer" => s.trim().parse::<i64>().ok().map(Value::from),
+        Value::String(s) => s.trim().parse::<f64>().ok().map(Value::from),
+        _ => None,
+    }
+}
+
+/// Coerce enum values to the field's JSON type, as Table Schema allows them to be
+/// given in their string representation.
+fn typed_enum_values(values: &[Value], json_type: &str) -> Vec<Value> {
+    values
+        .iter()
+        .map(|v| match (v, json_type) {
+            (Value::String(_), "integer" | "number") => {
+                numeric_constraint(v, json_type).unwrap_or_else(|| v.clone())
+            },
+            _ => v.clone(),
+        })
+        .collect()
+}
+
+/// Apply the min/max constraints of a field, warning about those that can't be
+/// checked on its JSON type (e.g. date ranges).
+fn set_range(
+    field: &mut FieldSpec,
+    kind: DescriptorKind,
+    min: Option<&Value>,
+    max: Option<&Value>,
+    exclusive_min: Option<&Value>,
+    exclusive_max: Option<&Value>,
+) {
+    if min.is_none() && max.is_none() && exclusive_min.is_none() && exclusive_max.is_none() {
+        return;
+    }
+    if field.json_type == "string" {
+        wwarn!(
+            "{} minimum/maximum constraints on field \"{}\" are only supported for numeric types \
+             and are ignored.",
+            kind.name(),
+            field.name
+        );
+        return;
+    }
+    let json_type = field.json_type;
+    field.minimum = min.and_then(|v| numeric_constraint(v, json_type));
+    field.maximum = max.and_then(|v| numeric_constraint(v, json_type));
+    field.exclusive_minimum = exclusive_min.and_then(|v| numeric_constraint(v, json_type));
+    field.exclusive_maximum = exclusive_max.and_then(|v| numeric_constraint(v, json_type));
+}
+
+/// Warn about missing value markers other than the empty string, as only empty
+/// values are treated as null.
+fn check_missing_values(markers: &[String], kind: DescriptorKind) {
+    let others: Vec<&str> = markers
+        .iter()
+        .filter(|m| !m.is_empty())
+        .map(String::as_str)
+        .collect();
+    if !others.is_empty() {
+        wwarn!(
+            "Only empty values are treated as missing. The {} missing value marker(s) {} are \
+             validated as regular values.",
+            kind.name(),
+            others.join(", ")
+        );
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Frictionless Table Schema
+// ---------------------------------------------------------------------------
+
+fn parse_table_schema(schema: &Value, base_dir: &Path, input_path: &Path) -> CliResult<TableSpec> {
+    let kind = DescriptorKind::TableSchema;
+    let mut spec = TableSpec::default();
+
+    if let Some(missing_values) = schema.get("missingValues") {
+        check_missing_values(&string_or_array(Some(missing_values)), kind);
+    }
+
+    // safety: DescriptorKind::detect checked that fields is an array
+    for field_def in schema["fields"].as_array().unwrap() {
+        let Some(name) = field_def.get("name").and_then(Value::as_str) else {
+            return fail_clierror!("Table Schema field without a name: {field_def}");
+        };
+        let field_type = field_def
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or("string");
+        let format = field_def
+            .get("format")
+            .and_then(Value::as_str)
+            .unwrap_or("default");
+        let format = format.strip_prefix("fmt:").unwrap_or(format);
+
+        let mut field = FieldSpec {
+            name: name.to_string(),
+            ..FieldSpec::default()
+        };
+
+        match field_type {
+            "string" => {
+                field.json_type = "string";
+                match format {
+                    "email" => field.format = Some("email"),
+                    "uri" => field.format = Some("uri"),
+                    "uuid" => field.format = Some("uuid"),
+                    "binary" => field.patterns.push(BASE64_PATTERN.to_string()),
+                    _ => {},
+                }
+            },
+            "number" | "integer" => {
+                let non_default_chars = field_def
+                    .get("decimalChar")
+                    .is_some_and(|c| c.as_str() != Some("."))
+                    || field_def.get("groupChar").is_some()
+                    || field_def.get("bareNumber") == Some(&Value::Bool(false));
+                if non_default_chars {
+                    wwarn!(
+                        "Table Schema decimalChar, groupChar and bareNumber are not supported. \
+                         Field \"{name}\" is validated as a string."
+                    );
+                    field.json_type = "string";
+                } else {
+                    field.json_type = if field_type == "integer" {
+                        "integer"
+                    } else {
+                        "number"
+                    };
+                }
+            },
+            "boolean" => {
+                // booleans are validated against their true & false values
+                field.json_type = "string";
+                let mut true_values = string_or_array(field_def.get("trueValues"));
+                if true_values.is_empty() {
+                    true_values = vec!["true".into(), "True".into(), "TRUE".into(), "1".into()];
+                }
+                let mut false_values = string_or_array(field_def.get("falseValues"));
+                if false_values.is_empty() {
+                    false_values = vec!["false".into(), "False".into(), "FALSE".into(), "0".into()];
+                }
+                field.enum_values = Some(
+                    true_values
+                        .into_iter()
+                        .chain(false_values)
+                        .map(Value::String)
+                        .collect(),
+                );
+            },
+            "date" | "time" | "datetime" => {
+                field.json_type = "string";
+                match (field_type, format) {
+                    (_, "any") => {},
+                    ("date", "default") => field.format = Some("date"),
+                    ("datetime", "default") => field.format = Some("date-time"),
+                    ("time", "default") => field.patterns.push(TIME_PATTERN.to_string()),
+                    (_, pattern) => field.patterns.push(strftime_to_regex(pattern)?),
+                }
+            },
+            "year" => field.json_type = "integer",
+            "yearmonth" => {
+                field.json_type = "string";
+                field.patterns.push(YEARMONTH_PATTERN.to_string());
+            },
+            "duration" => {
+                field.json_type = "string";
+                field.format = Some("duration");
+            },
+            "object" | "array" | "list" | "geopoint" | "geojson" | "any" => {
+                field.json_type = "string";
+            },
+            other => {
+                return fail_incorrectusage_clierror!(
+                    "Unsupported Table Schema type \"{other}\" for field \"{name}\"."
+                );
+            },
+        }
+
+        if let Some(constraints) = field_def.get("constraints") {
+            field.required = constraints.get("required") == Some(&Value::Bool(true));
+            field.unique = constraints.get("unique") == Some(&Value::Bool(true));
+            field.min_length = constraints.get("minLength").and_then(Value::as_u64);
+            field.max_length = constraints.get("maxLength").and_then(Value::as_u64);
+            if let Some(pattern) = constraints.get("pattern").and_then(Value::as_str) {
+                // Table Schema patterns are implicitly anchored (XML Schema regex)
+                field.patterns.push(format!("^(?:{pattern})$"));
+            }
+            if let Some(values) = constraints.get("enum").and_then(Value::as_array) {
+                field.enum_values = Some(typed_enum_values(values, field.json_type));
+            }
+            set_range(
+                &mut field,
+                kind,
+                constraints.get("minimum"),
+                constraints.get("maximum"),
+                constraints.get("exclusiveMinimum"),
+                constraints.get("exclusiveMaximum"),
+            );
+        }
+
+        spec.fields.push(field);
+    }
+
+    spec.primary_key = string_or_array(schema.get("primaryKey"));
+
+    if let Some(foreign_keys) = schema.get("foreignKeys").and_then(Value::as_array) {
+        for fk in foreign_keys {
+            let fields = string_or_array(fk.get("fields"));
+            let reference = fk.get("reference").unwrap_or(&Value::Null);
+            let reference_fields = string_or_array(reference.get("fields"));
+            if fields.len() != 1 || reference_fields.len() != 1 {
+                return fail_incorrectusage_clierror!(
+                    "Only single-field Table Schema foreign keys are supported: {fk}"
+                );
+            }
+            let resource = reference
+                .get("resource")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            spec.foreign_keys.push(ForeignKey {
+                field:           fields[0].clone(),
+                resource:        resolve_resource(resource, base_dir, input_path),
+                reference_field: reference_fields[0].clone(),
+            });
+        }
+    }
+
+    Ok(spec)
+}
+
+/// Convert a Table Schema strftime-style date/time pattern to an anchored regex.
+/// This is a syntactic check - e.g. it does not reject February 30th.
+fn strftime_to_regex(pattern: &str) -> CliResult<String> {
+    let mut regex = String::from("^");
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            regex.push_str(&regex::escape(&c.to_string()));
+            continue;
+        }
+        // skip chrono's padding modifiers, e.g. %-d
+        let mut spec = chars.next();
+        if matches!(spec, Some('-' | '_' | '0')) {
+            spec = chars.next();
+        }
+        let part = match spec {
+            Some('Y') => r"\d{4}",
+            Some('y' | 'C') => r"\d{2}",
+            Some('m') => r"(0?[1-9]|1[0-2])",
+            Some('d' | 'e') => r"( ?[1-9]|0[1-9]|[12]\d|3[01])",
+            Some('H' | 'k') => r"( ?\d|[01]\d|2[0-3])",
+            Some('I' | 'l') => r"( ?[1-9]|0[1-9]|1[0-2])",
+            Some('M') => r"[0-5]\d",
+            Some('S') => r"([0-5]\d|60)",
+            Some('f') => r"\d+",
+            Some('p' | 'P') => r"(AM|PM|am|pm)",
+            Some('b' | 'h' | 'a') => r"[A-Za-z]{3}",
+            Some('B' | 'A') => r"[A-Za-z]+",
+            Some('j') => r"\d{3}",
+            Some('z') => r"(Z|[+-]\d{2}:?\d{2})",
+            Some('Z') => r"[A-Za-z]+",
+            Some('T') => r"([01]\d|2[0-3]):[0-5]\d:([0-5]\d|60)",
+            Some('D') => r"(0?[1-9]|1[0-2])/(0?[1-9]|[12]\d|3[01])/\d{2}",
+            Some('F') => r"\d{4}-(0[1-9]|1[0-2])-(0[1-9]|[12]\d|3[01])",
+            Some('%') => "%",
+            other => {
+                return fail_incorrectusage_clierror!(
+                    "Unsupported date/time format specifier %{} in \"{pattern}\".",
+                    other.map(String::from).unwrap_or_default()
+                );
+            },
+        };
+        regex.push_str(part);
+    }
+    regex.push('$');
+    Ok(regex)
+}
+
+// ---------------------------------------------------------------------------
+// CSVW
+// ---------------------------------------------------------------------------
+
+fn parse_csvw(metadata: &Value, base_dir: &Path, input_path: &Path) -> CliResult<TableSpec> {
+    let kind = DescriptorKind::Csvw;
+
+    // a table group describes several tables - pick the one describing the input
+    let table = if let Some(tables) = metadata.get("tables").and_then(Value::as_array) {
+        let input_name = input_path.file_name().map(|n| n.to_string_lossy());
+        let matching = tables.iter().find(|t| {
+            t.get("url").and_then(Value::as_str).is_some_and(|url| {
+                input_name.as_deref() == Some(url.rsplit('/').next().unwrap_or(url))
+            })
+        });
+        match (matching, tables.len()) {
+            (Some(table), _) => table,
+            (None, 1) => &tables[0],
+            (None, _) => {
+                return fail_incorrectusage_clierror!(
+                    "No table in the CSVW table group has a url matching {}.",
+                    input_path.display()
+                );
+            },
+        }
+    } else {
+        metadata
+    };
+
+    let Some(table_schema) = table.get("tableSchema") else {
+        return fail_clierror!("CSVW table has no tableSchema.");
+    };
+    if table_schema.is_string() {
+        return fail_incorrectusage_clierror!(
+            "Referenced CSVW tableSchema documents are not supported. Inline the schema in the \
+             metadata file."
+        );
+    }
+
+    let mut spec = TableSpec::default();
+    // column references use the column names, the CSV headers are the column titles
+    let mut names_to_titles: Vec<(String, String)> = Vec::new();
+
+    let columns = table_schema
+        .get("columns")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for column in columns {
+        if column.get("virtual") == Some(&Value::Bool(true)) {
+            continue;
+        }
+        let title = match column.get("titles") {
+            Some(Value::String(s)) => Some(s.clone()),
+            Some(Value::Array(arr)) => arr.first().and_then(Value::as_str).map(str::to_string),
+            // language map, e.g. {"en": "Name"}
+            Some(Value::Object(map)) => map.values().find_map(|v| match v {
+                Value::String(s) => Some(s.clone()),
+                Value::Array(arr) => arr.first().and_then(Value::as_str).map(str::to_string),
+                _ => None,
+            }),
+            _ => None,
+        };
+        let name = column.get("name").and_then(Value::as_str);
+        let Some(header) = title.or_else(|| name.map(str::to_string)) else {
+            return fail_clierror!("CSVW column without a name or titles: {column}");
+        };
+        names_to_titles.push((name.unwrap_or(&header).to_string(), header.clone()));
+
+        if let Some(null) = column.get("null") {
+            check_missing_values(&string_or_array(Some(null)), kind);
+        }
+
+        let mut field = FieldSpec {
+            name: header,
+            required: column.get("required") == Some(&Value::Bool(true)),
+            ..FieldSpec::default()
+        };
+
+        let (base, datatype) = match column.get("datatype") {
+            Some(Value::String(s)) => (s.as_str(), None),
+            Some(dt @ Value::Object(_)) => (
+                dt.get("base").and_then(Value::as_str).unwrap_or("string"),
+                Some(dt),
+            ),
+            _ => ("string", None),
+        };
+        let format = datatype.and_then(|dt| dt.get("format"));
+        let format_str = format.and_then(Value::as_str);
+
+        match base {
+            "string" | "normalizedString" | "token" | "language" | "Name" | "NMTOKEN" | "xml"
+            | "html" | "json" | "any" | "anyAtomicType" => {
+                field.json_type = "string";
+                if let Some(pattern) = format_str {
+                    // string formats are regexes that must match the whole value
+                    field.patterns.push(format!("^(?:{pattern})$"));
+                }
+            },
+            "anyURI" => {
+                field.json_type = "string";
+                field.format = Some("uri");
+            },
+            "integer" | "long" | "int" | "short" | "byte" | "nonNegativeInteger"
+            | "positiveInteger" | "unsignedLong" | "unsignedInt" | "unsignedShort"
+            | "unsignedByte" | "negativeInteger" | "nonPositiveInteger" => {
+                field.json_type = "integer";
+                field.minimum = match base {
+                    "nonNegativeInteger" | "unsignedLong" | "unsignedInt" | "unsignedShort"
+                    | "unsignedByte" => Some(json!(0)),
+                    "positiveInteger" => Some(json!(1)),
+                    _ => None,
+                };
+                field.maximum = match base {
+                    "negativeInteger" => Some(json!(-1)),
+                    "nonPositiveInteger" => Some(json!(0)),
+                    _ => None,
+                };
+            },
+            "number" | "decimal" | "double" | "float" => {
+                if format.is_some() {
+                    wwarn!(
+                        "CSVW numeric formats are not supported. Column \"{}\" is validated as a \
+                         string.",
+                        field.name
+                    );
+                    field.json_type = "string";
+                } else {
+                    field.json_type = "number";
+                }
+            },
+            "boolean" => {
+                field.json_type = "string";
+                let values: Vec<Value> = match format_str.and_then(|f| f.split_once('|')) {
+                    Some((t, f)) => vec![json!(t), json!(f)],
+                    None => vec![json!("true"), json!("false"), json!("1"), json!("0")],
+                };
+                field.enum_values = Some(values);
+            },
+            "date" | "dateTime" | "datetime" | "dateTimeStamp" | "time" => {
+                field.json_type = "string";
+                match (base, format_str) {
+                    (_, Some(pattern)) => field.patterns.push(uts35_to_regex(pattern)?),
+                    ("date", None) => field.format = Some("date"),
+                    ("time", None) => field.patterns.push(TIME_PATTERN.to_string()),
+                    (_, None) => field.format = Some("date-time"),
+                }
+            },
+            "gYear" => {
+                field.json_type = "string";
+                field.patterns.push(YEAR_PATTERN.to_string());
+            },
+            "gYearMonth" => {
+                field.json_type = "string";
+                field.patterns.push(YEARMONTH_PATTERN.to_string());
+            },
+            "duration" | "dayTimeDuration" | "yearMonthDuration" => {
+                field.json_type = "string";
+                field.format = Some("duration");
+            },
+            other => {
+                return fail_incorrectusage_clierror!(
+                    "Unsupported CSVW datatype \"{other}\" for column \"{}\".",
+                    field.name
+                );
+            },
+        }
+
+        if let Some(dt) = datatype {
+            if let Some(length) = dt.get("length").and_then(Value::as_u64) {
+                field.min_length = Some(length);
+                field.max_length = Some(length);
+            }
+            if let Some(min_length) = dt.get("minLength").and_then(Value::as_u64) {
+                field.min_length = Some(min_length);
+            }
+            if let Some(max_length) = dt.get("maxLength").and_then(Value::as_u64) {
+                field.max_length = Some(max_length);
+            }
+            let (implicit_min, implicit_max) = (field.minimum.take(), field.maximum.take());
+            set_range(
+                &mut field,
+                kind,
+                dt.get("minimum").or_else(|| dt.get("minInclusive")),
+                dt.get("maximum").or_else(|| dt.get("maxInclusive")),
+                dt.get("minExclusive"),
+                dt.get("maxExclusive"),
+            );
+            field.minimum = field.minimum.take().or(implicit_min);
+            field.maximum = field.maximum.take().or(implicit_max);
+        }
+
+        spec.fields.push(field);
+    }
+
+    let title_of = |name: &str| -> String {
+        names_to_titles
+            .iter()
+            .find(|(n, _)| n == name)
+            .map_or_else(|| name.to_string(), |(_, t)| t.clone())
+    };
+
+    spec.primary_key = string_or_array(table_schema.get("primaryKey"))
+        .iter()
+        .map(|name| title_of(name))
+        .collect();
+
+    if let Some(foreign_keys) = table_schema.get("foreignKeys").and_then(Value::as_array) {
+        for fk in foreign_keys {
+            let columns = string_or_array(fk.get("columnReference"));
+            let reference = fk.get("reference").unwrap_or(&Value::Null);
+            let reference_columns = string_or_array(reference.get("columnReference"));
+            if columns.len() != 1 || reference_columns.len() != 1 {
+                return fail_incorrectusage_clierror!(
+                    "Only single-column CSVW foreign keys are supported: {fk}"
+                );
+            }
+            let Some(resource) = reference.get("resource").and_then(Value::as_str) else {
+                return fail_incorrectusage_clierror!(
+                    "Only CSVW foreign keys with a reference resource are supported: {fk}"
+                );
+            };
+            // the referenced column is read by its header, which is usually the column name
+            spec.foreign_keys.push(ForeignKey {
+                field:           title_of(&columns[0]),
+                resource:        resolve_resource(resource, base_dir, input_path),
+                reference_field: reference_columns[0].clone(),
+            });
+        }
+    }
+
+    Ok(spec)
+}
+
+/// Convert a CSVW (Unicode TR35) date/time format pattern to an anchored regex.
+/// This is a syntactic check - e.g. it does not reject February 30th.
+fn uts35_to_regex(pattern: &str) -> CliResult<String> {
+    let mut regex = String::from("^");
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        // quoted literal, e.g. 'T'
+        if c == '\'' {
+            let end = chars[i + 1..]
+                .iter()
+                .position(|&q| q == '\'')
+                .map_or(chars.len(), |p| i + 1 + p);
+            let literal: String = chars[i + 1..end].iter().collect();
+            regex.push_str(&regex::escape(if literal.is_empty() {
+                "'"
+            } else {
+                &literal
+            }));
+            i = end + 1;
+            continue;
+        }
+        if !c.is_ascii_alphabetic() {
+            regex.push_str(&regex::escape(&c.to_string()));
+            i += 1;
+            continue;
+        }
+        let run = chars[i..].iter().take_while(|&&r| r == c).count();
+        let part = match (c, run) {
+            ('y', 2) => r"\d{2}".to_string(),
+            ('y', _) => r"\d{4}".to_string(),
+            ('M', 1) => r"([1-9]|1[0-2])".to_string(),
+            ('M', 2) => r"(0[1-9]|1[0-2])".to_string(),
+            ('M', 3) | ('E', 1..=3) => r"[A-Za-z]{3}".to_string(),
+            ('M' | 'E', _) => r"[A-Za-z]+".to_string(),
+            ('d', 1) => r"([1-9]|[12]\d|3[01])".to_string(),
+            ('d', _) => r"(0[1-9]|[12]\d|3[01])".to_string(),
+            ('H', 1) => r"(1?\d|2[0-3])".to_string(),
+            ('H', _) => r"([01]\d|2[0-3])".to_string(),
+            ('h', 1) => r"([1-9]|1[0-2])".to_string(),
+            ('h', _) => r"(0[1-9]|1[0-2])".to_string(),
+            ('m' | 's', 1) => r"[1-5]?\d".to_string(),
+            ('m' | 's', _) => r"[0-5]\d".to_string(),
+            ('S', n) => format!(r"\d{{{n}}}"),
+            ('a', _) => r"(AM|PM|am|pm)".to_string(),
+            ('X', _) => r"(Z|[+-]\d{2}(:?\d{2})?)".to_string(),
+            ('x' | 'Z', _) => r"([+-]\d{2}(:?\d{2})?)".to_string(),
+            _ => {
+                return fail_incorrectusage_clierror!(
+                    "Unsupported date/time format field \"{}\" in \"{pattern}\".",
+                    chars[i..i + run].iter().collect::<String>()
+                );
+            },
+        };
+        regex.push_str(&part);
+        i += run;
+    }
+    regex.push('$');
+    Ok(regex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_descriptor_kind() {
+        assert_eq!(
+            DescriptorKind::detect(&json!({"$schema": "x", "properties": {}})),
+            DescriptorKind::JsonSchema
+        );
+        assert_eq!(
+            DescriptorKind::detect(&json!({"fields": [{"name": "a"}]})),
+            DescriptorKind::TableSchema
+        );
+        assert_eq!(
+            DescriptorKind::detect(&json!({"url": "a.csv", "tableSchema": {"columns": []}})),
+            DescriptorKind::Csvw
+        );
+        assert_eq!(
+            DescriptorKind::detect(&json!({"tables": [{"url": "a.csv"}]})),
+            DescriptorKind::Csvw
+        );
+    }
+
+    #[test]
+    fn test_date_format_regexes() {
+        let re = regex::Regex::new(&strftime_to_regex("%d/%m/%Y").unwrap()).unwrap();
+        assert!(re.is_match("31/12/2024"));
+        assert!(!re.is_match("2024-12-31"));
+
+        let re = regex::Regex::new(&uts35_to_regex("yyyy-MM-dd'T'HH:mm:ss").unwrap()).unwrap();
+        assert!(re.is_match("2024-12-31T23:59:00"));
+        assert!(!re.is_match("2024-12-31 23:59:00"));
+
+        assert!(strftime_to_regex("%Q").is_err());
+        assert!(uts35_to_regex("yyyy-QQ").is_err());
+    }
+}
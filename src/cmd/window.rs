@@ -0,0 +1,509 @@
+static USAGE: &str = r#"
+Compute window functions & rolling aggregations using the Polars engine.
+
+For each of the --columns, every window function in <funcs> is computed and appended
+to the input as a new column named <column>_<function>[_<param>]
+(e.g. "sales_cumsum", "sales_lag_1", "sales_rolling_mean_7d").
+
+With --partition-by, the functions are computed separately for each partition
+(e.g. a running total per customer). With --order-by, the rows of each partition are
+sorted before the functions are computed (e.g. by date for lag/lead & rolling windows).
+The output always keeps the original row order of the input.
+
+The supported window functions are:
+  cumsum              Cumulative sum
+  cumprod             Cumulative product
+  cummin              Cumulative minimum
+  cummax              Cumulative maximum
+  cumcount            Cumulative count of non-null values
+  lag[:<n>]           Value of the n-th previous row (default: 1)
+  lead[:<n>]          Value of the n-th next row (default: 1)
+  rank                Rank with gaps for ties (1, 2, 2, 4). See --descending.
+  dense_rank          Rank without gaps for ties (1, 2, 2, 3). See --descending.
+  row_number          Ordinal rank - ties are broken by row order (1, 2, 3, 4).
+  pct_change[:<n>]    Percentage change from the n-th previous row (default: 1)
+  rolling_mean:<w>    Rolling mean over a window of <w>.
+  rolling_sum:<w>     Rolling sum over a window of <w>.
+  rolling_min:<w>     Rolling minimum over a window of <w>.
+  rolling_max:<w>     Rolling maximum over a window of <w>.
+  rolling_std:<w>     Rolling sample standard deviation over a window of <w>.
+                      For the rolling functions, <w> is either:
+                        - an integer - a window of the last <w> rows (e.g. rolling_mean:7), or
+                        - a duration - a time-based window (e.g. rolling_mean:7d), in which case
+                          --order-by must be a single Date or Datetime column, and the window
+                          covers the rows whose --order-by value is in (value - <w>, value].
+                          Durations are made up of an integer & a unit:
+                          ns, us, ms, s, m, h, d, w, mo, q & y (e.g. 90m, 1d12h, 3mo).
+  ewm_mean[:<alpha>]  Exponentially-weighted moving mean with smoothing factor <alpha>,
+                      where 0 < alpha <= 1 (default: 0.5).
+
+Examples:
+
+  # running total & 7-row rolling mean of sales per region, ordered by date
+  qsv window --columns sales --partition-by region --order-by date \
+    cumsum,rolling_mean:7 sales.csv
+
+  # previous & next day's price and the day-over-day change for each ticker
+  qsv window -c price -p ticker -O date lag,lead,pct_change prices.csv
+
+  # 30-day time-based rolling average of two columns
+  qsv window -c temp,humidity -O date rolling_mean:30d weather.csv
+
+  # rank each salesperson's sales within their region, highest first
+  qsv window -c sales -p region --descending rank,dense_rank sales.csv
+
+For more examples, see https://github.com/dathere/qsv/blob/master/tests/test_window.rs.
+
+Usage:
+    qsv window [options] --columns <cols> <funcs> <input>
+    qsv window --help
+
+window arguments:
+    <funcs>                The comma-separated window function(s) to compute.
+                           See the list of supported functions above.
+    <input>                The input CSV file. The file must have headers.
+                           If the file has a pschema.json file, it will be used to
+                           inform the data types of the columns unless --infer-len is
+                           explicitly set to a value other than the default of 10,000 rows.
+                           Stdin is not supported.
+
+window options:
+    -c, --columns <cols>   The column(s) the window functions are computed on.
+                           Specify multiple columns by separating them with a comma.
+    -p, --partition-by <cols>  The column(s) to partition the rows by. The functions are
+                           computed separately for each unique combination of values.
+                           If omitted, the whole input is a single partition.
+    -O, --order-by <cols>  The column(s) to sort the rows of each partition by before
+                           computing the functions. If omitted, the input order is used.
+    --descending           Sort --order-by descending & rank the highest values first.
+                           Not allowed with time-based rolling windows.
+    --try-parsedates       Attempt to parse columns as dates. Automatically enabled
+                           when a time-based rolling window is used.
+    --infer-len <arg>      Number of rows to scan when inferring schema.
+                           Set to 0 to scan entire file. [default: 10000]
+    --ignore-errors        Skip rows that can't be parsed.
+
+Common options:
+    -h, --help             Display this message
+    -o, --output <file>    Write output to <file> instead of stdout.
+    -d, --delimiter <arg>  The field delimiter for reading/writing CSV data.
+                           Must be a single character. (default: ,)
+    -q, --quiet            Do not print the result shape to stderr.
+"#;
+
+use std::{fs::File, io, io::Write, path::Path};
+
+use foldhash::HashSet;
+use polars::prelude::*;
+use serde::Deserialize;
+
+use crate::{CliResult, config::Delimiter, util};
+
+#[derive(Deserialize)]
+struct Args {
+    arg_funcs:           String,
+    arg_input:           String,
+    flag_columns:        String,
+    flag_partition_by:   Option<String>,
+    flag_order_by:       Option<String>,
+    flag_descending:     bool,
+    flag_try_parsedates: bool,
+    flag_infer_len:      usize,
+    flag_ignore_errors:  bool,
+    flag_output:         Option<String>,
+    flag_delimiter:      Option<Delimiter>,
+    flag_quiet:          bool,
+}
+
+// IMPORTANT: This must be kept in sync with the default value
+// of the --infer-len option in the USAGE string above.
+const DEFAULT_INFER_LEN: usize = 10000;
+
+const ROW_ORDER_COL: &str = "__qsv_row_order__";
+
+/// The size of a rolling window - a number of rows or a duration.
+#[derive(Debug, Clone, PartialEq)]
+enum WindowSize {
+    Rows(usize),
+    Duration(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RollingAgg {
+    Mean,
+    Sum,
+    Min,
+    Max,
+    Std,
+}
+
+/// A parsed window function from <funcs>.
+#[derive(Debug, Clone, PartialEq)]
+enum WindowFunc {
+    CumSum,
+    CumProd,
+    CumMin,
+    CumMax,
+    CumCount,
+    Lag(i64),
+    Lead(i64),
+    Rank,
+    DenseRank,
+    RowNumber,
+    PctChange(i64),
+    Rolling(RollingAgg, WindowSize),
+    EwmMean(f64),
+}
+
+impl WindowFunc {
+    /// Parse a single window function spec, e.g. `lag:2` or `rolling_mean:7d`.
+    fn parse(spec: &str) -> Result<Self, String> {
+        let spec = spec.trim();
+        let (name, param) = match spec.split_once(':') {
+            Some((name, param)) => (name.trim().to_lowercase(), Some(param.trim())),
+            None => (spec.to_lowercase(), None),
+        };
+
+        let parse_offset = |default: i64| -> Result<i64, String> {
+            match param {
+                None => Ok(default),
+                Some(p) => match p.parse::<i64>() {
+                    Ok(n) if n > 0 => Ok(n),
+                    _ => Err(format!(
+                        "Invalid offset \"{p}\" for {name}: must be a positive integer."
+                    )),
+                },
+            }
+        };
+        let no_param = |func: WindowFunc| -> Result<WindowFunc, String> {
+            if param.is_some() {
+                Err(format!("{name} does not take a parameter."))
+            } else {
+                Ok(func)
+            }
+        };
+
+        match name.as_str() {
+            "cumsum" => no_param(WindowFunc::CumSum),
+            "cumprod" => no_param(WindowFunc::CumProd),
+            "cummin" => no_param(WindowFunc::CumMin),
+            "cummax" => no_param(WindowFunc::CumMax),
+            "cumcount" => no_param(WindowFunc::CumCount),
+            "lag" => Ok(WindowFunc::Lag(parse_offset(1)?)),
+            "lead" => Ok(WindowFunc::Lead(parse_offset(1)?)),
+            "rank" => no_param(WindowFunc::Rank),
+            "dense_rank" => no_param(WindowFunc::DenseRank),
+            "row_number" => no_param(WindowFunc::RowNumber),
+            "pct_change" => Ok(WindowFunc::PctChange(parse_offset(1)?)),
+            "rolling_mean" | "rolling_sum" | "rolling_min" | "rolling_max" | "rolling_std" => {
+                let agg = match name.as_str() {
+                    "rolling_mean" => RollingAgg::Mean,
+                    "rolling_sum" => RollingAgg::Sum,
+                    "rolling_min" => RollingAgg::Min,
+                    "rolling_max" => RollingAgg::Max,
+                    _ => RollingAgg::Std,
+                };
+                let Some(p) = param else {
+                    return Err(format!(
+                        "{name} requires a window size, e.g. {name}:7 or {name}:7d."
+                    ));
+                };
+                let size = if p.bytes().all(|b| b.is_ascii_digit()) {
+                    match p.parse::<usize>() {
+                        Ok(n) if n > 0 => WindowSize::Rows(n),
+                        _ => {
+                            return Err(format!(
+                                "Invalid window size \"{p}\" for {name}: must be a positive \
+                                 integer or a duration."
+                            ));
+                        },
+                    }
+                } else {
+                    if Duration::try_parse(p).is_err() {
+                        return Err(format!(
+                            "Invalid window duration \"{p}\" for {name}. Examples of valid \
+                             durations: 7d, 12h, 2w, 3mo."
+                        ));
+                    }
+                    WindowSize::Duration(p.to_lowercase())
+                };
+                Ok(WindowFunc::Rolling(agg, size))
+            },
+            "ewm_mean" => {
+                let alpha = match param {
+                    None => 0.5,
+                    Some(p) => match p.parse::<f64>() {
+                        Ok(a) if a > 0.0 && a <= 1.0 => a,
+                        _ => {
+                            return Err(format!(
+                                "Invalid alpha \"{p}\" for ewm_mean: must be a number where 0 < \
+                                 alpha <= 1."
+                            ));
+                        },
+                    },
+                };
+                Ok(WindowFunc::EwmMean(alpha))
+            },
+            _ => Err(format!("Unknown window function: {spec}")),
+        }
+    }
+
+    /// The suffix appended to the column name for the output column.
+    fn suffix(&self) -> String {
+        match self {
+            WindowFunc::CumSum => "cumsum".to_string(),
+            WindowFunc::CumProd => "cumprod".to_string(),
+            WindowFunc::CumMin => "cummin".to_string(),
+            WindowFunc::CumMax => "cummax".to_string(),
+            WindowFunc::CumCount => "cumcount".to_string(),
+            WindowFunc::Lag(n) => format!("lag_{n}"),
+            WindowFunc::Lead(n) => format!("lead_{n}"),
+            WindowFunc::Rank => "rank".to_string(),
+            WindowFunc::DenseRank => "dense_rank".to_string(),
+            WindowFunc::RowNumber => "row_number".to_string(),
+            WindowFunc::PctChange(n) => format!("pct_change_{n}"),
+            WindowFunc::Rolling(agg, size) => {
+                let agg_name = match agg {
+                    RollingAgg::Mean => "mean",
+                    RollingAgg::Sum => "sum",
+                    RollingAgg::Min => "min",
+                    RollingAgg::Max => "max",
+                    RollingAgg::Std => "std",
+                };
+                match size {
+                    WindowSize::Rows(n) => format!("rolling_{agg_name}_{n}"),
+                    WindowSize::Duration(d) => format!("rolling_{agg_name}_{d}"),
+                }
+            },
+            WindowFunc::EwmMean(alpha) => format!("ewm_mean_{alpha}"),
+        }
+    }
+
+    const fn is_time_based(&self) -> bool {
+        matches!(self, WindowFunc::Rolling(_, WindowSize::Duration(_)))
+    }
+
+    /// Build the (unpartitioned) expression computing this function on column `c`.
+    /// `order_by` is the single --order-by column used by time-based rolling windows.
+    fn to_expr(&self, c: Expr, order_by: Option<&str>, descending: bool) -> PolarsResult<Expr> {
+        let rank_options = |method| RankOptions { method, descending };
+
+        Ok(match self {
+            WindowFunc::CumSum => c.cum_sum(false),
+            WindowFunc::CumProd => c.cum_prod(false),
+            WindowFunc::CumMin => c.cum_min(false),
+            WindowFunc::CumMax => c.cum_max(false),
+            WindowFunc::CumCount => c.cum_count(false),
+            WindowFunc::Lag(n) => c.shift(lit(*n)),
+            WindowFunc::Lead(n) => c.shift(lit(-*n)),
+            WindowFunc::Rank => c.rank(rank_options(RankMethod::Min), None),
+            WindowFunc::DenseRank => c.rank(rank_options(RankMethod::Dense), None),
+            WindowFunc::RowNumber => c.rank(rank_options(RankMethod::Ordinal), None),
+            WindowFunc::PctChange(n) => c.pct_change(lit(*n)),
+            WindowFunc::Rolling(agg, WindowSize::Rows(n)) => {
+                let options = RollingOptionsFixedWindow {
+                    window_size: *n,
+                    min_periods: 1,
+                    ..Default::default()
+                };
+                match agg {
+                    RollingAgg::Mean => c.rolling_mean(options),
+                    RollingAgg::Sum => c.rolling_sum(options),
+                    RollingAgg::Min => c.rolling_min(options),
+                    RollingAgg::Max => c.rolling_max(options),
+                    RollingAgg::Std => c.rolling_std(options),
+                }
+            },
+            WindowFunc::Rolling(agg, WindowSize::Duration(d)) => {
+                // safety: run() checks that there is exactly one --order-by column
+                // when a time-based window is used
+                let by = col(PlSmallStr::from_str(order_by.unwrap()));
+                let options = RollingOptionsDynamicWindow {
+                    window_size:   Duration::try_parse(d)?,
+                    min_periods:   1,
+                    closed_window: ClosedWindow::Right,
+                    fn_params:     None,
+                };
+                match agg {
+                    RollingAgg::Mean => c.rolling_mean_by(by, options),
+                    RollingAgg::Sum => c.rolling_sum_by(by, options),
+                    RollingAgg::Min => c.rolling_min_by(by, options),
+                    RollingAgg::Max => c.rolling_max_by(by, options),
+                    RollingAgg::Std => c.rolling_std_by(by, options),
+                }
+            },
+            WindowFunc::EwmMean(alpha) => c.ewm_mean(EWMOptions {
+                alpha: *alpha,
+                ..Default::default()
+            }),
+        })
+    }
+}
+
+/// Split a comma-separated list of column names.
+fn split_cols(cols: Option<&str>) -> Vec<String> {
+    cols.map(|s| s.split(',').map(std::string::ToString::to_string).collect())
+        .unwrap_or_default()
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    let value_cols = split_cols(Some(args.flag_columns.as_str()));
+    let partition_cols = split_cols(args.flag_partition_by.as_deref());
+    let order_cols = split_cols(args.flag_order_by.as_deref());
+
+    let mut funcs: Vec<WindowFunc> = Vec::new();
+    for spec in args.arg_funcs.split(',') {
+        match WindowFunc::parse(spec) {
+            Ok(func) => {
+                if !funcs.contains(&func) {
+                    funcs.push(func);
+                }
+            },
+            Err(e) => return fail_incorrectusage_clierror!("{e}"),
+        }
+    }
+
+    let time_based = funcs.iter().any(WindowFunc::is_time_based);
+    if time_based && order_cols.len() != 1 {
+        return fail_incorrectusage_clierror!(
+            "Time-based rolling windows require a single Date or Datetime --order-by column."
+        );
+    }
+    // a time-based window looks back in time, so it needs its rows in ascending time order
+    if time_based && args.flag_descending {
+        return fail_incorrectusage_clierror!(
+            "Time-based rolling windows require an ascending --order-by. Remove --descending."
+        );
+    }
+
+    let delim = if let Some(delimiter) = args.flag_delimiter {
+        delimiter.as_byte()
+    } else {
+        b','
+    };
+
+    // Create CSV reader config
+    let mut csv_reader = LazyCsvReader::new(PlRefPath::new(&args.arg_input))
+        .with_has_header(true)
+        .with_try_parse_dates(args.flag_try_parsedates || time_based)
+        .with_separator(delim)
+        .with_ignore_errors(args.flag_ignore_errors);
+
+    // use the pschema.json file if it is current, unless --infer-len is set
+    let schema = if args.flag_infer_len == DEFAULT_INFER_LEN {
+        util::load_current_pschema(Path::new(&args.arg_input))?
+    } else {
+        None
+    };
+    if let Some(schema) = schema {
+        csv_reader = csv_reader.with_schema(Some(Arc::new(schema)));
+    } else {
+        csv_reader = csv_reader.with_infer_schema_length(Some(args.flag_infer_len));
+    }
+
+    let mut lf = csv_reader.finish()?;
+    let schema = lf.collect_schema()?;
+
+    for (cols, opt) in [
+        (&value_cols, "--columns"),
+        (&partition_cols, "--partition-by"),
+        (&order_cols, "--order-by"),
+    ] {
+        for c in cols {
+            if schema.get(c).is_none() {
+                return fail_incorrectusage_clierror!("{opt} column \"{c}\" not found.");
+            }
+        }
+    }
+
+    if time_based {
+        let order_col = &order_cols[0];
+        if !matches!(
+            schema.get(order_col),
+            Some(DataType::Date | DataType::Datetime(_, _))
+        ) {
+            return fail_incorrectusage_clierror!(
+                "--order-by column \"{order_col}\" must be a Date or Datetime column for \
+                 time-based rolling windows."
+            );
+        }
+    }
+
+    // build the window expressions, checking for output column name collisions
+    let mut out_names: HashSet<String> = schema.iter_names().map(|n| n.to_string()).collect();
+    let partition_exprs: Vec<Expr> = partition_cols.iter().map(col).collect();
+    let mut window_exprs: Vec<Expr> = Vec::with_capacity(value_cols.len() * funcs.len());
+    for vc in &value_cols {
+        for func in &funcs {
+            let out_name = format!("{vc}_{}", func.suffix());
+            if !out_names.insert(out_name.clone()) {
+                return fail_incorrectusage_clierror!(
+                    "Output column \"{out_name}\" already exists."
+                );
+            }
+            let expr = func.to_expr(
+                col(PlSmallStr::from_str(vc)),
+                order_cols.first().map(String::as_str),
+                args.flag_descending,
+            )?;
+            let expr = if partition_exprs.is_empty() {
+                expr
+            } else {
+                expr.over(&partition_exprs)
+            };
+            window_exprs.push(expr.alias(PlSmallStr::from_str(&out_name)));
+        }
+    }
+
+    // Add a row index so we can restore the input order after sorting
+    lf = lf.with_row_index(PlSmallStr::from_str(ROW_ORDER_COL), None);
+
+    // sort by the partition & order columns so each partition's rows are contiguous and in
+    // order. The row index is the final sort key, so ties keep their input order.
+    if !order_cols.is_empty() || time_based {
+        let mut sort_cols: Vec<PlSmallStr> = partition_cols
+            .iter()
+            .chain(order_cols.iter())
+            .map(|c| PlSmallStr::from_str(c))
+            .collect();
+        sort_cols.push(PlSmallStr::from_str(ROW_ORDER_COL));
+        let mut descending = vec![false; partition_cols.len()];
+        descending.extend(std::iter::repeat_n(args.flag_descending, order_cols.len()));
+        descending.push(false);
+        lf = lf.sort(
+            sort_cols,
+            SortMultipleOptions::default()
+                .with_order_descending_multi(descending)
+                .with_maintain_order(true),
+        );
+    }
+
+    let mut result = lf
+        .with_columns(window_exprs)
+        .sort([ROW_ORDER_COL], SortMultipleOptions::default())
+        .drop(cols([ROW_ORDER_COL]))
+        .collect()?;
+
+    let mut writer = match args.flag_output {
+        Some(ref output_file) => {
+            // no need to use buffered writer here, as CsvWriter already does that
+            let path = Path::new(&output_file);
+            Box::new(File::create(path)?) as Box<dyn Write>
+        },
+        None => Box::new(io::stdout()) as Box<dyn Write>,
+    };
+    let datetime_fmt: PlSmallStr = PlSmallStr::from_str("%Y-%m-%d %H:%M:%S");
+    CsvWriter::new(&mut writer)
+        .include_header(true)
+        .with_datetime_format(Some(datetime_fmt))
+        .with_separator(delim)
+        .finish(&mut result)?;
+
+    if !args.flag_quiet {
+        eprintln!("{:?}", result.shape());
+    }
+
+    Ok(())
+}
@@ -1132,7 +1132,39 @@ impl Config {
             warn!("failed to write UTF-8 BOM: {e}");
         }
 
-        csv::WriterBuilder::new()
+        self.writer_builder().from_writer(wtr)
+    }
+
+    /// A writer appending to the output file from `len` bytes in, dropping anything
+    /// written past that. Used to resume a run from a `--checkpoint`, so unlike
+    /// `writer`, it never writes a BOM.
+    #[cfg(not(feature = "lite"))]
+    #[cfg_attr(not(any(feature = "fetch", feature = "geocode")), allow(dead_code))]
+    pub fn resume_writer(&self, len: u64) -> io::Result<csv::Writer<Box<dyn io::Write + 'static>>> {
+        use std::io::Seek;
+
+        let Some(ref p) = self.path else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot resume writing to stdout",
+            ));
+        };
+        if self.snappy {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot resume writing a snappy-compressed file",
+            ));
+        }
+        let mut file = fs::OpenOptions::new().write(true).open(p)?;
+        file.set_len(len)?;
+        file.seek(io::SeekFrom::End(0))?;
+        let wtr: Box<dyn io::Write + 'static> = Box::new(file);
+        Ok(self.writer_builder().from_writer(wtr))
+    }
+
+    fn writer_builder(&self) -> csv::WriterBuilder {
+        let mut builder = csv::WriterBuilder::new();
+        builder
             .flexible(self.flexible)
             .delimiter(self.delimiter)
             .terminator(self.terminator)
@@ -1140,8 +1172,8 @@ impl Config {
             .quote_style(self.quote_style)
             .double_quote(self.double_quote)
             .escape(self.escape.unwrap_or(b'\\'))
-            .buffer_capacity(self.write_buffer as usize)
-            .from_writer(wtr)
+            .buffer_capacity(self.write_buffer as usize);
+        builder
     }
 }
 
@@ -31,6 +31,8 @@ static GLOBAL: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
 #[cfg(all(feature = "viz", feature = "feature_capable"))]
 rust_i18n::i18n!("src/cmd/locales", fallback = "en");
 
+mod auth;
+mod checkpoint;
 mod clitypes;
 mod cmd;
 mod config;
@@ -44,6 +46,8 @@ mod lookup;
 mod mcp_skills_gen;
 mod minijinja_filters;
 mod odhtcache;
+#[cfg(all(any(feature = "geocode", feature = "viz"), feature = "feature_capable"))]
+mod pip;
 mod select;
 mod util;
 
@@ -153,6 +157,9 @@ fn main() -> QsvExitCode {
 
     enabled_commands.push_str("    frequency   Show frequency tables\n");
 
+    #[cfg(all(feature = "fuzzydedup", feature = "feature_capable"))]
+    enabled_commands.push_str("    fuzzydedup  Cluster near-duplicate rows (record linkage)\n");
+
     #[cfg(all(feature = "get", feature = "feature_capable"))]
     enabled_commands
         .push_str("    get         Get tabular data from various sources into a disk cache\n");
@@ -160,7 +167,8 @@ fn main() -> QsvExitCode {
     #[cfg(all(feature = "geocode", not(feature = "lite")))]
     enabled_commands.push_str(
         "    geocode     Geocodes a location against the Geonames cities database.
-    geoconvert  Convert between spatial formats & CSV, including GeoJSON, SHP & more\n",
+    geoconvert  Convert between spatial formats & CSV, including GeoJSON, SHP & more
+    geojoin     Join points to the polygons that contain them (point-in-polygon)\n",
     );
 
     enabled_commands.push_str(
@@ -190,7 +198,8 @@ fn main() -> QsvExitCode {
     enabled_commands.push_str("    log         Log MCP tool invocations to qsvmcp.log\n");
 
     enabled_commands.push_str(
-        "    moarstats   Add \"moar\" statistics to existing stats CSV
+        "    merge       Merge sorted CSV files into one sorted CSV
+    moarstats   Add \"moar\" statistics to existing stats CSV
     partition   Partition CSV data based on a column value\n",
     );
 
@@ -252,7 +261,8 @@ fn main() -> QsvExitCode {
     enabled_commands.push_str(
         "    table       Align CSV data into columns
     template    Render templates using CSV data
-    tojsonl     Convert CSV to newline-delimited JSON\n",
+    tojsonl     Convert CSV to newline-delimited JSON
+    top         Get the N largest or smallest records of a CSV\n",
     );
 
     #[cfg(all(feature = "to", feature = "feature_capable"))]
@@ -273,6 +283,11 @@ fn main() -> QsvExitCode {
     #[cfg(all(feature = "viz", feature = "feature_capable"))]
     enabled_commands
         .push_str("\n    viz         Generate charts & dashboards from CSV data using plotly");
+    #[cfg(all(feature = "polars", feature = "feature_capable"))]
+    enabled_commands.push_str(
+        "\n    window      Compute window functions & rolling aggregations using the Pola.rs \
+         engine",
+    );
     let num_commands = enabled_commands.split('\n').count();
 
     let now = Instant::now();
@@ -478,12 +493,16 @@ enum Command {
     #[cfg(all(feature = "foreach", not(feature = "lite")))]
     ForEach,
     Frequency,
+    #[cfg(all(feature = "fuzzydedup", feature = "feature_capable"))]
+    FuzzyDedup,
     #[cfg(all(feature = "get", feature = "feature_capable"))]
     Get,
     #[cfg(all(feature = "geocode", feature = "feature_capable"))]
     Geocode,
     #[cfg(all(feature = "geocode", feature = "feature_capable"))]
     Geoconvert,
+    #[cfg(all(feature = "geocode", feature = "feature_capable"))]
+    Geojoin,
     Headers,
     Help,
     Implode,
@@ -500,6 +519,7 @@ enum Command {
     Luau,
     #[cfg(feature = "mcp")]
     Log,
+    Merge,
     Partition,
     #[cfg(all(feature = "polars", feature = "feature_capable"))]
     PivotP,
@@ -541,9 +561,12 @@ enum Command {
     #[cfg(all(feature = "to", feature = "feature_capable"))]
     To,
     Tojsonl,
+    Top,
     Validate,
     #[cfg(all(feature = "viz", feature = "feature_capable"))]
     Viz,
+    #[cfg(all(feature = "polars", feature = "feature_capable"))]
+    Window,
 }
 
 impl Command {
@@ -603,12 +626,16 @@ impl Command {
             Command::Flatten => cmd::flatten::run(argv),
             Command::Fmt => cmd::fmt::run(argv),
             Command::Frequency => cmd::frequency::run(argv),
+            #[cfg(all(feature = "fuzzydedup", feature = "feature_capable"))]
+            Command::FuzzyDedup => cmd::fuzzydedup::run(argv),
             #[cfg(all(feature = "get", feature = "feature_capable"))]
             Command::Get => cmd::get::run(argv),
             #[cfg(all(feature = "geocode", feature = "feature_capable"))]
             Command::Geocode => cmd::geocode::run(argv),
             #[cfg(all(feature = "geocode", feature = "feature_capable"))]
             Command::Geoconvert => cmd::geoconvert::run(argv),
+            #[cfg(all(feature = "geocode", feature = "feature_capable"))]
+            Command::Geojoin => cmd::geojoin::run(argv),
             Command::Headers => cmd::headers::run(argv),
             Command::Help => {
                 wout!("{USAGE}\n\n{SPONSOR_MESSAGE}");
@@ -629,6 +656,7 @@ impl Command {
             Command::Luau => cmd::luau::run(argv),
             #[cfg(feature = "mcp")]
             Command::Log => cmd::log::run(argv),
+            Command::Merge => cmd::merge::run(argv),
             Command::Partition => cmd::partition::run(argv),
             #[cfg(all(feature = "polars", feature = "feature_capable"))]
             Command::PivotP => cmd::pivotp::run(argv),
@@ -670,9 +698,12 @@ impl Command {
             #[cfg(all(feature = "to", feature = "feature_capable"))]
             Command::To => cmd::to::run(argv),
             Command::Tojsonl => cmd::tojsonl::run(argv),
+            Command::Top => cmd::top::run(argv),
             Command::Validate => cmd::validate::run(argv),
             #[cfg(all(feature = "viz", feature = "feature_capable"))]
             Command::Viz => cmd::viz::run(argv),
+            #[cfg(all(feature = "polars", feature = "feature_capable"))]
+            Command::Window => cmd::window::run(argv),
         }
     }
 }
@@ -47,6 +47,7 @@ static COMMAND_LIST: &str = r#"
     join        Join CSV files
     json        Convert JSON to CSV
     jsonl       Convert newline-delimited JSON files to CSV
+    merge       Merge sorted CSV files into one sorted CSV
     moarstats   Add "moar" statistics to existing stats CSV
     partition   Partition CSV data based on a column value
     pragmastat  Pragmatic statistical toolkit
@@ -70,6 +71,7 @@ static COMMAND_LIST: &str = r#"
     stats       Infer data types and compute summary statistics
     table       Align CSV data into columns
     tojsonl     Convert CSV to newline-delimited JSON
+    top         Get the N largest or smallest records of a CSV
     transpose   Transpose rows/columns of CSV data
     validate    Validate CSV data for RFC4180-compliance or with JSON Schema"#;
 
@@ -263,6 +265,7 @@ enum Command {
     Join,
     Json,
     Jsonl,
+    Merge,
     Partition,
     Pragmastat,
     Pro,
@@ -286,6 +289,7 @@ enum Command {
     Moarstats,
     Table,
     Tojsonl,
+    Top,
     Transpose,
     Validate,
 }
@@ -335,6 +339,7 @@ impl Command {
             Command::Join => cmd::join::run(argv),
             Command::Json => cmd::json::run(argv),
             Command::Jsonl => cmd::jsonl::run(argv),
+            Command::Merge => cmd::merge::run(argv),
             Command::Partition => cmd::partition::run(argv),
             Command::Pragmastat => cmd::pragmastat::run(argv),
             Command::Pro => cmd::pro::run(argv),
@@ -358,6 +363,7 @@ impl Command {
             Command::Moarstats => cmd::moarstats::run(argv),
             Command::Table => cmd::table::run(argv),
             Command::Tojsonl => cmd::tojsonl::run(argv),
+            Command::Top => cmd::top::run(argv),
             Command::Transpose => cmd::transpose::run(argv),
             Command::Validate => cmd::validate::run(argv),
         }
@@ -1220,6 +1220,7 @@ pub fn generate_mcp_skills() -> CliResult<()> {
     // - fetchpost: not available in qsvmcp
     // - flatten: not suitable for AI agent use
     // - foreach: not available in qsvmcp
+    // - fuzzydedup: not available in qsvmcp
     // - geoconvert: experimental command (not yet stable)
     // - lens: interactive TUI viewer (requires terminal)
     // - luau: not available in qsvmcp (requires panic=unwind; see qsv issue #3937)
@@ -1251,6 +1252,7 @@ pub fn generate_mcp_skills() -> CliResult<()> {
         "fmt",
         "frequency",
         "geocode",
+        "geojoin",
         "headers",
         "implode",
         "index",
@@ -1259,6 +1261,7 @@ pub fn generate_mcp_skills() -> CliResult<()> {
         "joinp",
         "json",
         "jsonl",
+        "merge",
         "moarstats",
         "partition",
         "pivotp",
@@ -1285,9 +1288,11 @@ pub fn generate_mcp_skills() -> CliResult<()> {
         "template",
         "to",
         "tojsonl",
+        "top",
         "transpose",
         "validate",
         "viz",
+        "window",
     ];
 
     // Determine repository root - look for Cargo.toml with src/cmd
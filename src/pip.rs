@@ -0,0 +1,636 @@
+//! Point-in-polygon binning of lon/lat points into GeoJSON polygon features, shared by `viz`'s
+//! choropleths and `geojoin`'s spatial joins.
+//!
+//! Features are reduced to closed `[lon, lat]` rings & tested with an even-odd ray cast. Polygons
+//! that cross the ±180° antimeridian are unwrapped into a contiguous longitude frame first, and
+//! distances to a feature's boundary use an equirectangular km approximation centered on the
+//! query latitude.
+
+/// One GeoJSON feature reduced to its polygon rings for point-in-polygon binning. `polygons` holds
+/// one entry per polygon (a `MultiPolygon` yields several); each polygon is a list of linear rings
+/// (ring 0 is the exterior, the rest are holes); each ring is a closed list of `[lon, lat]`
+/// vertices. `bbox` is `[min_lon, min_lat, max_lon, max_lat]` over all vertices, for cheap
+/// candidate prefiltering.
+pub struct PipFeature {
+    pub id:                 String,
+    /// Human-readable region label (e.g. `properties.name` → "Kagoshima"), when a name key is
+    /// given or auto-detected (`viz` HTML-escapes it for hover). `None` when no name resolves.
+    pub name:               Option<String>,
+    pub polygons:           Vec<Vec<Vec<[f64; 2]>>>,
+    pub bbox:               [f64; 4],
+    /// True when at least one of this feature's polygons genuinely crosses the ±180° antimeridian
+    /// and was continuity-unwrapped into a contiguous extended longitude frame (vertices may lie
+    /// beyond ±180°, within `(-180, 540)`), so the planar ray-cast and bbox prefilter stay
+    /// contiguous across the dateline seam. Queries against such a feature must be probed at both
+    /// 360°-equivalent longitudes via [`PipFeature::candidate_lons`]. Pole-enclosing rings pre-cut
+    /// along the seam (e.g. Natural Earth's Antarctica) are deliberately KEPT RAW — their planar
+    /// form is already ray-cast-correct — and don't set this flag by themselves.
+    pub wraps_antimeridian: bool,
+}
+
+impl PipFeature {
+    /// Build a feature from its closed polygon rings (see [`geojson_value_to_polygons`]),
+    /// unwrapping the antimeridian-crossing polygons and computing the bbox.
+    pub fn new(id: String, name: Option<String>, mut polygons: Vec<Vec<Vec<[f64; 2]>>>) -> Self {
+        // Normalize each antimeridian-crossing polygon into a contiguous extended longitude frame
+        // (continuity unwrapping) so its bbox and the planar ray-cast stay contiguous across the
+        // ±180° seam. Query longitudes are probed at both 360°-equivalent representations via
+        // PipFeature::candidate_lons. Per-POLYGON (not per-feature) so a multipolygon's
+        // non-crossing parts keep their raw frame, and per-polygon rings stay in ONE frame so
+        // hole subtraction (even-odd parity) still works.
+        let mut wraps_antimeridian = false;
+        for polygon in &mut polygons {
+            if !polygon_crosses_antimeridian(polygon) {
+                continue;
+            }
+            // exterior + holes unwrapped together; a pole-cut ring (unwrap doesn't close) reverts
+            // the whole polygon to its raw — already ray-cast-correct — planar form
+            let Some(mut rings) = polygon
+                .iter()
+                .map(|ring| unwrap_ring_across_seam(ring))
+                .collect::<Option<Vec<_>>>()
+            else {
+                continue;
+            };
+            // continuity anchors each ring at its own first vertex, so a hole can land a full
+            // 360° away from its exterior; re-align each hole's frame to the exterior's midpoint
+            let mid_lon = |ring: &[[f64; 2]]| -> f64 {
+                let (min, max) = ring
+                    .iter()
+                    .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+                        (lo.min(v[0]), hi.max(v[0]))
+                    });
+                f64::midpoint(min, max)
+            };
+            if let Some((exterior, holes)) = rings.split_first_mut() {
+                let ext_mid = mid_lon(exterior);
+                for hole in holes {
+                    let k = ((ext_mid - mid_lon(hole)) / 360.0).round();
+                    if k != 0.0 {
+                        for v in hole.iter_mut() {
+                            v[0] += k * 360.0;
+                        }
+                    }
+                }
+            }
+            // keep every vertex within (-180, 540) so the {lon, lon+360} query candidates can
+            // reach it (a ring anchored near -180 that walks west unwraps below -180)
+            let min = rings
+                .iter()
+                .flatten()
+                .map(|v| v[0])
+                .fold(f64::INFINITY, f64::min);
+            if min < -180.0 {
+                for v in rings.iter_mut().flatten() {
+                    v[0] += 360.0;
+                }
+            }
+            *polygon = rings;
+            wraps_antimeridian = true;
+        }
+        let mut bbox = [
+            f64::INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NEG_INFINITY,
+        ];
+        for ring in polygons.iter().flatten() {
+            for &[lon, lat] in ring {
+                bbox[0] = bbox[0].min(lon);
+                bbox[1] = bbox[1].min(lat);
+                bbox[2] = bbox[2].max(lon);
+                bbox[3] = bbox[3].max(lat);
+            }
+        }
+        Self {
+            id,
+            name,
+            polygons,
+            bbox,
+            wraps_antimeridian,
+        }
+    }
+
+    /// The query longitudes to test against this feature. An antimeridian-crossing feature stores
+    /// continuity-unwrapped vertices extending past ±180°, so the query is probed at both of its
+    /// 360°-equivalent representations — at most one can fall inside any single planar polygon
+    /// (per-polygon spans are < 360°), so OR-ing the probes can't double-count. Non-wrapping
+    /// features are tested at the raw longitude only.
+    pub fn candidate_lons(&self, lon: f64) -> [Option<f64>; 2] {
+        if self.wraps_antimeridian {
+            return [Some(lon), Some(lon + 360.0)];
+        }
+        // A feature can sit ADJACENT to the antimeridian without crossing it (e.g. spanning
+        // 179.5..180.0), in which case it carries no wrap flag — but a query just the other side
+        // of the seam is still only a fraction of a degree away going east, while the raw
+        // subtraction measures it as ~359.5 degrees the long way round. Distances are computed in
+        // raw degree space, so without a second probe such a point measures ~40,000 km, exceeds
+        // any snap cap, and is silently dropped as "outside every region".
+        //
+        // Only offer the alternate when the raw longitude gap already exceeds 180 degrees — i.e.
+        // the short way round is genuinely the other direction. That is exactly the seam case, so
+        // ordinary features pay one comparison and containment cannot gain a false positive (the
+        // shifted query only enters the feature's span when the true angular distance is small).
+        let (west, east) = (self.bbox[0], self.bbox[2]);
+        let alt = if lon < west - 180.0 {
+            Some(lon + 360.0)
+        } else if lon > east + 180.0 {
+            Some(lon - 360.0)
+        } else {
+            None
+        };
+        [Some(lon), alt]
+    }
+}
+
+/// Resolve a dotted property path against a GeoJSON feature, returning the raw JSON value. Handles
+/// `"properties.<...>"` paths (mirroring plotly's `featureidkey` convention) and any other key as a
+/// top-level foreign member (e.g. a bare `"name"` on each feature — TopoJSON-style exports carry
+/// names there rather than under `properties`). The top-level `"id"` is NOT handled here because it
+/// lives outside both maps; [`feature_id_by_path`] special-cases it. Shared by `feature_id_by_path`
+/// and `viz`'s `feature_f64_by_path` so `--feature-id-key` and `--denominator-key` address
+/// features identically.
+#[cfg_attr(not(feature = "viz"), allow(dead_code))]
+pub fn feature_member_by_path<'a>(
+    feature: &'a geojson::Feature,
+    key: &str,
+) -> Option<&'a serde_json::Value> {
+    let (root, segs) = match key.strip_prefix("properties.") {
+        Some(rest) => {
+            let mut segs = rest.split('.');
+            (feature.properties.as_ref()?.get(segs.next()?)?, segs)
+        },
+        // no "properties." prefix: a top-level foreign member
+        None => {
+            let mut segs = key.split('.');
+            (feature.foreign_members.as_ref()?.get(segs.next()?)?, segs)
+        },
+    };
+    let mut cur = root;
+    for seg in segs {
+        cur = cur.get(seg)?;
+    }
+    Some(cur)
+}
+
+/// Resolve a GeoJSON feature's id by a dotted `--feature-id-key` path. Supports the top-level
+/// `"id"`, `"properties.<...>"` paths (mirroring plotly's `featureidkey` convention), and any
+/// other key as a top-level foreign member (e.g. a bare `"name"` on each feature — TopoJSON-style
+/// exports carry names there rather than under `properties`). Strings and numbers both coerce to
+/// `String` (CSV cells and plotly match feature ids as strings). Returns `None` when the path is
+/// absent or the value isn't a string/number.
+#[cfg_attr(not(feature = "viz"), allow(dead_code))]
+pub fn feature_id_by_path(feature: &geojson::Feature, key: &str) -> Option<String> {
+    if key == "id" {
+        return feature.id.as_ref().map(|id| match id {
+            geojson::feature::Id::String(s) => s.clone(),
+            geojson::feature::Id::Number(n) => n.to_string(),
+        });
+    }
+    match feature_member_by_path(feature, key)? {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Convert a `geojson::GeometryValue::Polygon` ring set to closed `[lon, lat]` rings (appending the
+/// first vertex when a ring isn't already closed, so even-odd ray-casting via `windows(2)` covers
+/// every edge). Rings with fewer than 3 distinct vertices are dropped (degenerate point/line
+/// rings).
+#[cfg_attr(not(feature = "viz"), allow(dead_code))]
+pub fn geojson_rings_to_closed(poly: &[Vec<geojson::Position>]) -> Vec<Vec<[f64; 2]>> {
+    poly.iter()
+        .filter_map(|ring| {
+            let mut pts: Vec<[f64; 2]> = ring
+                .iter()
+                .filter(|p| p.len() >= 2)
+                .map(|p| [p[0], p[1]])
+                .collect();
+            // require at least 3 DISTINCT vertices: a ring with fewer is degenerate (a point or a
+            // line segment, zero area) — it contributes no even-odd crossings and would only serve
+            // as a phantom nearest-feature snap target. Short-circuits once 3 distinct points seen.
+            let mut distinct: Vec<[f64; 2]> = Vec::with_capacity(3);
+            for &p in &pts {
+                if !distinct.contains(&p) {
+                    distinct.push(p);
+                    if distinct.len() >= 3 {
+                        break;
+                    }
+                }
+            }
+            if distinct.len() < 3 {
+                return None;
+            }
+            if pts.first() != pts.last() {
+                pts.push(pts[0]);
+            }
+            Some(pts)
+        })
+        .collect()
+}
+
+/// Flatten a `geojson::GeometryValue` into a list of polygons (each = exterior + hole rings).
+/// Handles Polygon, `MultiPolygon`, and nested `GeometryCollection`; other geometry types yield
+/// nothing.
+pub fn geojson_value_to_polygons(value: &geojson::GeometryValue) -> Vec<Vec<Vec<[f64; 2]>>> {
+    match value {
+        geojson::GeometryValue::Polygon { coordinates } => {
+            let rings = geojson_rings_to_closed(coordinates);
+            if rings.is_empty() {
+                vec![]
+            } else {
+                vec![rings]
+            }
+        },
+        geojson::GeometryValue::MultiPolygon { coordinates } => coordinates
+            .iter()
+            .map(|poly| geojson_rings_to_closed(poly))
+            .filter(|rings| !rings.is_empty())
+            .collect(),
+        geojson::GeometryValue::GeometryCollection { geometries } => geometries
+            .iter()
+            .flat_map(|g| geojson_value_to_polygons(&g.value))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Does any edge of this polygon's rings make a genuine ±180° antimeridian jump? True when an
+/// edge's endpoints are far apart in raw longitude but CLOSE the short way across the seam (within
+/// `MAX_SEAM_GAP_DEG`) — the signature of a dateline crossing (e.g. 179° -> -179°, or a wider
+/// Pacific box 160° -> -160°). A genuinely wide but non-crossing polygon (e.g. a -100°..100° span
+/// centered on the prime meridian) has a large short-way gap and is deliberately NOT flagged, so
+/// its planar interior is binned correctly. When true, the polygon is continuity-unwrapped into a
+/// contiguous extended longitude frame before planar point-in-polygon tests, which can't reason
+/// across the seam.
+pub fn polygon_crosses_antimeridian(polygon: &[Vec<[f64; 2]>]) -> bool {
+    // A dateline crossing connects two vertices that are far apart in raw longitude (>180°) but
+    // CLOSE the short way across the ±180° seam. We key off that short-way gap: `360 - |Δlon|`. A
+    // real crossing like 179° -> -179° has a 2° seam gap; a wide Pacific box 160° -> -160° has a
+    // 40° gap (still a crossing). A prime-meridian-centered wide polygon like -100° -> 100° spans
+    // 200° but its short-way gap is 160° — it is a genuinely wide non-crossing region and must NOT
+    // be normalized (that would push its prime-meridian interior outside the bbox). The
+    // MAX_SEAM_GAP_DEG cutoff (90°) separates the two: normalize when the edge's endpoints are
+    // within 90° of each other across the seam (inclusive), i.e. |Δlon| >= 360 - 90 = 270°. The
+    // boundary is inclusive so an exactly-90°-gap box (135° -> -135°) is still treated as crossing.
+    const MAX_SEAM_GAP_DEG: f64 = 90.0;
+    polygon.iter().any(|ring| {
+        ring.windows(2).any(|edge| {
+            let &[[lon_a, _], [lon_b, _]] = edge else {
+                return false;
+            };
+            (lon_a - lon_b).abs() >= 360.0 - MAX_SEAM_GAP_DEG
+        })
+    })
+}
+
+/// Continuity-unwrap a pre-closed ring across the ±180° antimeridian: each vertex longitude is
+/// shifted by the multiple of 360° that puts it within 180° of its predecessor, so a seam-jumping
+/// vertex walk (179° -> -179°) becomes contiguous (179° -> 181°) while genuine prime-meridian
+/// crossings (0.4° -> -0.2°) stay short — a blanket "shift all negative lons by +360" would turn
+/// the latter into a fake ~359°-long edge, scrambling ray-cast parity near 0° for any feature that
+/// crosses BOTH meridians. Returns `None` when the unwrapped ring does not arrive back at its
+/// starting longitude (a ±360° residue): that's the signature of a pole-enclosing ring pre-cut
+/// along the seam (e.g. Natural Earth's Antarctica), whose RAW planar form is already correct for
+/// even-odd ray casting and must not be reframed.
+pub fn unwrap_ring_across_seam(ring: &[[f64; 2]]) -> Option<Vec<[f64; 2]>> {
+    let mut out = Vec::with_capacity(ring.len());
+    let mut prev = ring.first()?[0];
+    for &[lon, lat] in ring {
+        // FMA version of let lon = lon + ((prev - lon) / 360.0).round() * 360.0;
+        let lon = ((prev - lon) / 360.0).round().mul_add(360.0, lon);
+        out.push([lon, lat]);
+        prev = lon;
+    }
+    if (out.last()?[0] - out.first()?[0]).abs() > 180.0 {
+        return None;
+    }
+    Some(out)
+}
+
+/// Even-odd ray-casting test: is `(lon, lat)` inside this single polygon (exterior + holes)? A
+/// point in a hole crosses an even number of edges and is correctly reported as outside. Rings are
+/// pre-closed by [`geojson_rings_to_closed`], so `windows(2)` enumerates every edge.
+pub fn point_in_polygon(polygon: &[Vec<[f64; 2]>], lon: f64, lat: f64) -> bool {
+    let mut inside = false;
+    for ring in polygon {
+        for edge in ring.windows(2) {
+            let &[[xi, yi], [xj, yj]] = edge else {
+                continue;
+            };
+            if ((yi > lat) != (yj > lat)) && (lon < (xj - xi) * (lat - yi) / (yj - yi) + xi) {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+/// Is `(lon, lat)` inside this feature (bbox prefilter, then any constituent polygon)? The query
+/// is probed at each of the feature's candidate longitudes (both 360°-equivalent representations
+/// for antimeridian-crossing features, whose unwrapped vertices may extend past ±180°); a
+/// wrong-frame probe lands planar-outside every ring, so OR-ing the probes is safe.
+pub fn feature_contains(feature: &PipFeature, lon: f64, lat: f64) -> bool {
+    if lat < feature.bbox[1] || lat > feature.bbox[3] {
+        return false;
+    }
+    feature
+        .candidate_lons(lon)
+        .into_iter()
+        .flatten()
+        .any(|lon| {
+            lon >= feature.bbox[0]
+                && lon <= feature.bbox[2]
+                && feature
+                    .polygons
+                    .iter()
+                    .any(|poly| point_in_polygon(poly, lon, lat))
+        })
+}
+
+/// Mean km per degree of latitude (and of longitude at the equator). Used by the point-in-polygon
+/// snap-distance cap to turn lon/lat degrees into an isotropic km metric.
+pub const KM_PER_DEG: f64 = 111.32;
+
+/// Squared Euclidean (degree-space) distance from a point to a line segment.
+pub fn point_seg_dist2(px: f64, py: f64, ax: f64, ay: f64, bx: f64, by: f64) -> f64 {
+    let (dx, dy) = (bx - ax, by - ay);
+    // FMA version of let len2 = dx * dx + dy * dy;
+    let len2 = dy.mul_add(dy, dx * dx);
+    let t = if len2 <= f64::EPSILON {
+        0.0
+    } else {
+        // (((px - ax) * dx + (py - ay) * dy) / len2).clamp(0.0, 1.0)
+        ((py - ay).mul_add(dy, (px - ax) * dx) / len2).clamp(0.0, 1.0)
+    };
+    let (cx, cy) = (ax + t * dx, ay + t * dy);
+    let (ex, ey) = (px - cx, py - cy);
+    // ex * ex + ey * ey
+    ey.mul_add(ey, ex * ex)
+}
+
+/// Squared distance from `(lon, lat)` to the nearest edge of any of the feature's rings, in the
+/// equirectangular km space defined by the `(sx, sy)` per-degree scale factors (see
+/// [`pip_assign`]). Passing `sx = sy = 1.0` recovers raw degree-space distance.
+pub fn feature_dist2(feature: &PipFeature, lon: f64, lat: f64, sx: f64, sy: f64) -> f64 {
+    let (px, py) = (lon * sx, lat * sy);
+    let mut best = f64::INFINITY;
+    for ring in feature.polygons.iter().flatten() {
+        for edge in ring.windows(2) {
+            let &[[ax, ay], [bx, by]] = edge else {
+                continue;
+            };
+            let d = point_seg_dist2(px, py, ax * sx, ay * sy, bx * sx, by * sy);
+            if d < best {
+                best = d;
+            }
+        }
+    }
+    best
+}
+
+/// Lower-bound squared distance from `(lon, lat)` to a feature's bbox (0 when inside the bbox), in
+/// the same `(sx, sy)`-scaled space as [`feature_dist2`] so it remains a valid prune bound.
+pub fn bbox_dist2(bbox: &[f64; 4], lon: f64, lat: f64, sx: f64, sy: f64) -> f64 {
+    let dx = (bbox[0] - lon).max(0.0).max(lon - bbox[2]) * sx;
+    let dy = (bbox[1] - lat).max(0.0).max(lat - bbox[3]) * sy;
+    dx * dx + dy * dy
+}
+
+/// Result of binning one point into a GeoJSON feature set.
+#[cfg_attr(not(feature = "viz"), allow(dead_code))]
+#[derive(Debug, PartialEq, Eq)]
+pub enum PipOutcome {
+    /// Contained by feature at this index.
+    Inside(usize),
+    /// Outside every polygon; snapped to the nearest feature at this index.
+    Snapped(usize),
+    /// Outside every polygon and not snapped (dropped).
+    Outside,
+}
+
+/// Assign a point to a feature: exact containment first; if none and `snap`, the nearest feature by
+/// edge distance (bbox lower-bound pruned) provided it lies within `snap_max_km` (`f64::INFINITY`
+/// = unlimited); otherwise [`PipOutcome::Outside`]. Distances use an equirectangular km
+/// approximation centered on the query latitude (longitude degrees scaled by `cos(lat)`), so the
+/// cap means the same east-west and north-south. The `Inside`/`Snapped`/`Outside` distinction lets
+/// callers report how many points missed every polygon and how many were too far to snap.
+#[cfg_attr(not(feature = "viz"), allow(dead_code))]
+pub fn pip_assign(
+    features: &[PipFeature],
+    lat: f64,
+    lon: f64,
+    snap: bool,
+    snap_max_km: f64,
+) -> PipOutcome {
+    if let Some(i) = features.iter().position(|f| feature_contains(f, lon, lat)) {
+        return PipOutcome::Inside(i);
+    }
+    if !snap {
+        return PipOutcome::Outside;
+    }
+    // equirectangular km scale at the query latitude: km per degree of lat is ~constant; km per
+    // degree of lon shrinks by cos(lat). Applying both to every distance keeps the cap isotropic.
+    let sx = lat.to_radians().cos() * KM_PER_DEG;
+    let sy = KM_PER_DEG;
+    let mut best_i = None;
+    let mut best_d2 = f64::INFINITY;
+    // Seed the search with the feature whose BBOX is nearest, then let the loop below prune
+    // against that. `best_d2` otherwise starts at infinity, so the first feature in FILE ORDER is
+    // always fully edge-scanned no matter how far away it is, and pruning quality depends
+    // entirely on how the boundary file happens to be ordered. This pass is bbox arithmetic only
+    // (no vertices), so it costs O(features) cheap comparisons and typically reduces the full
+    // ring scans below from "every unpruned feature" to one or two. The result is unchanged:
+    // bbox_dist2 is a true lower bound, so pruning against a real distance can never discard a
+    // nearer feature.
+    let mut seed: Option<(usize, f64)> = None;
+    let mut seed_bbox_d2 = f64::INFINITY;
+    for (i, f) in features.iter().enumerate() {
+        for flon in f.candidate_lons(lon).into_iter().flatten() {
+            let b = bbox_dist2(&f.bbox, flon, lat, sx, sy);
+            if b < seed_bbox_d2 {
+                seed_bbox_d2 = b;
+                seed = Some((i, flon));
+            }
+        }
+    }
+    if let Some((i, flon)) = seed {
+        best_d2 = feature_dist2(&features[i], flon, lat, sx, sy);
+        best_i = Some(i);
+    }
+    for (i, f) in features.iter().enumerate() {
+        // probe the query at each of the feature's candidate longitudes so antimeridian-crossing
+        // features (stored continuity-unwrapped past ±180°) measure distance consistently with
+        // their vertices/bbox; the nearer representation wins.
+        for flon in f.candidate_lons(lon).into_iter().flatten() {
+            if bbox_dist2(&f.bbox, flon, lat, sx, sy) >= best_d2 {
+                continue;
+            }
+            let d2 = feature_dist2(f, flon, lat, sx, sy);
+            if d2 < best_d2 {
+                best_d2 = d2;
+                best_i = Some(i);
+            }
+        }
+    }
+    match best_i {
+        Some(i) if best_d2 <= snap_max_km * snap_max_km => PipOutcome::Snapped(i),
+        _ => PipOutcome::Outside,
+    }
+}
+
+/// The maximum number of children of a [`PipIndex`] node.
+const NODE_CAPACITY: usize = 16;
+
+/// A static R-tree over the bboxes of a set of [`PipFeature`]s, so locating a point only tests the
+/// few features whose bbox can contain it - or lies within the distance cap - instead of scanning
+/// all of them as [`pip_assign`] does. It is bulk-loaded once with Sort-Tile-Recursive packing:
+/// the features are sorted into vertical slices by the longitude of their bbox center, then by
+/// latitude within each slice. Each level is a flat array of boxes, node `i` of a level covering
+/// nodes `i * NODE_CAPACITY..(i + 1) * NODE_CAPACITY` of the level below.
+#[cfg_attr(not(feature = "geocode"), allow(dead_code))]
+pub struct PipIndex {
+    /// the boxes of each level, from the leaves (one per feature, in packed order) up to the root
+    levels: Vec<Vec<[f64; 4]>>,
+    /// the feature index of each leaf
+    leaves: Vec<usize>,
+}
+
+#[cfg_attr(not(feature = "geocode"), allow(dead_code))]
+impl PipIndex {
+    pub fn new(features: &[PipFeature]) -> Self {
+        let center = |i: usize, axis: usize| features[i].bbox[axis] + features[i].bbox[axis + 2];
+        let mut leaves: Vec<usize> = (0..features.len()).collect();
+        leaves.sort_unstable_by(|&a, &b| center(a, 0).total_cmp(&center(b, 0)));
+        // ceil(sqrt(node count)) slices of ceil(sqrt(node count)) nodes each
+        let nodes = features.len().div_ceil(NODE_CAPACITY);
+        let slices = if nodes.isqrt().pow(2) < nodes {
+            nodes.isqrt() + 1
+        } else {
+            nodes.isqrt()
+        };
+        for slice in leaves.chunks_mut(NODE_CAPACITY * slices.max(1)) {
+            slice.sort_unstable_by(|&a, &b| center(a, 1).total_cmp(&center(b, 1)));
+        }
+
+        let mut levels = vec![leaves.iter().map(|&i| features[i].bbox).collect::<Vec<_>>()];
+        while let Some(level) = levels.last()
+            && level.len() > 1
+        {
+            let parents = level
+                .chunks(NODE_CAPACITY)
+                .map(|boxes| {
+                    boxes.iter().fold(
+                        [
+                            f64::INFINITY,
+                            f64::INFINITY,
+                            f64::NEG_INFINITY,
+                            f64::NEG_INFINITY,
+                        ],
+                        |acc, b| {
+                            [
+                                acc[0].min(b[0]),
+                                acc[1].min(b[1]),
+                                acc[2].max(b[2]),
+                                acc[3].max(b[3]),
+                            ]
+                        },
+                    )
+                })
+                .collect();
+            levels.push(parents);
+        }
+        Self { levels, leaves }
+    }
+
+    /// Visit the index of every feature whose bbox intersects the `query` box.
+    fn search(&self, query: &[f64; 4], mut visit: impl FnMut(usize)) {
+        let Some(root_level) = self.levels.len().checked_sub(1) else {
+            return;
+        };
+        let mut stack: Vec<(usize, usize)> = (0..self.levels[root_level].len())
+            .map(|i| (root_level, i))
+            .collect();
+        while let Some((level, i)) = stack.pop() {
+            let bbox = &self.levels[level][i];
+            if bbox[0] > query[2] || bbox[2] < query[0] || bbox[1] > query[3] || bbox[3] < query[1]
+            {
+                continue;
+            }
+            if level == 0 {
+                visit(self.leaves[i]);
+            } else {
+                let below = self.levels[level - 1].len();
+                stack.extend(
+                    (i * NODE_CAPACITY..below.min((i + 1) * NODE_CAPACITY)).map(|j| (level - 1, j)),
+                );
+            }
+        }
+    }
+
+    /// Locate a point: the feature that contains it (the first in file order, as with
+    /// [`pip_assign`]), else the feature whose boundary is nearest, if within `max_km` (0 =
+    /// containment only). Returns the index of the feature & the distance to it in km - 0 when
+    /// the point is inside it.
+    pub fn locate(
+        &self,
+        features: &[PipFeature],
+        lat: f64,
+        lon: f64,
+        max_km: f64,
+    ) -> Option<(usize, f64)> {
+        let mut inside: Option<usize> = None;
+        // antimeridian-crossing features are stored unwrapped past +180°, so they're looked up
+        // at both 360°-equivalent longitudes (see PipFeature::candidate_lons)
+        for probe in [lon, lon + 360.0] {
+            self.search(&[probe, lat, probe, lat], |i| {
+                if inside.is_none_or(|first| i < first) && feature_contains(&features[i], lon, lat)
+                {
+                    inside = Some(i);
+                }
+            });
+        }
+        if let Some(i) = inside {
+            return Some((i, 0.0));
+        }
+        if max_km <= 0.0 {
+            return None;
+        }
+
+        // the same equirectangular km scale as pip_assign, so the search window is exactly the
+        // set of bboxes within max_km. Near the poles, where a degree of longitude shrinks to
+        // nothing, the window spans every longitude.
+        let sx = lat.to_radians().cos() * KM_PER_DEG;
+        let sy = KM_PER_DEG;
+        let dlat = max_km / sy;
+        let dlon = if sx * 360.0 > max_km {
+            max_km / sx
+        } else {
+            360.0
+        };
+        let mut nearest: Option<(usize, f64)> = None;
+        for probe in [lon - 360.0, lon, lon + 360.0] {
+            self.search(&[probe - dlon, lat - dlat, probe + dlon, lat + dlat], |i| {
+                let feature = &features[i];
+                for flon in feature.candidate_lons(lon).into_iter().flatten() {
+                    if nearest.is_some_and(|(_, best_d2)| {
+                        bbox_dist2(&feature.bbox, flon, lat, sx, sy) > best_d2
+                    }) {
+                        continue;
+                    }
+                    let d2 = feature_dist2(feature, flon, lat, sx, sy);
+                    // ties go to the first feature in file order
+                    if nearest.is_none_or(|(best_i, best_d2)| {
+                        d2.total_cmp(&best_d2).then(i.cmp(&best_i)).is_lt()
+                    }) {
+                        nearest = Some((i, d2));
+                    }
+                }
+            });
+        }
+        nearest
+            .filter(|&(_, d2)| d2 <= max_km * max_km)
+            .map(|(i, d2)| (i, d2.sqrt()))
+    }
+}
@@ -502,6 +502,17 @@ pub fn create_reqwest_async_client(
     timeout_secs: u16,
     base_url: Option<String>,
 ) -> CliResult<Client> {
+    Ok(reqwest_async_client_builder(user_agent, timeout_secs, base_url)?.build()?)
+}
+
+/// The `ClientBuilder` behind `create_reqwest_async_client`, for callers that need
+/// to add settings of their own (e.g. default headers or a cookie store) before
+/// building the client.
+pub fn reqwest_async_client_builder(
+    user_agent: Option<String>,
+    timeout_secs: u16,
+    base_url: Option<String>,
+) -> CliResult<reqwest::ClientBuilder> {
     let base_url_for_retry = base_url.unwrap_or_default();
 
     let retries = reqwest::retry::for_host(base_url_for_retry).classify_fn(|req_rep| {
@@ -537,7 +548,7 @@ pub fn create_reqwest_async_client(
             .connect_timeout(timeout_duration);
     }
 
-    Ok(builder.build()?)
+    Ok(builder)
 }
 
 /// Creates a standardized blocking reqwest client with common configuration options.
@@ -2325,6 +2336,152 @@ pub fn transform(bs: &[u8], casei: bool) -> ByteString {
     }
 }
 
+/// A single key normalization step of a `--key-transform` pipeline.
+///
+/// The operation names follow `apply operations` where an equivalent operation exists
+/// (trim, ltrim, rtrim, lower, upper, squeeze & squeeze0), so the same rule is spelled
+/// the same way everywhere.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyTransformOp {
+    Trim,
+    Ltrim,
+    Rtrim,
+    Lower,
+    Upper,
+    Squeeze,
+    Squeeze0,
+    Digits,
+    Alnum,
+    StripZeros,
+    Date,
+}
+
+impl KeyTransformOp {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "trim" => Self::Trim,
+            "ltrim" => Self::Ltrim,
+            "rtrim" => Self::Rtrim,
+            "lower" => Self::Lower,
+            "upper" => Self::Upper,
+            "squeeze" => Self::Squeeze,
+            "squeeze0" => Self::Squeeze0,
+            "digits" => Self::Digits,
+            "alnum" => Self::Alnum,
+            "strip_zeros" => Self::StripZeros,
+            "date" => Self::Date,
+            _ => return None,
+        })
+    }
+}
+
+/// A key normalization pipeline, parsed from a comma-separated `--key-transform` list
+/// (e.g. "trim,lower,digits") and applied in order to every key field.
+///
+/// It is shared by the commands that match rows on key columns (`join`, `exclude`,
+/// `dedup` & `diff`) so a given rule normalizes keys identically in all of them.
+/// Like `transform`, invalid UTF-8 keys are returned unchanged.
+#[derive(Debug, Clone, Default)]
+pub struct KeyTransform {
+    ops:        Vec<KeyTransformOp>,
+    prefer_dmy: bool,
+}
+
+/// The valid `--key-transform` operation names, for error messages.
+pub const KEY_TRANSFORM_OPS: &str =
+    "trim, ltrim, rtrim, lower, upper, squeeze, squeeze0, digits, alnum, strip_zeros, date";
+
+impl KeyTransform {
+    /// Parse a comma-separated list of key transform operations.
+    /// The `date` operation honors `QSV_PREFER_DMY` when parsing ambiguous dates.
+    pub fn parse(spec: &str) -> CliResult<Self> {
+        let mut ops = Vec::new();
+        for name in spec.split(',') {
+            let name = name.trim().to_ascii_lowercase();
+            if name.is_empty() {
+                continue;
+            }
+            let Some(op) = KeyTransformOp::from_name(&name) else {
+                return fail_incorrectusage_clierror!(
+                    "Invalid --key-transform operation: \"{name}\". Valid operations are: \
+                     {KEY_TRANSFORM_OPS}."
+                );
+            };
+            ops.push(op);
+        }
+        if ops.is_empty() {
+            return fail_incorrectusage_clierror!(
+                "--key-transform requires at least one operation."
+            );
+        }
+        Ok(Self {
+            ops,
+            prefer_dmy: get_envvar_flag("QSV_PREFER_DMY"),
+        })
+    }
+
+    /// Parse an optional `--key-transform` flag value.
+    pub fn from_flag(spec: Option<&str>) -> CliResult<Option<Self>> {
+        spec.map(Self::parse).transpose()
+    }
+
+    /// Apply the pipeline to a single key field.
+    ///
+    /// trim (with or without a following lower) is `transform`, and squeeze/squeeze0
+    /// use `apply operations`' whitespace regex, so these rules match keys exactly as
+    /// the rest of qsv normalizes them.
+    pub fn apply(&self, bs: &[u8]) -> ByteString {
+        let Ok(s) = simdutf8::basic::from_utf8(bs) else {
+            return bs.to_vec();
+        };
+        let mut value = s.to_string();
+        let mut ops = self.ops.iter().peekable();
+        while let Some(op) = ops.next() {
+            value = match op {
+                KeyTransformOp::Trim => {
+                    let casei = ops.next_if_eq(&&KeyTransformOp::Lower).is_some();
+                    // transform returns valid UTF-8 input as valid UTF-8
+                    String::from_utf8(transform(value.as_bytes(), casei)).unwrap_or_default()
+                },
+                KeyTransformOp::Ltrim => value.trim_start().to_string(),
+                KeyTransformOp::Rtrim => value.trim_end().to_string(),
+                KeyTransformOp::Lower => {
+                    let mut buffer = String::with_capacity(value.len());
+                    to_lowercase_into(&value, &mut buffer);
+                    buffer
+                },
+                KeyTransformOp::Upper => value.to_uppercase(),
+                KeyTransformOp::Squeeze => regex_oncelock!(r"\s+")
+                    .replace_all(&value, " ")
+                    .into_owned(),
+                KeyTransformOp::Squeeze0 => {
+                    regex_oncelock!(r"\s+").replace_all(&value, "").into_owned()
+                },
+                KeyTransformOp::Digits => value.chars().filter(char::is_ascii_digit).collect(),
+                KeyTransformOp::Alnum => value.chars().filter(|c| c.is_alphanumeric()).collect(),
+                KeyTransformOp::StripZeros => {
+                    // keep at least one character so an all-zero key
+                    // collapses to a single "0" rather than an empty key
+                    let stripped = value.trim_start_matches('0');
+                    if stripped.is_empty() && !value.is_empty() {
+                        "0".to_string()
+                    } else {
+                        stripped.to_string()
+                    }
+                },
+                KeyTransformOp::Date => {
+                    // normalize to an ISO 8601 date. Values that are not dates are left as is.
+                    match qsv_dateparser::parse_with_preference(&value, self.prefer_dmy) {
+                        Ok(dt) => dt.format("%Y-%m-%d").to_string(),
+                        Err(_) => value,
+                    }
+                },
+            };
+        }
+        value.into_bytes()
+    }
+}
+
 pub fn load_dotenv() -> CliResult<()> {
     // First, check if there is a QSV_DOTENV_PATH environment variable set
     // if there is, use that as the .env file.
@@ -4491,6 +4648,27 @@ fn load_schema_from_file(path: &Path) -> Result<Option<Arc<Schema>>, Box<dyn std
     }
 }
 
+/// Loads the Polars schema of `input_path` from its pschema.json file, if the file exists
+/// and is newer than or as old as the input, so a stale schema is never used.
+#[cfg(feature = "polars")]
+pub fn load_current_pschema(input_path: &Path) -> CliResult<Option<Schema>> {
+    let schema_file = PathBuf::from(format!(
+        "{}.pschema.json",
+        input_path.canonicalize()?.display()
+    ));
+    let valid_schema_exists = schema_file.exists()
+        && schema_file.metadata()?.modified()? >= input_path.metadata()?.modified()?;
+    if !valid_schema_exists {
+        return Ok(None);
+    }
+
+    let file = File::open(&schema_file)?;
+    let mut buf_reader = BufReader::new(file);
+    let mut schema_json = String::with_capacity(100);
+    buf_reader.read_to_string(&mut schema_json)?;
+    Ok(Some(serde_json::from_str(&schema_json)?))
+}
+
 /// Tabular file extensions qsv recognizes for delimiter inference inside a zip.
 const ZIP_TABULAR_EXTS: [&str; 4] = ["csv", "tsv", "tab", "ssv"];
 
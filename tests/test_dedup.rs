@@ -422,3 +422,56 @@ fn dedup_not_sorted2() {
     let got: String = wrk.output_stderr(&mut cmd);
     assert!(got.contains("Aborting! Input not sorted!"));
 }
+
+#[test]
+fn dedup_key_transform() {
+    let wrk = Workdir::new("dedup_key_transform");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["email", "n"],
+            svec![" Ann@example.com", "1"],
+            svec!["bob@example.com", "2"],
+            svec!["ann@EXAMPLE.com ", "3"],
+        ],
+    );
+
+    let mut cmd = wrk.command("dedup");
+    cmd.args(["-s", "email", "--key-transform", "trim,lower", "in.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["email", "n"],
+        svec!["ann@EXAMPLE.com ", "3"],
+        svec!["bob@example.com", "2"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn dedup_key_transform_sorted() {
+    let wrk = Workdir::new("dedup_key_transform_sorted");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["id", "n"],
+            svec!["001", "1"],
+            svec!["1", "2"],
+            svec!["02", "3"],
+        ],
+    );
+
+    let mut cmd = wrk.command("dedup");
+    cmd.args([
+        "-s",
+        "id",
+        "--key-transform",
+        "strip_zeros",
+        "--sorted",
+        "in.csv",
+    ]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["id", "n"], svec!["001", "1"], svec!["02", "3"]];
+    assert_eq!(got, expected);
+}
@@ -1004,3 +1004,405 @@ diffresult|h1|h2|h3
 +|1|foo_changed|bar";
     assert_eq!(got.as_str(), expected);
 }
+
+#[test]
+fn diff_key_transform() {
+    let wrk = Workdir::new("diff_key_transform");
+
+    let left = vec![
+        svec!["email", "score"],
+        svec!["Ann@Example.com", "1"],
+        svec!["bob@example.com", "2"],
+    ];
+    wrk.create("left.csv", left);
+
+    let right = vec![
+        svec!["email", "score"],
+        svec![" ann@example.com", "1"],
+        svec!["BOB@example.com", "3"],
+    ];
+    wrk.create("right.csv", right);
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["--key-transform", "trim,lower", "left.csv", "right.csv"]);
+
+    let got: String = wrk.stdout_on_success(&mut cmd);
+    let expected = "\
+diffresult,email,score
+-,Ann@Example.com,1
++, ann@example.com,1
+-,bob@example.com,2
++,BOB@example.com,3";
+    assert_eq!(got.as_str(), expected);
+
+    // --external matches the keys the same way
+    let mut cmd = wrk.command("diff");
+    cmd.args([
+        "--key-transform",
+        "trim,lower",
+        "--external",
+        "--partitions",
+        "3",
+        "left.csv",
+        "right.csv",
+    ]);
+    let got: String = wrk.stdout_on_success(&mut cmd);
+    assert_eq!(got.as_str(), expected);
+}
+
+fn setup_changelog(name: &str) -> Workdir {
+    let wrk = Workdir::new(name);
+
+    let left = vec![
+        svec!["id", "name"],
+        svec!["1", "ann"],
+        svec!["2", "bob"],
+        svec!["3", "cat"],
+    ];
+    wrk.create("left.csv", left);
+
+    let right = vec![
+        svec!["id", "name"],
+        svec!["1", "ann"],
+        svec!["4", "dan"],
+        svec!["3", "kat"],
+    ];
+    wrk.create("right.csv", right);
+    wrk
+}
+
+#[test]
+fn diff_format_jsonl() {
+    let wrk = setup_changelog("diff_format_jsonl");
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["--format", "jsonl", "left.csv", "right.csv"]);
+
+    let got: String = wrk.stdout_on_success(&mut cmd);
+    let mut lines: Vec<&str> = got.lines().collect();
+    assert_eq!(
+        lines.remove(0),
+        r#"{"op":"summary","headers":["id","name"],"key":["id"],"key_transform":null,"added":1,"deleted":1,"modified":1}"#
+    );
+    lines.sort_unstable();
+    assert_eq!(
+        lines,
+        vec![
+            r#"{"op":"add","key":{"id":"4"},"position":1,"values":{"id":"4","name":"dan"}}"#,
+            r#"{"op":"delete","key":{"id":"2"},"values":{"id":"2","name":"bob"}}"#,
+            r#"{"op":"modify","key":{"id":"3"},"changes":{"name":{"old":"cat","new":"kat"}}}"#,
+        ]
+    );
+}
+
+#[test]
+fn diff_format_drop_equal_error() {
+    let wrk = setup_changelog("diff_format_drop_equal_error");
+
+    for (format, flag) in [
+        ("jsonl", "--drop-equal-fields"),
+        ("jsonpatch", "--drop-equal-columns"),
+    ] {
+        let mut cmd = wrk.command("diff");
+        cmd.args(["--format", format, flag, "left.csv", "right.csv"]);
+        let got = wrk.output_stderr(&mut cmd);
+        assert!(got.contains("only supported with --format csv"), "{got}");
+    }
+}
+
+#[test]
+fn diff_format_jsonpatch() {
+    let wrk = setup_changelog("diff_format_jsonpatch");
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["--format", "jsonpatch", "left.csv", "right.csv"]);
+
+    let got: String = wrk.stdout_on_success(&mut cmd);
+    let ops: Vec<serde_json::Value> = serde_json::from_str(&got).unwrap();
+    assert_eq!(ops.len(), 5);
+    for expected in [
+        serde_json::json!({"op": "add", "path": "/4", "value": {"id": "4", "name": "dan"}}),
+        serde_json::json!({"op": "test", "path": "/2", "value": {"id": "2", "name": "bob"}}),
+        serde_json::json!({"op": "remove", "path": "/2"}),
+        serde_json::json!({"op": "test", "path": "/3/name", "value": "cat"}),
+        serde_json::json!({"op": "replace", "path": "/3/name", "value": "kat"}),
+    ] {
+        assert!(ops.contains(&expected), "missing op {expected}");
+    }
+}
+
+#[test]
+fn diff_apply_jsonl_roundtrip() {
+    let wrk = setup_changelog("diff_apply_jsonl_roundtrip");
+
+    let mut cmd = wrk.command("diff");
+    cmd.args([
+        "--format",
+        "jsonl",
+        "--output",
+        "changes.jsonl",
+        "left.csv",
+        "right.csv",
+    ]);
+    wrk.assert_success(&mut cmd);
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["--apply", "changes.jsonl", "left.csv"]);
+
+    let got: String = wrk.stdout_on_success(&mut cmd);
+    let expected = wrk.read_to_string("right.csv").unwrap();
+    assert_eq!(got, expected.trim_end());
+}
+
+#[test]
+fn diff_apply_key_transform_roundtrip() {
+    let wrk = Workdir::new("diff_apply_key_transform_roundtrip");
+
+    let left = vec![
+        svec!["email", "score"],
+        svec!["Ann@Example.com", "1"],
+        svec!["bob@example.com", "2"],
+        svec!["cat@example.com", "3"],
+    ];
+    wrk.create("left.csv", left);
+
+    let right = vec![
+        svec!["email", "score"],
+        svec![" ann@example.com", "1"],
+        svec!["dan@example.com", "4"],
+        svec!["BOB@example.com", "5"],
+    ];
+    wrk.create("right.csv", right);
+
+    let mut cmd = wrk.command("diff");
+    cmd.args([
+        "--key-transform",
+        "trim,lower",
+        "--format",
+        "jsonl",
+        "--output",
+        "changes.jsonl",
+        "left.csv",
+        "right.csv",
+    ]);
+    wrk.assert_success(&mut cmd);
+
+    // the change log has the original keys & values
+    let changes = wrk.read_to_string("changes.jsonl").unwrap();
+    assert!(
+        changes.contains(r#"{"op":"delete","key":{"email":"cat@example.com"}"#),
+        "{changes}"
+    );
+    assert!(
+        changes.contains(
+            r#"{"op":"modify","key":{"email":"BOB@example.com"},"changes":{"email":{"old":"bob@example.com","new":"BOB@example.com"},"score":{"old":"2","new":"5"}}}"#
+        ),
+        "{changes}"
+    );
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["--apply", "changes.jsonl", "left.csv"]);
+
+    let got: String = wrk.stdout_on_success(&mut cmd);
+    let expected = wrk.read_to_string("right.csv").unwrap();
+    assert_eq!(got, expected.trim_end());
+}
+
+#[test]
+fn diff_apply_jsonpatch() {
+    let wrk = setup_changelog("diff_apply_jsonpatch");
+
+    let mut cmd = wrk.command("diff");
+    cmd.args([
+        "--format",
+        "jsonpatch",
+        "--output",
+        "changes.json",
+        "left.csv",
+        "right.csv",
+    ]);
+    wrk.assert_success(&mut cmd);
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["--apply", "changes.json", "left.csv"]);
+
+    // JSON Patch added rows are appended
+    let got: String = wrk.stdout_on_success(&mut cmd);
+    let expected = "id,name\n1,ann\n3,kat\n4,dan";
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn diff_apply_stale_changelog() {
+    let wrk = setup_changelog("diff_apply_stale_changelog");
+
+    let mut cmd = wrk.command("diff");
+    cmd.args([
+        "--format",
+        "jsonl",
+        "--output",
+        "changes.jsonl",
+        "left.csv",
+        "right.csv",
+    ]);
+    wrk.assert_success(&mut cmd);
+
+    // the change log doesn't apply to the right CSV
+    let mut cmd = wrk.command("diff");
+    cmd.args(["--apply", "changes.jsonl", "right.csv"]);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn diff_external_same_as_in_memory() {
+    let wrk = Workdir::new("diff_external_same_as_in_memory");
+    let test_file = wrk.load_test_file("boston311-100.csv");
+    let test_file2 = wrk.load_test_file("boston311-100-diff.csv");
+
+    let mut cmd = wrk.command("diff");
+    cmd.arg(&test_file).arg(&test_file2);
+    let expected: String = wrk.stdout_on_success(&mut cmd);
+
+    for partitions in ["1", "4"] {
+        let mut cmd = wrk.command("diff");
+        cmd.args(["--external", "--partitions", partitions])
+            .arg(&test_file)
+            .arg(&test_file2);
+        let got: String = wrk.stdout_on_success(&mut cmd);
+        assert_eq!(got, expected, "--partitions {partitions}");
+    }
+}
+
+#[test]
+fn diff_external_many_partitions() {
+    let wrk = setup_changelog("diff_external_many_partitions");
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["left.csv", "right.csv"]);
+    let expected: String = wrk.stdout_on_success(&mut cmd);
+
+    // the partitions of both CSVs outnumber the usual open files limit of 1024,
+    // but only one partition file is open at a time
+    let mut cmd = wrk.command("diff");
+    cmd.args(["--external", "--partitions", "1024", "left.csv", "right.csv"]);
+    let got: String = wrk.stdout_on_success(&mut cmd);
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn diff_external_stdin() {
+    let wrk = setup_changelog("diff_external_stdin");
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["--external", "--partitions", "3", "left.csv", "-"]);
+
+    let stdin_data = wrk.read_to_string("right.csv").unwrap();
+    cmd.stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped());
+    let mut child = cmd.spawn().unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    std::thread::spawn(move || {
+        std::io::Write::write_all(&mut stdin, stdin_data.as_bytes()).unwrap();
+    });
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    let got = String::from_utf8_lossy(&output.stdout);
+    let expected = "\
+diffresult,id,name
+-,2,bob
++,4,dan
+-,3,cat
++,3,kat";
+    assert_eq!(got.trim_end(), expected);
+}
+
+#[test]
+fn diff_external_both_stdin() {
+    let wrk = Workdir::new("diff_external_both_stdin");
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["--external", "-", "-"]);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn diff_numeric_tolerance() {
+    let wrk = Workdir::new("diff_numeric_tolerance");
+
+    let left = vec![
+        svec!["id", "price", "qty"],
+        svec!["1", "10.00", "5"],
+        svec!["2", "20.00", "7"],
+        svec!["3", "1000", "9"],
+    ];
+    wrk.create("left.csv", left);
+
+    let right = vec![
+        svec!["id", "price", "qty"],
+        svec!["1", "10.004", "5"],
+        svec!["2", "20.50", "7"],
+        svec!["3", "1005", "9"],
+    ];
+    wrk.create("right.csv", right);
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["--abs-tolerance", "0.01", "left.csv", "right.csv"]);
+    let got: String = wrk.stdout_on_success(&mut cmd);
+    let expected = "\
+diffresult,id,price,qty
+-,2,20.00,7
++,2,20.50,7
+-,3,1000,9
++,3,1005,9";
+    assert_eq!(got, expected);
+
+    // 1005 is within 1% of 1000, but 20.50 isn't within 1% of 20.00
+    let mut cmd = wrk.command("diff");
+    cmd.args(["--rel-tolerance", "0.01", "left.csv", "right.csv"]);
+    let got: String = wrk.stdout_on_success(&mut cmd);
+    let expected = "\
+diffresult,id,price,qty
+-,2,20.00,7
++,2,20.50,7";
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["--abs-tolerance", "-1", "left.csv", "right.csv"]);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn diff_ignore_case() {
+    let wrk = Workdir::new("diff_ignore_case");
+
+    let left = vec![
+        svec!["id", "city", "state"],
+        svec!["1", "Boston", "MA"],
+        svec!["2", "MÜNCHEN", "BY"],
+        svec!["3", "Austin", "TX"],
+    ];
+    wrk.create("left.csv", left);
+
+    let right = vec![
+        svec!["id", "city", "state"],
+        svec!["1", "BOSTON", "ma"],
+        svec!["2", "münchen", "BY"],
+        svec!["3", "Dallas", "tx"],
+    ];
+    wrk.create("right.csv", right);
+
+    let mut cmd = wrk.command("diff");
+    cmd.args([
+        "--ignore-case",
+        "--drop-equal-fields",
+        "left.csv",
+        "right.csv",
+    ]);
+    let got: String = wrk.stdout_on_success(&mut cmd);
+    let expected = "\
+diffresult,id,city,state
+-,3,Austin,
++,3,Dallas,";
+    assert_eq!(got, expected);
+}
@@ -314,3 +314,29 @@ fn exclude_1497_empty_fields() {
 
     assert_eq!(got, expected);
 }
+
+#[test]
+fn exclude_key_transform() {
+    let wrk = Workdir::new("exclude_key_transform");
+    wrk.create(
+        "records.csv",
+        vec![
+            svec!["id", "date"],
+            svec!["a", "2024-01-05"],
+            svec!["b", "2024-01-06 12:30:00"],
+            svec!["c", "2024-01-07"],
+        ],
+    );
+    wrk.create(
+        "seen.csv",
+        vec![svec!["date"], svec!["01/05/2024"], svec!["2024-01-06"]],
+    );
+
+    let mut cmd = wrk.command("exclude");
+    cmd.args(["date", "records.csv", "date", "seen.csv"])
+        .args(["--key-transform", "date"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["id", "date"], svec!["c", "2024-01-07"]];
+    assert_eq!(got, expected);
+}
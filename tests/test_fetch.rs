@@ -770,12 +770,23 @@ fn fetchpost_custom_user_agent() {
     ));
 }
 
-use std::{net::SocketAddr, sync::mpsc, thread};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
 
 use actix_web::{
-    App, HttpRequest, HttpServer, Responder, Result, dev::ServerHandle, middleware, rt, web,
+    App, HttpRequest, HttpResponse, HttpServer, Responder, Result, dev::ServerHandle, middleware,
+    rt, web,
 };
 use serde::Serialize;
+use serde_json::json;
 #[derive(Serialize)]
 struct MyObj {
     fullname: String,
@@ -797,6 +808,102 @@ async fn get_fullname(req: HttpRequest, name: web::Path<String>) -> Result<impl
     Ok(web::Json(obj))
 }
 
+const SMURFS: [&str; 5] = ["Papa", "Smurfette", "Brainy", "Hefty", "Clumsy"];
+
+fn smurf_item(i: usize) -> serde_json::Value {
+    json!({"id": i + 1, "name": SMURFS[i], "village": {"house": format!("mushroom {}", i + 1)}})
+}
+
+fn query_num(query: &web::Query<HashMap<String, String>>, name: &str) -> usize {
+    query
+        .get(name)
+        .and_then(|v| v.parse().ok())
+        .unwrap_or_default()
+}
+
+/// `/items/offset?offset=&limit=` - the items wrapped in a "results" object
+async fn get_items_offset(query: web::Query<HashMap<String, String>>) -> HttpResponse {
+    let offset = query_num(&query, "offset");
+    let limit = query_num(&query, "limit");
+    let results: Vec<_> = (offset..SMURFS.len().min(offset + limit))
+        .map(smurf_item)
+        .collect();
+    HttpResponse::Ok().json(json!({ "results": results }))
+}
+
+/// `/items/link?page=` - two items per page, with a rel="next" Link header
+async fn get_items_link(query: web::Query<HashMap<String, String>>) -> HttpResponse {
+    let page = query_num(&query, "page").max(1);
+    let start = (page - 1) * 2;
+    let items: Vec<_> = (start..SMURFS.len().min(start + 2))
+        .map(smurf_item)
+        .collect();
+    let mut resp = HttpResponse::Ok();
+    if start + 2 < SMURFS.len() {
+        resp.insert_header((
+            "Link",
+            format!(
+                r#"</items/link?page={}>; rel="next", </items/link?page=3>; rel="last""#,
+                page + 1
+            ),
+        ));
+    }
+    resp.json(items)
+}
+
+/// `/items/cursor?cursor=` - two items per page, with the next cursor in "meta"
+async fn get_items_cursor(query: web::Query<HashMap<String, String>>) -> HttpResponse {
+    // the cursor is the id of the next item, prefixed with "c"
+    let start = query
+        .get("cursor")
+        .and_then(|c| c.strip_prefix('c'))
+        .and_then(|c| c.parse::<usize>().ok())
+        .map_or(0, |id| id - 1);
+    let end = SMURFS.len().min(start + 2);
+    let data: Vec<_> = (start..end).map(smurf_item).collect();
+    let next = (end < SMURFS.len()).then(|| format!("c{}", end + 1));
+    HttpResponse::Ok().json(json!({ "data": data, "meta": { "next_cursor": next } }))
+}
+
+/// `/whoami?key=` - echoes the request's credentials
+async fn whoami(req: HttpRequest, query: web::Query<HashMap<String, String>>) -> HttpResponse {
+    let authorization = req
+        .headers()
+        .get("authorization")
+        .and_then(|v| v.to_str().ok());
+    HttpResponse::Ok().json(json!({ "authorization": authorization, "key": query.get("key") }))
+}
+
+/// handler with path parameters like `/slow/{ms}/{name}` that answers after `ms`
+/// milliseconds, so the responses of concurrent requests arrive out of order
+async fn slow_smurf(path: web::Path<(u64, String)>) -> HttpResponse {
+    let (ms, name) = path.into_inner();
+    rt::time::sleep(Duration::from_millis(ms)).await;
+    HttpResponse::Ok().json(json!({ "fullname": format!("{name} Smurf") }))
+}
+
+static TOKEN_REQUESTS: AtomicUsize = AtomicUsize::new(0);
+
+/// `/oauth/token` - an OAuth2 client-credentials token endpoint for client "qsv"
+async fn oauth_token(req: HttpRequest, form: web::Form<HashMap<String, String>>) -> HttpResponse {
+    TOKEN_REQUESTS.fetch_add(1, Ordering::SeqCst);
+    let basic = req
+        .headers()
+        .get("authorization")
+        .and_then(|v| v.to_str().ok());
+    // base64 of "qsv:t0ps3cret"
+    if form.get("grant_type").map(String::as_str) != Some("client_credentials")
+        || basic != Some("Basic cXN2OnQwcHMzY3JldA==")
+    {
+        return HttpResponse::Unauthorized().json(json!({ "error": "invalid_client" }));
+    }
+    HttpResponse::Ok().json(json!({
+        "access_token": "smurfberry",
+        "token_type": "Bearer",
+        "expires_in": 3600
+    }))
+}
+
 // Bind to 127.0.0.1 with an OS-assigned ephemeral port. Hardcoded ports
 // (this suite previously used 8081) collide on macOS CI runners with peer
 // integration-test binaries / lingering TIME_WAIT sockets and produce flaky
@@ -828,6 +935,12 @@ async fn run_webserver(
             .wrap(middleware::Compress::default())
             .wrap(Governor::new(&governor_conf))
             .service(web::resource("/user/{name}").route(web::get().to(get_fullname)))
+            .service(web::resource("/items/offset").route(web::get().to(get_items_offset)))
+            .service(web::resource("/items/link").route(web::get().to(get_items_link)))
+            .service(web::resource("/items/cursor").route(web::get().to(get_items_cursor)))
+            .service(web::resource("/whoami").to(whoami))
+            .service(web::resource("/slow/{ms}/{name}").to(slow_smurf))
+            .service(web::resource("/oauth/token").route(web::post().to(oauth_token)))
             .service(web::resource("/").to(index))
     });
 
@@ -961,6 +1074,410 @@ fn fetch_ratelimit() {
     rt::System::new().block_on(server_handle.stop(true));
 }
 
+#[test]
+#[serial]
+fn fetch_paginate() {
+    let (server_handle, addr) = start_fetch_webserver();
+    let items_url = |strategy: &str| format!("http://{addr}/items/{strategy}");
+    let wrk = Workdir::new("fetch_paginate");
+
+    let expected = vec![
+        svec!["id", "name", "village.house"],
+        svec!["1", "Papa", "mushroom 1"],
+        svec!["2", "Smurfette", "mushroom 2"],
+        svec!["3", "Brainy", "mushroom 3"],
+        svec!["4", "Hefty", "mushroom 4"],
+        svec!["5", "Clumsy", "mushroom 5"],
+    ];
+
+    // 3 pages, following the rel="next" Link header
+    let mut cmd = wrk.command("fetch");
+    cmd.args(["--paginate", "link", &items_url("link")])
+        .args(["--rate-limit", "4"]);
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got, expected);
+
+    // 3 pages of two records & an empty one, cached on disk
+    let dc_dir = wrk.path("dcache");
+    let mut cmd = wrk.command("fetch");
+    cmd.args(["--paginate", "offset", &items_url("offset")])
+        .args(["--jaq", ".results[]", "--page-size", "2"])
+        .args(["--rate-limit", "4", "--disk-cache"])
+        .args(["--disk-cache-dir", dc_dir.to_str().unwrap()]);
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got, expected);
+    assert!(dc_dir.join("fetch_pages_v3.redb").exists());
+
+    // all 4 pages are cache hits the second time around
+    let got = wrk.stderr_on_success(&mut cmd);
+    assert!(got.contains("from 4 pages (4 cached)"), "{got}");
+
+    // the pages cached with other HTTP headers are not reused
+    let mut cmd = wrk.command("fetch");
+    cmd.args(["--paginate", "offset", &items_url("offset")])
+        .args(["--jaq", ".results[]", "--page-size", "2"])
+        .args(["--http-header", "X-Tenant: smurfs"])
+        .args(["--rate-limit", "4", "--disk-cache"])
+        .args(["--disk-cache-dir", dc_dir.to_str().unwrap()]);
+    let got = wrk.stderr_on_success(&mut cmd);
+    assert!(got.contains("from 4 pages (0 cached)"), "{got}");
+
+    let mut cmd = wrk.command("fetch");
+    cmd.args(["--paginate", "cursor", &items_url("cursor")])
+        .args(["--jaq", ".data[]", "--next", ".meta.next_cursor"])
+        .args(["--max-pages", "2", "--rate-limit", "4"]);
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got, expected[..5].to_vec());
+
+    // the cursor strategy requires --next
+    let mut cmd = wrk.command("fetch");
+    cmd.args(["--paginate", "cursor", &items_url("cursor")]);
+    let got = wrk.stderr_on_error(&mut cmd);
+    assert!(got.contains("requires the --next option"), "{got}");
+
+    rt::System::new().block_on(server_handle.stop(true));
+}
+
+#[test]
+#[serial]
+fn fetch_auth_profiles() {
+    let (server_handle, addr) = start_fetch_webserver();
+    let wrk = Workdir::new("fetch_auth_profiles");
+    wrk.create_from_string("census.key", "k3y\n");
+    wrk.create_from_string(
+        "auth.toml",
+        &format!(
+            r#"
+[github]
+type = "bearer"
+token_env = "QSV_TEST_GITHUB_TOKEN"
+
+[intranet]
+type = "basic"
+username = "jdoe"
+password_env = "QSV_TEST_INTRANET_PASSWORD"
+
+[census]
+type = "api-key"
+param = "key"
+key_file = '{}'
+
+[warehouse]
+type = "oauth2"
+token_url = "http://{addr}/oauth/token"
+client_id = "qsv"
+client_secret_env = "QSV_TEST_CLIENT_SECRET"
+
+[leaky]
+type = "bearer"
+token = "hunter2"
+"#,
+            wrk.path("census.key").display()
+        ),
+    );
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["URL"],
+            vec![format!("http://{addr}/whoami")],
+            vec![format!("http://{addr}/whoami?page=2")],
+        ],
+    );
+
+    let fetch_auth = |profile: &str| {
+        let mut cmd = wrk.command("fetch");
+        cmd.env("QSV_AUTH_FILE", wrk.path("auth.toml"))
+            .env("QSV_TEST_GITHUB_TOKEN", "gh0")
+            .env("QSV_TEST_INTRANET_PASSWORD", "hunter2")
+            .env("QSV_TEST_CLIENT_SECRET", "t0ps3cret")
+            .args(["URL", "data.csv", "--rate-limit", "4", "--auth", profile]);
+        cmd
+    };
+
+    let got: String = wrk.stdout(&mut fetch_auth("github"));
+    assert_eq!(
+        got,
+        "{\"authorization\":\"Bearer gh0\",\"key\":null}\n{\"authorization\":\"Bearer \
+         gh0\",\"key\":null}"
+    );
+
+    let got: String = wrk.stdout(&mut fetch_auth("intranet"));
+    assert!(
+        got.starts_with(r#"{"authorization":"Basic amRvZTpodW50ZXIy","key":null}"#),
+        "{got}"
+    );
+
+    let got: String = wrk.stdout(&mut fetch_auth("census"));
+    assert!(
+        got.starts_with(r#"{"authorization":null,"key":"k3y"}"#),
+        "{got}"
+    );
+
+    // the access token is requested once, then reused for every row
+    TOKEN_REQUESTS.store(0, Ordering::SeqCst);
+    let got: String = wrk.stdout(&mut fetch_auth("warehouse"));
+    assert_eq!(
+        got,
+        "{\"authorization\":\"Bearer smurfberry\",\"key\":null}\n{\"authorization\":\"Bearer \
+         smurfberry\",\"key\":null}"
+    );
+    assert_eq!(TOKEN_REQUESTS.load(Ordering::SeqCst), 1);
+
+    // fetchpost uses the same profiles
+    let mut cmd = wrk.command("fetchpost");
+    cmd.env("QSV_AUTH_FILE", wrk.path("auth.toml"))
+        .env("QSV_TEST_GITHUB_TOKEN", "gh0")
+        .args([
+            "URL",
+            "URL",
+            "data.csv",
+            "--rate-limit",
+            "4",
+            "--auth",
+            "github",
+        ]);
+    let got: String = wrk.stdout(&mut cmd);
+    assert!(
+        got.starts_with(r#"{"authorization":"Bearer gh0","key":null}"#),
+        "{got}"
+    );
+
+    // a bad client secret fails up-front
+    let mut cmd = fetch_auth("warehouse");
+    cmd.env("QSV_TEST_CLIENT_SECRET", "wrong");
+    let got = wrk.stderr_on_error(&mut cmd);
+    assert!(got.contains("401 Unauthorized"), "{got}");
+
+    // secrets can't be given inline
+    let got = wrk.stderr_on_error(&mut fetch_auth("leaky"));
+    assert!(
+        got.contains("token is a secret and can't be given inline"),
+        "{got}"
+    );
+
+    let got = wrk.stderr_on_error(&mut fetch_auth("gargamel"));
+    assert!(
+        got.contains(r#"auth profile "gargamel" not found"#),
+        "{got}"
+    );
+
+    rt::System::new().block_on(server_handle.stop(true));
+}
+
+#[test]
+#[serial]
+fn fetch_concurrency() {
+    let (server_handle, addr) = start_fetch_webserver();
+
+    // the later a row, the sooner its response - ending with a repeat of the first
+    // request, while it's still in flight
+    let smurfs = [
+        (600, "Papa"),
+        (500, "Smurfette"),
+        (400, "Brainy"),
+        (300, "Hefty"),
+        (200, "Clumsy"),
+        (100, "Jokey"),
+        (600, "Papa"),
+    ];
+    let wrk = Workdir::new("fetch_concurrency");
+    let mut rows = vec![svec!["URL", "name"]];
+    for (ms, name) in smurfs {
+        rows.push(vec![
+            format!("http://{addr}/slow/{ms}/{name}"),
+            name.to_string(),
+        ]);
+    }
+    wrk.create("data.csv", rows);
+
+    let mut cmd = wrk.command("fetch");
+    cmd.arg("URL")
+        .arg("--new-column")
+        .arg("fullname")
+        .arg("--jaq")
+        .arg(r#"."fullname""#)
+        .arg("--concurrency")
+        .arg("8")
+        .arg("--report")
+        .arg("short")
+        .arg("data.csv");
+    let start = Instant::now();
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let elapsed = start.elapsed();
+
+    // still in the input order
+    let fullnames: Vec<&str> = got[1..].iter().map(|row| row[2].as_str()).collect();
+    assert_eq!(
+        fullnames,
+        [
+            "Papa Smurf",
+            "Smurfette Smurf",
+            "Brainy Smurf",
+            "Hefty Smurf",
+            "Clumsy Smurf",
+            "Jokey Smurf",
+            "Papa Smurf"
+        ]
+    );
+    // one at a time, the requests take at least 2.1 seconds
+    assert!(
+        elapsed < Duration::from_millis(2000),
+        "requests were not concurrent: {elapsed:?}"
+    );
+
+    // the repeated request got the response of the one in flight
+    let mut cmd = wrk.command("select");
+    cmd.arg("cache_hit").arg("data.csv.fetch-report.tsv");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["cache_hit"],
+        svec!["0"],
+        svec!["0"],
+        svec!["0"],
+        svec!["0"],
+        svec!["0"],
+        svec!["0"],
+        svec!["1"],
+    ];
+    assert_eq!(got, expected);
+
+    // fetchpost keeps the input order as well. The requests above used up the
+    // webserver's burst, so stay within its 4 qps
+    let mut cmd = wrk.command("fetchpost");
+    cmd.arg("URL")
+        .arg("name")
+        .arg("--new-column")
+        .arg("fullname")
+        .arg("--jaq")
+        .arg(r#"."fullname""#)
+        .arg("--concurrency")
+        .arg("4")
+        .arg("--rate-limit")
+        .arg("4")
+        .arg("data.csv");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let fullnames: Vec<&str> = got[1..].iter().map(|row| row[2].as_str()).collect();
+    assert_eq!(
+        fullnames,
+        [
+            "Papa Smurf",
+            "Smurfette Smurf",
+            "Brainy Smurf",
+            "Hefty Smurf",
+            "Clumsy Smurf",
+            "Jokey Smurf",
+            "Papa Smurf"
+        ]
+    );
+
+    let mut cmd = wrk.command("fetch");
+    cmd.arg("URL").arg("--concurrency").arg("0").arg("data.csv");
+    wrk.assert_err(&mut cmd);
+
+    rt::System::new().block_on(server_handle.stop(true));
+}
+
+#[test]
+#[serial]
+fn fetch_checkpoint() {
+    let (server_handle, addr) = start_fetch_webserver();
+
+    let wrk = Workdir::new("fetch_checkpoint");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["URL"],
+            vec![format!("http://{addr}/user/Papa")],
+            vec![format!("http://{addr}/user/Smurfette")],
+            svec!["thisisnotaurl"],
+            vec![format!("http://{addr}/user/Brainy")],
+            vec![format!("http://{addr}/user/Hefty")],
+        ],
+    );
+
+    let fetch_cmd = || {
+        let mut cmd = wrk.command("fetch");
+        cmd.arg("URL")
+            .arg("--new-column")
+            .arg("fullname")
+            .arg("--jaq")
+            .arg(r#"."fullname""#)
+            .arg("--max-errors")
+            .arg("1")
+            .arg("--report")
+            .arg("short")
+            .arg("--checkpoint")
+            .arg("data.ckpt")
+            .arg("--output")
+            .arg("out.csv")
+            .arg("data.csv");
+        cmd
+    };
+
+    // the invalid URL stops the run at --max-errors, keeping the checkpoint
+    wrk.assert_success(&mut fetch_cmd());
+    assert_eq!(wrk.read_csv("out.csv").len(), 3);
+    assert!(wrk.path("data.ckpt").exists());
+
+    // a record half-written when the run died
+    let mut out = std::fs::OpenOptions::new()
+        .append(true)
+        .open(wrk.path("out.csv"))
+        .unwrap();
+    std::io::Write::write_all(&mut out, b"http://partial").unwrap();
+    drop(out);
+
+    // a different run can't resume it
+    let mut cmd = wrk.command("fetch");
+    cmd.arg("URL")
+        .arg("--checkpoint")
+        .arg("data.ckpt")
+        .arg("--output")
+        .arg("out.csv")
+        .arg("data.csv");
+    wrk.assert_err(&mut cmd);
+
+    // the same run resumes after the error, dropping the partial record
+    wrk.assert_success(&mut fetch_cmd());
+    let got = wrk.read_csv("out.csv");
+    // (read_csv skips the header - a second header would show up as a record)
+    let expected = vec![
+        vec![format!("http://{addr}/user/Papa"), "Papa Smurf".to_string()],
+        vec![
+            format!("http://{addr}/user/Smurfette"),
+            "Smurfette Smurf".to_string(),
+        ],
+        svec!["thisisnotaurl", ""],
+        vec![
+            format!("http://{addr}/user/Brainy"),
+            "Brainy Smurf".to_string(),
+        ],
+        vec![
+            format!("http://{addr}/user/Hefty"),
+            "Hefty Smurf".to_string(),
+        ],
+    ];
+    assert_eq!(got, expected);
+    assert!(!wrk.path("data.ckpt").exists());
+
+    // the report has a single header & every record once
+    let mut cmd = wrk.command("select");
+    cmd.arg("url").arg("data.csv.fetch-report.tsv");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got.len(), 6);
+    assert_eq!(got[0], svec!["url"]);
+
+    // stdout can't be resumed
+    let mut cmd = wrk.command("fetch");
+    cmd.arg("URL")
+        .arg("--checkpoint")
+        .arg("data.ckpt")
+        .arg("data.csv");
+    wrk.assert_err(&mut cmd);
+
+    rt::System::new().block_on(server_handle.stop(true));
+}
+
 #[test]
 #[serial]
 fn fetch_complex_url_template() {
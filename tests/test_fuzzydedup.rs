@@ -0,0 +1,168 @@
+use crate::workdir::Workdir;
+
+fn setup(name: &str) -> Workdir {
+    let customers = vec![
+        svec!["id", "name", "city", "updated"],
+        svec!["1", "Acme Inc.", "Boston", "2024-01-05"],
+        svec!["2", "ACME, Inc", "Boston", "2024-03-01"],
+        svec!["3", "Globex", "Springfield", "2023-12-01"],
+        svec!["4", "Initech", "Austin", ""],
+        svec!["5", "acme inc", "Boston", ""],
+        svec!["6", "Initech LLC", "Dallas", "2024-02-02"],
+    ];
+
+    let wrk = Workdir::new(name);
+    wrk.create("customers.csv", customers);
+    wrk
+}
+
+#[test]
+fn fuzzydedup_block() {
+    let wrk = setup("fuzzydedup_block");
+    let mut cmd = wrk.command("fuzzydedup");
+    cmd.args(["--compare", "name", "--block", "city", "customers.csv"]);
+
+    let (got, stderr): (Vec<Vec<String>>, String) = wrk.read_stdout_and_stderr_on_success(&mut cmd);
+    // the Initech rows are in different cities, so they aren't compared
+    let expected = vec![
+        svec![
+            "id",
+            "name",
+            "city",
+            "updated",
+            "cluster_id",
+            "cluster_size",
+            "representative"
+        ],
+        svec!["1", "Acme Inc.", "Boston", "2024-01-05", "1", "3", "true"],
+        svec!["2", "ACME, Inc", "Boston", "2024-03-01", "1", "3", "false"],
+        svec!["3", "Globex", "Springfield", "2023-12-01", "2", "1", "true"],
+        svec!["4", "Initech", "Austin", "", "3", "1", "true"],
+        svec!["5", "acme inc", "Boston", "", "1", "3", "false"],
+        svec!["6", "Initech LLC", "Dallas", "2024-02-02", "4", "1", "true"],
+    ];
+    assert_eq!(got, expected);
+    assert_eq!(stderr.trim(), "2");
+}
+
+#[test]
+fn fuzzydedup_no_block_threshold() {
+    let wrk = setup("fuzzydedup_no_block_threshold");
+    let mut cmd = wrk.command("fuzzydedup");
+    cmd.args(["-c", "name:jaro_winkler", "--collapse", "customers.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec![
+            "id",
+            "name",
+            "city",
+            "updated",
+            "cluster_id",
+            "cluster_size"
+        ],
+        svec!["1", "Acme Inc.", "Boston", "2024-01-05", "1", "3"],
+        svec!["3", "Globex", "Springfield", "2023-12-01", "2", "1"],
+        svec!["4", "Initech", "Austin", "", "3", "2"],
+    ];
+    assert_eq!(got, expected);
+
+    // "Initech" & "Initech LLC" score ~0.93
+    let mut cmd = wrk.command("fuzzydedup");
+    cmd.args(["-c", "name", "-t", "0.95", "--collapse", "customers.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got.len(), 5);
+}
+
+#[test]
+fn fuzzydedup_most_recent() {
+    let wrk = setup("fuzzydedup_most_recent");
+    let mut cmd = wrk.command("fuzzydedup");
+    cmd.args([
+        "-c",
+        "name:jaro_winkler:2,city:exact",
+        "--survivorship",
+        "most_recent:updated",
+        "--collapse",
+        "customers.csv",
+    ]);
+
+    // the Initech rows' cities differ, so they score (2 * 0.93 + 0) / 3
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec![
+            "id",
+            "name",
+            "city",
+            "updated",
+            "cluster_id",
+            "cluster_size"
+        ],
+        svec!["2", "ACME, Inc", "Boston", "2024-03-01", "1", "3"],
+        svec!["3", "Globex", "Springfield", "2023-12-01", "2", "1"],
+        svec!["4", "Initech", "Austin", "", "3", "1"],
+        svec!["6", "Initech LLC", "Dallas", "2024-02-02", "4", "1"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn fuzzydedup_most_complete_fill_empty() {
+    let wrk = Workdir::new("fuzzydedup_most_complete_fill_empty");
+    wrk.create(
+        "contacts.csv",
+        vec![
+            svec!["name", "phone", "email"],
+            svec!["Acme Inc.", "", "info@acme.com"],
+            svec!["ACME, Inc", "555-1234", ""],
+            svec!["Acme Inc", "555-1234", "sales@acme.com"],
+            svec!["Globex", "555-9999", "hello@globex.com"],
+        ],
+    );
+
+    let mut cmd = wrk.command("fuzzydedup");
+    cmd.args([
+        "-c",
+        "name",
+        "--survivorship",
+        "most_complete",
+        "--collapse",
+        "contacts.csv",
+    ]);
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "phone", "email", "cluster_id", "cluster_size"],
+        svec!["Acme Inc", "555-1234", "sales@acme.com", "1", "3"],
+        svec!["Globex", "555-9999", "hello@globex.com", "2", "1"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("fuzzydedup");
+    cmd.args(["-c", "name", "--collapse", "--fill-empty", "contacts.csv"]);
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "phone", "email", "cluster_id", "cluster_size"],
+        svec!["Acme Inc.", "555-1234", "info@acme.com", "1", "3"],
+        svec!["Globex", "555-9999", "hello@globex.com", "2", "1"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn fuzzydedup_invalid_args() {
+    let wrk = setup("fuzzydedup_invalid_args");
+
+    let mut cmd = wrk.command("fuzzydedup");
+    cmd.args(["-c", "name:soundex", "customers.csv"]);
+    let got = wrk.stderr_on_error(&mut cmd);
+    assert!(got.contains("Invalid metric \"soundex\""), "{got}");
+
+    let mut cmd = wrk.command("fuzzydedup");
+    cmd.args(["-c", "nom", "customers.csv"]);
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("fuzzydedup");
+    cmd.args(["-c", "name", "--fill-empty", "customers.csv"]);
+    wrk.assert_err(&mut cmd);
+}
@@ -2419,3 +2419,215 @@ fn geocode_index_load_numeric_named_file_not_shortcut() {
         "index-load must not overwrite a real file whose name parses as a number"
     );
 }
+
+#[test]
+fn geocode_parse_address() {
+    let wrk = Workdir::new("geocode_parse_address");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["id", "address"],
+            svec![
+                "1",
+                "123 North Main Street, Apt. 4B, Springfield, IL 62704-1234, USA"
+            ],
+            svec!["2", "456 Oak Avenue Suite 200 Portland OR 97201"],
+            svec!["3", "PO Box 1234, Ottawa, Ontario K1A0B1, Canada"],
+            svec!["4", "77 Elm Ct 06103"],
+            svec!["5", "not an address"],
+            svec!["6", ""],
+        ],
+    );
+    let mut cmd = wrk.command("geocode");
+    cmd.arg("parse-address").arg("address").arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout_on_success(&mut cmd);
+    let expected = vec![
+        svec![
+            "id",
+            "address",
+            "house_number",
+            "street",
+            "unit",
+            "city",
+            "state",
+            "postcode",
+            "country"
+        ],
+        svec![
+            "1",
+            "123 North Main Street, Apt. 4B, Springfield, IL 62704-1234, USA",
+            "123",
+            "N MAIN ST",
+            "APT 4B",
+            "SPRINGFIELD",
+            "IL",
+            "62704-1234",
+            "US"
+        ],
+        svec![
+            "2",
+            "456 Oak Avenue Suite 200 Portland OR 97201",
+            "456",
+            "OAK AVE",
+            "STE 200",
+            "PORTLAND",
+            "OR",
+            "97201",
+            ""
+        ],
+        svec![
+            "3",
+            "PO Box 1234, Ottawa, Ontario K1A0B1, Canada",
+            "",
+            "PO BOX 1234",
+            "",
+            "OTTAWA",
+            "ON",
+            "K1A 0B1",
+            "CA"
+        ],
+        svec![
+            "4",
+            "77 Elm Ct 06103",
+            "77",
+            "ELM CT",
+            "",
+            "",
+            "",
+            "06103",
+            ""
+        ],
+        svec!["5", "not an address", "", "", "", "", "", "", ""],
+        svec!["6", "", "", "", "", "", "", "", ""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn geocode_parse_address_expanded_single_column() {
+    let wrk = Workdir::new("geocode_parse_address_expanded_single_column");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["address"],
+            svec!["500 e 42nd st. #12, new york, ny 10017"],
+            svec!["not an address"],
+        ],
+    );
+    let mut cmd = wrk.command("geocode");
+    cmd.arg("parse-address")
+        .arg("address")
+        .args(["--address-style", "expanded"])
+        .args(["--formatstr", "%address"])
+        .args(["--new-column", "clean"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout_on_success(&mut cmd);
+    let expected = vec![
+        svec!["address", "clean"],
+        svec![
+            "500 e 42nd st. #12, new york, ny 10017",
+            "500 East 42nd Street # 12, New York, New York 10017"
+        ],
+        svec!["not an address", "not an address"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn geocode_parse_address_dyncols_invalid_result() {
+    let wrk = Workdir::new("geocode_parse_address_dyncols_invalid_result");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["address"],
+            svec!["1600 Pennsylvania Ave NW, Washington, DC 20500"],
+            svec!["somewhere"],
+        ],
+    );
+    let mut cmd = wrk.command("geocode");
+    cmd.arg("parse-address")
+        .arg("address")
+        .args(["--formatstr", "%dyncols: {street:street}, {zip:postcode}"])
+        .args(["--invalid-result", "?"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout_on_success(&mut cmd);
+    let expected = vec![
+        svec!["address", "street", "zip"],
+        svec![
+            "1600 Pennsylvania Ave NW, Washington, DC 20500",
+            "PENNSYLVANIA AVE NW",
+            "20500"
+        ],
+        svec!["somewhere", "?", "?"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn geocode_parse_address_dynfmt_raw() {
+    let wrk = Workdir::new("geocode_parse_address_dynfmt_raw");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["address"],
+            svec!["9000 State Highway 50 Carson City nv"],
+        ],
+    );
+    let mut cmd = wrk.command("geocode");
+    cmd.arg("parse-address")
+        .arg("address")
+        .args(["--address-style", "raw"])
+        .args(["--formatstr", "{street} in {city}, {state}"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout_on_success(&mut cmd);
+    let expected = vec![
+        svec!["address"],
+        svec!["State Highway 50 in Carson City, nv"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn geocode_parse_addressnow_json() {
+    let wrk = Workdir::new("geocode_parse_addressnow_json");
+    let mut cmd = wrk.command("geocode");
+    cmd.arg("parse-addressnow")
+        .arg("1 Infinite Loop Cupertino CA 95014")
+        .args(["--formatstr", "%json"]);
+
+    let got: String = wrk.stdout_on_success(&mut cmd);
+    let expected = r#"{"house_number":"1","street":"INFINITE LOOP","unit":"","city":"CUPERTINO","state":"CA","postcode":"95014","country":""}"#;
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn geocode_parse_address_invalid_options() {
+    let wrk = Workdir::new("geocode_parse_address_invalid_options");
+    wrk.create("data.csv", vec![svec!["address"], svec!["1 Main St"]]);
+
+    // '%+' adds a column per component, so it can't be put in a --new-column
+    let mut cmd = wrk.command("geocode");
+    cmd.arg("parse-address")
+        .arg("address")
+        .args(["--new-column", "parsed"])
+        .arg("data.csv");
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("geocode");
+    cmd.arg("parse-address")
+        .arg("address")
+        .args(["--address-style", "fancy"])
+        .arg("data.csv");
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("geocode");
+    cmd.arg("parse-address")
+        .arg("address")
+        .args(["--formatstr", "%dyncols: {zip:zipcode}"])
+        .arg("data.csv");
+    wrk.assert_err(&mut cmd);
+}
@@ -0,0 +1,219 @@
+use crate::workdir::Workdir;
+
+/// Two adjacent 10° squares - "West" with a 1° hole at (2..3, 2..3) - and a box straddling the
+/// antimeridian.
+const BOUNDARIES: &str = r#"{
+  "type": "FeatureCollection",
+  "features": [
+    {
+      "type": "Feature",
+      "properties": { "name": "West", "code": 1 },
+      "geometry": {
+        "type": "Polygon",
+        "coordinates": [
+          [[0, 0], [10, 0], [10, 10], [0, 10], [0, 0]],
+          [[2, 2], [3, 2], [3, 3], [2, 3], [2, 2]]
+        ]
+      }
+    },
+    {
+      "type": "Feature",
+      "properties": { "name": "East", "code": 2 },
+      "geometry": {
+        "type": "Polygon",
+        "coordinates": [[[10, 0], [20, 0], [20, 10], [10, 10], [10, 0]]]
+      }
+    },
+    {
+      "type": "Feature",
+      "properties": { "name": "Dateline", "code": null },
+      "geometry": {
+        "type": "Polygon",
+        "coordinates": [[[170, -10], [-170, -10], [-170, 10], [170, 10], [170, -10]]]
+      }
+    },
+    {
+      "type": "Feature",
+      "properties": { "name": "Somewhere" },
+      "geometry": { "type": "Point", "coordinates": [5, 5] }
+    }
+  ]
+}"#;
+
+fn setup(name: &str) -> Workdir {
+    let wrk = Workdir::new(name);
+    wrk.create_from_string("boundaries.geojson", BOUNDARIES);
+    wrk.create(
+        "points.csv",
+        vec![
+            svec!["id", "lat", "lon"],
+            svec!["1", "5", "5"],
+            svec!["2", "5", "15"],
+            svec!["3", "2.5", "2.5"],
+            svec!["4", "5", "20.05"],
+            svec!["5", "0", "-179"],
+            svec!["6", "", ""],
+            svec!["7", "95", "5"],
+        ],
+    );
+    wrk
+}
+
+#[test]
+fn geojoin_properties() {
+    let wrk = setup("geojoin_properties");
+    let mut cmd = wrk.command("geojoin");
+    cmd.arg("boundaries.geojson")
+        .arg("points.csv")
+        .args(["--latitude", "lat"])
+        .args(["--longitude", "lon"])
+        .args(["--properties", "name,code"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "lat", "lon", "name", "code"],
+        svec!["1", "5", "5", "West", "1"],
+        svec!["2", "5", "15", "East", "2"],
+        // in West's hole
+        svec!["3", "2.5", "2.5", "", ""],
+        svec!["4", "5", "20.05", "", ""],
+        svec!["5", "0", "-179", "Dateline", ""],
+        svec!["6", "", "", "", ""],
+        // out of range
+        svec!["7", "95", "5", "", ""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn geojoin_all_properties_prefix() {
+    let wrk = setup("geojoin_all_properties_prefix");
+    let mut cmd = wrk.command("geojoin");
+    cmd.arg("boundaries.geojson")
+        .arg("points.csv")
+        .args(["-y", "lat", "-x", "lon"])
+        .args(["--prefix", "zone_"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got[0], svec!["id", "lat", "lon", "zone_name", "zone_code"]);
+    assert_eq!(got[2], svec!["2", "5", "15", "East", "2"]);
+}
+
+#[test]
+fn geojoin_max_distance() {
+    let wrk = setup("geojoin_max_distance");
+    let mut cmd = wrk.command("geojoin");
+    cmd.arg("boundaries.geojson")
+        .arg("points.csv")
+        .args(["-y", "lat", "-x", "lon"])
+        .args(["--properties", "name"])
+        .args(["--max-distance", "10"])
+        .args(["--distance-column", "dist_km"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "lat", "lon", "name", "dist_km"],
+        svec!["1", "5", "5", "West", "0"],
+        svec!["2", "5", "15", "East", "0"],
+        // ~55 km from the edge of the hole
+        svec!["3", "2.5", "2.5", "", ""],
+        // 0.05° east of East, at 5°N
+        svec!["4", "5", "20.05", "East", "5.545"],
+        svec!["5", "0", "-179", "Dateline", "0"],
+        svec!["6", "", "", "", ""],
+        svec!["7", "95", "5", "", ""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn geojoin_inner() {
+    let wrk = setup("geojoin_inner");
+    let mut cmd = wrk.command("geojoin");
+    cmd.arg("boundaries.geojson")
+        .arg("points.csv")
+        .args(["-y", "lat", "-x", "lon"])
+        .args(["--properties", "name"])
+        .arg("--inner");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "lat", "lon", "name"],
+        svec!["1", "5", "5", "West"],
+        svec!["2", "5", "15", "East"],
+        svec!["5", "0", "-179", "Dateline"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn geojoin_wkt_geometry() {
+    let wrk = Workdir::new("geojoin_wkt_geometry");
+    wrk.create_from_string("boundaries.geojson", BOUNDARIES);
+    wrk.create(
+        "points.csv",
+        vec![
+            svec!["id", "wkt"],
+            svec!["1", "POINT (15 5)"],
+            svec!["2", "point z(179.5 1 12)"],
+            svec!["3", "POINT EMPTY"],
+            svec!["4", "LINESTRING (0 0, 1 1)"],
+        ],
+    );
+    let mut cmd = wrk.command("geojoin");
+    cmd.arg("boundaries.geojson")
+        .arg("points.csv")
+        .args(["--geometry", "wkt"])
+        .args(["--properties", "name"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "wkt", "name"],
+        svec!["1", "POINT (15 5)", "East"],
+        svec!["2", "point z(179.5 1 12)", "Dateline"],
+        svec!["3", "POINT EMPTY", ""],
+        svec!["4", "LINESTRING (0 0, 1 1)", ""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn geojoin_invalid_options() {
+    let wrk = setup("geojoin_invalid_options");
+
+    // no point columns
+    let mut cmd = wrk.command("geojoin");
+    cmd.arg("boundaries.geojson").arg("points.csv");
+    wrk.assert_err(&mut cmd);
+
+    // --geometry with --latitude
+    let mut cmd = wrk.command("geojoin");
+    cmd.arg("boundaries.geojson").arg("points.csv").args([
+        "--geometry",
+        "lat",
+        "--latitude",
+        "lat",
+    ]);
+    wrk.assert_err(&mut cmd);
+
+    // a property that no polygon has
+    let mut cmd = wrk.command("geojoin");
+    cmd.arg("boundaries.geojson")
+        .arg("points.csv")
+        .args(["-y", "lat", "-x", "lon"])
+        .args(["--properties", "nmae"]);
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.contains("has the property \"nmae\""), "{got}");
+
+    // boundaries without polygons
+    wrk.create_from_string(
+        "points.geojson",
+        r#"{"type": "FeatureCollection", "features": [{"type": "Feature", "properties": {},
+            "geometry": {"type": "Point", "coordinates": [5, 5]}}]}"#,
+    );
+    let mut cmd = wrk.command("geojoin");
+    cmd.arg("points.geojson")
+        .arg("points.csv")
+        .args(["-y", "lat", "-x", "lon"]);
+    wrk.assert_err(&mut cmd);
+}
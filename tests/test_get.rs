@@ -243,6 +243,18 @@ async fn serve_boston_zst(c: web::Data<Counters>, req: HttpRequest) -> HttpRespo
     ranged_response(BOSTON_ZST, BOSTON_ZST_ETAG, &req, &c)
 }
 
+// STATES_CSV for requests bearing the "s3cret" token only, for `get --auth`.
+async fn serve_private(c: web::Data<Counters>, req: HttpRequest) -> HttpResponse {
+    if req
+        .headers()
+        .get("authorization")
+        .is_none_or(|v| v.to_str().unwrap_or_default() != "Bearer s3cret")
+    {
+        return HttpResponse::Unauthorized().finish();
+    }
+    ranged_response(STATES_CSV.as_bytes(), ETAG, &req, &c)
+}
+
 async fn run_webserver(
     tx: mpsc::Sender<Result<(ServerHandle, SocketAddr), String>>,
     counters: Counters,
@@ -253,6 +265,7 @@ async fn run_webserver(
             .service(web::resource("/states.csv").to(serve_states))
             .service(web::resource("/states_fresh.csv").to(serve_states_fresh))
             .service(web::resource("/one_fresh.csv").to(serve_one_fresh))
+            .service(web::resource("/private/states.csv").to(serve_private))
             // A larger object for the HTTP ranged/streaming download test.
             .service(web::resource("/big.csv").to(serve_big))
             // Inactivity-timeout tests: a slow-but-active stream and a stalled stream.
@@ -1219,6 +1232,329 @@ fn get_cache_set_policy() {
     wrk.assert_err(&mut bad);
 }
 
+// The `cache-refresh --json` statuses, by name.
+fn refresh_statuses(wrk: &Workdir, cmd: &mut std::process::Command) -> Vec<(String, String)> {
+    let got: String = wrk.stdout(cmd);
+    let outcomes: serde_json::Value = serde_json::from_str(&got).unwrap();
+    outcomes
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|o| {
+            (
+                o["logical_name"].as_str().unwrap().to_string(),
+                o["status"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn get_cache_refresh() {
+    let wrk = Workdir::new("get_cache_refresh");
+    wrk.create_from_string("stale.csv", STATES_CSV);
+    wrk.create_from_string("fresh.csv", STATES_CSV);
+    let cache_dir = wrk.path("qsvcache");
+
+    // a 0 TTL is stale right away
+    let mut g = wrk.command("get");
+    g.env("QSV_CACHE_DIR", &cache_dir)
+        .args(["--ttl", "0"])
+        .arg("stale.csv");
+    wrk.assert_success(&mut g);
+    let mut g = wrk.command("get");
+    g.env("QSV_CACHE_DIR", &cache_dir).arg("fresh.csv");
+    wrk.assert_success(&mut g);
+
+    wrk.create_from_string("stale.csv", &format!("{STATES_CSV}California,CA\n"));
+    wrk.create_from_string("fresh.csv", &format!("{STATES_CSV}Colorado,CO\n"));
+
+    // --all only refreshes the stale entry
+    let mut refresh = wrk.command("get");
+    refresh
+        .env("QSV_CACHE_DIR", &cache_dir)
+        .args(["cache-refresh", "--all", "--json"]);
+    assert_eq!(
+        refresh_statuses(&wrk, &mut refresh),
+        vec![("stale.csv".to_string(), "changed".to_string())]
+    );
+    let mut count = wrk.command("count");
+    count.env("QSV_CACHE_DIR", &cache_dir).arg("dc:stale.csv");
+    let got: String = wrk.stdout(&mut count);
+    assert_eq!(got, "5");
+
+    // a named entry is refreshed even when fresh
+    let mut refresh = wrk.command("get");
+    refresh.env("QSV_CACHE_DIR", &cache_dir).args([
+        "cache-refresh",
+        "dc:fresh.csv",
+        "stale.csv",
+        "--json",
+    ]);
+    assert_eq!(
+        refresh_statuses(&wrk, &mut refresh),
+        vec![
+            ("fresh.csv".to_string(), "changed".to_string()),
+            ("stale.csv".to_string(), "unchanged".to_string()),
+        ]
+    );
+
+    // an entry with the never policy is skipped
+    let mut set = wrk.command("get");
+    set.env("QSV_CACHE_DIR", &cache_dir).args([
+        "cache-set-policy",
+        "fresh.csv",
+        "--refresh",
+        "never",
+    ]);
+    wrk.assert_success(&mut set);
+    let mut refresh = wrk.command("get");
+    refresh
+        .env("QSV_CACHE_DIR", &cache_dir)
+        .args(["cache-refresh", "fresh.csv", "--json"]);
+    assert_eq!(
+        refresh_statuses(&wrk, &mut refresh),
+        vec![("fresh.csv".to_string(), "skipped".to_string())]
+    );
+
+    // an unknown name is an error
+    let mut bad = wrk.command("get");
+    bad.env("QSV_CACHE_DIR", &cache_dir)
+        .args(["cache-refresh", "nope.csv"]);
+    wrk.assert_err(&mut bad);
+}
+
+#[test]
+#[serial]
+fn get_http_auth_profile() {
+    let server = GetWebServer::start();
+    let wrk = Workdir::new("get_http_auth_profile");
+    let cache_dir = wrk.path("qsvcache");
+    wrk.create_from_string(
+        "auth.toml",
+        "[private]\ntype = \"bearer\"\ntoken_env = \"QSV_TEST_PRIVATE_TOKEN\"\n",
+    );
+    let url = server.url("private/states.csv");
+
+    let mut get = wrk.command("get");
+    get.env("QSV_CACHE_DIR", &cache_dir).arg(&url);
+    wrk.assert_err(&mut get);
+
+    let mut get = wrk.command("get");
+    get.env("QSV_CACHE_DIR", &cache_dir)
+        .env("QSV_AUTH_FILE", wrk.path("auth.toml"))
+        .env("QSV_TEST_PRIVATE_TOKEN", "s3cret")
+        .args(["--auth", "private", "--name", "private.csv"])
+        .arg(&url);
+    wrk.assert_success(&mut get);
+
+    let mut count = wrk.command("count");
+    count.env("QSV_CACHE_DIR", &cache_dir).arg("dc:private.csv");
+    let got: String = wrk.stdout(&mut count);
+    assert_eq!(got, "4");
+
+    // the entry remembers the profile, so a refresh authenticates too
+    let mut refresh = wrk.command("get");
+    refresh
+        .env("QSV_CACHE_DIR", &cache_dir)
+        .env("QSV_AUTH_FILE", wrk.path("auth.toml"))
+        .env("QSV_TEST_PRIVATE_TOKEN", "s3cret")
+        .args(["cache-refresh", "private.csv", "--force", "--json"]);
+    assert_eq!(
+        refresh_statuses(&wrk, &mut refresh),
+        vec![("private.csv".to_string(), "unchanged".to_string())]
+    );
+
+    // ...but never its credentials
+    let mut list = wrk.command("get");
+    list.env("QSV_CACHE_DIR", &cache_dir)
+        .args(["cache-list", "--json"]);
+    let got: String = wrk.stdout(&mut list);
+    assert!(!got.contains("s3cret"), "{got}");
+    let entries: serde_json::Value = serde_json::from_str(&got).unwrap();
+    assert_eq!(entries[0]["auth_profile"], "private");
+}
+
+#[cfg(not(windows))]
+#[test]
+fn get_cache_refresh_hook() {
+    let wrk = Workdir::new("get_cache_refresh_hook");
+    wrk.create_from_string("a.csv", STATES_CSV);
+    wrk.create_from_string("b.csv", STATES_CSV);
+    let cache_dir = wrk.path("qsvcache");
+
+    for src in ["a.csv", "b.csv"] {
+        let mut g = wrk.command("get");
+        g.env("QSV_CACHE_DIR", &cache_dir)
+            .args(["--ttl", "0"])
+            .arg(src);
+        wrk.assert_success(&mut g);
+    }
+    wrk.create_from_string("b.csv", &format!("{STATES_CSV}California,CA\n"));
+
+    // the hook only runs for the changed entry, and can read it via dc:
+    let mut refresh = wrk.command("get");
+    refresh.env("QSV_CACHE_DIR", &cache_dir).args([
+        "cache-refresh",
+        "--all",
+        "--jobs",
+        "2",
+        "--hook",
+        r#"echo "$QSV_GET_DC" >> hook.log && "$QSV_TEST_BIN" count "$QSV_GET_DC" >> hook.log"#,
+    ]);
+    refresh.env("QSV_TEST_BIN", wrk.qsv_bin());
+    let got: String = wrk.stdout(&mut refresh);
+    let status_of = |name: &str| {
+        got.lines()
+            .find(|l| l.starts_with(name))
+            .and_then(|l| l.split_whitespace().nth(1))
+            .map(ToString::to_string)
+    };
+    assert_eq!(status_of("a.csv").as_deref(), Some("unchanged"), "{got}");
+    assert_eq!(status_of("b.csv").as_deref(), Some("changed"), "{got}");
+
+    let hook_log: String = wrk.from_str(&wrk.path("hook.log"));
+    assert_eq!(hook_log, "dc:b.csv\n5\n");
+
+    // a failing hook fails the refresh
+    wrk.create_from_string("b.csv", STATES_CSV);
+    let mut refresh = wrk.command("get");
+    refresh
+        .env("QSV_CACHE_DIR", &cache_dir)
+        .args(["cache-refresh", "--all", "--hook", "exit 3"]);
+    wrk.assert_err(&mut refresh);
+}
+
+// The BLAKE3 hashes of an entry's versions, oldest first, from `cache-history --json`.
+fn history_hashes(wrk: &Workdir, cache_dir: &Path, name: &str) -> Vec<String> {
+    let mut history = wrk.command("get");
+    history
+        .env("QSV_CACHE_DIR", cache_dir)
+        .args(["cache-history", name, "--json"]);
+    let got: String = wrk.stdout(&mut history);
+    let versions: serde_json::Value = serde_json::from_str(&got).unwrap();
+    versions
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v["blake3"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn get_cache_history_versions() {
+    let wrk = Workdir::new("get_cache_history_versions");
+    let cache_dir = wrk.path("qsvcache");
+    let contents = [
+        STATES_CSV.to_string(),
+        format!("{STATES_CSV}California,CA\n"),
+        format!("{STATES_CSV}California,CA\nColorado,CO\n"),
+    ];
+    for content in &contents {
+        wrk.create_from_string("src.csv", content);
+        let mut g = wrk.command("get");
+        g.env("QSV_CACHE_DIR", &cache_dir)
+            .args(["--name", "x.csv", "--keep-versions", "2"])
+            .arg("src.csv");
+        wrk.assert_success(&mut g);
+    }
+
+    // the oldest version fell off, and its blob was reclaimed
+    let hashes = history_hashes(&wrk, &cache_dir, "dc:x.csv");
+    assert_eq!(hashes.len(), 2);
+    assert_eq!(count_content_blobs(&cache_dir), 2);
+
+    let count_of = |handle: &str| -> String {
+        let mut count = wrk.command("count");
+        count.env("QSV_CACHE_DIR", &cache_dir).arg(handle);
+        wrk.stdout(&mut count)
+    };
+    assert_eq!(count_of("dc:x.csv"), "6");
+    // by content...
+    assert_eq!(count_of(&format!("dc:x.csv@{}", &hashes[0][..8])), "5");
+    assert_eq!(count_of(&format!("dc:x.csv@{}", &hashes[1][..8])), "6");
+    // ...and by date: every version was fetched by 2999
+    assert_eq!(count_of("dc:x.csv@2999-01-01"), "6");
+
+    // the earlier version can be diffed against the current one
+    let mut diff = wrk.command("diff");
+    diff.env("QSV_CACHE_DIR", &cache_dir)
+        .args([&format!("dc:x.csv@{}", &hashes[0][..8]), "dc:x.csv"]);
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut diff);
+    assert_eq!(
+        got,
+        vec![
+            svec!["diffresult", "name", "abbr"],
+            svec!["+", "Colorado", "CO"],
+        ]
+    );
+
+    // and exported
+    let mut fetch = wrk.command("get");
+    fetch.env("QSV_CACHE_DIR", &cache_dir).args([
+        "cache-fetch",
+        &format!("x.csv@{}", &hashes[0][..8]),
+        "--output",
+        "old.csv",
+    ]);
+    wrk.assert_success(&mut fetch);
+    assert_eq!(wrk.read_to_string("old.csv").unwrap(), contents[1]);
+
+    // nothing was fetched by 2000, and an unknown hash matches nothing
+    let mut count = wrk.command("count");
+    count
+        .env("QSV_CACHE_DIR", &cache_dir)
+        .arg("dc:x.csv@2000-01-01");
+    wrk.assert_err(&mut count);
+    let mut count = wrk.command("count");
+    count
+        .env("QSV_CACHE_DIR", &cache_dir)
+        .arg("dc:x.csv@0000000000000000");
+    wrk.assert_err(&mut count);
+
+    // lowering the versions kept reclaims the earlier ones
+    let mut set = wrk.command("get");
+    set.env("QSV_CACHE_DIR", &cache_dir).args([
+        "cache-set-versions",
+        "x.csv",
+        "--keep-versions",
+        "1",
+    ]);
+    wrk.assert_success(&mut set);
+    assert_eq!(
+        history_hashes(&wrk, &cache_dir, "x.csv"),
+        vec![hashes[1].clone()]
+    );
+    assert_eq!(count_content_blobs(&cache_dir), 1);
+}
+
+#[test]
+fn get_keep_versions_inherited_on_refetch() {
+    let wrk = Workdir::new("get_keep_versions_inherited_on_refetch");
+    let cache_dir = wrk.path("qsvcache");
+    let contents = [
+        STATES_CSV.to_string(),
+        format!("{STATES_CSV}California,CA\n"),
+        format!("{STATES_CSV}California,CA\nColorado,CO\n"),
+    ];
+    for (i, content) in contents.iter().enumerate() {
+        wrk.create_from_string("src.csv", content);
+        let mut g = wrk.command("get");
+        g.env("QSV_CACHE_DIR", &cache_dir)
+            .args(["--name", "x.csv"])
+            .arg("src.csv");
+        // only the first fetch sets the versions kept
+        if i == 0 {
+            g.args(["--keep-versions", "12"]);
+        }
+        wrk.assert_success(&mut g);
+    }
+
+    // the plain re-fetches kept the entry's setting, so no version was reclaimed
+    assert_eq!(history_hashes(&wrk, &cache_dir, "x.csv").len(), 3);
+    assert_eq!(count_content_blobs(&cache_dir), 3);
+}
+
 #[test]
 fn get_cache_verify_ok() {
     let wrk = Workdir::new("get_cache_verify_ok");
@@ -2164,3 +2500,98 @@ fn get_dc_approx_cardinality_does_not_take_all_unique_shortcut() {
          input:\n{got}"
     );
 }
+
+// sftp:// & ftp(s):// sources need real servers, so these tests are ignored by
+// default. Run them against local containers serving resources/test, e.g.:
+//   docker run -d -p 2222:22 -v "$PWD/resources/test:/home/qsv/data:ro" atmoz/sftp qsv:qsv:1000
+//   docker run -d -p 2121:21 -p 21000-21010:21000-21010 -e USERS="qsv|qsv" \
+//     -v "$PWD/resources/test:/ftp/qsv/data:ro" delfer/alpine-ftp-server
+// then:
+//   QSV_TEST_SFTP_URL=sftp://qsv@127.0.0.1:2222/data/ QSV_SFTP_PASSWORD=qsv \
+//   QSV_SFTP_ACCEPT_NEW_HOSTS=1 QSV_TEST_FTP_URL=ftp://qsv@127.0.0.1:2121/data/ \
+//   QSV_FTP_PASSWORD=qsv cargo test -F get_sftp get_transfer -- --ignored
+#[cfg(feature = "get_sftp")]
+fn transfer_dir_url(var: &str) -> String {
+    let url = std::env::var(var).unwrap_or_else(|_| panic!("{var} is not set"));
+    if url.ends_with('/') {
+        url
+    } else {
+        format!("{url}/")
+    }
+}
+
+// The entry's synthetic size/mtime validator, from `cache-list --json`.
+#[cfg(feature = "get_sftp")]
+fn transfer_validator(wrk: &Workdir, cache_dir: &Path, name: &str) -> String {
+    let mut list = wrk.command("get");
+    list.env("QSV_CACHE_DIR", cache_dir)
+        .args(["cache-list", "--json"]);
+    let got: String = wrk.stdout(&mut list);
+    let entries: serde_json::Value = serde_json::from_str(&got).unwrap();
+    entries
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["logical_name"] == name)
+        .and_then(|e| e["etag"].as_str())
+        .unwrap_or_else(|| panic!("no validator for {name}:\n{got}"))
+        .to_string()
+}
+
+#[cfg(feature = "get_sftp")]
+fn transfer_fetch_revalidate_and_dc_read(var: &str, test_name: &str) {
+    let wrk = Workdir::new(test_name);
+    let cache_dir = wrk.path("qsvcache");
+    let source = format!("{}boston311-100.csv", transfer_dir_url(var));
+
+    let mut g = wrk.command("get");
+    g.env("QSV_CACHE_DIR", &cache_dir)
+        .args(["--name", "boston.csv"])
+        .arg(&source);
+    wrk.assert_success(&mut g);
+    let validator = transfer_validator(&wrk, &cache_dir, "boston.csv");
+    assert!(validator.starts_with("W/\""), "{validator}");
+
+    let mut count = wrk.command("count");
+    count.env("QSV_CACHE_DIR", &cache_dir).arg("dc:boston.csv");
+    let got: String = wrk.stdout(&mut count);
+    assert_eq!(got, "100");
+
+    // the file is unchanged, so the re-fetch revalidates the same entry & blob
+    let mut g = wrk.command("get");
+    g.env("QSV_CACHE_DIR", &cache_dir)
+        .args(["--name", "boston.csv"])
+        .arg(&source);
+    wrk.assert_success(&mut g);
+    assert_eq!(
+        transfer_validator(&wrk, &cache_dir, "boston.csv"),
+        validator
+    );
+    assert_eq!(count_content_blobs(&cache_dir), 1);
+
+    // a glob in the file name fetches every match
+    let mut g = wrk.command("get");
+    g.env("QSV_CACHE_DIR", &cache_dir)
+        .arg(format!("{}boston311-100.cs?", transfer_dir_url(var)));
+    wrk.assert_success(&mut g);
+    let mut count = wrk.command("count");
+    count
+        .env("QSV_CACHE_DIR", &cache_dir)
+        .arg("dc:boston311-100.csv");
+    let got: String = wrk.stdout(&mut count);
+    assert_eq!(got, "100");
+}
+
+#[cfg(feature = "get_sftp")]
+#[test]
+#[ignore = "requires a local SFTP server (see QSV_TEST_SFTP_URL above)"]
+fn get_transfer_sftp() {
+    transfer_fetch_revalidate_and_dc_read("QSV_TEST_SFTP_URL", "get_transfer_sftp");
+}
+
+#[cfg(feature = "get_sftp")]
+#[test]
+#[ignore = "requires a local FTP server (see QSV_TEST_FTP_URL above)"]
+fn get_transfer_ftp() {
+    transfer_fetch_revalidate_and_dc_read("QSV_TEST_FTP_URL", "get_transfer_ftp");
+}
@@ -732,6 +732,92 @@ fn join_inner_zeros_casei() {
     assert_eq!(got, expected);
 }
 
+#[test]
+fn join_inner_key_transform_digits() {
+    let wrk = Workdir::new("join_inner_key_transform_digits");
+
+    wrk.create(
+        "people.csv",
+        vec![
+            svec!["phone", "name"],
+            svec!["(555) 123-4567", "Ann"],
+            svec!["555.987.6543", "Bob"],
+            svec!["555-000-1111", "Cy"],
+        ],
+    );
+    wrk.create(
+        "calls.csv",
+        vec![
+            svec!["number", "minutes"],
+            svec!["5551234567", "12"],
+            svec!["555 987 6543", "3"],
+        ],
+    );
+
+    let mut cmd = wrk.command("join");
+    cmd.args(["phone", "people.csv", "number", "calls.csv"])
+        .args(["--key-transform", "digits"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["phone", "name", "number", "minutes"],
+        svec!["(555) 123-4567", "Ann", "5551234567", "12"],
+        svec!["555.987.6543", "Bob", "555 987 6543", "3"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn join_inner_key_transform_pipeline() {
+    let wrk = Workdir::new("join_inner_key_transform_pipeline");
+
+    wrk.create(
+        "left.csv",
+        vec![
+            svec!["name", "value"],
+            svec!["New   York", "1"],
+            svec!["Boston", "2"],
+        ],
+    );
+    wrk.create(
+        "right.csv",
+        vec![
+            svec!["name", "info"],
+            svec!["NEW YORK", "a"],
+            svec!["boston ", "b"],
+        ],
+    );
+
+    let mut cmd = wrk.command("join");
+    cmd.args(["name", "left.csv", "name", "right.csv"])
+        .args(["--key-transform", "squeeze,lower"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "value", "name", "info"],
+        svec!["New   York", "1", "NEW YORK", "a"],
+        svec!["Boston", "2", "boston ", "b"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn join_key_transform_invalid_op() {
+    let wrk = Workdir::new("join_key_transform_invalid_op");
+    wrk.create("left.csv", vec![svec!["id"], svec!["1"]]);
+    wrk.create("right.csv", vec![svec!["id"], svec!["1"]]);
+
+    let mut cmd = wrk.command("join");
+    cmd.args(["id", "left.csv", "id", "right.csv"])
+        .args(["--key-transform", "trim,soundex"]);
+
+    let stderr = wrk.stderr_on_error(&mut cmd);
+    assert!(
+        stderr.contains("Invalid --key-transform operation: \"soundex\""),
+        "Expected invalid operation error, got: {stderr}"
+    );
+}
+
 join_test_with_zeros!(
     join_outer_left_zeros,
     |wrk: Workdir, mut cmd: process::Command, headers: bool| {
@@ -1012,3 +1098,116 @@ fn join_right_anti_different_fields_issue_2437() {
     let expected = vec![svec!["id", "PA", "PB"], svec!["4", "105", "0101"]];
     assert_eq!(got, expected);
 }
+
+fn setup_report(name: &str) -> Workdir {
+    let wrk = Workdir::new(name);
+    wrk.create(
+        "left.csv",
+        vec![
+            svec!["id", "lval"],
+            svec!["a", "1"],
+            svec!["a", "2"],
+            svec!["b", "3"],
+            svec!["c", "4"],
+            svec!["", "5"],
+        ],
+    );
+    wrk.create(
+        "right.csv",
+        vec![
+            svec!["id", "rval"],
+            svec!["a", "x"],
+            svec!["d", "y"],
+            svec!["b", "z"],
+            svec!["a", "w"],
+            svec!["d", "v"],
+        ],
+    );
+    wrk
+}
+
+#[test]
+fn join_report() {
+    let wrk = setup_report("join_report");
+    let mut cmd = wrk.command("join");
+    cmd.args(["id", "left.csv", "id", "right.csv"])
+        .args(["--report", "report.json"]);
+    wrk.assert_success(&mut cmd);
+
+    let report: serde_json::Value =
+        serde_json::from_str(&wrk.read_to_string("report.json").unwrap()).unwrap();
+    assert_eq!(report["join_type"], "inner");
+    assert_eq!(report["matched_keys"], 2);
+    assert_eq!(report["cardinality"], "many_to_many");
+
+    let left = &report["left"];
+    assert_eq!(left["rows"], 5);
+    assert_eq!(left["null_key_rows"], 1);
+    assert_eq!(left["matched_rows"], 3);
+    assert_eq!(left["unmatched_rows"], 1);
+    assert_eq!(left["distinct_keys"], 3);
+    assert_eq!(left["unmatched_keys"], 1);
+    assert_eq!(left["duplicate_keys"], 1);
+    assert_eq!(left["match_rate"], 0.6);
+
+    let right = &report["right"];
+    assert_eq!(right["rows"], 5);
+    assert_eq!(right["null_key_rows"], 0);
+    assert_eq!(right["matched_rows"], 3);
+    assert_eq!(right["unmatched_rows"], 2);
+    assert_eq!(right["unmatched_keys"], 1);
+    assert_eq!(right["duplicate_keys"], 2);
+
+    let m2m = &report["many_to_many"];
+    assert_eq!(m2m["keys"], 1);
+    assert_eq!(m2m["output_rows"], 4);
+    assert_eq!(m2m["top_keys"][0]["key"][0], "a");
+    assert_eq!(m2m["top_keys"][0]["left_rows"], 2);
+    assert_eq!(m2m["top_keys"][0]["right_rows"], 2);
+}
+
+#[test]
+fn join_report_right_join_keeps_sides() {
+    let wrk = setup_report("join_report_right_join_keeps_sides");
+    let mut cmd = wrk.command("join");
+    cmd.args(["--right", "id", "left.csv", "id", "right.csv"])
+        .args(["--report", "report.json"]);
+    wrk.assert_success(&mut cmd);
+
+    let report: serde_json::Value =
+        serde_json::from_str(&wrk.read_to_string("report.json").unwrap()).unwrap();
+    assert_eq!(report["join_type"], "right");
+    assert_eq!(report["left"]["null_key_rows"], 1);
+    assert_eq!(report["left"]["unmatched_rows"], 1);
+    assert_eq!(report["right"]["unmatched_rows"], 2);
+    assert_eq!(report["right"]["duplicate_keys"], 2);
+}
+
+#[test]
+fn join_unmatched_sidecars() {
+    let wrk = setup_report("join_unmatched_sidecars");
+    let mut cmd = wrk.command("join");
+    cmd.args(["id", "left.csv", "id", "right.csv"])
+        .args(["--unmatched-left", "left-unmatched.csv"])
+        .args(["--unmatched-right", "right-unmatched.csv"]);
+    wrk.assert_success(&mut cmd);
+
+    let left_unmatched = wrk.read_to_string("left-unmatched.csv").unwrap();
+    assert_eq!(left_unmatched, "id,lval\nc,4\n,5\n");
+    let right_unmatched = wrk.read_to_string("right-unmatched.csv").unwrap();
+    assert_eq!(right_unmatched, "id,rval\nd,y\nd,v\n");
+}
+
+#[test]
+fn join_report_cross_error() {
+    let wrk = setup_report("join_report_cross_error");
+    let mut cmd = wrk.command("join");
+    cmd.args(["--cross", "id", "left.csv", "id", "right.csv"])
+        .args(["--report", "report.json"]);
+
+    let stderr = wrk.stderr_on_error(&mut cmd);
+    assert!(
+        stderr.contains("not supported for cross joins"),
+        "Expected cross join error, got: {stderr}"
+    );
+}
@@ -3173,3 +3173,114 @@ fn joinp_tsv_cache_schema_minus1() {
     assert_eq!(got.len(), 3); // header + 2 matched rows
     assert_eq!(got[0], svec!["id", "name", "city"]);
 }
+
+#[test]
+fn joinp_report() {
+    let wrk = setup("joinp_report");
+    let mut cmd = wrk.command("joinp");
+    cmd.args(["city", "cities.csv", "city", "places.csv"])
+        .args(["--report", "report.json"]);
+    wrk.assert_success(&mut cmd);
+
+    let report: serde_json::Value =
+        serde_json::from_str(&wrk.read_to_string("report.json").unwrap()).unwrap();
+    assert_eq!(report["join_type"], "inner");
+    assert_eq!(report["matched_keys"], 2);
+    assert_eq!(report["cardinality"], "one_to_many");
+    assert_eq!(report["left"]["rows"], 4);
+    assert_eq!(report["left"]["matched_rows"], 2);
+    assert_eq!(report["left"]["unmatched_keys"], 2);
+    assert_eq!(report["left"]["match_rate"], 0.5);
+    assert_eq!(report["right"]["rows"], 4);
+    assert_eq!(report["right"]["matched_rows"], 3);
+    assert_eq!(report["right"]["unmatched_rows"], 1);
+    assert_eq!(report["right"]["duplicate_keys"], 1);
+    assert_eq!(report["many_to_many"]["keys"], 0);
+}
+
+#[test]
+fn joinp_report_right_anti_keeps_sides() {
+    let wrk = setup("joinp_report_right_anti_keeps_sides");
+    let mut cmd = wrk.command("joinp");
+    cmd.args(["--right-anti", "city", "cities.csv", "city", "places.csv"])
+        .args(["--report", "report.json"]);
+    wrk.assert_success(&mut cmd);
+
+    let report: serde_json::Value =
+        serde_json::from_str(&wrk.read_to_string("report.json").unwrap()).unwrap();
+    assert_eq!(report["join_type"], "right_anti");
+    assert_eq!(report["cardinality"], "one_to_many");
+    assert_eq!(report["left"]["unmatched_rows"], 2);
+    assert_eq!(report["right"]["unmatched_rows"], 1);
+}
+
+#[test]
+fn joinp_unmatched_sidecars() {
+    let wrk = setup("joinp_unmatched_sidecars");
+    let mut cmd = wrk.command("joinp");
+    cmd.args(["city", "cities.csv", "city", "places.csv"])
+        .args(["--unmatched-left", "left-unmatched.csv"])
+        .args(["--unmatched-right", "right-unmatched.csv"])
+        .arg("--ignore-case");
+    wrk.assert_success(&mut cmd);
+
+    let mut left_unmatched = wrk.read_csv("left-unmatched.csv");
+    left_unmatched.sort();
+    assert_eq!(
+        left_unmatched,
+        vec![svec!["New York", "NY"], svec!["San Francisco", "CA"]]
+    );
+    let right_unmatched = wrk.read_csv("right-unmatched.csv");
+    assert_eq!(right_unmatched, vec![svec!["Orlando", "Disney World"]]);
+}
+
+#[test]
+fn joinp_report_nulls() {
+    let wrk = Workdir::new("joinp_report_nulls");
+    wrk.create(
+        "left.csv",
+        vec![svec!["id", "key"], svec!["1", "a"], svec!["2", ""]],
+    );
+    wrk.create(
+        "right.csv",
+        vec![svec!["key", "val"], svec!["", "x"], svec!["b", "y"]],
+    );
+    let mut cmd = wrk.command("joinp");
+    cmd.args(["key", "left.csv", "key", "right.csv"])
+        .arg("--nulls")
+        .args(["--report", "report.json"])
+        .args(["--unmatched-left", "left-unmatched.csv"])
+        .args(["--unmatched-right", "right-unmatched.csv"]);
+
+    // with --nulls, the empty keys are read as nulls, which never match each other
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got.len(), 1);
+
+    // the report describes the join that ran
+    let report: serde_json::Value =
+        serde_json::from_str(&wrk.read_to_string("report.json").unwrap()).unwrap();
+    assert_eq!(report["matched_keys"], 0);
+    assert_eq!(report["left"]["matched_rows"], 0);
+    assert_eq!(report["left"]["null_key_rows"], 1);
+    assert_eq!(report["right"]["matched_rows"], 0);
+    assert_eq!(report["right"]["null_key_rows"], 1);
+
+    assert_eq!(
+        wrk.read_csv("left-unmatched.csv"),
+        vec![svec!["1", "a"], svec!["2", ""]]
+    );
+    assert_eq!(
+        wrk.read_csv("right-unmatched.csv"),
+        vec![svec!["", "x"], svec!["b", "y"]]
+    );
+}
+
+#[test]
+fn joinp_report_cross_error() {
+    let wrk = setup("joinp_report_cross_error");
+    let mut cmd = wrk.command("joinp");
+    cmd.args(["--cross", "cities.csv", "places.csv"])
+        .args(["--report", "report.json"]);
+
+    wrk.assert_err(&mut cmd);
+}
@@ -0,0 +1,105 @@
+use crate::workdir::Workdir;
+
+#[test]
+fn merge_sorted_inputs() {
+    let wrk = Workdir::new("merge_sorted_inputs");
+    wrk.create(
+        "part1.csv",
+        vec![
+            svec!["id", "src"],
+            svec!["a", "1"],
+            svec!["c", "1"],
+            svec!["e", "1"],
+        ],
+    );
+    wrk.create(
+        "part2.csv",
+        vec![
+            svec!["id", "src"],
+            svec!["b", "2"],
+            svec!["c", "2"],
+            svec!["f", "2"],
+        ],
+    );
+    let mut cmd = wrk.command("merge");
+    cmd.args(["--select", "id", "part1.csv", "part2.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    // equal keys are output in input order
+    let expected = vec![
+        svec!["id", "src"],
+        svec!["a", "1"],
+        svec!["b", "2"],
+        svec!["c", "1"],
+        svec!["c", "2"],
+        svec!["e", "1"],
+        svec!["f", "2"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn merge_numeric_three_inputs() {
+    let wrk = Workdir::new("merge_numeric_three_inputs");
+    wrk.create("a.csv", vec![svec!["n"], svec!["2"], svec!["10"]]);
+    wrk.create("b.csv", vec![svec!["n"], svec!["1"], svec!["100"]]);
+    wrk.create("c.csv", vec![svec!["n"], svec!["9"]]);
+    let mut cmd = wrk.command("merge");
+    cmd.args(["--numeric", "a.csv", "b.csv", "c.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["n"],
+        svec!["1"],
+        svec!["2"],
+        svec!["9"],
+        svec!["10"],
+        svec!["100"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn merge_unsorted_input_error() {
+    let wrk = Workdir::new("merge_unsorted_input_error");
+    wrk.create("a.csv", vec![svec!["n"], svec!["1"], svec!["3"]]);
+    wrk.create("b.csv", vec![svec!["n"], svec!["4"], svec!["2"]]);
+    let mut cmd = wrk.command("merge");
+    cmd.args(["a.csv", "b.csv"]);
+
+    let stderr = wrk.stderr_on_error(&mut cmd);
+    assert!(
+        stderr.contains("is not sorted"),
+        "Expected unsorted input error, got: {stderr}"
+    );
+}
+
+#[test]
+fn merge_unsorted_input_no_output() {
+    let wrk = Workdir::new("merge_unsorted_input_no_output");
+    wrk.create("a.csv", vec![svec!["n"], svec!["1"], svec!["3"]]);
+    wrk.create("b.csv", vec![svec!["n"], svec!["4"], svec!["2"]]);
+    let mut cmd = wrk.command("merge");
+    cmd.args(["a.csv", "b.csv", "--output", "merged.csv"]);
+
+    wrk.assert_err(&mut cmd);
+    // neither a partial output nor the temp file is left behind
+    assert!(!wrk.path("merged.csv").exists());
+    let leftovers = std::fs::read_dir(wrk.path(".")).unwrap().count();
+    assert_eq!(leftovers, 2);
+}
+
+#[test]
+fn merge_headers_mismatch_error() {
+    let wrk = Workdir::new("merge_headers_mismatch_error");
+    wrk.create("a.csv", vec![svec!["n"], svec!["1"]]);
+    wrk.create("b.csv", vec![svec!["m"], svec!["2"]]);
+    let mut cmd = wrk.command("merge");
+    cmd.args(["a.csv", "b.csv"]);
+
+    let stderr = wrk.stderr_on_error(&mut cmd);
+    assert!(
+        stderr.contains("All inputs must have the same headers"),
+        "Expected headers mismatch error, got: {stderr}"
+    );
+}
@@ -2063,3 +2063,201 @@ pivotp_groupby_test!(
         );
     }
 );
+
+// ==================== MULTIPLE AGGREGATION TESTS ====================
+
+// Test group-by with multiple aggregations
+pivotp_test!(
+    pivotp_groupby_multi_agg,
+    |wrk: Workdir, mut cmd: process::Command| {
+        cmd.args([
+            "--index",
+            "product",
+            "--values",
+            "sales",
+            "--agg",
+            "sum,mean,count",
+            "sales.csv",
+        ]);
+
+        wrk.assert_success(&mut cmd);
+
+        let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+        let expected = vec![
+            svec!["product", "sales_sum", "sales_mean", "sales_count"],
+            svec!["A", "600", "200.0", "3"],
+            svec!["B", "750", "250.0", "3"],
+        ];
+        assert_eq!(got, expected);
+    }
+);
+
+// Test pivot with multiple aggregations
+pivotp_test!(
+    pivotp_multi_agg,
+    |wrk: Workdir, mut cmd: process::Command| {
+        cmd.args([
+            "product",
+            "--index",
+            "date",
+            "--values",
+            "sales",
+            "--agg",
+            "sum,max",
+            "sales.csv",
+        ]);
+
+        wrk.assert_success(&mut cmd);
+
+        let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+        let expected = vec![
+            svec!["date", "A_sum", "B_sum", "A_max", "B_max"],
+            svec!["2023-01-01", "300", "150", "200", "150"],
+            svec!["2023-01-02", "300", "600", "300", "350"],
+        ];
+        assert_eq!(got, expected);
+    }
+);
+
+// Test error: smart cannot be combined with other aggregations
+pivotp_test!(
+    pivotp_multi_agg_smart_error,
+    |wrk: Workdir, mut cmd: process::Command| {
+        cmd.args([
+            "product",
+            "--index",
+            "date",
+            "--values",
+            "sales",
+            "--agg",
+            "smart,sum",
+            "sales.csv",
+        ]);
+
+        let stderr = wrk.stderr_on_error(&mut cmd);
+        assert!(
+            stderr.contains("--agg smart cannot be combined with other aggregations"),
+            "Expected multi-agg smart error, got: {stderr}"
+        );
+    }
+);
+
+// ==================== UNPIVOT MODE TESTS ====================
+
+macro_rules! pivotp_unpivot_test {
+    ($name:ident, $fun:expr_2021) => {
+        mod $name {
+            use std::process;
+
+            #[allow(unused_imports)]
+            use super::setup_unpivot;
+            use crate::workdir::Workdir;
+
+            #[test]
+            fn main() {
+                let wrk = setup_unpivot(stringify!($name));
+                let cmd = wrk.command("pivotp");
+                $fun(wrk, cmd);
+            }
+        }
+    };
+}
+
+fn setup_unpivot(name: &str) -> Workdir {
+    let data = vec![
+        svec!["id", "name", "q1", "q2"],
+        svec!["1", "a", "10", "20"],
+        svec!["2", "b", "30", "40"],
+    ];
+
+    let wrk = Workdir::new(name);
+    wrk.create("wide.csv", data);
+    wrk
+}
+
+// Test basic unpivot with index columns
+pivotp_unpivot_test!(
+    pivotp_unpivot_basic,
+    |wrk: Workdir, mut cmd: process::Command| {
+        cmd.args(["--unpivot", "--index", "id,name", "wide.csv"]);
+
+        wrk.assert_success(&mut cmd);
+
+        let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+        let expected = vec![
+            svec!["id", "name", "variable", "value"],
+            svec!["1", "a", "q1", "10"],
+            svec!["2", "b", "q1", "30"],
+            svec!["1", "a", "q2", "20"],
+            svec!["2", "b", "q2", "40"],
+        ];
+        assert_eq!(got, expected);
+    }
+);
+
+// Test unpivot with --values-regex and custom variable/value names
+pivotp_unpivot_test!(
+    pivotp_unpivot_regex_names,
+    |wrk: Workdir, mut cmd: process::Command| {
+        cmd.args([
+            "--unpivot",
+            "--index",
+            "id",
+            "--values-regex",
+            "^q",
+            "--variable-name",
+            "quarter",
+            "--value-name",
+            "amount",
+            "wide.csv",
+        ]);
+
+        wrk.assert_success(&mut cmd);
+
+        let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+        let expected = vec![
+            svec!["id", "quarter", "amount"],
+            svec!["1", "q1", "10"],
+            svec!["2", "q1", "30"],
+            svec!["1", "q2", "20"],
+            svec!["2", "q2", "40"],
+        ];
+        assert_eq!(got, expected);
+    }
+);
+
+// Test unpivot of columns with different data types (unpivoted as strings)
+pivotp_unpivot_test!(
+    pivotp_unpivot_mixed_types,
+    |wrk: Workdir, mut cmd: process::Command| {
+        cmd.args(["--unpivot", "--index", "id", "wide.csv"]);
+
+        wrk.assert_success(&mut cmd);
+
+        let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+        let expected = vec![
+            svec!["id", "variable", "value"],
+            svec!["1", "name", "a"],
+            svec!["2", "name", "b"],
+            svec!["1", "q1", "10"],
+            svec!["2", "q1", "30"],
+            svec!["1", "q2", "20"],
+            svec!["2", "q2", "40"],
+        ];
+        assert_eq!(got, expected);
+    }
+);
+
+// Test error: --values-regex without --unpivot
+pivotp_unpivot_test!(
+    pivotp_values_regex_requires_unpivot,
+    |wrk: Workdir, mut cmd: process::Command| {
+        cmd.args(["--index", "id", "--values-regex", "^q", "wide.csv"]);
+
+        let stderr = wrk.stderr_on_error(&mut cmd);
+        assert!(
+            stderr.contains("--values-regex requires --unpivot"),
+            "Expected --values-regex error, got: {stderr}"
+        );
+    }
+);
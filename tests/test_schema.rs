@@ -835,3 +835,505 @@ fn diagnose_schema_generation_s390x() {
     // This test always passes - we just want the diagnostic output
     // The actual assertion failures are in the other tests
 }
+
+fn create_compare_schemas(wrk: &Workdir) {
+    wrk.create_from_string(
+        "old.schema.json",
+        r#"{
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer" },
+                "customer_name": { "type": "string", "maxLength": 10 },
+                "status": { "type": "string", "enum": ["open", "closed"] },
+                "amount": { "type": "integer", "minimum": 0 },
+                "legacy": { "type": "string" }
+            },
+            "required": ["id", "customer_name", "status", "amount", "legacy"]
+        }"#,
+    );
+    wrk.create_from_string(
+        "new.schema.json",
+        r#"{
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer" },
+                "customer_nm": { "type": ["string", "null"], "maxLength": 20 },
+                "status": { "type": "string", "enum": ["open", "closed", "pending"] },
+                "amount": { "type": "number", "minimum": 0 },
+                "region": { "type": "string" }
+            },
+            "required": ["id", "customer_nm", "status", "amount", "region"]
+        }"#,
+    );
+}
+
+#[test]
+fn schema_compare() {
+    let wrk = Workdir::new("schema_compare");
+    create_compare_schemas(&wrk);
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("--compare")
+        .arg("old.schema.json")
+        .arg("new.schema.json");
+    let (got, stderr): (Vec<Vec<String>>, String) = wrk.read_stdout_and_stderr_on_error(&mut cmd);
+    let expected = vec![
+        svec!["column", "change", "detail", "old", "new", "compatibility"],
+        svec![
+            "customer_nm",
+            "column_renamed",
+            "header similarity 0.83",
+            "customer_name",
+            "customer_nm",
+            "breaking"
+        ],
+        svec![
+            "customer_nm",
+            "type_widened",
+            "",
+            "string",
+            "string|null",
+            "backward"
+        ],
+        svec![
+            "customer_nm",
+            "constraint_loosened",
+            "maxLength",
+            "10",
+            "20",
+            "backward"
+        ],
+        svec![
+            "status",
+            "enum_values_added",
+            "pending",
+            "2",
+            "3",
+            "backward"
+        ],
+        svec![
+            "amount",
+            "type_widened",
+            "",
+            "integer",
+            "number",
+            "backward"
+        ],
+        svec!["legacy", "column_removed", "", "string", "", "backward"],
+        svec!["region", "column_added", "", "", "string", "forward"],
+    ];
+    assert_eq!(got, expected);
+    assert!(
+        stderr.contains("Schema changes are not backward compatible (compatibility: breaking)."),
+        "got: {stderr}"
+    );
+
+    // --compat none only reports the changes
+    let mut cmd = wrk.command("schema");
+    cmd.arg("--compare")
+        .args(["--compat", "none"])
+        .arg("old.schema.json")
+        .arg("new.schema.json");
+    wrk.assert_success(&mut cmd);
+}
+
+#[test]
+fn schema_compare_backward_compatible() {
+    let wrk = Workdir::new("schema_compare_backward_compatible");
+    create_compare_schemas(&wrk);
+
+    // the reverse changes are forward compatible, except for the rename
+    let mut cmd = wrk.command("schema");
+    cmd.arg("--compare")
+        .args(["--rename-threshold", "0.9"])
+        .arg("new.schema.json")
+        .arg("old.schema.json");
+    let (got, _): (Vec<Vec<String>>, String) = wrk.read_stdout_and_stderr_on_error(&mut cmd);
+    let compatibilities: Vec<(&str, &str)> = got[1..]
+        .iter()
+        .map(|row| (row[1].as_str(), row[5].as_str()))
+        .collect();
+    assert_eq!(
+        compatibilities,
+        vec![
+            ("column_removed", "backward"),
+            ("enum_values_removed", "forward"),
+            ("type_narrowed", "forward"),
+            ("column_removed", "backward"),
+            ("column_added", "forward"),
+            ("column_added", "forward"),
+        ]
+    );
+
+    // an enum value added is backward compatible
+    wrk.create_from_string(
+        "status.schema.json",
+        r#"{ "properties": { "status": { "type": "string", "enum": ["open", "closed"] } } }"#,
+    );
+    wrk.create_from_string(
+        "status2.schema.json",
+        r#"{ "properties": { "status": { "type": "string", "enum": ["open", "closed", "new"] } } }"#,
+    );
+    let mut cmd = wrk.command("schema");
+    cmd.arg("--compare")
+        .arg("status.schema.json")
+        .arg("status2.schema.json");
+    wrk.assert_success(&mut cmd);
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("--compare")
+        .args(["--compat", "full"])
+        .arg("status.schema.json")
+        .arg("status2.schema.json");
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+#[file_serial]
+fn schema_compare_csv() {
+    let wrk = Workdir::new("schema_compare_csv");
+    wrk.create(
+        "old.csv",
+        vec![svec!["id", "name"], svec!["1", "alpha"], svec!["2", "beta"]],
+    );
+    wrk.create(
+        "new.csv",
+        vec![
+            svec!["id", "name", "region"],
+            svec!["1", "alpha", "EU"],
+            svec!["2", "beta", "US"],
+        ],
+    );
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("old.csv");
+    wrk.assert_success(&mut cmd);
+
+    // compare the generated schema against the schema inferred from the new CSV
+    let mut cmd = wrk.command("schema");
+    cmd.arg("--compare")
+        .arg("old.csv.schema.json")
+        .arg("new.csv");
+    let (got, _): (Vec<Vec<String>>, String) = wrk.read_stdout_and_stderr_on_error(&mut cmd);
+    assert_eq!(
+        got,
+        vec![
+            svec!["column", "change", "detail", "old", "new", "compatibility"],
+            svec!["region", "column_added", "", "", "string", "forward"],
+        ]
+    );
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("--compare")
+        .args(["--compat", "forward"])
+        .arg("old.csv.schema.json")
+        .arg("new.csv");
+    wrk.assert_success(&mut cmd);
+}
+
+#[test]
+fn schema_merge() {
+    let wrk = Workdir::new("schema_merge");
+    wrk.create_from_string(
+        "a.schema.json",
+        r#"{
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer", "minimum": 1, "maximum": 10 },
+                "status": { "type": "string", "enum": ["open", "closed"] },
+                "code": { "type": "integer", "const": 5 },
+                "note": { "type": "string", "pattern": "^[a-z]+$", "minLength": 2, "maxLength": 5 }
+            },
+            "required": ["id", "status", "code", "note"]
+        }"#,
+    );
+    wrk.create_from_string(
+        "b.schema.json",
+        r#"{
+            "type": "object",
+            "properties": {
+                "id": { "type": "number", "minimum": 0.5, "maximum": 8 },
+                "status": { "type": ["string", "null"], "enum": ["closed", "pending"] },
+                "code": { "type": "string", "enum": ["A", "B"] },
+                "note": { "type": "string", "pattern": "^[A-Z]+$", "minLength": 1, "maxLength": 4 },
+                "extra": { "type": "string" }
+            },
+            "required": ["id", "status", "code", "note", "extra"]
+        }"#,
+    );
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("--merge").arg("a.schema.json").arg("b.schema.json");
+    let got: String = wrk.stdout(&mut cmd);
+    let got_json: Value = serde_json::from_str(&got).unwrap();
+    let expected = serde_json::json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "JSON Schema merged from 2 inputs",
+        "$comment": "Merged with `qsv schema --merge` from: a.schema.json, b.schema.json",
+        "type": "object",
+        "properties": {
+            "id": { "type": "number", "minimum": 0.5, "maximum": 10 },
+            "status": { "type": ["string", "null"], "enum": ["open", "closed", "pending"] },
+            "code": { "type": "string", "enum": ["5", "A", "B"] },
+            "note": { "type": "string", "minLength": 1, "maxLength": 5 },
+            "extra": { "type": "string" }
+        },
+        "required": ["id", "status", "code", "note"]
+    });
+    assert_json_eq!(expected, got_json);
+
+    // enums over --enum-threshold are dropped
+    let mut cmd = wrk.command("schema");
+    cmd.arg("--merge")
+        .args(["--enum-threshold", "2"])
+        .arg("a.schema.json")
+        .arg("b.schema.json");
+    let got: String = wrk.stdout(&mut cmd);
+    let got_json: Value = serde_json::from_str(&got).unwrap();
+    assert_json_eq!(
+        serde_json::json!({ "type": ["string", "null"] }),
+        got_json["properties"]["status"]
+    );
+}
+
+#[test]
+#[file_serial]
+fn schema_merge_csv() {
+    let wrk = Workdir::new("schema_merge_csv");
+    wrk.create(
+        "jan.csv",
+        vec![
+            svec!["id", "status"],
+            svec!["1", "open"],
+            svec!["2", "closed"],
+        ],
+    );
+    wrk.create(
+        "feb.csv",
+        vec![
+            svec!["id", "status"],
+            svec!["3", "open"],
+            svec!["40", "pending"],
+        ],
+    );
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("--merge")
+        .arg("jan.csv")
+        .arg("feb.csv")
+        .args(["--output", "merged.schema.json"]);
+    wrk.assert_success(&mut cmd);
+
+    let merged: Value =
+        serde_json::from_str(&wrk.read_to_string("merged.schema.json").unwrap()).unwrap();
+    assert_eq!(
+        merged["$comment"],
+        "Merged with `qsv schema --merge` from: jan.csv, feb.csv"
+    );
+    let mut status_enum: Vec<&str> = merged["properties"]["status"]["enum"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v.as_str().unwrap())
+        .collect();
+    status_enum.sort_unstable();
+    assert_eq!(status_enum, vec!["closed", "open", "pending"]);
+
+    // the merged schema accepts both files
+    for input in ["jan.csv", "feb.csv"] {
+        let mut cmd = wrk.command("validate");
+        cmd.arg(input).arg("merged.schema.json");
+        wrk.assert_success(&mut cmd);
+    }
+}
+
+fn setup_format(name: &str) -> Workdir {
+    let wrk = Workdir::new(name);
+    wrk.create_from_string(
+        "data.schema.json",
+        r#"{
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer", "minimum": 1, "maximum": 3 },
+                "Start Date": { "type": "string", "format": "date" },
+                "status": { "type": ["string", "null"], "enum": ["open", "closed", null] },
+                "amount": { "type": "number", "minimum": 0.5, "maximum": 10 },
+                "note": { "type": "string", "minLength": 1, "maxLength": 20 },
+                "updated": { "type": ["string", "null"], "format": "date-time" }
+            }
+        }"#,
+    );
+    wrk
+}
+
+#[test]
+fn schema_format_postgres() {
+    let wrk = setup_format("schema_format_postgres");
+
+    let mut cmd = wrk.command("schema");
+    cmd.args(["--format", "postgres"])
+        .arg("--stdout")
+        .arg("data.schema.json");
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = r#"-- Generated with `qsv schema --format postgres` from data.schema.json
+CREATE TABLE "data" (
+    "id" BIGINT NOT NULL CHECK ("id" BETWEEN 1 AND 3),
+    "Start Date" DATE NOT NULL,
+    "status" TEXT CHECK ("status" IN ('open', 'closed')),
+    "amount" DOUBLE PRECISION NOT NULL CHECK ("amount" BETWEEN 0.5 AND 10),
+    "note" TEXT NOT NULL CHECK (char_length("note") BETWEEN 1 AND 20),
+    "updated" TIMESTAMPTZ
+);"#;
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn schema_format_avro() {
+    let wrk = setup_format("schema_format_avro");
+
+    let mut cmd = wrk.command("schema");
+    cmd.args(["--format", "avro"])
+        .args(["--table", "supplier"])
+        .arg("data.schema.json");
+    wrk.assert_success(&mut cmd);
+
+    let got: Value =
+        serde_json::from_str(&wrk.read_to_string("data.schema.json.avsc").unwrap()).unwrap();
+    let expected = serde_json::json!({
+        "type": "record",
+        "name": "supplier",
+        "doc": "Generated with `qsv schema --format avro` from data.schema.json",
+        "fields": [
+            { "name": "id", "type": "long" },
+            { "name": "Start_Date", "doc": "Start Date", "type": { "type": "int", "logicalType": "date" } },
+            {
+                "name": "status",
+                "type": ["null", { "type": "enum", "name": "status_enum", "symbols": ["open", "closed"] }],
+                "default": null
+            },
+            { "name": "amount", "type": "double" },
+            { "name": "note", "type": "string" },
+            {
+                "name": "updated",
+                "type": ["null", { "type": "long", "logicalType": "timestamp-micros" }],
+                "default": null
+            }
+        ]
+    });
+    assert_json_eq!(got, expected);
+}
+
+#[test]
+fn schema_format_arrow() {
+    let wrk = setup_format("schema_format_arrow");
+
+    let mut cmd = wrk.command("schema");
+    cmd.args(["--format", "arrow"])
+        .arg("--stdout")
+        .arg("data.schema.json");
+    let got: String = wrk.stdout(&mut cmd);
+    let got: Value = serde_json::from_str(&got).unwrap();
+    assert_eq!(
+        got["fields"][0],
+        serde_json::json!({
+            "name": "id",
+            "nullable": false,
+            "type": { "name": "int", "bitWidth": 64, "isSigned": true },
+            "children": []
+        })
+    );
+    assert_eq!(
+        got["fields"][1]["type"],
+        serde_json::json!({ "name": "date", "unit": "DAY" })
+    );
+    assert_eq!(got["fields"][2]["nullable"], true);
+    assert_eq!(
+        got["fields"][2]["type"],
+        serde_json::json!({ "name": "utf8" })
+    );
+}
+
+#[test]
+fn schema_format_pydantic() {
+    let wrk = setup_format("schema_format_pydantic");
+
+    let mut cmd = wrk.command("schema");
+    cmd.args(["--format", "pydantic"])
+        .arg("--stdout")
+        .arg("data.schema.json");
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = r#"# Generated with `qsv schema --format pydantic` from data.schema.json
+
+import datetime
+from typing import Literal, Optional
+
+from pydantic import BaseModel, Field
+
+
+class Data(BaseModel):
+    id: int = Field(ge=1, le=3)
+    Start_Date: datetime.date = Field(alias="Start Date")
+    status: Optional[Literal["open", "closed"]] = None
+    amount: float = Field(ge=0.5, le=10)
+    note: str = Field(min_length=1, max_length=20)
+    updated: Optional[datetime.datetime] = None"#;
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn schema_format_typescript() {
+    let wrk = setup_format("schema_format_typescript");
+
+    let mut cmd = wrk.command("schema");
+    cmd.args(["--format", "typescript"])
+        .args(["--table", "supplier_data"])
+        .arg("--stdout")
+        .arg("data.schema.json");
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = r#"// Generated with `qsv schema --format typescript` from data.schema.json
+
+export interface SupplierData {
+  id: number;
+  "Start Date": string;
+  status: "open" | "closed" | null;
+  amount: number;
+  note: string;
+  updated: string | null;
+}"#;
+    assert_eq!(got, expected);
+}
+
+#[test]
+#[file_serial]
+fn schema_format_csv() {
+    let wrk = Workdir::new("schema_format_csv");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["id", "name", "close_date"],
+            svec!["1", "alpha", "2024-01-15"],
+            svec!["2", "beta", "2024-02-20"],
+            svec!["3", "", "2024-03-01"],
+        ],
+    );
+
+    let mut cmd = wrk.command("schema");
+    cmd.args(["--format", "duckdb"]).arg("data.csv");
+    wrk.assert_success(&mut cmd);
+
+    // the types & nullability come from the stats cache
+    let got = wrk.read_to_string("data.csv.duckdb.sql").unwrap();
+    assert!(got.contains("CREATE TABLE \"data\" ("));
+    assert!(got.contains("\"id\" BIGINT NOT NULL"));
+    assert!(got.contains("\"close_date\" DATE NOT NULL"));
+    assert!(got.contains("\"name\" VARCHAR CHECK"));
+}
+
+#[test]
+fn schema_format_invalid() {
+    let wrk = setup_format("schema_format_invalid");
+
+    let mut cmd = wrk.command("schema");
+    cmd.args(["--format", "protobuf"]).arg("data.schema.json");
+    wrk.assert_err(&mut cmd);
+}
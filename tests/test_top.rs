@@ -0,0 +1,129 @@
+use crate::workdir::Workdir;
+
+fn setup(name: &str) -> Workdir {
+    let scores = vec![
+        svec!["name", "score", "updated"],
+        svec!["a", "9", "2024-03-01"],
+        svec!["b", "100", "01/15/2025"],
+        svec!["c", "25", "2023-12-31"],
+        svec!["d", "100", "2024-11-05"],
+        svec!["e", "3", "not a date"],
+    ];
+
+    let wrk = Workdir::new(name);
+    wrk.create("scores.csv", scores);
+    wrk
+}
+
+#[test]
+fn top_numeric_largest() {
+    let wrk = setup("top_numeric_largest");
+    let mut cmd = wrk.command("top");
+    cmd.args(["3", "--select", "score", "--numeric", "scores.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    // ties keep input order
+    let expected = vec![
+        svec!["name", "score", "updated"],
+        svec!["b", "100", "01/15/2025"],
+        svec!["d", "100", "2024-11-05"],
+        svec!["c", "25", "2023-12-31"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn top_numeric_smallest() {
+    let wrk = setup("top_numeric_smallest");
+    let mut cmd = wrk.command("top");
+    cmd.args(["2", "-s", "score", "-N", "--smallest", "scores.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "score", "updated"],
+        svec!["e", "3", "not a date"],
+        svec!["a", "9", "2024-03-01"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn top_lexicographic() {
+    let wrk = setup("top_lexicographic");
+    let mut cmd = wrk.command("top");
+    cmd.args(["2", "-s", "score", "scores.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "score", "updated"],
+        svec!["a", "9", "2024-03-01"],
+        svec!["e", "3", "not a date"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn top_date() {
+    let wrk = setup("top_date");
+    let mut cmd = wrk.command("top");
+    cmd.args(["3", "-s", "updated", "--date", "scores.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "score", "updated"],
+        svec!["b", "100", "01/15/2025"],
+        svec!["d", "100", "2024-11-05"],
+        svec!["a", "9", "2024-03-01"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn top_date_smallest() {
+    let wrk = setup("top_date_smallest");
+    let mut cmd = wrk.command("top");
+    cmd.args(["5", "-s", "updated", "--date", "--smallest", "scores.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    // values that are not dates still rank last
+    let expected = vec![
+        svec!["name", "score", "updated"],
+        svec!["c", "25", "2023-12-31"],
+        svec!["a", "9", "2024-03-01"],
+        svec!["d", "100", "2024-11-05"],
+        svec!["b", "100", "01/15/2025"],
+        svec!["e", "3", "not a date"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn top_natural() {
+    let wrk = Workdir::new("top_natural");
+    wrk.create(
+        "releases.csv",
+        vec![
+            svec!["version"],
+            svec!["v1.9"],
+            svec!["v1.10"],
+            svec!["v1.2"],
+        ],
+    );
+    let mut cmd = wrk.command("top");
+    cmd.args(["2", "--natural", "releases.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["version"], svec!["v1.10"], svec!["v1.9"]];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn top_n_larger_than_input() {
+    let wrk = setup("top_n_larger_than_input");
+    let mut cmd = wrk.command("top");
+    cmd.args(["10", "-s", "score", "-N", "scores.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got.len(), 6);
+    assert_eq!(got[5], svec!["e", "3", "not a date"]);
+}
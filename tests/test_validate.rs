@@ -3343,3 +3343,704 @@ fn validate_with_multiple_formats_strict() {
     ];
     assert_eq!(invalid_records, expected_invalid);
 }
+
+#[test]
+fn validate_table_schema() {
+    let wrk = Workdir::new("validate_table_schema").flexible(true);
+
+    wrk.create(
+        "departments.csv",
+        vec![
+            svec!["code", "label"],
+            svec!["IT", "Tech"],
+            svec!["HR", "People"],
+        ],
+    );
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["id", "name", "age", "dept", "active", "hired"],
+            svec!["1", "John", "34", "IT", "true", "2020-01-15"],
+            svec!["2", "Jane", "", "HR", "0", "2021-06-01"],
+            svec!["2", "Bob", "41", "IT", "true", "2019-03-10"], // duplicate primary key
+            svec!["4", "", "29", "IT", "false", "2022-11-30"],   // missing required name
+            svec!["5", "Sue", "-3", "IT", "true", "2018-07-04"], // age below minimum
+            svec!["6", "Ann", "50", "OPS", "true", "2017-02-20"], // unknown department
+            svec!["7", "Tom", "45", "HR", "yes", "2016-05-05"],  // not a boolean value
+            svec!["8", "Kim", "38", "", "true", "05/05/2016"],   // bad date, null dept is ok
+        ],
+    );
+
+    wrk.create_from_string(
+        "tableschema.json",
+        r#"{
+            "fields": [
+                { "name": "id", "type": "integer" },
+                { "name": "name", "type": "string", "constraints": { "required": true } },
+                { "name": "age", "type": "integer", "constraints": { "minimum": 0 } },
+                { "name": "dept", "type": "string" },
+                { "name": "active", "type": "boolean" },
+                { "name": "hired", "type": "date" }
+            ],
+            "primaryKey": "id",
+            "foreignKeys": [
+                {
+                    "fields": "dept",
+                    "reference": { "resource": "departments.csv", "fields": "code" }
+                }
+            ]
+        }"#,
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("tableschema.json");
+    wrk.assert_err(&mut cmd);
+
+    let invalid_records: Vec<Vec<String>> = wrk.read_csv("data.csv.invalid");
+    let invalid_ids: Vec<&str> = invalid_records.iter().map(|r| r[1].as_str()).collect();
+    assert_eq!(invalid_ids, vec!["Bob", "", "Sue", "Ann", "Tom", "Kim"]);
+
+    let validation_errors = wrk
+        .read_to_string("data.csv.validation-errors.tsv")
+        .unwrap();
+    let error_rows: Vec<&str> = validation_errors
+        .lines()
+        .skip(1)
+        .map(|l| l.split('\t').next().unwrap())
+        .collect();
+    assert_eq!(error_rows, vec!["3", "4", "5", "6", "7", "8"]);
+    assert!(validation_errors.contains("not a valid dynamicEnum value"));
+    assert!(validation_errors.contains("Combination of values for columns id is not unique"));
+}
+
+#[test]
+fn validate_table_schema_unique_pattern_enum() {
+    let wrk = Workdir::new("validate_table_schema_unique_pattern_enum").flexible(true);
+
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["sku", "size", "ean"],
+            svec!["AB-001", "S", "4006381333931"],
+            svec!["AB-002", "", ""],
+            svec!["AB-003", "M", ""], // null ean values don't break uniqueness
+            svec!["ab-004", "L", "4006381333948"], // bad pattern
+            svec!["AB-005", "XXL", "4006381333955"], // not in enum
+            svec!["AB-006", "S", "4006381333931"], // duplicate ean
+        ],
+    );
+
+    wrk.create_from_string(
+        "tableschema.json",
+        r#"{
+            "fields": [
+                { "name": "sku", "constraints": { "pattern": "[A-Z]{2}-\\d{3}" } },
+                { "name": "size", "constraints": { "enum": ["S", "M", "L", "XL"] } },
+                { "name": "ean", "constraints": { "unique": true } }
+            ]
+        }"#,
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("tableschema.json");
+    wrk.assert_err(&mut cmd);
+
+    let invalid_records: Vec<Vec<String>> = wrk.read_csv("data.csv.invalid");
+    let expected_invalid = vec![
+        svec!["ab-004", "L", "4006381333948"],
+        svec!["AB-005", "XXL", "4006381333955"],
+        svec!["AB-006", "S", "4006381333931"],
+    ];
+    assert_eq!(invalid_records, expected_invalid);
+}
+
+#[test]
+fn validate_table_schema_missing_field() {
+    let wrk = Workdir::new("validate_table_schema_missing_field");
+
+    wrk.create("data.csv", vec![svec!["id"], svec!["1"]]);
+    wrk.create_from_string(
+        "tableschema.json",
+        r#"{ "fields": [ { "name": "id" }, { "name": "name" } ] }"#,
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("tableschema.json");
+    let stderr = wrk.stderr_on_error(&mut cmd);
+    assert!(
+        stderr.contains("Table Schema field(s) not found in the CSV headers: name"),
+        "Expected missing field error, got: {stderr}"
+    );
+}
+
+#[test]
+fn validate_csvw() {
+    let wrk = Workdir::new("validate_csvw").flexible(true);
+
+    wrk.create(
+        "countries.csv",
+        vec![
+            svec!["code", "name"],
+            svec!["US", "United States"],
+            svec!["FR", "France"],
+        ],
+    );
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["Order ID", "Country", "Ordered", "Quantity"],
+            svec!["1", "US", "15/01/2024", "3"],
+            svec!["2", "FR", "2024-01-16", "1"], // wrong date format
+            svec!["3", "DE", "17/01/2024", "2"], // unknown country
+            svec!["4", "US", "18/01/2024", "0"], // not a positive integer
+        ],
+    );
+
+    wrk.create_from_string(
+        "data.csv-metadata.json",
+        r#"{
+            "@context": "http://www.w3.org/ns/csvw",
+            "url": "data.csv",
+            "tableSchema": {
+                "columns": [
+                    { "name": "order_id", "titles": "Order ID", "datatype": "integer",
+                      "required": true },
+                    { "name": "country", "titles": "Country", "datatype": "string" },
+                    { "name": "ordered", "titles": "Ordered",
+                      "datatype": { "base": "date", "format": "dd/MM/yyyy" } },
+                    { "name": "quantity", "titles": "Quantity", "datatype": "positiveInteger" }
+                ],
+                "primaryKey": "order_id",
+                "foreignKeys": [
+                    {
+                        "columnReference": "country",
+                        "reference": { "resource": "countries.csv", "columnReference": "code" }
+                    }
+                ]
+            }
+        }"#,
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("data.csv-metadata.json");
+    wrk.assert_err(&mut cmd);
+
+    let invalid_records: Vec<Vec<String>> = wrk.read_csv("data.csv.invalid");
+    let expected_invalid = vec![
+        svec!["2", "FR", "2024-01-16", "1"],
+        svec!["3", "DE", "17/01/2024", "2"],
+        svec!["4", "US", "18/01/2024", "0"],
+    ];
+    assert_eq!(invalid_records, expected_invalid);
+}
+
+#[cfg(not(feature = "lite"))]
+fn setup_relations(name: &str) -> Workdir {
+    let wrk = Workdir::new(name).flexible(true);
+    wrk.create(
+        "customers.csv",
+        vec![
+            svec!["id", "region", "name"],
+            svec!["1", "EU", "Acme"],
+            svec!["1", "US", "Acme US"],
+            svec!["2", "EU", "Globex"],
+            svec!["2", "EU", "Globex (dup)"],
+            svec!["3", "US", "Initech"],
+        ],
+    );
+    wrk.create(
+        "orders.csv",
+        vec![
+            svec!["order_id", "customer_id", "region"],
+            svec!["100", "1", "EU"],
+            svec!["101", "1", "US"],
+            svec!["102", "2", "US"], // no customer 2 in US
+            svec!["103", "", "EU"],  // null key
+            svec!["104", "1", "EU"],
+        ],
+    );
+    wrk
+}
+
+#[cfg(not(feature = "lite"))]
+#[test]
+fn validate_relations() {
+    let wrk = setup_relations("validate_relations");
+    wrk.create_from_string(
+        "release.json",
+        r#"{
+            "tables": { "customers": "customers.csv", "orders": "orders.csv" },
+            "relations": [
+                {
+                    "name": "order_customer",
+                    "from": { "table": "orders", "columns": ["customer_id", "region"] },
+                    "to": { "table": "customers", "columns": ["id", "region"] },
+                    "cardinality": "N:1",
+                    "orphans": "both"
+                }
+            ]
+        }"#,
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("relations").arg("release.json");
+    wrk.assert_err(&mut cmd);
+
+    let report = wrk
+        .read_to_string("release.json.validation-errors.tsv")
+        .unwrap();
+    let expected = "table\trow_number\tfield\terror
+customers\t4\tid,region\t[order_customer] duplicate key (2, EU) in customers, but cardinality N:1 \
+                    allows one customers row per key
+orders\t3\tcustomer_id,region\t[order_customer] orphan key (2, US): not found in \
+                    customers.id,region
+customers\t3\tid,region\t[order_customer] orphan key (2, EU): not referenced by \
+                    orders.customer_id,region
+customers\t4\tid,region\t[order_customer] orphan key (2, EU): not referenced by \
+                    orders.customer_id,region
+customers\t5\tid,region\t[order_customer] orphan key (3, US): not referenced by \
+                    orders.customer_id,region
+";
+    assert_eq!(report, expected);
+}
+
+#[cfg(not(feature = "lite"))]
+#[test]
+fn validate_relations_one_to_one_key_transform() {
+    let wrk = setup_relations("validate_relations_one_to_one_key_transform");
+    wrk.create(
+        "regions.csv",
+        vec![svec!["code"], svec![" eu "], svec!["us"]],
+    );
+    wrk.create_from_string(
+        "release.json",
+        r#"{
+            "tables": {
+                "customers": "customers.csv",
+                "regions": { "path": "regions.csv" }
+            },
+            "relations": [
+                {
+                    "from": { "table": "customers", "columns": "region" },
+                    "to": { "table": "regions", "columns": "code" },
+                    "key_transform": "trim,upper"
+                }
+            ]
+        }"#,
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("relations").arg("release.json");
+    wrk.assert_success(&mut cmd);
+    assert!(!wrk.path("release.json.validation-errors.tsv").exists());
+}
+
+#[cfg(not(feature = "lite"))]
+#[test]
+fn validate_relations_unknown_column() {
+    let wrk = setup_relations("validate_relations_unknown_column");
+    wrk.create_from_string(
+        "release.json",
+        r#"{
+            "tables": { "customers": "customers.csv", "orders": "orders.csv" },
+            "relations": [
+                {
+                    "from": { "table": "orders", "columns": "cust_id" },
+                    "to": { "table": "customers", "columns": "id" }
+                }
+            ]
+        }"#,
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("relations").arg("release.json");
+    let stderr = wrk.stderr_on_error(&mut cmd);
+    assert!(
+        stderr.contains("Column \"cust_id\" not found in table \"orders\""),
+        "Expected unknown column error, got: {stderr}"
+    );
+}
+
+fn setup_rules(name: &str) -> Workdir {
+    let wrk = Workdir::new(name);
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["id", "Start Date", "end_date", "status", "closed_at"],
+            svec!["1", "2024-01-01", "2024-02-01", "closed", "2024-02-02"],
+            svec!["2", "2024-03-01", "2024-02-01", "open", ""],
+            svec!["3", "2024-01-01", "2024-01-05", "closed", ""],
+        ],
+    );
+    wrk.create_from_string(
+        "rules.json",
+        r#"{
+            "rules": [
+                {
+                    "id": "dates_ordered",
+                    "expression": "end_date >= row['Start Date']",
+                    "field": "end_date",
+                    "message": "end_date {{ end_date }} is before Start Date {{ Start_Date }}"
+                },
+                {
+                    "id": "closed_has_date",
+                    "expression": "status != 'closed' or closed_at is not none",
+                    "severity": "warning"
+                }
+            ]
+        }"#,
+    );
+    wrk
+}
+
+#[test]
+fn validate_rules() {
+    let wrk = setup_rules("validate_rules");
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("--rules").arg("rules.json").arg("data.csv");
+    wrk.assert_err(&mut cmd);
+
+    let report = wrk
+        .read_to_string("data.csv.validation-errors.tsv")
+        .unwrap();
+    let expected = "row_number\tfield\terror
+2\tend_date\terror dates_ordered: end_date 2024-02-01 is before Start Date 2024-03-01
+3\t<RULE>\twarning closed_has_date: rule failed: status != 'closed' or closed_at is not none
+";
+    assert_eq!(report, expected);
+
+    // only the row with an error-level violation is invalid
+    let valid_records: Vec<Vec<String>> = wrk.read_csv("data.csv.valid");
+    assert_eq!(
+        valid_records,
+        vec![
+            svec!["1", "2024-01-01", "2024-02-01", "closed", "2024-02-02"],
+            svec!["3", "2024-01-01", "2024-01-05", "closed", ""],
+        ]
+    );
+    let invalid_records: Vec<Vec<String>> = wrk.read_csv("data.csv.invalid");
+    assert_eq!(
+        invalid_records,
+        vec![svec!["2", "2024-03-01", "2024-02-01", "open", ""]]
+    );
+}
+
+#[test]
+fn validate_rules_fail_on_warning() {
+    let wrk = setup_rules("validate_rules_fail_on_warning");
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("--rules")
+        .arg("rules.json")
+        .args(["--fail-on", "warning"])
+        .arg("data.csv");
+    let got_err = wrk.stderr_on_error(&mut cmd);
+    assert_eq!(got_err, "2 out of 3 records invalid.\n");
+
+    let valid_records: Vec<Vec<String>> = wrk.read_csv("data.csv.valid");
+    assert_eq!(
+        valid_records,
+        vec![svec![
+            "1",
+            "2024-01-01",
+            "2024-02-01",
+            "closed",
+            "2024-02-02"
+        ]]
+    );
+}
+
+#[test]
+fn validate_rules_warnings_only_with_schema() {
+    let wrk = setup_rules("validate_rules_warnings_only_with_schema");
+    wrk.create_from_string(
+        "schema.json",
+        r#"{
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer", "minimum": 1 },
+                "status": { "type": "string", "enum": ["open", "closed"] }
+            }
+        }"#,
+    );
+
+    // the row with the error-level violation is also invalid per the schema
+    let mut cmd = wrk.command("validate");
+    cmd.arg("--rules")
+        .arg("rules.json")
+        .arg("data.csv")
+        .arg("schema.json");
+    wrk.assert_err(&mut cmd);
+
+    // without rule errors, warnings are reported but all records are valid
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["id", "Start Date", "end_date", "status", "closed_at"],
+            svec!["1", "2024-01-01", "2024-02-01", "closed", "2024-02-02"],
+            svec!["3", "2024-01-01", "2024-01-05", "closed", ""],
+        ],
+    );
+    let mut cmd = wrk.command("validate");
+    cmd.arg("--rules")
+        .arg("rules.json")
+        .arg("data.csv")
+        .arg("schema.json");
+    wrk.assert_success(&mut cmd);
+
+    let report = wrk
+        .read_to_string("data.csv.validation-errors.tsv")
+        .unwrap();
+    let expected = "row_number\tfield\terror
+2\t<RULE>\twarning closed_has_date: rule failed: status != 'closed' or closed_at is not none
+";
+    assert_eq!(report, expected);
+}
+
+fn setup_report(name: &str) -> Workdir {
+    let wrk = Workdir::new(name);
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["id", "status"],
+            svec!["1", "open"],
+            svec!["0", "open"],
+            svec!["-1", "pending"],
+            svec!["2", "pending"],
+            svec!["3", "closed"],
+        ],
+    );
+    wrk.create_from_string(
+        "schema.json",
+        r#"{
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer", "minimum": 1 },
+                "status": { "type": "string", "enum": ["open", "closed"] }
+            }
+        }"#,
+    );
+    wrk
+}
+
+#[test]
+fn validate_report_format_json() {
+    let wrk = setup_report("validate_report_format_json");
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv")
+        .arg("schema.json")
+        .args(["--report-format", "json"])
+        .args(["--report-examples", "1"]);
+    wrk.assert_err(&mut cmd);
+
+    // the existing outputs are still written
+    assert!(wrk.path("data.csv.valid").exists());
+    assert!(wrk.path("data.csv.invalid").exists());
+    assert!(wrk.path("data.csv.validation-errors.tsv").exists());
+
+    let report: serde_json::Value = serde_json::from_str(
+        &wrk.read_to_string("data.csv.validation-report.json")
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(report["input"], "data.csv");
+    assert_eq!(report["schema"], "schema.json");
+    assert!(
+        report["schema_fingerprint"]
+            .as_str()
+            .unwrap()
+            .starts_with("blake3:")
+    );
+    assert_eq!(report["valid"], false);
+    assert_eq!(report["total_records"], 5);
+    assert_eq!(report["valid_records"], 2);
+    assert_eq!(report["invalid_records"], 3);
+    assert_eq!(report["warning_records"], 0);
+    assert_eq!(
+        report["errors"],
+        serde_json::json!([
+            { "field": "id", "keyword": "minimum", "failing": true, "count": 2, "example_rows": [2] },
+            { "field": "status", "keyword": "enum", "failing": true, "count": 2, "example_rows": [3] }
+        ])
+    );
+}
+
+#[test]
+fn validate_report_format_json_valid() {
+    let wrk = setup_report("validate_report_format_json_valid");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["id", "status"],
+            svec!["1", "open"],
+            svec!["3", "closed"],
+        ],
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv")
+        .arg("schema.json")
+        .args(["--report-format", "json"]);
+    wrk.assert_success(&mut cmd);
+
+    // all records are valid, so only the summary is written
+    assert!(!wrk.path("data.csv.validation-errors.tsv").exists());
+    let report: serde_json::Value = serde_json::from_str(
+        &wrk.read_to_string("data.csv.validation-report.json")
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(report["valid"], true);
+    assert_eq!(report["valid_records"], 2);
+    assert_eq!(report["errors"], serde_json::json!([]));
+}
+
+#[test]
+fn validate_report_format_junit() {
+    let wrk = setup_report("validate_report_format_junit");
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv")
+        .arg("schema.json")
+        .args(["--report-format", "junit"]);
+    wrk.assert_err(&mut cmd);
+
+    let report = wrk
+        .read_to_string("data.csv.validation-report.xml")
+        .unwrap();
+    assert!(report.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+    assert!(report.contains("<testsuites name=\"qsv validate\" tests=\"2\" failures=\"2\">"));
+    assert!(report.contains("<property name=\"invalid_records\" value=\"3\"/>"));
+    assert!(report.contains(
+        "    <testcase classname=\"data.csv\" name=\"id minimum\">\n      <failure \
+         type=\"minimum\" message=\"2 errors\">example rows: 2, 3</failure>\n    </testcase>\n"
+    ));
+    assert!(
+        report.contains("<failure type=\"enum\" message=\"2 errors\">example rows: 3, 4</failure>")
+    );
+}
+
+fn setup_fixes(name: &str) -> Workdir {
+    let wrk = Workdir::new(name);
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["id", "amount", "status", "opened"],
+            svec!["1", "1,234", "open", "2024-01-15"],
+            svec!["2", "42", " Open", "2024-01-20"],
+            svec!["3", "7", "closed", "01/20/2024"],
+            svec!["4", "9", "pending", "2024-03-01"],
+            svec!["5", "10", "open", "2024-03-02"],
+        ],
+    );
+    wrk.create_from_string(
+        "schema.json",
+        r#"{
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer" },
+                "amount": { "type": "integer" },
+                "status": { "type": "string", "enum": ["open", "closed"] },
+                "opened": { "type": "string", "format": "date" }
+            }
+        }"#,
+    );
+    wrk
+}
+
+#[test]
+fn validate_suggest_fixes() {
+    let wrk = setup_fixes("validate_suggest_fixes");
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv")
+        .arg("schema.json")
+        .arg("--suggest-fixes");
+    let got = wrk.stderr_on_error(&mut cmd);
+    assert!(got.contains("3 of 4 invalid records can be repaired with the suggested fixes"));
+
+    let got = wrk.read_to_string("data.csv.fix-suggestions.tsv").unwrap();
+    let expected = r"field	fix	rows	example	fixed_example	command
+amount	thousands_separator	1	1,234	1234	qsv replace --select amount ',' '' data.csv
+opened	date_format	1	01/20/2024	2024-01-20	qsv datefmt opened --formatstr '%Y-%m-%d' data.csv
+status	trim	1	 Open	Open	qsv apply operations trim status data.csv
+status	enum_case	1	Open	open	qsv replace --select status '(?i)^\s*open\s*$' open data.csv
+";
+    assert_eq!(got, expected);
+
+    // the fixes are only suggested, so all the invalid records are still invalid
+    let invalid: Vec<Vec<String>> = wrk.read_csv("data.csv.invalid");
+    assert_eq!(invalid.len(), 4);
+    assert!(!wrk.path("data.csv.fixes.tsv").exists());
+}
+
+#[test]
+fn validate_auto_fix() {
+    let wrk = setup_fixes("validate_auto_fix");
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("schema.json").arg("--auto-fix");
+    wrk.assert_err(&mut cmd);
+
+    // the repaired records are re-validated & written to the valid file
+    let valid: Vec<Vec<String>> = wrk.read_csv("data.csv.valid");
+    assert_eq!(
+        valid,
+        vec![
+            svec!["1", "1234", "open", "2024-01-15"],
+            svec!["2", "42", "open", "2024-01-20"],
+            svec!["3", "7", "closed", "2024-01-20"],
+            svec!["5", "10", "open", "2024-03-02"],
+        ]
+    );
+    let invalid: Vec<Vec<String>> = wrk.read_csv("data.csv.invalid");
+    assert_eq!(invalid, vec![svec!["4", "9", "pending", "2024-03-01"]]);
+
+    let got = wrk.read_to_string("data.csv.fixes.tsv").unwrap();
+    let expected = "row_number	field	fix	old_value	new_value
+1	amount	thousands_separator	1,234	1234
+2	status	trim	 Open	Open
+2	status	enum_case	Open	open
+3	opened	date_format	01/20/2024	2024-01-20
+";
+    assert_eq!(got, expected);
+
+    // only the record that couldn't be repaired is in the error report
+    let errors = wrk
+        .read_to_string("data.csv.validation-errors.tsv")
+        .unwrap();
+    assert_eq!(errors.lines().count(), 2);
+    assert!(errors.lines().nth(1).unwrap().starts_with("4\tstatus\t"));
+}
+
+#[test]
+fn validate_auto_fix_all_repaired() {
+    let wrk = setup_fixes("validate_auto_fix_all_repaired");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["id", "amount", "status", "opened"],
+            svec!["1", "1,234", "CLOSED", "2024-01-15"],
+            svec!["2", "42", "open", "2024-01-20"],
+        ],
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("schema.json").arg("--auto-fix");
+    wrk.assert_success(&mut cmd);
+
+    let valid: Vec<Vec<String>> = wrk.read_csv("data.csv.valid");
+    assert_eq!(
+        valid,
+        vec![
+            svec!["1", "1234", "closed", "2024-01-15"],
+            svec!["2", "42", "open", "2024-01-20"],
+        ]
+    );
+    assert!(!wrk.path("data.csv.invalid").exists());
+}
@@ -0,0 +1,172 @@
+use crate::workdir::Workdir;
+
+fn setup(name: &str) -> Workdir {
+    let sales = vec![
+        svec!["date", "region", "sales"],
+        svec!["2023-01-01", "North", "100"],
+        svec!["2023-01-02", "South", "200"],
+        svec!["2023-01-03", "North", "300"],
+        svec!["2023-01-04", "South", "400"],
+        svec!["2023-01-05", "North", "500"],
+    ];
+
+    let wrk = Workdir::new(name);
+    wrk.create("sales.csv", sales);
+    wrk
+}
+
+#[test]
+fn window_cumsum_lag_partitioned() {
+    let wrk = setup("window_cumsum_lag_partitioned");
+    let mut cmd = wrk.command("window");
+    cmd.args([
+        "--columns",
+        "sales",
+        "--partition-by",
+        "region",
+        "--order-by",
+        "date",
+        "cumsum,lag",
+        "sales.csv",
+    ]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["date", "region", "sales", "sales_cumsum", "sales_lag_1"],
+        svec!["2023-01-01", "North", "100", "100", ""],
+        svec!["2023-01-02", "South", "200", "200", ""],
+        svec!["2023-01-03", "North", "300", "400", "100"],
+        svec!["2023-01-04", "South", "400", "600", "200"],
+        svec!["2023-01-05", "North", "500", "900", "300"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn window_lead_descending_order_keeps_input_order() {
+    let wrk = setup("window_lead_descending_order_keeps_input_order");
+    let mut cmd = wrk.command("window");
+    cmd.args([
+        "-c",
+        "sales",
+        "-O",
+        "date",
+        "--descending",
+        "lead:2",
+        "sales.csv",
+    ]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["date", "region", "sales", "sales_lead_2"],
+        svec!["2023-01-01", "North", "100", ""],
+        svec!["2023-01-02", "South", "200", ""],
+        svec!["2023-01-03", "North", "300", "100"],
+        svec!["2023-01-04", "South", "400", "200"],
+        svec!["2023-01-05", "North", "500", "300"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn window_rank_dense_rank() {
+    let wrk = Workdir::new("window_rank_dense_rank");
+    wrk.create(
+        "scores.csv",
+        vec![
+            svec!["name", "score"],
+            svec!["a", "10"],
+            svec!["b", "20"],
+            svec!["c", "20"],
+            svec!["d", "30"],
+        ],
+    );
+    let mut cmd = wrk.command("window");
+    cmd.args(["-c", "score", "rank,dense_rank", "scores.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "score", "score_rank", "score_dense_rank"],
+        svec!["a", "10", "1", "1"],
+        svec!["b", "20", "2", "2"],
+        svec!["c", "20", "2", "2"],
+        svec!["d", "30", "4", "3"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn window_rolling_mean_rows() {
+    let wrk = setup("window_rolling_mean_rows");
+    let mut cmd = wrk.command("window");
+    cmd.args(["-c", "sales", "-O", "date", "rolling_mean:2", "sales.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["date", "region", "sales", "sales_rolling_mean_2"],
+        svec!["2023-01-01", "North", "100", "100.0"],
+        svec!["2023-01-02", "South", "200", "150.0"],
+        svec!["2023-01-03", "North", "300", "250.0"],
+        svec!["2023-01-04", "South", "400", "350.0"],
+        svec!["2023-01-05", "North", "500", "450.0"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn window_rolling_sum_time_based() {
+    let wrk = setup("window_rolling_sum_time_based");
+    let mut cmd = wrk.command("window");
+    cmd.args(["-c", "sales", "-O", "date", "rolling_sum:2d", "sales.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["date", "region", "sales", "sales_rolling_sum_2d"],
+        svec!["2023-01-01", "North", "100", "100"],
+        svec!["2023-01-02", "South", "200", "300"],
+        svec!["2023-01-03", "North", "300", "500"],
+        svec!["2023-01-04", "South", "400", "700"],
+        svec!["2023-01-05", "North", "500", "900"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn window_time_based_requires_order_by() {
+    let wrk = setup("window_time_based_requires_order_by");
+    let mut cmd = wrk.command("window");
+    cmd.args(["-c", "sales", "rolling_mean:7d", "sales.csv"]);
+
+    let stderr = wrk.stderr_on_error(&mut cmd);
+    assert!(
+        stderr.contains("Time-based rolling windows require a single Date or Datetime"),
+        "Expected --order-by error, got: {stderr}"
+    );
+}
+
+#[test]
+fn window_time_based_descending_error() {
+    let wrk = setup("window_time_based_descending_error");
+    let mut cmd = wrk.command("window");
+    cmd.args(["-c", "sales", "-O", "date", "--descending"])
+        .args(["rolling_sum:2d", "sales.csv"]);
+
+    let stderr = wrk.stderr_on_error(&mut cmd);
+    assert!(
+        stderr.contains("require an ascending --order-by"),
+        "Expected --descending error, got: {stderr}"
+    );
+}
+
+#[test]
+fn window_unknown_function() {
+    let wrk = setup("window_unknown_function");
+    let mut cmd = wrk.command("window");
+    cmd.args(["-c", "sales", "cumsum,median", "sales.csv"]);
+
+    let stderr = wrk.stderr_on_error(&mut cmd);
+    assert!(
+        stderr.contains("Unknown window function: median"),
+        "Expected unknown function error, got: {stderr}"
+    );
+}
@@ -92,11 +92,15 @@ mod test_fmt;
 #[cfg(feature = "foreach")]
 mod test_foreach;
 mod test_frequency;
+#[cfg(feature = "fuzzydedup")]
+mod test_fuzzydedup;
 #[cfg(feature = "geocode")]
 // #[cfg(not(target_arch = "powerpc64"))]
 mod test_geocode;
 #[cfg(feature = "geocode")]
 mod test_geoconvert;
+#[cfg(feature = "geocode")]
+mod test_geojoin;
 #[cfg(feature = "get")]
 mod test_get;
 mod test_headers;
@@ -115,6 +119,8 @@ mod test_jsonl;
 mod test_log;
 #[cfg(feature = "luau")]
 mod test_luau;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+mod test_merge;
 mod test_moarstats;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 mod test_partition;
@@ -161,12 +167,16 @@ mod test_to;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 mod test_tojsonl;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
+mod test_top;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
 mod test_transpose;
 mod test_validate;
 #[cfg(feature = "viz")]
 mod test_viz;
 #[cfg(feature = "viz")]
 mod test_viz_census;
+#[cfg(feature = "polars")]
+mod test_window;
 
 fn qcheck<T: Testable>(p: T) {
     // safety: we are in single-threaded code.